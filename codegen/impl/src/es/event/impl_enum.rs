@@ -1,18 +1,53 @@
 //! `#[derive(Event)]` macro implementation for enums.
 
 #[cfg(all(doc, feature = "doc"))]
-use arcane_core::es::{Event, event};
+use arcane_core::es::{Event, event, stream};
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{parse_quote, spanned::Spanned as _};
 use synthez::{ParseAttrs, ToTokens};
 
+use super::NamingPolicy;
+#[cfg(any(
+    feature = "cloudevents",
+    feature = "proptest",
+    feature = "registry",
+))]
+use super::higher_ranked;
+#[cfg(feature = "protobuf")]
+use super::proto;
+
 /// Attributes of the `#[derive(Event)]` macro placed on an enum.
 #[derive(Debug, Default, ParseAttrs)]
 pub struct Attrs {
     /// Indicator whether an enum should be treated as an [`event::Revisable`].
     #[parse(ident, alias = rev)]
     pub revision: Option<syn::Ident>,
+
+    /// Prefix to prepend to all the nested [`event::Name`]s.
+    #[parse(value)]
+    pub prefix: Option<syn::LitStr>,
+
+    /// [`event::name::Case`] all the nested [`event::Name`]s segments should
+    /// match.
+    #[parse(value)]
+    pub case: Option<syn::LitStr>,
+
+    /// Maximum length of all the nested [`event::Name`]s.
+    #[parse(value)]
+    pub max_len: Option<syn::LitInt>,
+
+    /// Value for the [`stream::Categorized::CATEGORY`] constant.
+    #[parse(value)]
+    pub stream: Option<syn::LitStr>,
+
+    /// Indicator whether a [`protobuf::Proto`] implementation should be
+    /// generated.
+    ///
+    /// [`protobuf::Proto`]: arcane_core::es::protobuf::Proto
+    #[cfg(feature = "protobuf")]
+    #[parse(ident)]
+    pub proto: Option<syn::Ident>,
 }
 
 /// Representation of an enum implementing [`Event`] (and [`event::Revisable`],
@@ -22,12 +57,22 @@ pub struct Attrs {
     impl_event,
     impl_event_revisable,
     impl_event_sourced,
-    gen_uniqueness_assertion
+    impl_stream_categorized,
+    gen_uniqueness_assertion,
+    gen_naming_assertion
 ))]
+#[cfg_attr(feature = "cloudevents", to_tokens(append(impl_cloudevents)))]
+#[cfg_attr(feature = "protobuf", to_tokens(append(impl_proto)))]
+#[cfg_attr(feature = "registry", to_tokens(append(impl_registry)))]
 #[cfg_attr(
     feature = "reflect",
-    to_tokens(append(impl_reflect_static, impl_reflect_concrete))
+    to_tokens(append(
+        impl_reflect_static,
+        impl_reflect_concrete,
+        impl_reflect_compatible
+    ))
 )]
+#[cfg_attr(feature = "proptest", to_tokens(append(impl_arbitrary)))]
 pub struct Definition {
     /// [`syn::Ident`](struct@syn::Ident) of this enum's type.
     pub ident: syn::Ident,
@@ -44,6 +89,23 @@ pub struct Definition {
 
     /// Indicator whether this enum should implement [`event::Revisable`].
     pub is_revisable: bool,
+
+    /// Prefix prepended to all the nested [`event::Name`]s, if any.
+    pub prefix: Option<syn::LitStr>,
+
+    /// [`NamingPolicy`] all the nested [`event::Name`]s are checked against.
+    pub naming_policy: NamingPolicy,
+
+    /// Value of the [`stream::Categorized::CATEGORY`] constant in the
+    /// generated code, if any.
+    pub stream_category: Option<syn::LitStr>,
+
+    /// Protobuf tags of the [`Variant`]s, if this enum should implement
+    /// [`protobuf::Proto`].
+    ///
+    /// [`protobuf::Proto`]: arcane_core::es::protobuf::Proto
+    #[cfg(feature = "protobuf")]
+    pub proto_tags: Option<Vec<u32>>,
 }
 
 impl TryFrom<syn::DeriveInput> for Definition {
@@ -73,12 +135,63 @@ impl TryFrom<syn::DeriveInput> for Definition {
 
         let has_ignored_variants = variants.len() < data.variants.len();
 
+        if let Some(prefix) = &attrs.prefix {
+            // Only the last segment may be empty, as it's continued by the
+            // names of the variants.
+            let value = prefix.value();
+            let mut segments = value.split('.').rev();
+            _ = segments.next();
+            if value.is_empty()
+                || value.contains(|c: char| c.is_whitespace() || c.is_control())
+                || segments.any(str::is_empty)
+            {
+                return Err(syn::Error::new(
+                    prefix.span(),
+                    "`prefix` must be non-empty and contain no whitespaces \
+                     or empty segments",
+                ));
+            }
+        }
+        let naming_policy =
+            NamingPolicy::parse(attrs.case.as_ref(), attrs.max_len.as_ref())?;
+
+        if let Some(category) = &attrs.stream {
+            let value = category.value();
+            if value.is_empty()
+                || value.contains(|c: char| c == '-' || c.is_whitespace())
+            {
+                return Err(syn::Error::new(
+                    category.span(),
+                    "`stream` must be non-empty and contain no `-` or \
+                     whitespaces",
+                ));
+            }
+        }
+
+        #[cfg(feature = "protobuf")]
+        let proto_tags = attrs
+            .proto
+            .as_ref()
+            .map(|_| {
+                proto::parse_tags(
+                    variants
+                        .iter()
+                        .map(|v| (v.ident.span(), v.proto_tag.clone())),
+                )
+            })
+            .transpose()?;
+
         Ok(Self {
             ident: input.ident,
             generics: input.generics,
             variants,
             has_ignored_variants,
             is_revisable: attrs.revision.is_some(),
+            prefix: attrs.prefix,
+            naming_policy,
+            stream_category: attrs.stream,
+            #[cfg(feature = "protobuf")]
+            proto_tags,
         })
     }
 }
@@ -127,16 +240,16 @@ impl Definition {
         let ty = &self.ident;
         let (impl_gens, ty_gens, where_clause) = self.generics.split_for_impl();
 
-        let var_ident = self.variants.iter().map(|v| &v.ident);
+        let var_ident =
+            self.variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
 
         let unreachable_arm = self.has_ignored_variants.then(|| {
             quote! { _ => unreachable!(), }
         });
 
-        quote! {
-            #[automatically_derived]
-            impl #impl_gens ::arcane::es::Event for #ty #ty_gens #where_clause {
-                fn name(&self) -> ::arcane::es::event::Name {
+        let name = self.prefix.as_ref().map_or_else(
+            || {
+                quote! {
                     match self {
                         #(
                             Self::#var_ident(f) => ::arcane::es::Event::name(f),
@@ -144,6 +257,41 @@ impl Definition {
                         #unreachable_arm
                     }
                 }
+            },
+            |prefix| {
+                let var_ty = self.variants.iter().map(|v| &v.ty);
+                let subst_gen_types =
+                    Self::shadow_generics_trivially(&self.generics);
+
+                quote! {
+                    match self {
+                        #(
+                            Self::#var_ident(_) => {
+                                const NAME: ::arcane::es::event::Name = {
+                                    #subst_gen_types
+                                    ::arcane::es::event::codegen
+                                        ::const_prefix_names!(
+                                            #prefix,
+                                            &[<#var_ty
+                                               as ::arcane::es::event::Static>
+                                                ::NAME],
+                                        )[0]
+                                };
+                                NAME
+                            }
+                        )*
+                        #unreachable_arm
+                    }
+                }
+            },
+        );
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::Event for #ty #ty_gens #where_clause {
+                fn name(&self) -> ::arcane::es::event::Name {
+                    #name
+                }
             }
         }
     }
@@ -269,6 +417,27 @@ impl Definition {
         }
     }
 
+    /// Generates code of a [`stream::Categorized`] trait implementation, if
+    /// the `stream` argument is specified.
+    #[must_use]
+    pub fn impl_stream_categorized(&self) -> TokenStream {
+        let Some(category) = &self.stream_category else {
+            return TokenStream::new();
+        };
+
+        let ty = &self.ident;
+        let (impl_gens, ty_gens, where_clause) = self.generics.split_for_impl();
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::stream::Categorized for #ty #ty_gens
+                 #where_clause
+            {
+                const CATEGORY: ::arcane::es::stream::Category = #category;
+            }
+        }
+    }
+
     #[cfg(feature = "reflect")]
     /// Generates code of an [`event::reflect::Static`] trait implementation.
     #[must_use]
@@ -280,6 +449,25 @@ impl Definition {
 
         let subst_gen_types = Self::shadow_generics_trivially(&self.generics);
 
+        let names = quote! {
+            ::arcane::es::event::codegen::const_concat_slices!(
+                #(
+                    <#var_ty as ::arcane::es::event::reflect::Static>::NAMES,
+                )*
+            )
+        };
+        let names = self.prefix.as_ref().map_or_else(
+            || names.clone(),
+            |prefix| {
+                quote! {
+                    ::arcane::es::event::codegen::const_prefix_names!(
+                        #prefix,
+                        #names,
+                    )
+                }
+            },
+        );
+
         quote! {
             #[automatically_derived]
             impl #impl_gens ::arcane::es::event::reflect::Static
@@ -287,13 +475,13 @@ impl Definition {
             {
                 const NAMES: &'static [::arcane::es::event::Name] = {
                     #subst_gen_types
-                    ::arcane::es::event::codegen::const_concat_slices!(
-                        #(
-                            <#var_ty as ::arcane::es::event::reflect::Static>
-                                ::NAMES,
-                        )*
-                    )
+                    #names
                 };
+
+                const ALIASES: &'static [(
+                    ::arcane::es::event::Name,
+                    ::arcane::es::event::Name,
+                )] = <Self as ::arcane::es::event::codegen::Reflect>::ALIASES;
             }
         }
     }
@@ -334,6 +522,417 @@ impl Definition {
         }
     }
 
+    #[cfg(feature = "reflect")]
+    /// Generates code of an [`event::reflect::Compatible`] trait
+    /// implementation.
+    #[must_use]
+    pub fn impl_reflect_compatible(&self) -> TokenStream {
+        if !self.is_revisable {
+            return TokenStream::new();
+        }
+
+        let ty = &self.ident;
+        let (impl_gens, ty_gens, where_clause) = self.generics.split_for_impl();
+
+        let var_ty = self.variants.iter().map(|f| &f.ty);
+
+        let subst_gen_types = Self::shadow_generics_trivially(&self.generics);
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::event::reflect::Compatible
+             for #ty #ty_gens #where_clause
+            {
+                const SUPPORTED_REVISIONS: &'static [
+                    ::arcane::es::event::VersionRange
+                ] = {
+                    #subst_gen_types
+                    ::arcane::es::event::codegen::const_concat_slices!(
+                        #(
+                            <#var_ty
+                             as ::arcane::es::event::reflect::Compatible>
+                                ::SUPPORTED_REVISIONS,
+                        )*
+                    )
+                };
+            }
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    /// Generates code of [`event::arbitrary::Arbitrary`] and
+    /// `proptest::arbitrary::Arbitrary` trait implementations, by generating
+    /// each enum variant, which is expected to be itself a
+    /// `proptest::arbitrary::Arbitrary` implementer.
+    ///
+    /// Bounds are [`higher_ranked()`].
+    #[must_use]
+    pub fn impl_arbitrary(&self) -> TokenStream {
+        let ty = &self.ident;
+        let (impl_gens, ty_gens, where_clause) = self.generics.split_for_impl();
+
+        let where_clause = {
+            let mut clause =
+                where_clause.cloned().unwrap_or_else(|| parse_quote! { where });
+            clause.predicates.push(higher_ranked(
+                &quote! { Self },
+                &quote! { ::std::fmt::Debug + 'static },
+            ));
+            for v in &self.variants {
+                let var_ty = &v.ty;
+
+                clause.predicates.push(higher_ranked(
+                    &quote! { #var_ty },
+                    &quote! { ::arcane::es::event::arbitrary::proptest
+                    ::arbitrary::Arbitrary + 'static },
+                ));
+            }
+            clause
+        };
+
+        let strategies = |sourcing: VariantEventSourcing| {
+            let vars = self
+                .variants
+                .iter()
+                .filter(move |v| v.sourcing == sourcing)
+                .map(|v| {
+                    let (var, var_ty) = (&v.ident, &v.ty);
+                    quote! {
+                        ::arcane::es::event::arbitrary::variant::<
+                            #var_ty, Self,
+                        >(Self::#var),
+                    }
+                });
+            quote! {
+                ::arcane::es::event::arbitrary::one_of(::std::vec![#( #vars )*])
+            }
+        };
+        let initial = strategies(VariantEventSourcing::Initialized);
+        let sourced = strategies(VariantEventSourcing::Sourced);
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::event::arbitrary::Arbitrary
+             for #ty #ty_gens #where_clause
+            {
+                fn initial() -> ::std::option::Option<
+                    ::arcane::es::event::arbitrary::proptest::strategy
+                        ::BoxedStrategy<Self>
+                > {
+                    #initial
+                }
+
+                fn sourced() -> ::std::option::Option<
+                    ::arcane::es::event::arbitrary::proptest::strategy
+                        ::BoxedStrategy<Self>
+                > {
+                    #sourced
+                }
+            }
+
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::event::arbitrary::proptest::arbitrary
+                 ::Arbitrary for #ty #ty_gens #where_clause
+            {
+                type Parameters = ();
+                type Strategy = ::arcane::es::event::arbitrary::proptest
+                    ::strategy::BoxedStrategy<Self>;
+
+                fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+                    ::arcane::es::event::arbitrary::any_event::<Self>()
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "cloudevents")]
+    /// Generates code of [`cloudevents::Encode`] and [`cloudevents::Decode`]
+    /// trait implementations, by encoding the enum variant, or trying to
+    /// decode each enum variant, which is expected to be itself a
+    /// [`cloudevents::Encode`] and [`cloudevents::Decode`] implementer.
+    ///
+    /// Bounds are [`higher_ranked()`].
+    ///
+    /// [`cloudevents::Decode`]: arcane_core::es::cloudevents::Decode
+    /// [`cloudevents::Encode`]: arcane_core::es::cloudevents::Encode
+    #[must_use]
+    pub fn impl_cloudevents(&self) -> TokenStream {
+        let ty = &self.ident;
+        let (impl_gens, ty_gens, _) = self.generics.split_for_impl();
+
+        let mut encode_generics = self.generics.clone();
+        let encode_clause = encode_generics.make_where_clause();
+        for v in &self.variants {
+            let var_ty = &v.ty;
+            encode_clause.predicates.push(higher_ranked(
+                &quote! { #var_ty },
+                &quote! { ::arcane::es::cloudevents::Encode },
+            ));
+        }
+        let encode_where_clause = &encode_generics.where_clause;
+
+        let unreachable_arm = self.has_ignored_variants.then(|| {
+            quote! { _ => ::std::unreachable!(), }
+        });
+        let encode_arms = self.variants.iter().map(|v| {
+            let (var_ident, var_ty) = (&v.ident, &v.ty);
+            quote! {
+                Self::#var_ident(ev) => {
+                    <#var_ty as ::arcane::es::cloudevents::Encode>::encode(ev)
+                }
+            }
+        });
+        let encode = quote! {
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::cloudevents::Encode
+             for #ty #ty_gens #encode_where_clause
+            {
+                fn encode(&self) -> ::std::result::Result<
+                    ::arcane::es::cloudevents::serde_json::Value,
+                    ::arcane::es::cloudevents::serde_json::Error,
+                > {
+                    match self {
+                        #( #encode_arms )*
+                        #unreachable_arm
+                    }
+                }
+            }
+        };
+
+        let mut generics = self.generics.clone();
+        let clause = generics.make_where_clause();
+        for v in &self.variants {
+            let var_ty = &v.ty;
+            clause.predicates.push(higher_ranked(
+                &quote! { #var_ty },
+                &quote! { ::arcane::es::cloudevents::Decode },
+            ));
+        }
+        let where_clause = &generics.where_clause;
+
+        let prefix = self.prefix.as_ref().map(|p| {
+            quote! { let ty = ty.strip_prefix(#p)?; }
+        });
+        let var_ident = self.variants.iter().map(|v| &v.ident);
+        let var_ty = self.variants.iter().map(|v| &v.ty);
+
+        quote! {
+            #encode
+
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::cloudevents::Decode
+             for #ty #ty_gens #where_clause
+            {
+                fn decode(
+                    ty: &::std::primitive::str,
+                    revision: ::arcane::es::event::Version,
+                    data: &::arcane::es::cloudevents::serde_json::Value,
+                ) -> ::std::option::Option<::std::result::Result<
+                    Self,
+                    ::arcane::es::cloudevents::serde_json::Error,
+                >> {
+                    #prefix
+                    #(
+                        if let ::std::option::Option::Some(ev) =
+                            <#var_ty as ::arcane::es::cloudevents::Decode>
+                                ::decode(ty, revision, data)
+                        {
+                            return ::std::option::Option::Some(
+                                ev.map(Self::#var_ident),
+                            );
+                        }
+                    )*
+                    ::std::option::Option::None
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "protobuf")]
+    /// Generates code of a [`protobuf::Proto`] trait implementation, if the
+    /// enum is marked with `#[event(proto)]` attribute, by representing each
+    /// enum variant, which is expected to be itself a [`protobuf::Proto`]
+    /// implementer, as a field of a `oneof event`.
+    ///
+    /// Tags of the `oneof event` fields are assigned in the declaration order
+    /// of the non-ignored enum variants.
+    ///
+    /// [`protobuf::Proto`]: arcane_core::es::protobuf::Proto
+    #[must_use]
+    pub fn impl_proto(&self) -> TokenStream {
+        let Some(var_tag) = &self.proto_tags else {
+            return TokenStream::new();
+        };
+
+        let ty = &self.ident;
+        let (impl_gens, ty_gens, _) = self.generics.split_for_impl();
+
+        let mut generics = self.generics.clone();
+        let clause = generics.make_where_clause();
+        for v in &self.variants {
+            let var_ty = &v.ty;
+            clause.predicates.push(parse_quote! {
+                #var_ty: ::arcane::es::protobuf::Proto
+            });
+        }
+        let where_clause = &generics.where_clause;
+
+        let message = ty.to_string();
+        let var_ident =
+            self.variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
+        let var_ty = self.variants.iter().map(|v| &v.ty).collect::<Vec<_>>();
+        let var_field = self
+            .variants
+            .iter()
+            .map(|v| proto::snake_case(&v.ident.to_string()));
+
+        let unreachable_arm = self.has_ignored_variants.then(|| {
+            quote! { _ => ::std::unreachable!(), }
+        });
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::protobuf::Proto
+             for #ty #ty_gens #where_clause
+            {
+                const MESSAGE: &'static ::std::primitive::str = #message;
+
+                fn definitions(
+                    defs: &mut ::std::vec::Vec<::std::string::String>,
+                ) {
+                    #(
+                        <#var_ty as ::arcane::es::protobuf::Proto>
+                            ::definitions(defs);
+                    )*
+                    ::arcane::es::protobuf::define_oneof(defs, #message, &[#(
+                        (
+                            <#var_ty as ::arcane::es::protobuf::Proto>::MESSAGE,
+                            #var_field,
+                            #var_tag,
+                        ),
+                    )*]);
+                }
+
+                fn to_proto(&self) -> ::std::vec::Vec<::std::primitive::u8> {
+                    match self {
+                        #(
+                            Self::#var_ident(ev) => {
+                                ::arcane::es::protobuf::encode_oneof(
+                                    #var_tag,
+                                    &<#var_ty as ::arcane::es::protobuf::Proto>
+                                        ::to_proto(ev),
+                                )
+                            }
+                        )*
+                        #unreachable_arm
+                    }
+                }
+
+                fn from_proto(
+                    bytes: &[::std::primitive::u8],
+                ) -> ::std::result::Result<
+                    Self,
+                    ::arcane::es::protobuf::prost::DecodeError,
+                > {
+                    ::arcane::es::protobuf::decode_oneof(bytes, |tag, msg| {
+                        match tag {
+                            #(
+                                #var_tag => ::std::option::Option::Some(
+                                    <#var_ty as ::arcane::es::protobuf::Proto>
+                                        ::from_proto(msg)
+                                        .map(Self::#var_ident),
+                                ),
+                            )*
+                            _ => ::std::option::Option::None,
+                        }
+                    })
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "registry")]
+    /// Generates code of an [`event::registry::Register`] trait
+    /// implementation, by registering each enum variant, which is expected to
+    /// be itself an [`event::registry::Register`] implementer (or an
+    /// [`event::Concrete`] one, if it's an `#[event(init)]` variant).
+    ///
+    /// Bounds are [`higher_ranked()`].
+    #[must_use]
+    pub fn impl_registry(&self) -> TokenStream {
+        let ty = &self.ident;
+        let (_, ty_gens, _) = self.generics.split_for_impl();
+
+        let mut ext_gens = self.generics.clone();
+        ext_gens.params.push(parse_quote! { __S });
+        let clause = ext_gens.make_where_clause();
+        for v in &self.variants {
+            let var_ty = &v.ty;
+            match v.sourcing {
+                VariantEventSourcing::Initialized => {
+                    clause.predicates.push(higher_ranked(
+                        &quote! { #var_ty },
+                        &quote! { ::arcane::es::event::Concrete
+                        + ::arcane::es::event::Revisable<
+                              Revision = ::arcane::es::event::Version
+                          >
+                        + ::arcane::es::event::registry::serde::de
+                          ::DeserializeOwned
+                        + ::std::marker::Send
+                        + 'static },
+                    ));
+                    clause.predicates.push(parse_quote! {
+                        ::std::option::Option<__S>:
+                            ::arcane::es::event::Sourced<
+                                ::arcane::es::event::Initial<#var_ty>
+                            >
+                    });
+                }
+                VariantEventSourcing::Sourced => {
+                    clause.predicates.push(parse_quote! {
+                        #var_ty: ::arcane::es::event::registry::Register<__S>
+                    });
+                }
+            }
+        }
+        let (impl_gens, _, where_clause) = ext_gens.split_for_impl();
+
+        let prefix = self.prefix.as_ref().map(|p| {
+            quote! { let prefix = &::std::format!("{prefix}{}", #p); }
+        });
+        let registrations = self.variants.iter().map(|v| {
+            let var_ty = &v.ty;
+            match v.sourcing {
+                VariantEventSourcing::Initialized => quote! {
+                    _ = registry.insert::<
+                        #var_ty, ::arcane::es::event::Initial<#var_ty>
+                    >(prefix);
+                },
+                VariantEventSourcing::Sourced => quote! {
+                    <#var_ty as ::arcane::es::event::registry::Register<__S>>
+                        ::register(registry, prefix);
+                },
+            }
+        });
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::event::registry::Register<__S>
+             for #ty #ty_gens #where_clause
+            {
+                fn register<__F: ::arcane::es::event::registry::Format>(
+                    registry: &mut ::arcane::es::event::registry::Registry<
+                        __S, __F
+                    >,
+                    prefix: &::std::primitive::str,
+                ) {
+                    #prefix
+                    #( #registrations )*
+                }
+            }
+        }
+    }
+
     /// Generates non-public machinery code used to statically check whether all
     /// the [`Event::name`]s and [`event::Revisable::revision`]s pairs
     /// correspond to a single Rust type.
@@ -342,7 +941,7 @@ impl Definition {
         let ty = &self.ident;
         let (impl_gens, ty_gens, where_clause) = self.generics.split_for_impl();
 
-        let var_ty = self.variants.iter().map(|f| &f.ty);
+        let var_ty = self.variants.iter().map(|f| &f.ty).collect::<Vec<_>>();
 
         // TODO: Use `has_different_types_with_same_name_and_ver` inside impl
         //       instead of type params substitution, once rust-lang/rust#57775
@@ -351,6 +950,27 @@ impl Definition {
         let subst_gen_types = Self::shadow_generics_trivially(&self.generics);
 
         let codegen = quote! { ::arcane::es::event::codegen };
+        let meta = quote! {
+            #codegen ::const_concat_slices!(
+                #( <#var_ty as #codegen ::Reflect>::META, )*
+            )
+        };
+        let meta = self.prefix.as_ref().map_or_else(
+            || meta.clone(),
+            |prefix| quote! { #codegen ::const_prefix_meta!(#prefix, #meta) },
+        );
+        let aliases = quote! {
+            #codegen ::const_concat_aliases!(
+                #( <#var_ty as #codegen ::Reflect>::ALIASES, )*
+            )
+        };
+        let aliases = self.prefix.as_ref().map_or_else(
+            || aliases.clone(),
+            |prefix| {
+                quote! { #codegen ::const_prefix_aliases!(#prefix, #aliases) }
+            },
+        );
+
         quote! {
             #[automatically_derived]
             #[doc(hidden)]
@@ -360,9 +980,13 @@ impl Definition {
                     (&'static str, &'static str, &'static str)
                 ] = {
                     #subst_gen_types
-                    #codegen ::const_concat_slices!(
-                        #( <#var_ty as #codegen ::Reflect>::META, )*
-                    )
+                    #meta
+                };
+
+                #[doc(hidden)]
+                const ALIASES: &'static [(&'static str, &'static str)] = {
+                    #subst_gen_types
+                    #aliases
                 };
             }
 
@@ -376,6 +1000,43 @@ impl Definition {
             );
         }
     }
+
+    /// Generates non-public machinery code used to statically check whether
+    /// all the nested [`Event::name`]s conform to the [`event::name::Policy`].
+    ///
+    /// Generates nothing if no [`event::name::Policy`] is specified, so the
+    /// naming checks remain opt-in. [`event::Static::ALIASES`] are not
+    /// checked, as they're allowed to follow some former naming conventions.
+    #[must_use]
+    pub fn gen_naming_assertion(&self) -> TokenStream {
+        if self.naming_policy.is_basic() {
+            return TokenStream::new();
+        }
+
+        let ty = &self.ident;
+        let ty_subst_gens = Self::substitute_generics_trivially(&self.generics);
+        let policy = self.naming_policy.expand();
+
+        quote! {
+            #[automatically_derived]
+            #[doc(hidden)]
+            const _: () = {
+                let policy = #policy;
+                let meta = <#ty #ty_subst_gens
+                            as ::arcane::es::event::codegen::Reflect>::META;
+                let mut i = 0;
+                while i < meta.len() {
+                    let name = meta[i].1;
+                    if !::arcane::es::event::codegen::is_alias::<
+                        #ty #ty_subst_gens
+                    >(name) {
+                        policy.assert(name);
+                    }
+                    i += 1;
+                }
+            };
+        }
+    }
 }
 
 /// Attributes of `#[derive(Event)]` macro placed on a [`Variant`].
@@ -389,10 +1050,15 @@ pub struct VariantAttrs {
     /// Indicator whether to ignore this enum variant for code generation.
     #[parse(ident, alias = skip)]
     pub ignore: Option<syn::Ident>,
+
+    /// [`proto::Attrs`] of this enum variant.
+    #[cfg(feature = "protobuf")]
+    #[parse(value)]
+    pub proto: Option<proto::Attrs>,
 }
 
 /// Type of event sourcing the [`Variant`] is using.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum VariantEventSourcing {
     /// [`Variant`] used as [`event::Initialized`].
     Initialized,
@@ -413,6 +1079,10 @@ pub struct Variant {
 
     /// [`VariantEventSourcing`] type of this [`Variant`].
     pub sourcing: VariantEventSourcing,
+
+    /// Explicit Protobuf tag of this [`Variant`], if any.
+    #[cfg(feature = "protobuf")]
+    pub proto_tag: Option<syn::LitInt>,
 }
 
 impl Variant {
@@ -470,6 +1140,8 @@ impl Variant {
             ident: variant.ident.clone(),
             ty: field.ty.clone(),
             sourcing,
+            #[cfg(feature = "protobuf")]
+            proto_tag: attrs.proto.map(|p| p.tag),
         }))
     }
 }
@@ -549,6 +1221,16 @@ mod spec {
                          as ::arcane::es::event::codegen::Reflect>::META,
                     )
                 };
+
+                #[doc(hidden)]
+                const ALIASES: &'static [(&'static str, &'static str)] = {
+                    ::arcane::es::event::codegen::const_concat_aliases!(
+                        <FileEvent
+                         as ::arcane::es::event::codegen::Reflect>::ALIASES,
+                        <ChatEvent
+                         as ::arcane::es::event::codegen::Reflect>::ALIASES,
+                    )
+                };
             }
 
             #[automatically_derived]
@@ -561,6 +1243,101 @@ mod spec {
                  and revision inside a single enum is forbidden",
             );
         };
+        if cfg!(feature = "cloudevents") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl ::arcane::es::cloudevents::Encode for Event
+                where
+                    for<'__a> FileEvent: ::arcane::es::cloudevents::Encode,
+                    for<'__a> ChatEvent: ::arcane::es::cloudevents::Encode
+                {
+                    fn encode(&self) -> ::std::result::Result<
+                        ::arcane::es::cloudevents::serde_json::Value,
+                        ::arcane::es::cloudevents::serde_json::Error,
+                    > {
+                        match self {
+                        Self::File(ev) => {
+                            <FileEvent as ::arcane::es::cloudevents::Encode>
+                                ::encode(ev)
+                        }
+                        Self::Chat(ev) => {
+                            <ChatEvent as ::arcane::es::cloudevents::Encode>
+                                ::encode(ev)
+                        }
+                        }
+                    }
+                }
+
+                #[automatically_derived]
+                impl ::arcane::es::cloudevents::Decode for Event
+                where
+                    for<'__a> FileEvent: ::arcane::es::cloudevents::Decode,
+                    for<'__a> ChatEvent: ::arcane::es::cloudevents::Decode
+                {
+                    fn decode(
+                        ty: &::std::primitive::str,
+                        revision: ::arcane::es::event::Version,
+                        data: &::arcane::es::cloudevents::serde_json::Value,
+                    ) -> ::std::option::Option<::std::result::Result<
+                        Self,
+                        ::arcane::es::cloudevents::serde_json::Error,
+                    >> {
+                        if let ::std::option::Option::Some(ev) =
+                            <FileEvent as ::arcane::es::cloudevents::Decode>
+                                ::decode(ty, revision, data)
+                        {
+                            return ::std::option::Option::Some(
+                                ev.map(Self::File),
+                            );
+                        }
+                        if let ::std::option::Option::Some(ev) =
+                            <ChatEvent as ::arcane::es::cloudevents::Decode>
+                                ::decode(ty, revision, data)
+                        {
+                            return ::std::option::Option::Some(
+                                ev.map(Self::Chat),
+                            );
+                        }
+                        ::std::option::Option::None
+                    }
+                }
+            }]);
+        }
+        if cfg!(feature = "registry") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl<__S> ::arcane::es::event::registry::Register<__S>
+                 for Event
+                where
+                    for<'__a> FileEvent: ::arcane::es::event::Concrete
+                      + ::arcane::es::event::Revisable<
+                            Revision = ::arcane::es::event::Version
+                        >
+                      + ::arcane::es::event::registry::serde::de
+                        ::DeserializeOwned
+                      + ::std::marker::Send
+                      + 'static,
+                    ::std::option::Option<__S>: ::arcane::es::event::Sourced<
+                        ::arcane::es::event::Initial<FileEvent>
+                    >,
+                    ChatEvent: ::arcane::es::event::registry::Register<__S>
+                {
+                    fn register<__F: ::arcane::es::event::registry::Format>(
+                        registry: &mut ::arcane::es::event::registry::Registry<
+                            __S, __F
+                        >,
+                        prefix: &::std::primitive::str,
+                    ) {
+                        _ = registry.insert::<
+                            FileEvent, ::arcane::es::event::Initial<FileEvent>
+                        >(prefix);
+                        <ChatEvent as
+                            ::arcane::es::event::registry::Register<__S>>
+                            ::register(registry, prefix);
+                    }
+                }
+            }]);
+        }
         if cfg!(feature = "reflect") {
             output.extend([quote! {
                 #[automatically_derived]
@@ -573,6 +1350,72 @@ mod spec {
                              as ::arcane::es::event::reflect::Static>::NAMES,
                         )
                     };
+
+                    const ALIASES: &'static [(
+                        ::arcane::es::event::Name,
+                        ::arcane::es::event::Name,
+                    )] = <Self
+                          as ::arcane::es::event::codegen::Reflect>::ALIASES;
+                }
+            }]);
+        }
+
+        if cfg!(feature = "proptest") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl ::arcane::es::event::arbitrary::Arbitrary
+                 for Event
+                where
+                    for<'__a> Self: ::std::fmt::Debug + 'static,
+                    for<'__a> FileEvent: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static,
+                    for<'__a> ChatEvent: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static
+                {
+                    fn initial() -> ::std::option::Option<
+                        ::arcane::es::event::arbitrary::proptest::strategy
+                            ::BoxedStrategy<Self>
+                    > {
+                        ::arcane::es::event::arbitrary::one_of(
+                            ::std::vec![
+                                ::arcane::es::event::arbitrary::variant::<
+                                    FileEvent, Self,
+                                >(Self::File),
+                            ]
+                        )
+                    }
+
+                    fn sourced() -> ::std::option::Option<
+                        ::arcane::es::event::arbitrary::proptest::strategy
+                            ::BoxedStrategy<Self>
+                    > {
+                        ::arcane::es::event::arbitrary::one_of(
+                            ::std::vec![
+                                ::arcane::es::event::arbitrary::variant::<
+                                    ChatEvent, Self,
+                                >(Self::Chat),
+                            ]
+                        )
+                    }
+                }
+
+                #[automatically_derived]
+                impl ::arcane::es::event::arbitrary::proptest::arbitrary
+                     ::Arbitrary for Event
+                where
+                    for<'__a> Self: ::std::fmt::Debug + 'static,
+                    for<'__a> FileEvent: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static,
+                    for<'__a> ChatEvent: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static
+                {
+                    type Parameters = ();
+                    type Strategy = ::arcane::es::event::arbitrary::proptest
+                        ::strategy::BoxedStrategy<Self>;
+
+                    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+                        ::arcane::es::event::arbitrary::any_event::<Self>()
+                    }
                 }
             }]);
         }
@@ -667,6 +1510,16 @@ mod spec {
                          as ::arcane::es::event::codegen::Reflect>::META,
                     )
                 };
+
+                #[doc(hidden)]
+                const ALIASES: &'static [(&'static str, &'static str)] = {
+                    ::arcane::es::event::codegen::const_concat_aliases!(
+                        <FileEvent
+                         as ::arcane::es::event::codegen::Reflect>::ALIASES,
+                        <ChatEvent
+                         as ::arcane::es::event::codegen::Reflect>::ALIASES,
+                    )
+                };
             }
 
             #[automatically_derived]
@@ -679,6 +1532,101 @@ mod spec {
                  and revision inside a single enum is forbidden",
             );
         };
+        if cfg!(feature = "cloudevents") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl ::arcane::es::cloudevents::Encode for Event
+                where
+                    for<'__a> FileEvent: ::arcane::es::cloudevents::Encode,
+                    for<'__a> ChatEvent: ::arcane::es::cloudevents::Encode
+                {
+                    fn encode(&self) -> ::std::result::Result<
+                        ::arcane::es::cloudevents::serde_json::Value,
+                        ::arcane::es::cloudevents::serde_json::Error,
+                    > {
+                        match self {
+                        Self::File(ev) => {
+                            <FileEvent as ::arcane::es::cloudevents::Encode>
+                                ::encode(ev)
+                        }
+                        Self::Chat(ev) => {
+                            <ChatEvent as ::arcane::es::cloudevents::Encode>
+                                ::encode(ev)
+                        }
+                        }
+                    }
+                }
+
+                #[automatically_derived]
+                impl ::arcane::es::cloudevents::Decode for Event
+                where
+                    for<'__a> FileEvent: ::arcane::es::cloudevents::Decode,
+                    for<'__a> ChatEvent: ::arcane::es::cloudevents::Decode
+                {
+                    fn decode(
+                        ty: &::std::primitive::str,
+                        revision: ::arcane::es::event::Version,
+                        data: &::arcane::es::cloudevents::serde_json::Value,
+                    ) -> ::std::option::Option<::std::result::Result<
+                        Self,
+                        ::arcane::es::cloudevents::serde_json::Error,
+                    >> {
+                        if let ::std::option::Option::Some(ev) =
+                            <FileEvent as ::arcane::es::cloudevents::Decode>
+                                ::decode(ty, revision, data)
+                        {
+                            return ::std::option::Option::Some(
+                                ev.map(Self::File),
+                            );
+                        }
+                        if let ::std::option::Option::Some(ev) =
+                            <ChatEvent as ::arcane::es::cloudevents::Decode>
+                                ::decode(ty, revision, data)
+                        {
+                            return ::std::option::Option::Some(
+                                ev.map(Self::Chat),
+                            );
+                        }
+                        ::std::option::Option::None
+                    }
+                }
+            }]);
+        }
+        if cfg!(feature = "registry") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl<__S> ::arcane::es::event::registry::Register<__S>
+                 for Event
+                where
+                    for<'__a> FileEvent: ::arcane::es::event::Concrete
+                      + ::arcane::es::event::Revisable<
+                            Revision = ::arcane::es::event::Version
+                        >
+                      + ::arcane::es::event::registry::serde::de
+                        ::DeserializeOwned
+                      + ::std::marker::Send
+                      + 'static,
+                    ::std::option::Option<__S>: ::arcane::es::event::Sourced<
+                        ::arcane::es::event::Initial<FileEvent>
+                    >,
+                    ChatEvent: ::arcane::es::event::registry::Register<__S>
+                {
+                    fn register<__F: ::arcane::es::event::registry::Format>(
+                        registry: &mut ::arcane::es::event::registry::Registry<
+                            __S, __F
+                        >,
+                        prefix: &::std::primitive::str,
+                    ) {
+                        _ = registry.insert::<
+                            FileEvent, ::arcane::es::event::Initial<FileEvent>
+                        >(prefix);
+                        <ChatEvent as
+                            ::arcane::es::event::registry::Register<__S>>
+                            ::register(registry, prefix);
+                    }
+                }
+            }]);
+        }
         if cfg!(feature = "reflect") {
             output.extend([quote! {
                 #[automatically_derived]
@@ -691,6 +1639,12 @@ mod spec {
                              as ::arcane::es::event::reflect::Static>::NAMES,
                         )
                     };
+
+                    const ALIASES: &'static [(
+                        ::arcane::es::event::Name,
+                        ::arcane::es::event::Name,
+                    )] = <Self
+                          as ::arcane::es::event::codegen::Reflect>::ALIASES;
                 }
 
                 #[automatically_derived]
@@ -704,6 +1658,82 @@ mod spec {
                         )
                     };
                 }
+
+                #[automatically_derived]
+                impl ::arcane::es::event::reflect::Compatible for Event {
+                    const SUPPORTED_REVISIONS: &'static [
+                        ::arcane::es::event::VersionRange
+                    ] = {
+                        ::arcane::es::event::codegen::const_concat_slices!(
+                            <FileEvent as
+                             ::arcane::es::event::reflect::Compatible>
+                                ::SUPPORTED_REVISIONS,
+                            <ChatEvent as
+                             ::arcane::es::event::reflect::Compatible>
+                                ::SUPPORTED_REVISIONS,
+                        )
+                    };
+                }
+            }]);
+        }
+
+        if cfg!(feature = "proptest") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl ::arcane::es::event::arbitrary::Arbitrary
+                 for Event
+                where
+                    for<'__a> Self: ::std::fmt::Debug + 'static,
+                    for<'__a> FileEvent: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static,
+                    for<'__a> ChatEvent: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static
+                {
+                    fn initial() -> ::std::option::Option<
+                        ::arcane::es::event::arbitrary::proptest::strategy
+                            ::BoxedStrategy<Self>
+                    > {
+                        ::arcane::es::event::arbitrary::one_of(
+                            ::std::vec![
+                                ::arcane::es::event::arbitrary::variant::<
+                                    FileEvent, Self,
+                                >(Self::File),
+                            ]
+                        )
+                    }
+
+                    fn sourced() -> ::std::option::Option<
+                        ::arcane::es::event::arbitrary::proptest::strategy
+                            ::BoxedStrategy<Self>
+                    > {
+                        ::arcane::es::event::arbitrary::one_of(
+                            ::std::vec![
+                                ::arcane::es::event::arbitrary::variant::<
+                                    ChatEvent, Self,
+                                >(Self::Chat),
+                            ]
+                        )
+                    }
+                }
+
+                #[automatically_derived]
+                impl ::arcane::es::event::arbitrary::proptest::arbitrary
+                     ::Arbitrary for Event
+                where
+                    for<'__a> Self: ::std::fmt::Debug + 'static,
+                    for<'__a> FileEvent: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static,
+                    for<'__a> ChatEvent: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static
+                {
+                    type Parameters = ();
+                    type Strategy = ::arcane::es::event::arbitrary::proptest
+                        ::strategy::BoxedStrategy<Self>;
+
+                    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+                        ::arcane::es::event::arbitrary::any_event::<Self>()
+                    }
+                }
             }]);
         }
 
@@ -805,6 +1835,19 @@ mod spec {
                          as ::arcane::es::event::codegen::Reflect>::META,
                     )
                 };
+
+                #[doc(hidden)]
+                const ALIASES: &'static [(&'static str, &'static str)] = {
+                    type F = ();
+                    type C = ();
+
+                    ::arcane::es::event::codegen::const_concat_aliases!(
+                        <FileEvent<'a, F>
+                         as ::arcane::es::event::codegen::Reflect>::ALIASES,
+                        <ChatEvent<'a, C>
+                         as ::arcane::es::event::codegen::Reflect>::ALIASES,
+                    )
+                };
             }
 
             #[automatically_derived]
@@ -817,6 +1860,113 @@ mod spec {
                  and revision inside a single enum is forbidden",
             );
         };
+        if cfg!(feature = "cloudevents") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl<'a, F, C> ::arcane::es::cloudevents::Encode
+                 for Event<'a, F, C>
+                where
+                    for<'__a> FileEvent<'a, F>:
+                        ::arcane::es::cloudevents::Encode,
+                    for<'__a> ChatEvent<'a, C>:
+                        ::arcane::es::cloudevents::Encode
+                {
+                    fn encode(&self) -> ::std::result::Result<
+                        ::arcane::es::cloudevents::serde_json::Value,
+                        ::arcane::es::cloudevents::serde_json::Error,
+                    > {
+                        match self {
+                        Self::File(ev) => {
+                            <FileEvent<'a, F> as
+                                ::arcane::es::cloudevents::Encode>
+                                ::encode(ev)
+                        }
+                        Self::Chat(ev) => {
+                            <ChatEvent<'a, C> as
+                                ::arcane::es::cloudevents::Encode>
+                                ::encode(ev)
+                        }
+                        }
+                    }
+                }
+
+                #[automatically_derived]
+                impl<'a, F, C> ::arcane::es::cloudevents::Decode
+                 for Event<'a, F, C>
+                where
+                    for<'__a> FileEvent<'a, F>:
+                        ::arcane::es::cloudevents::Decode,
+                    for<'__a> ChatEvent<'a, C>:
+                        ::arcane::es::cloudevents::Decode
+                {
+                    fn decode(
+                        ty: &::std::primitive::str,
+                        revision: ::arcane::es::event::Version,
+                        data: &::arcane::es::cloudevents::serde_json::Value,
+                    ) -> ::std::option::Option<::std::result::Result<
+                        Self,
+                        ::arcane::es::cloudevents::serde_json::Error,
+                    >> {
+                        if let ::std::option::Option::Some(ev) =
+                            <FileEvent<'a, F> as
+                                ::arcane::es::cloudevents::Decode>
+                                ::decode(ty, revision, data)
+                        {
+                            return ::std::option::Option::Some(
+                                ev.map(Self::File),
+                            );
+                        }
+                        if let ::std::option::Option::Some(ev) =
+                            <ChatEvent<'a, C> as
+                                ::arcane::es::cloudevents::Decode>
+                                ::decode(ty, revision, data)
+                        {
+                            return ::std::option::Option::Some(
+                                ev.map(Self::Chat),
+                            );
+                        }
+                        ::std::option::Option::None
+                    }
+                }
+            }]);
+        }
+        if cfg!(feature = "registry") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl<'a, F, C, __S> ::arcane::es::event::registry::Register<__S>
+                 for Event<'a, F, C>
+                where
+                    for<'__a> FileEvent<'a, F>: ::arcane::es::event::Concrete
+                      + ::arcane::es::event::Revisable<
+                            Revision = ::arcane::es::event::Version
+                        >
+                      + ::arcane::es::event::registry::serde::de
+                        ::DeserializeOwned
+                      + ::std::marker::Send
+                      + 'static,
+                    ::std::option::Option<__S>: ::arcane::es::event::Sourced<
+                        ::arcane::es::event::Initial<FileEvent<'a, F> >
+                    >,
+                    ChatEvent<'a, C>:
+                        ::arcane::es::event::registry::Register<__S>
+                {
+                    fn register<__F: ::arcane::es::event::registry::Format>(
+                        registry: &mut ::arcane::es::event::registry::Registry<
+                            __S, __F
+                        >,
+                        prefix: &::std::primitive::str,
+                    ) {
+                        _ = registry.insert::<
+                            FileEvent<'a, F>,
+                            ::arcane::es::event::Initial<FileEvent<'a, F> >
+                        >(prefix);
+                        <ChatEvent<'a, C> as
+                            ::arcane::es::event::registry::Register<__S>>
+                            ::register(registry, prefix);
+                    }
+                }
+            }]);
+        }
         if cfg!(feature = "reflect") {
             output.extend([quote! {
                 #[automatically_derived]
@@ -834,6 +1984,12 @@ mod spec {
                              as ::arcane::es::event::reflect::Static>::NAMES,
                         )
                     };
+
+                    const ALIASES: &'static [(
+                        ::arcane::es::event::Name,
+                        ::arcane::es::event::Name,
+                    )] = <Self
+                          as ::arcane::es::event::codegen::Reflect>::ALIASES;
                 }
 
                 #[automatically_derived]
@@ -852,6 +2008,87 @@ mod spec {
                         )
                     };
                 }
+
+                #[automatically_derived]
+                impl<'a, F, C> ::arcane::es::event::reflect::Compatible
+                 for Event<'a, F, C>
+                {
+                    const SUPPORTED_REVISIONS: &'static [
+                        ::arcane::es::event::VersionRange
+                    ] = {
+                        type F = ();
+                        type C = ();
+
+                        ::arcane::es::event::codegen::const_concat_slices!(
+                            <FileEvent<'a, F> as
+                             ::arcane::es::event::reflect::Compatible>
+                                ::SUPPORTED_REVISIONS,
+                            <ChatEvent<'a, C> as
+                             ::arcane::es::event::reflect::Compatible>
+                                ::SUPPORTED_REVISIONS,
+                        )
+                    };
+                }
+            }]);
+        }
+
+        if cfg!(feature = "proptest") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl<'a, F, C> ::arcane::es::event::arbitrary::Arbitrary
+                 for Event<'a, F, C>
+                where
+                    for<'__a> Self: ::std::fmt::Debug + 'static,
+                    for<'__a> FileEvent<'a, F>: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static,
+                    for<'__a> ChatEvent<'a, C>: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static
+                {
+                    fn initial() -> ::std::option::Option<
+                        ::arcane::es::event::arbitrary::proptest::strategy
+                            ::BoxedStrategy<Self>
+                    > {
+                        ::arcane::es::event::arbitrary::one_of(
+                            ::std::vec![
+                                ::arcane::es::event::arbitrary::variant::<
+                                    FileEvent<'a, F>, Self,
+                                >(Self::File),
+                            ]
+                        )
+                    }
+
+                    fn sourced() -> ::std::option::Option<
+                        ::arcane::es::event::arbitrary::proptest::strategy
+                            ::BoxedStrategy<Self>
+                    > {
+                        ::arcane::es::event::arbitrary::one_of(
+                            ::std::vec![
+                                ::arcane::es::event::arbitrary::variant::<
+                                    ChatEvent<'a, C>, Self,
+                                >(Self::Chat),
+                            ]
+                        )
+                    }
+                }
+
+                #[automatically_derived]
+                impl<'a, F, C> ::arcane::es::event::arbitrary::proptest
+                     ::arbitrary::Arbitrary for Event<'a, F, C>
+                where
+                    for<'__a> Self: ::std::fmt::Debug + 'static,
+                    for<'__a> FileEvent<'a, F>: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static,
+                    for<'__a> ChatEvent<'a, C>: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static
+                {
+                    type Parameters = ();
+                    type Strategy = ::arcane::es::event::arbitrary::proptest
+                        ::strategy::BoxedStrategy<Self>;
+
+                    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+                        ::arcane::es::event::arbitrary::any_event::<Self>()
+                    }
+                }
             }]);
         }
 
@@ -952,6 +2189,16 @@ mod spec {
                          as ::arcane::es::event::codegen::Reflect>::META,
                     )
                 };
+
+                #[doc(hidden)]
+                const ALIASES: &'static [(&'static str, &'static str)] = {
+                    ::arcane::es::event::codegen::const_concat_aliases!(
+                        <FileEvent
+                         as ::arcane::es::event::codegen::Reflect>::ALIASES,
+                        <ChatEvent
+                         as ::arcane::es::event::codegen::Reflect>::ALIASES,
+                    )
+                };
             }
 
             #[automatically_derived]
@@ -964,6 +2211,92 @@ mod spec {
                  and revision inside a single enum is forbidden",
             );
         };
+        if cfg!(feature = "cloudevents") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl ::arcane::es::cloudevents::Encode for Event
+                where
+                    for<'__a> FileEvent: ::arcane::es::cloudevents::Encode,
+                    for<'__a> ChatEvent: ::arcane::es::cloudevents::Encode
+                {
+                    fn encode(&self) -> ::std::result::Result<
+                        ::arcane::es::cloudevents::serde_json::Value,
+                        ::arcane::es::cloudevents::serde_json::Error,
+                    > {
+                        match self {
+                        Self::File(ev) => {
+                            <FileEvent as ::arcane::es::cloudevents::Encode>
+                                ::encode(ev)
+                        }
+                        Self::Chat(ev) => {
+                            <ChatEvent as ::arcane::es::cloudevents::Encode>
+                                ::encode(ev)
+                        }
+                        _ => ::std::unreachable!(),
+                        }
+                    }
+                }
+
+                #[automatically_derived]
+                impl ::arcane::es::cloudevents::Decode for Event
+                where
+                    for<'__a> FileEvent: ::arcane::es::cloudevents::Decode,
+                    for<'__a> ChatEvent: ::arcane::es::cloudevents::Decode
+                {
+                    fn decode(
+                        ty: &::std::primitive::str,
+                        revision: ::arcane::es::event::Version,
+                        data: &::arcane::es::cloudevents::serde_json::Value,
+                    ) -> ::std::option::Option<::std::result::Result<
+                        Self,
+                        ::arcane::es::cloudevents::serde_json::Error,
+                    >> {
+                        if let ::std::option::Option::Some(ev) =
+                            <FileEvent as ::arcane::es::cloudevents::Decode>
+                                ::decode(ty, revision, data)
+                        {
+                            return ::std::option::Option::Some(
+                                ev.map(Self::File),
+                            );
+                        }
+                        if let ::std::option::Option::Some(ev) =
+                            <ChatEvent as ::arcane::es::cloudevents::Decode>
+                                ::decode(ty, revision, data)
+                        {
+                            return ::std::option::Option::Some(
+                                ev.map(Self::Chat),
+                            );
+                        }
+                        ::std::option::Option::None
+                    }
+                }
+            }]);
+        }
+        if cfg!(feature = "registry") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl<__S> ::arcane::es::event::registry::Register<__S>
+                 for Event
+                where
+                    FileEvent: ::arcane::es::event::registry::Register<__S>,
+                    ChatEvent: ::arcane::es::event::registry::Register<__S>
+                {
+                    fn register<__F: ::arcane::es::event::registry::Format>(
+                        registry: &mut ::arcane::es::event::registry::Registry<
+                            __S, __F
+                        >,
+                        prefix: &::std::primitive::str,
+                    ) {
+                        <FileEvent
+                         as ::arcane::es::event::registry::Register<__S>>
+                            ::register(registry, prefix);
+                        <ChatEvent
+                         as ::arcane::es::event::registry::Register<__S>>
+                            ::register(registry, prefix);
+                    }
+                }
+            }]);
+        }
         if cfg!(feature = "reflect") {
             output.extend([quote! {
                 #[automatically_derived]
@@ -976,6 +2309,12 @@ mod spec {
                              as ::arcane::es::event::reflect::Static>::NAMES,
                         )
                     };
+
+                    const ALIASES: &'static [(
+                        ::arcane::es::event::Name,
+                        ::arcane::es::event::Name,
+                    )] = <Self
+                          as ::arcane::es::event::codegen::Reflect>::ALIASES;
                 }
 
                 #[automatically_derived]
@@ -989,6 +2328,82 @@ mod spec {
                         )
                     };
                 }
+
+                #[automatically_derived]
+                impl ::arcane::es::event::reflect::Compatible for Event {
+                    const SUPPORTED_REVISIONS: &'static [
+                        ::arcane::es::event::VersionRange
+                    ] = {
+                        ::arcane::es::event::codegen::const_concat_slices!(
+                            <FileEvent as
+                             ::arcane::es::event::reflect::Compatible>
+                                ::SUPPORTED_REVISIONS,
+                            <ChatEvent as
+                             ::arcane::es::event::reflect::Compatible>
+                                ::SUPPORTED_REVISIONS,
+                        )
+                    };
+                }
+            }]);
+        }
+
+        if cfg!(feature = "proptest") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl ::arcane::es::event::arbitrary::Arbitrary
+                 for Event
+                where
+                    for<'__a> Self: ::std::fmt::Debug + 'static,
+                    for<'__a> FileEvent: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static,
+                    for<'__a> ChatEvent: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static
+                {
+                    fn initial() -> ::std::option::Option<
+                        ::arcane::es::event::arbitrary::proptest::strategy
+                            ::BoxedStrategy<Self>
+                    > {
+                        ::arcane::es::event::arbitrary::one_of(
+                            ::std::vec![
+                            ]
+                        )
+                    }
+
+                    fn sourced() -> ::std::option::Option<
+                        ::arcane::es::event::arbitrary::proptest::strategy
+                            ::BoxedStrategy<Self>
+                    > {
+                        ::arcane::es::event::arbitrary::one_of(
+                            ::std::vec![
+                                ::arcane::es::event::arbitrary::variant::<
+                                    FileEvent, Self,
+                                >(Self::File),
+                                ::arcane::es::event::arbitrary::variant::<
+                                    ChatEvent, Self,
+                                >(Self::Chat),
+                            ]
+                        )
+                    }
+                }
+
+                #[automatically_derived]
+                impl ::arcane::es::event::arbitrary::proptest::arbitrary
+                     ::Arbitrary for Event
+                where
+                    for<'__a> Self: ::std::fmt::Debug + 'static,
+                    for<'__a> FileEvent: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static,
+                    for<'__a> ChatEvent: ::arcane::es::event::arbitrary
+                        ::proptest::arbitrary::Arbitrary + 'static
+                {
+                    type Parameters = ();
+                    type Strategy = ::arcane::es::event::arbitrary::proptest
+                        ::strategy::BoxedStrategy<Self>;
+
+                    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+                        ::arcane::es::event::arbitrary::any_event::<Self>()
+                    }
+                }
             }]);
         }
 
@@ -1074,4 +2489,324 @@ mod spec {
             "`init` and `ignore`/`skip` arguments are mutually exclusive",
         );
     }
+
+    #[cfg(feature = "reflect")]
+    #[test]
+    fn derives_prefixed_names() {
+        let input = parse_quote! {
+            #[event(prefix = "chat.")]
+            enum Event {
+                Created(Created),
+                Posted(Posted),
+            }
+        };
+        let def = Definition::try_from(
+            syn::parse2::<syn::DeriveInput>(input).expect("valid input"),
+        )
+        .expect("valid definition");
+
+        let event = quote! {
+            #[automatically_derived]
+            impl ::arcane::es::Event for Event {
+                fn name(&self) -> ::arcane::es::event::Name {
+                    match self {
+                        Self::Created(_) => {
+                            const NAME: ::arcane::es::event::Name = {
+                                ::arcane::es::event::codegen
+                                    ::const_prefix_names!(
+                                        "chat.",
+                                        &[<Created
+                                           as ::arcane::es::event::Static>
+                                            ::NAME],
+                                    )[0]
+                            };
+                            NAME
+                        }
+                        Self::Posted(_) => {
+                            const NAME: ::arcane::es::event::Name = {
+                                ::arcane::es::event::codegen
+                                    ::const_prefix_names!(
+                                        "chat.",
+                                        &[<Posted
+                                           as ::arcane::es::event::Static>
+                                            ::NAME],
+                                    )[0]
+                            };
+                            NAME
+                        }
+                    }
+                }
+            }
+        };
+        let names = quote! {
+            #[automatically_derived]
+            impl ::arcane::es::event::reflect::Static for Event {
+                const NAMES: &'static [::arcane::es::event::Name] = {
+                    ::arcane::es::event::codegen::const_prefix_names!(
+                        "chat.",
+                        ::arcane::es::event::codegen::const_concat_slices!(
+                            <Created as ::arcane::es::event::reflect::Static>
+                                ::NAMES,
+                            <Posted as ::arcane::es::event::reflect::Static>
+                                ::NAMES,
+                        ),
+                    )
+                };
+
+                const ALIASES: &'static [(
+                    ::arcane::es::event::Name,
+                    ::arcane::es::event::Name,
+                )] = <Self as ::arcane::es::event::codegen::Reflect>::ALIASES;
+            }
+        };
+
+        assert_eq!(def.impl_event().to_string(), event.to_string());
+        assert_eq!(def.impl_reflect_static().to_string(), names.to_string());
+        assert!(
+            def.gen_uniqueness_assertion()
+                .to_string()
+                .contains("const_prefix_meta ! (\"chat.\""),
+        );
+        #[cfg(feature = "cloudevents")]
+        assert!(
+            def.impl_cloudevents()
+                .to_string()
+                .contains("let ty = ty . strip_prefix (\"chat.\") ?"),
+        );
+        #[cfg(feature = "registry")]
+        assert!(def.impl_registry().to_string().contains(
+            "let prefix = & :: std :: format ! (\"{prefix}{}\" , \"chat.\")"
+        ),);
+    }
+
+    #[test]
+    fn derives_naming_policy_assertion() {
+        let input = parse_quote! {
+            #[event(case = "snake_case", max_len = 64)]
+            enum Event<F> {
+                Created(ChatCreated<F>),
+            }
+        };
+        let def = Definition::try_from(
+            syn::parse2::<syn::DeriveInput>(input).expect("valid input"),
+        )
+        .expect("valid definition");
+
+        let output = quote! {
+            #[automatically_derived]
+            #[doc(hidden)]
+            const _: () = {
+                let policy = ::arcane::es::event::name::Policy::new()
+                    .case(::arcane::es::event::name::Case::Snake)
+                    .max_len(64);
+                let meta = <Event<()>
+                            as ::arcane::es::event::codegen::Reflect>::META;
+                let mut i = 0;
+                while i < meta.len() {
+                    let name = meta[i].1;
+                    if !::arcane::es::event::codegen::is_alias::<Event<()> >(
+                        name
+                    ) {
+                        policy.assert(name);
+                    }
+                    i += 1;
+                }
+            };
+        };
+
+        assert_eq!(def.gen_naming_assertion().to_string(), output.to_string());
+    }
+
+    #[test]
+    fn errors_on_invalid_prefix() {
+        for prefix in ["", "chat .", ".", "chat..", ".chat."] {
+            let input = syn::parse_str::<TokenStream>(&format!(
+                "#[event(prefix = {prefix:?})] enum Event {{ A(A) }}",
+            ))
+            .expect("valid tokens");
+
+            let err = derive(input).expect_err("should fail");
+
+            assert_eq!(
+                err.to_string(),
+                "`prefix` must be non-empty and contain no whitespaces or \
+                 empty segments",
+            );
+        }
+    }
+
+    #[test]
+    fn derives_stream_category() {
+        let input = parse_quote! {
+            #[event(stream = "chat")]
+            enum Event<F> {
+                Created(ChatCreated<F>),
+            }
+        };
+        let def = Definition::try_from(
+            syn::parse2::<syn::DeriveInput>(input).expect("valid input"),
+        )
+        .expect("valid definition");
+
+        let output = quote! {
+            #[automatically_derived]
+            impl<F> ::arcane::es::stream::Categorized for Event<F> {
+                const CATEGORY: ::arcane::es::stream::Category = "chat";
+            }
+        };
+
+        assert_eq!(
+            def.impl_stream_categorized().to_string(),
+            output.to_string(),
+        );
+    }
+
+    #[test]
+    fn errors_on_invalid_stream_category() {
+        for category in ["", "chat-room", "chat room"] {
+            let input = syn::parse_str::<TokenStream>(&format!(
+                "#[event(stream = {category:?})] enum Event {{ A(A) }}",
+            ))
+            .expect("valid tokens");
+
+            let err = derive(input).expect_err("should fail");
+
+            assert_eq!(
+                err.to_string(),
+                "`stream` must be non-empty and contain no `-` or whitespaces",
+            );
+        }
+    }
+
+    #[cfg(feature = "protobuf")]
+    #[test]
+    fn derives_proto_impl() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[event(proto)]
+            enum Event {
+                #[event(init, proto(tag = 1))]
+                FileCreated(FileCreated),
+                #[event(ignore)]
+                Ignored(Ignored),
+                #[event(proto(tag = 2))]
+                FileDeleted(FileDeleted),
+            }
+        };
+
+        let output = quote! {
+            #[automatically_derived]
+            impl ::arcane::es::protobuf::Proto for Event
+            where
+                FileCreated: ::arcane::es::protobuf::Proto,
+                FileDeleted: ::arcane::es::protobuf::Proto
+            {
+                const MESSAGE: &'static ::std::primitive::str = "Event";
+
+                fn definitions(
+                    defs: &mut ::std::vec::Vec<::std::string::String>,
+                ) {
+                    <FileCreated as ::arcane::es::protobuf::Proto>
+                        ::definitions(defs);
+                    <FileDeleted as ::arcane::es::protobuf::Proto>
+                        ::definitions(defs);
+                    ::arcane::es::protobuf::define_oneof(defs, "Event", &[
+                        (
+                            <FileCreated as ::arcane::es::protobuf::Proto>
+                                ::MESSAGE,
+                            "file_created",
+                            1u32,
+                        ),
+                        (
+                            <FileDeleted as ::arcane::es::protobuf::Proto>
+                                ::MESSAGE,
+                            "file_deleted",
+                            2u32,
+                        ),
+                    ]);
+                }
+
+                fn to_proto(&self) -> ::std::vec::Vec<::std::primitive::u8> {
+                    match self {
+                        Self::FileCreated(ev) => {
+                            ::arcane::es::protobuf::encode_oneof(
+                                1u32,
+                                &<FileCreated as ::arcane::es::protobuf::Proto>
+                                    ::to_proto(ev),
+                            )
+                        }
+                        Self::FileDeleted(ev) => {
+                            ::arcane::es::protobuf::encode_oneof(
+                                2u32,
+                                &<FileDeleted as ::arcane::es::protobuf::Proto>
+                                    ::to_proto(ev),
+                            )
+                        }
+                        _ => ::std::unreachable!(),
+                    }
+                }
+
+                fn from_proto(
+                    bytes: &[::std::primitive::u8],
+                ) -> ::std::result::Result<
+                    Self,
+                    ::arcane::es::protobuf::prost::DecodeError,
+                > {
+                    ::arcane::es::protobuf::decode_oneof(bytes, |tag, msg| {
+                        match tag {
+                            1u32 => ::std::option::Option::Some(
+                                <FileCreated as ::arcane::es::protobuf::Proto>
+                                    ::from_proto(msg)
+                                    .map(Self::FileCreated),
+                            ),
+                            2u32 => ::std::option::Option::Some(
+                                <FileDeleted as ::arcane::es::protobuf::Proto>
+                                    ::from_proto(msg)
+                                    .map(Self::FileDeleted),
+                            ),
+                            _ => ::std::option::Option::None,
+                        }
+                    })
+                }
+            }
+        };
+
+        let def = Definition::try_from(input).unwrap();
+
+        assert_eq!(def.impl_proto().to_string(), output.to_string());
+    }
+
+    #[cfg(feature = "protobuf")]
+    #[test]
+    fn errors_on_invalid_proto_tags() {
+        for (input, msg) in [
+            (
+                quote! {
+                    #[event(proto)]
+                    enum Event {
+                        #[event(proto(tag = 1))]
+                        Created(Created),
+                        Deleted(Deleted),
+                    }
+                },
+                "`proto` argument requires an explicit \
+                 `#[event(proto(tag = N))]` attribute",
+            ),
+            (
+                quote! {
+                    #[event(proto)]
+                    enum Event {
+                        #[event(proto(tag = 1))]
+                        Created(Created),
+                        #[event(proto(tag = 1))]
+                        Deleted(Deleted),
+                    }
+                },
+                "duplicate Protobuf tag",
+            ),
+        ] {
+            let err = derive(input).expect_err("should fail");
+
+            assert_eq!(err.to_string(), msg);
+        }
+    }
 }
@@ -1,14 +1,31 @@
 //! `#[derive(Event)]` macro implementation for structs.
 
+#[cfg(feature = "protobuf")]
+use std::fmt::Write as _;
 use std::num::NonZero;
 
 #[cfg(all(doc, feature = "doc"))]
 use arcane_core::es::event;
+#[cfg(feature = "reflect")]
+use proc_macro2::Literal;
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::spanned::Spanned as _;
+#[cfg(feature = "registry")]
+use syn::parse_quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    spanned::Spanned as _,
+};
 use synthez::{ParseAttrs, Required, ToTokens};
 
+use super::NamingPolicy;
+#[cfg(any(feature = "cloudevents", feature = "registry"))]
+use super::higher_ranked;
+#[cfg(feature = "pii")]
+use super::pii;
+#[cfg(feature = "protobuf")]
+use super::proto;
+
 /// Attributes of `#[derive(Event)]` macro on structs.
 #[derive(Debug, Default, ParseAttrs)]
 pub struct Attrs {
@@ -16,9 +33,110 @@ pub struct Attrs {
     #[parse(value)]
     pub name: Required<syn::LitStr>,
 
+    /// Values for the [`event::Static::ALIASES`] constant.
+    #[parse(value)]
+    pub alias: Vec<syn::LitStr>,
+
     /// Value fot the [`event::Concrete::REVISION`] constant.
     #[parse(value, alias = rev, validate = can_parse_as_non_zero_u16)]
     pub revision: Option<syn::LitInt>,
+
+    /// Range of historical [`event::Version`]s, used as a value of the
+    /// [`event::reflect::Compatible::SUPPORTED_REVISIONS`] constant.
+    #[parse(value, alias = supported)]
+    pub supports: Option<RevisionRange>,
+
+    /// [`event::name::Case`] the [`event::Static::NAME`] segments should
+    /// match.
+    #[parse(value)]
+    pub case: Option<syn::LitStr>,
+
+    /// Maximum length of the [`event::Static::NAME`].
+    #[parse(value)]
+    pub max_len: Option<syn::LitInt>,
+
+    /// Indicator whether a [`protobuf::Proto`] implementation should be
+    /// generated.
+    ///
+    /// [`protobuf::Proto`]: arcane_core::es::protobuf::Proto
+    #[cfg(feature = "protobuf")]
+    #[parse(ident)]
+    pub proto: Option<syn::Ident>,
+}
+
+/// Range of [`event::Version`]s in a `<start>..=<end>` or `<start>..<end>`
+/// form.
+#[derive(Debug)]
+pub struct RevisionRange {
+    /// Start of this [`RevisionRange`].
+    pub start: syn::LitInt,
+
+    /// Limits of this [`RevisionRange`] (either `..=` or `..`).
+    pub limits: TokenStream,
+
+    /// End of this [`RevisionRange`].
+    pub end: syn::LitInt,
+
+    /// Indicator whether the `end` is included into this [`RevisionRange`].
+    pub is_inclusive: bool,
+}
+
+impl Parse for RevisionRange {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let start = input.parse()?;
+        let is_inclusive = input.peek(syn::Token![..=]);
+        let limits = if is_inclusive {
+            input.parse::<syn::Token![..=]>()?.into_token_stream()
+        } else {
+            input.parse::<syn::Token![..]>()?.into_token_stream()
+        };
+        let end = input.parse()?;
+        Ok(Self { start, limits, end, is_inclusive })
+    }
+}
+
+impl quote::ToTokens for RevisionRange {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.start.to_tokens(tokens);
+        self.limits.to_tokens(tokens);
+        self.end.to_tokens(tokens);
+    }
+}
+
+impl RevisionRange {
+    /// Validates this [`RevisionRange`] against the provided `revision` and
+    /// returns its inclusive bounds.
+    ///
+    /// # Errors
+    ///
+    /// - If any of the bounds cannot be parsed as [`NonZero`] [`u16`].
+    /// - If this [`RevisionRange`] is empty.
+    /// - If this [`RevisionRange`] doesn't contain the provided `revision`.
+    pub fn validate(&self, revision: &syn::LitInt) -> syn::Result<(u16, u16)> {
+        let start = self.start.base10_parse::<NonZero<u16>>()?.get();
+        let end = self.end.base10_parse::<NonZero<u16>>()?.get();
+        let end = if self.is_inclusive {
+            Some(end)
+        } else {
+            end.checked_sub(1).filter(|e| *e > 0)
+        };
+        let Some(end) = end.filter(|e| start <= *e) else {
+            return Err(syn::Error::new(
+                self.span(),
+                "`supports` range must not be empty",
+            ));
+        };
+
+        let revision = revision.base10_parse::<u16>()?;
+        if !(start..=end).contains(&revision) {
+            return Err(syn::Error::new(
+                self.span(),
+                "`supports` range must contain the `revision`",
+            ));
+        }
+
+        Ok((start, end))
+    }
 }
 
 /// Checks whether the given `value` can be parsed as [`NonZero`] [`u16`].
@@ -36,11 +154,20 @@ fn can_parse_as_non_zero_u16(value: &Option<syn::LitInt>) -> syn::Result<()> {
 #[to_tokens(append(
     impl_event_static,
     impl_event_concrete,
-    gen_uniqueness_assertion
+    gen_uniqueness_assertion,
+    gen_naming_assertion
 ))]
+#[cfg_attr(feature = "cloudevents", to_tokens(append(impl_cloudevents)))]
+#[cfg_attr(feature = "pii", to_tokens(append(impl_personal)))]
+#[cfg_attr(feature = "protobuf", to_tokens(append(impl_proto)))]
+#[cfg_attr(feature = "registry", to_tokens(append(impl_registry)))]
 #[cfg_attr(
     feature = "reflect",
-    to_tokens(append(impl_reflect_static, impl_reflect_concrete))
+    to_tokens(append(
+        impl_reflect_static,
+        impl_reflect_concrete,
+        impl_reflect_compatible
+    ))
 )]
 pub struct Definition {
     /// [`syn::Ident`](struct@syn::Ident) of this structure's type.
@@ -52,9 +179,32 @@ pub struct Definition {
     /// Value of the [`event::Static::NAME`] constant in the generated code.
     pub event_name: syn::LitStr,
 
+    /// Values of the [`event::Static::ALIASES`] constant in the generated
+    /// code.
+    pub event_aliases: Vec<syn::LitStr>,
+
     /// Value of the [`event::Concrete::REVISION`] constant in the generated
     /// code.
     pub event_revision: Option<syn::LitInt>,
+
+    /// Inclusive bounds of the [`event::VersionRange`] used as a value of the
+    /// [`event::reflect::Compatible::SUPPORTED_REVISIONS`] constant in the
+    /// generated code.
+    pub supported_revisions: Option<(u16, u16)>,
+
+    /// [`NamingPolicy`] the [`event::Static::NAME`] is checked against.
+    pub naming_policy: NamingPolicy,
+
+    /// [`pii::Personal`] data of this structure, if it has any.
+    #[cfg(feature = "pii")]
+    pub personal: Option<pii::Personal>,
+
+    /// [`proto::Field`]s of this structure, if it should implement
+    /// [`protobuf::Proto`].
+    ///
+    /// [`protobuf::Proto`]: arcane_core::es::protobuf::Proto
+    #[cfg(feature = "protobuf")]
+    pub proto_fields: Option<Vec<proto::Field>>,
 }
 
 impl TryFrom<syn::DeriveInput> for Definition {
@@ -70,11 +220,66 @@ impl TryFrom<syn::DeriveInput> for Definition {
 
         let attrs = Attrs::parse_attrs("event", &input)?;
 
+        let supported_revisions = attrs
+            .supports
+            .as_ref()
+            .map(|range| {
+                let revision = attrs.revision.as_ref().ok_or_else(|| {
+                    syn::Error::new(
+                        range.span(),
+                        "`supports` argument requires `revision` argument \
+                         to be present",
+                    )
+                })?;
+                range.validate(revision)
+            })
+            .transpose()?;
+
+        let naming_policy =
+            NamingPolicy::parse(attrs.case.as_ref(), attrs.max_len.as_ref())?;
+
+        #[cfg(feature = "pii")]
+        let personal = match &input.data {
+            syn::Data::Struct(data) => {
+                pii::Personal::parse(&data.fields, &input.attrs)?
+            }
+            syn::Data::Enum(_) | syn::Data::Union(_) => None,
+        };
+
+        #[cfg(feature = "protobuf")]
+        let proto_fields = attrs
+            .proto
+            .as_ref()
+            .map(|_| match &input.data {
+                syn::Data::Struct(data) => {
+                    proto::Field::parse_all(&data.fields, &input.generics)
+                }
+                syn::Data::Enum(_) | syn::Data::Union(_) => Ok(vec![]),
+            })
+            .transpose()?;
+
+        let event_name = attrs.name.into_inner();
+        if let Some(alias) =
+            attrs.alias.iter().find(|a| a.value() == event_name.value())
+        {
+            return Err(syn::Error::new(
+                alias.span(),
+                "`alias` must differ from the `name`",
+            ));
+        }
+
         Ok(Self {
             ident: input.ident,
             generics: input.generics,
-            event_name: attrs.name.into_inner(),
+            event_name,
+            event_aliases: attrs.alias,
             event_revision: attrs.revision,
+            supported_revisions,
+            naming_policy,
+            #[cfg(feature = "pii")]
+            personal,
+            #[cfg(feature = "protobuf")]
+            proto_fields,
         })
     }
 }
@@ -87,6 +292,13 @@ impl Definition {
         let (impl_gens, ty_gens, where_clause) = self.generics.split_for_impl();
 
         let event_name = &self.event_name;
+        let event_aliases = (!self.event_aliases.is_empty()).then(|| {
+            let aliases = &self.event_aliases;
+            quote! {
+                const ALIASES: &'static [::arcane::es::event::Name] =
+                    &[#( #aliases ),*];
+            }
+        });
 
         quote! {
             #[automatically_derived]
@@ -94,6 +306,7 @@ impl Definition {
                  #where_clause
             {
                 const NAME: ::arcane::es::event::Name = #event_name;
+                #event_aliases
             }
         }
     }
@@ -130,6 +343,18 @@ impl Definition {
         let ty = &self.ident;
         let (impl_gens, ty_gens, where_clause) = self.generics.split_for_impl();
 
+        let aliases = (!self.event_aliases.is_empty()).then(|| {
+            let aliases = &self.event_aliases;
+            quote! {
+                const ALIASES: &'static [(
+                    ::arcane::es::event::Name,
+                    ::arcane::es::event::Name,
+                )] = &[#(
+                    (#aliases, <Self as ::arcane::es::event::Static>::NAME)
+                ),*];
+            }
+        });
+
         quote! {
             #[automatically_derived]
             impl #impl_gens ::arcane::es::event::reflect::Static
@@ -137,6 +362,7 @@ impl Definition {
             {
                 const NAMES: &'static [::arcane::es::event::Name] =
                     &[<Self as ::arcane::es::event::Static>::NAME];
+                #aliases
             }
         }
     }
@@ -167,6 +393,323 @@ impl Definition {
         }
     }
 
+    #[cfg(feature = "reflect")]
+    /// Generates code of an [`event::reflect::Compatible`] trait
+    /// implementation.
+    #[must_use]
+    pub fn impl_reflect_compatible(&self) -> TokenStream {
+        if self.event_revision.is_none() {
+            return TokenStream::new();
+        }
+
+        let ty = &self.ident;
+        let (impl_gens, ty_gens, where_clause) = self.generics.split_for_impl();
+
+        let range = self.supported_revisions.map_or_else(
+            || {
+                quote! {
+                    ::arcane::es::event::VersionRange::single(
+                        <Self as ::arcane::es::event::Concrete>::REVISION
+                    )
+                }
+            },
+            |(start, end)| {
+                let (start, end) = (
+                    Literal::u16_unsuffixed(start),
+                    Literal::u16_unsuffixed(end),
+                );
+                quote! {
+                    // SAFETY: Safe, as checked by proc macro in compile time.
+                    unsafe {
+                        ::arcane::es::event::VersionRange::new_unchecked(
+                            ::arcane::es::event::Version::new_unchecked(#start),
+                            ::arcane::es::event::Version::new_unchecked(#end),
+                        )
+                    }
+                }
+            },
+        );
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::event::reflect::Compatible
+             for #ty #ty_gens #where_clause
+            {
+                const SUPPORTED_REVISIONS: &'static [
+                    ::arcane::es::event::VersionRange
+                ] = &[#range];
+            }
+        }
+    }
+
+    #[cfg(feature = "cloudevents")]
+    /// Generates code of [`cloudevents::Encode`] and [`cloudevents::Decode`]
+    /// trait implementations, if the struct is an [`event::Concrete`] one.
+    ///
+    /// Bounds are [`higher_ranked()`].
+    ///
+    /// [`cloudevents::Decode`]: arcane_core::es::cloudevents::Decode
+    /// [`cloudevents::Encode`]: arcane_core::es::cloudevents::Encode
+    #[must_use]
+    pub fn impl_cloudevents(&self) -> TokenStream {
+        if self.event_revision.is_none() {
+            return TokenStream::new();
+        }
+
+        let ty = &self.ident;
+        let (impl_gens, ty_gens, _) = self.generics.split_for_impl();
+
+        let mut encode_generics = self.generics.clone();
+        encode_generics.make_where_clause().predicates.push(higher_ranked(
+            &quote! { Self },
+            &quote! { ::arcane::es::cloudevents::serde::Serialize },
+        ));
+        let encode_where_clause = &encode_generics.where_clause;
+
+        let mut generics = self.generics.clone();
+        generics.make_where_clause().predicates.push(higher_ranked(
+            &quote! { Self },
+            &quote! { ::arcane::es::cloudevents::serde::de
+            ::DeserializeOwned },
+        ));
+        let where_clause = &generics.where_clause;
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::cloudevents::Encode
+             for #ty #ty_gens #encode_where_clause
+            {
+                fn encode(&self) -> ::std::result::Result<
+                    ::arcane::es::cloudevents::serde_json::Value,
+                    ::arcane::es::cloudevents::serde_json::Error,
+                > {
+                    ::arcane::es::cloudevents::serde_json::to_value(self)
+                }
+            }
+
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::cloudevents::Decode
+             for #ty #ty_gens #where_clause
+            {
+                fn decode(
+                    ty: &::std::primitive::str,
+                    revision: ::arcane::es::event::Version,
+                    data: &::arcane::es::cloudevents::serde_json::Value,
+                ) -> ::std::option::Option<::std::result::Result<
+                    Self,
+                    ::arcane::es::cloudevents::serde_json::Error,
+                >> {
+                    ::arcane::es::cloudevents::decode_concrete(
+                        ty, revision, data,
+                    )
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "pii")]
+    /// Generates code of a [`pii::Personal`] trait implementation, if the
+    /// struct has any fields marked with `#[event(personal)]` attribute.
+    ///
+    /// [`pii::Personal`]: arcane_core::es::pii::Personal
+    #[must_use]
+    pub fn impl_personal(&self) -> TokenStream {
+        let Some(personal) = &self.personal else {
+            return TokenStream::new();
+        };
+
+        let ty = &self.ident;
+        let (impl_gens, ty_gens, where_clause) = self.generics.split_for_impl();
+
+        let subject = &personal.subject;
+        let fields = personal.fields.iter().map(|f| &f.name);
+        let redacted = personal.redacted();
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::pii::Personal
+             for #ty #ty_gens #where_clause
+            {
+                const FIELDS: &'static [&'static ::std::primitive::str] =
+                    &[#( #fields ),*];
+
+                fn subject(&self) -> ::std::string::String {
+                    ::std::string::ToString::to_string(&self.#subject)
+                }
+
+                fn redacted(
+                    field: &::std::primitive::str,
+                ) -> ::arcane::es::pii::serde_json::Value {
+                    #redacted
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "protobuf")]
+    /// Generates code of [`protobuf::Proto`] and [`prost::Message`] traits
+    /// implementations, if the struct is marked with `#[event(proto)]`
+    /// attribute.
+    ///
+    /// [`prost::Message`]: arcane_core::es::protobuf::prost::Message
+    /// [`protobuf::Proto`]: arcane_core::es::protobuf::Proto
+    #[must_use]
+    pub fn impl_proto(&self) -> TokenStream {
+        let Some(fields) = &self.proto_fields else {
+            return TokenStream::new();
+        };
+
+        let ty = &self.ident;
+        let (impl_gens, ty_gens, _) = self.generics.split_for_impl();
+
+        let mut generics = self.generics.clone();
+        generics
+            .make_where_clause()
+            .predicates
+            .extend(fields.iter().filter_map(proto::Field::bound));
+        let where_clause = &generics.where_clause;
+
+        let message = ty.to_string();
+        let mut definition = format!(
+            "message {message} {{\n  \
+             option (arcane.event_name) = {};\n",
+            self.event_name.token(),
+        );
+        if let Some(rev) = &self.event_revision {
+            _ = writeln!(
+                definition,
+                "  option (arcane.event_revision) = {};",
+                rev.base10_digits(),
+            );
+        }
+        definition.extend(fields.iter().map(proto::Field::definition));
+        definition.push_str("}\n");
+
+        let field_ident = fields.iter().map(|f| &f.ident).collect::<Vec<_>>();
+        let encode = fields.iter().map(proto::Field::encode);
+        let merge = fields.iter().map(proto::Field::merge);
+        let encoded_len = fields.iter().map(proto::Field::encoded_len);
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::protobuf::prost::Message
+             for #ty #ty_gens #where_clause
+            {
+                fn encode_raw(
+                    &self,
+                    buf: &mut impl ::arcane::es::protobuf::prost::bytes::BufMut,
+                ) {
+                    #( #encode )*
+                }
+
+                fn merge_field(
+                    &mut self,
+                    tag: ::std::primitive::u32,
+                    wire_type: ::arcane::es::protobuf::prost::encoding
+                               ::WireType,
+                    buf: &mut impl ::arcane::es::protobuf::prost::bytes::Buf,
+                    ctx: ::arcane::es::protobuf::prost::encoding
+                         ::DecodeContext,
+                ) -> ::std::result::Result<
+                    (),
+                    ::arcane::es::protobuf::prost::DecodeError,
+                > {
+                    match tag {
+                        #( #merge )*
+                        _ => ::arcane::es::protobuf::prost::encoding
+                             ::skip_field(wire_type, tag, buf, ctx),
+                    }
+                }
+
+                fn encoded_len(&self) -> ::std::primitive::usize {
+                    0 #( + #encoded_len )*
+                }
+
+                fn clear(&mut self) {
+                    #(
+                        self.#field_ident = ::std::default::Default::default();
+                    )*
+                }
+            }
+
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::protobuf::Proto
+             for #ty #ty_gens #where_clause
+            {
+                const MESSAGE: &'static ::std::primitive::str = #message;
+
+                fn definitions(
+                    defs: &mut ::std::vec::Vec<::std::string::String>,
+                ) {
+                    ::arcane::es::protobuf::define(defs, #definition);
+                }
+
+                fn to_proto(&self) -> ::std::vec::Vec<::std::primitive::u8> {
+                    ::arcane::es::protobuf::prost::Message::encode_to_vec(self)
+                }
+
+                fn from_proto(
+                    bytes: &[::std::primitive::u8],
+                ) -> ::std::result::Result<
+                    Self,
+                    ::arcane::es::protobuf::prost::DecodeError,
+                > {
+                    let mut this = Self {
+                        #( #field_ident: ::std::default::Default::default(), )*
+                    };
+                    ::arcane::es::protobuf::prost::Message::merge(
+                        &mut this, bytes,
+                    )?;
+                    ::std::result::Result::Ok(this)
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "registry")]
+    /// Generates code of an [`event::registry::Register`] trait
+    /// implementation, if the struct is an [`event::Concrete`] one.
+    ///
+    /// Bounds are [`higher_ranked()`].
+    #[must_use]
+    pub fn impl_registry(&self) -> TokenStream {
+        if self.event_revision.is_none() {
+            return TokenStream::new();
+        }
+
+        let ty = &self.ident;
+        let (_, ty_gens, _) = self.generics.split_for_impl();
+
+        let mut ext_gens = self.generics.clone();
+        ext_gens.params.push(parse_quote! { __S });
+        let clause = ext_gens.make_where_clause();
+        clause.predicates.push(higher_ranked(
+            &quote! { Self },
+            &quote! { ::arcane::es::event::registry::serde::de
+            ::DeserializeOwned + ::std::marker::Send + 'static },
+        ));
+        clause.predicates.push(parse_quote! {
+            ::std::option::Option<__S>: ::arcane::es::event::Sourced<Self>
+        });
+        let (impl_gens, _, where_clause) = ext_gens.split_for_impl();
+
+        quote! {
+            #[automatically_derived]
+            impl #impl_gens ::arcane::es::event::registry::Register<__S>
+             for #ty #ty_gens #where_clause
+            {
+                fn register<__F: ::arcane::es::event::registry::Format>(
+                    registry: &mut ::arcane::es::event::registry::Registry<
+                        __S, __F
+                    >,
+                    prefix: &::std::primitive::str,
+                ) {
+                    _ = registry.insert::<Self, Self>(prefix);
+                }
+            }
+        }
+    }
+
     /// Generates non-public machinery code used to statically check whether
     /// [`Event::name`][0] and [`event::Revisable::revision`] pairs correspond
     /// to a single Rust type.
@@ -186,6 +729,28 @@ impl Definition {
         // TODO: Replace `::std::concat!(...)` with `TypeId::of()` once it gets
         //       `const`ified.
         //       https://github.com/rust-lang/rust/issues/77125
+        let ty_id = quote! {
+            ::std::concat!(
+                ::std::file!(),
+                "_",
+                ::std::line!(),
+                "_",
+                ::std::column!(),
+            )
+        };
+        let aliases_meta = self.event_aliases.iter().map(|alias| {
+            quote! { , (#ty_id, #alias, #revision) }
+        });
+        let aliases = (!self.event_aliases.is_empty()).then(|| {
+            let aliases = &self.event_aliases;
+            quote! {
+                #[doc(hidden)]
+                const ALIASES: &'static [(&'static str, &'static str)] = &[#(
+                    (#aliases, <Self as ::arcane::es::event::Static>::NAME)
+                ),*];
+            }
+        });
+
         quote! {
             #[automatically_derived]
             #[doc(hidden)]
@@ -196,19 +761,44 @@ impl Definition {
                 const META: &'static [
                     (&'static str, &'static str, &'static str)
                 ] = &[(
-                    ::std::concat!(
-                        ::std::file!(),
-                        "_",
-                        ::std::line!(),
-                        "_",
-                        ::std::column!(),
-                    ),
+                    #ty_id,
                     <Self as ::arcane::es::event::Static>::NAME,
                     #revision,
-                )];
+                ) #( #aliases_meta )*];
+                #aliases
             }
         }
     }
+
+    /// Generates non-public machinery code used to statically check whether
+    /// the [`event::Static::NAME`] conforms to the [`event::name::Policy`].
+    ///
+    /// Generates nothing if no [`event::name::Policy`] is specified, so the
+    /// naming checks remain opt-in. [`event::Static::ALIASES`] are checked
+    /// against the basic [`event::name::Policy`] only, as they're allowed to
+    /// follow some former naming conventions.
+    #[must_use]
+    pub fn gen_naming_assertion(&self) -> TokenStream {
+        if self.naming_policy.is_basic() {
+            return TokenStream::new();
+        }
+
+        let policy = self.naming_policy.expand();
+        let event_name = &self.event_name;
+        let aliases = &self.event_aliases;
+
+        quote! {
+            #[automatically_derived]
+            #[doc(hidden)]
+            const _: () = #policy.assert(#event_name);
+            #(
+                #[automatically_derived]
+                #[doc(hidden)]
+                const _: () = ::arcane::es::event::name::Policy::new()
+                    .assert(#aliases);
+            )*
+        }
+    }
 }
 
 #[cfg(test)]
@@ -257,6 +847,7 @@ mod spec {
                     "",
                 )];
             }
+
         };
         if cfg!(feature = "reflect") {
             output.extend([quote! {
@@ -312,7 +903,68 @@ mod spec {
                     "1",
                 )];
             }
+
         };
+        if cfg!(feature = "cloudevents") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl ::arcane::es::cloudevents::Encode for Event
+                where
+                    for<'__a> Self: ::arcane::es::cloudevents::serde::Serialize
+                {
+                    fn encode(&self) -> ::std::result::Result<
+                        ::arcane::es::cloudevents::serde_json::Value,
+                        ::arcane::es::cloudevents::serde_json::Error,
+                    > {
+                        ::arcane::es::cloudevents::serde_json::to_value(self)
+                    }
+                }
+
+                #[automatically_derived]
+                impl ::arcane::es::cloudevents::Decode for Event
+                where
+                    for<'__a> Self: ::arcane::es::cloudevents::serde::de
+                                    ::DeserializeOwned
+                {
+                    fn decode(
+                        ty: &::std::primitive::str,
+                        revision: ::arcane::es::event::Version,
+                        data: &::arcane::es::cloudevents::serde_json::Value,
+                    ) -> ::std::option::Option<::std::result::Result<
+                        Self,
+                        ::arcane::es::cloudevents::serde_json::Error,
+                    >> {
+                        ::arcane::es::cloudevents::decode_concrete(
+                            ty, revision, data,
+                        )
+                    }
+                }
+            }]);
+        }
+        if cfg!(feature = "registry") {
+            output.extend([quote! {
+                #[automatically_derived]
+                impl<__S> ::arcane::es::event::registry::Register<__S>
+                 for Event
+                where
+                    for<'__a> Self: ::arcane::es::event::registry::serde::de
+                                    ::DeserializeOwned
+                                  + ::std::marker::Send
+                                  + 'static,
+                    ::std::option::Option<__S>:
+                        ::arcane::es::event::Sourced<Self>
+                {
+                    fn register<__F: ::arcane::es::event::registry::Format>(
+                        registry: &mut ::arcane::es::event::registry::Registry<
+                            __S, __F
+                        >,
+                        prefix: &::std::primitive::str,
+                    ) {
+                        _ = registry.insert::<Self, Self>(prefix);
+                    }
+                }
+            }]);
+        }
         if cfg!(feature = "reflect") {
             output.extend([quote! {
                 #[automatically_derived]
@@ -326,12 +978,230 @@ mod spec {
                     const REVISIONS: &'static [::arcane::es::event::Version] =
                         &[<Self as ::arcane::es::event::Concrete>::REVISION];
                 }
+
+                #[automatically_derived]
+                impl ::arcane::es::event::reflect::Compatible for Event {
+                    const SUPPORTED_REVISIONS: &'static [
+                        ::arcane::es::event::VersionRange
+                    ] = &[::arcane::es::event::VersionRange::single(
+                        <Self as ::arcane::es::event::Concrete>::REVISION
+                    )];
+                }
             }]);
         }
 
         assert_eq!(derive(input).unwrap().to_string(), output.to_string());
     }
 
+    #[test]
+    fn derives_struct_impl_with_supported_revisions() {
+        let input = parse_quote! {
+            #[event(name = "event", revision = 3, supports = 1..=3)]
+            struct Event;
+        };
+        let input_exclusive = parse_quote! {
+            #[event(name = "event", revision = 3, supports = 1..4)]
+            struct Event;
+        };
+
+        let output = derive(input).expect("should derive").to_string();
+        let output_exclusive =
+            derive(input_exclusive).expect("should derive").to_string();
+
+        if cfg!(feature = "reflect") {
+            let expected = quote! {
+                #[automatically_derived]
+                impl ::arcane::es::event::reflect::Compatible for Event {
+                    const SUPPORTED_REVISIONS: &'static [
+                        ::arcane::es::event::VersionRange
+                    ] = &[
+                        // SAFETY: Safe, as checked by proc macro in compile
+                        //         time.
+                        unsafe {
+                            ::arcane::es::event::VersionRange::new_unchecked(
+                                ::arcane::es::event::Version::new_unchecked(1),
+                                ::arcane::es::event::Version::new_unchecked(3),
+                            )
+                        }
+                    ];
+                }
+            };
+            assert!(
+                output.ends_with(&expected.to_string()),
+                "missing `reflect::Compatible` impl in:\n{output}",
+            );
+        }
+        assert_eq!(output, output_exclusive);
+    }
+
+    #[test]
+    fn errors_on_supports_without_revision() {
+        let input = parse_quote! {
+            #[event(name = "event", supports = 1..=2)]
+            struct Event;
+        };
+
+        let err = derive(input).expect_err("should fail");
+
+        assert_eq!(
+            err.to_string(),
+            "`supports` argument requires `revision` argument to be present",
+        );
+    }
+
+    #[test]
+    fn errors_on_empty_supported_revisions() {
+        let input = parse_quote! {
+            #[event(name = "event", revision = 1, supports = 1..1)]
+            struct Event;
+        };
+
+        let err = derive(input).expect_err("should fail");
+
+        assert_eq!(err.to_string(), "`supports` range must not be empty");
+    }
+
+    #[test]
+    fn errors_on_supported_revisions_not_containing_revision() {
+        let input = parse_quote! {
+            #[event(name = "event", revision = 4, supports = 1..=3)]
+            struct Event;
+        };
+
+        let err = derive(input).expect_err("should fail");
+
+        assert_eq!(
+            err.to_string(),
+            "`supports` range must contain the `revision`",
+        );
+    }
+
+    #[test]
+    fn errors_on_zero_supported_revision() {
+        let input = parse_quote! {
+            #[event(name = "event", revision = 1, supports = 0..=1)]
+            struct Event;
+        };
+
+        let err = derive(input).expect_err("should fail");
+
+        assert_eq!(err.to_string(), "number would be zero for non-zero type");
+    }
+
+    #[test]
+    fn derives_naming_policy_assertion() {
+        let input = parse_quote! {
+            #[event(name = "chat.created", case = "snake_case", max_len = 64)]
+            struct Event;
+        };
+
+        let output = derive(input).expect("should derive").to_string();
+
+        let expected = quote! {
+            #[automatically_derived]
+            #[doc(hidden)]
+            const _: () = ::arcane::es::event::name::Policy::new()
+                .case(::arcane::es::event::name::Case::Snake)
+                .max_len(64)
+                .assert("chat.created");
+        };
+        assert!(
+            output.contains(&expected.to_string()),
+            "missing naming policy assertion in:\n{output}",
+        );
+    }
+
+    #[test]
+    fn derives_no_naming_assertion_without_policy() {
+        let input = parse_quote! {
+            #[event(name = "ChatCreated")]
+            struct Event;
+        };
+        let def = Definition::try_from(
+            syn::parse2::<syn::DeriveInput>(input).expect("valid input"),
+        )
+        .expect("valid definition");
+
+        assert!(def.gen_naming_assertion().is_empty());
+    }
+
+    #[test]
+    fn derives_struct_impl_with_aliases() {
+        let input = parse_quote! {
+            #[event(
+                name = "chat.created",
+                alias = "created",
+                alias = "new",
+                case = "snake_case",
+            )]
+            struct Event;
+        };
+        let def = Definition::try_from(
+            syn::parse2::<syn::DeriveInput>(input).expect("valid input"),
+        )
+        .expect("valid definition");
+
+        let event = quote! {
+            #[automatically_derived]
+            impl ::arcane::es::event::Static for Event {
+                const NAME: ::arcane::es::event::Name = "chat.created";
+                const ALIASES: &'static [::arcane::es::event::Name] =
+                    &["created", "new"];
+            }
+        };
+        let naming = quote! {
+            #[automatically_derived]
+            #[doc(hidden)]
+            const _: () = ::arcane::es::event::name::Policy::new()
+                .case(::arcane::es::event::name::Case::Snake)
+                .assert("chat.created");
+            #[automatically_derived]
+            #[doc(hidden)]
+            const _: () =
+                ::arcane::es::event::name::Policy::new().assert("created");
+            #[automatically_derived]
+            #[doc(hidden)]
+            const _: () =
+                ::arcane::es::event::name::Policy::new().assert("new");
+        };
+
+        assert_eq!(def.impl_event_static().to_string(), event.to_string());
+        assert_eq!(def.gen_naming_assertion().to_string(), naming.to_string());
+
+        let meta = def.gen_uniqueness_assertion().to_string();
+        assert!(meta.contains("\"created\" , \"\")"), "{meta}");
+        assert!(meta.contains("\"new\" , \"\")"), "{meta}");
+        assert!(meta.contains("const ALIASES"), "{meta}");
+    }
+
+    #[test]
+    fn errors_on_alias_equal_to_name() {
+        let input = parse_quote! {
+            #[event(name = "created", alias = "created")]
+            struct Event;
+        };
+
+        let err = derive(input).expect_err("should fail");
+
+        assert_eq!(err.to_string(), "`alias` must differ from the `name`");
+    }
+
+    #[test]
+    fn errors_on_unknown_case() {
+        let input = parse_quote! {
+            #[event(name = "event", case = "SCREAMING")]
+            struct Event;
+        };
+
+        let err = derive(input).expect_err("should fail");
+
+        assert_eq!(
+            err.to_string(),
+            "unknown case, expected one of: `snake_case`, `kebab-case`, \
+             `camelCase`, `PascalCase`, `lowercase`",
+        );
+    }
+
     #[test]
     fn name_arg_is_required() {
         let input = parse_quote! {
@@ -384,6 +1254,297 @@ mod spec {
         assert_eq!(err.to_string(), "number too large to fit in target type");
     }
 
+    #[cfg(feature = "pii")]
+    #[test]
+    fn derives_personal_impl() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[event(name = "event")]
+            struct Event {
+                #[event(subject)]
+                user_id: UserId,
+                #[event(personal)]
+                email: String,
+                #[event(pii, redacted = "forgotten")]
+                name: String,
+                plan: String,
+            }
+        };
+
+        let output = quote! {
+            #[automatically_derived]
+            impl ::arcane::es::pii::Personal for Event {
+                const FIELDS: &'static [&'static ::std::primitive::str] =
+                    &["email", "name"];
+
+                fn subject(&self) -> ::std::string::String {
+                    ::std::string::ToString::to_string(&self.user_id)
+                }
+
+                fn redacted(
+                    field: &::std::primitive::str,
+                ) -> ::arcane::es::pii::serde_json::Value {
+                    match field {
+                        "email" => ::arcane::es::pii::serde_json::to_value::<
+                            String
+                        >(<String as ::std::default::Default>::default()),
+                        "name" => ::arcane::es::pii::serde_json::to_value::<
+                            String
+                        >(forgotten()),
+                        _ => ::std::result::Result::Ok(
+                            ::arcane::es::pii::serde_json::Value::Null,
+                        ),
+                    }
+                    .unwrap_or_default()
+                }
+            }
+        };
+
+        let def = Definition::try_from(input).unwrap();
+
+        assert_eq!(def.impl_personal().to_string(), output.to_string());
+    }
+
+    #[cfg(feature = "pii")]
+    #[test]
+    fn respects_serde_renames_of_personal_fields() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[derive(Serialize)]
+            #[serde(deny_unknown_fields, rename_all = "camelCase")]
+            #[event(name = "event")]
+            struct Event {
+                #[event(subject)]
+                user_id: UserId,
+                #[event(personal)]
+                home_address: String,
+                #[event(personal)]
+                #[serde(default, rename = "mail")]
+                email: String,
+                #[event(personal)]
+                #[serde(rename(serialize = "nick", deserialize = "nick"))]
+                r#nickname: String,
+            }
+        };
+
+        let def = Definition::try_from(input).unwrap();
+
+        let names = def
+            .personal
+            .unwrap()
+            .fields
+            .into_iter()
+            .map(|f| f.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["homeAddress", "mail", "nick"]);
+    }
+
+    #[cfg(feature = "pii")]
+    #[test]
+    fn errors_on_asymmetric_rename_of_personal_field() {
+        let input = parse_quote! {
+            #[event(name = "event")]
+            struct Event {
+                #[event(subject)]
+                user_id: UserId,
+                #[event(personal)]
+                #[serde(rename(serialize = "mail"))]
+                email: String,
+            }
+        };
+
+        let err = derive(input).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "`rename` must be the same for serialization and deserialization \
+             of `personal` fields",
+        );
+    }
+
+    #[cfg(feature = "pii")]
+    #[test]
+    fn errors_on_personal_without_subject() {
+        let input = parse_quote! {
+            #[event(name = "event")]
+            struct Event {
+                #[event(personal)]
+                email: String,
+            }
+        };
+
+        let err = derive(input).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "`personal` fields require a field marked with \
+             `#[event(subject)]` attribute",
+        );
+    }
+
+    #[cfg(feature = "pii")]
+    #[test]
+    fn errors_on_personal_subject() {
+        let input = parse_quote! {
+            #[event(name = "event")]
+            struct Event {
+                #[event(subject, personal)]
+                email: String,
+            }
+        };
+
+        let err = derive(input).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "`subject` and `personal`/`pii` arguments are mutually exclusive",
+        );
+    }
+
+    #[cfg(feature = "protobuf")]
+    #[test]
+    fn derives_proto_impl() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[event(name = "event", revision = 1, proto)]
+            struct Event {
+                #[event(proto(tag = 1))]
+                text: String,
+                #[event(proto(tag = 2))]
+                meta: Option<Meta>,
+            }
+        };
+
+        let definition = "message Event {\n  \
+                          option (arcane.event_name) = \"event\";\n  \
+                          option (arcane.event_revision) = 1;\n  \
+                          string text = 1;\n  \
+                          optional Meta meta = 2;\n\
+                          }\n";
+        let output = quote! {
+            #[automatically_derived]
+            impl ::arcane::es::protobuf::prost::Message for Event
+            where
+                Meta: ::arcane::es::protobuf::prost::Message
+                    + ::std::default::Default
+            {
+                fn encode_raw(
+                    &self,
+                    buf: &mut impl ::arcane::es::protobuf::prost::bytes::BufMut,
+                ) {
+                    if self.text
+                        != <String as ::std::default::Default>::default()
+                    {
+                        ::arcane::es::protobuf::prost::encoding::string
+                            ::encode(1u32, &self.text, buf);
+                    }
+                    if let ::std::option::Option::Some(v) = &self.meta {
+                        ::arcane::es::protobuf::prost::encoding::message
+                            ::encode(2u32, v, buf);
+                    }
+                }
+
+                fn merge_field(
+                    &mut self,
+                    tag: ::std::primitive::u32,
+                    wire_type: ::arcane::es::protobuf::prost::encoding
+                               ::WireType,
+                    buf: &mut impl ::arcane::es::protobuf::prost::bytes::Buf,
+                    ctx: ::arcane::es::protobuf::prost::encoding
+                         ::DecodeContext,
+                ) -> ::std::result::Result<
+                    (),
+                    ::arcane::es::protobuf::prost::DecodeError,
+                > {
+                    match tag {
+                        1u32 => ::arcane::es::protobuf::prost::encoding::string
+                            ::merge(wire_type, &mut self.text, buf, ctx),
+                        2u32 => ::arcane::es::protobuf::prost::encoding::message
+                            ::merge(
+                                wire_type,
+                                self.meta.get_or_insert_with(
+                                    ::std::default::Default::default,
+                                ),
+                                buf,
+                                ctx,
+                            ),
+                        _ => ::arcane::es::protobuf::prost::encoding
+                             ::skip_field(wire_type, tag, buf, ctx),
+                    }
+                }
+
+                fn encoded_len(&self) -> ::std::primitive::usize {
+                    0 + if self.text
+                        == <String as ::std::default::Default>::default()
+                    {
+                        0
+                    } else {
+                        ::arcane::es::protobuf::prost::encoding::string
+                            ::encoded_len(1u32, &self.text)
+                    } + self.meta.as_ref().map_or(0, |v| ::arcane::es::protobuf
+                        ::prost::encoding::message::encoded_len(2u32, v))
+                }
+
+                fn clear(&mut self) {
+                    self.text = ::std::default::Default::default();
+                    self.meta = ::std::default::Default::default();
+                }
+            }
+
+            #[automatically_derived]
+            impl ::arcane::es::protobuf::Proto for Event
+            where
+                Meta: ::arcane::es::protobuf::prost::Message
+                    + ::std::default::Default
+            {
+                const MESSAGE: &'static ::std::primitive::str = "Event";
+
+                fn definitions(
+                    defs: &mut ::std::vec::Vec<::std::string::String>,
+                ) {
+                    ::arcane::es::protobuf::define(defs, #definition);
+                }
+
+                fn to_proto(&self) -> ::std::vec::Vec<::std::primitive::u8> {
+                    ::arcane::es::protobuf::prost::Message::encode_to_vec(self)
+                }
+
+                fn from_proto(
+                    bytes: &[::std::primitive::u8],
+                ) -> ::std::result::Result<
+                    Self,
+                    ::arcane::es::protobuf::prost::DecodeError,
+                > {
+                    let mut this = Self {
+                        text: ::std::default::Default::default(),
+                        meta: ::std::default::Default::default(),
+                    };
+                    ::arcane::es::protobuf::prost::Message::merge(
+                        &mut this, bytes,
+                    )?;
+                    ::std::result::Result::Ok(this)
+                }
+            }
+        };
+
+        let definition = Definition::try_from(input).unwrap();
+
+        assert_eq!(definition.impl_proto().to_string(), output.to_string(),);
+    }
+
+    #[cfg(feature = "protobuf")]
+    #[test]
+    fn errors_on_unsupported_proto_field() {
+        let input = parse_quote! {
+            #[event(name = "event", proto)]
+            struct Event {
+                #[event(proto(tag = 1))]
+                count: usize,
+            }
+        };
+
+        let err = derive(input).unwrap_err();
+
+        assert_eq!(err.to_string(), "type is not supported by Protobuf");
+    }
+
     #[test]
     fn errors_on_enum() {
         let input = parse_quote! {
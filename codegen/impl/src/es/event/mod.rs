@@ -2,9 +2,18 @@
 
 pub mod impl_enum;
 pub mod impl_struct;
+#[cfg(feature = "pii")]
+pub mod pii;
+#[cfg(feature = "protobuf")]
+pub mod proto;
 
+#[cfg(all(doc, feature = "doc"))]
+use arcane_core::es::event;
 use proc_macro2::TokenStream;
+use quote::quote;
 use syn::spanned::Spanned as _;
+#[cfg(any(feature = "pii", feature = "protobuf"))]
+use synthez::ParseAttrs;
 use synthez::ToTokens as _;
 
 /// Expands `#[derive(Event)]` macro.
@@ -31,3 +40,119 @@ pub fn derive(input: TokenStream) -> syn::Result<TokenStream> {
         }
     })
 }
+
+/// Attributes of `#[derive(Event)]` macro placed on a struct field.
+#[cfg(any(feature = "pii", feature = "protobuf"))]
+#[derive(Debug, Default, ParseAttrs)]
+pub struct FieldAttrs {
+    /// Indicator whether this field contains personal data.
+    #[cfg(feature = "pii")]
+    #[parse(ident, alias = pii)]
+    pub personal: Option<syn::Ident>,
+
+    /// Indicator whether this field contains the ID of the subject the
+    /// personal data belongs to.
+    #[cfg(feature = "pii")]
+    #[parse(ident)]
+    pub subject: Option<syn::Ident>,
+
+    /// Path to the function returning the redacted placeholder value of this
+    /// field.
+    #[cfg(feature = "pii")]
+    #[parse(value)]
+    pub redacted: Option<syn::LitStr>,
+
+    /// [`proto::Attrs`] of this field.
+    #[cfg(feature = "protobuf")]
+    #[parse(value)]
+    pub proto: Option<proto::Attrs>,
+}
+
+/// Builds a `where` clause predicate bounding the provided type with the
+/// provided bounds.
+///
+/// The predicate is higher-ranked (via a dummy `for<'__a>` lifetime), so it's
+/// checked at usage sites only: an implementation bounded by it is silently
+/// omitted for types not satisfying it, instead of failing the compilation.
+#[cfg(any(
+    feature = "cloudevents",
+    feature = "proptest",
+    feature = "registry",
+))]
+#[must_use]
+pub fn higher_ranked(
+    ty: &TokenStream,
+    bounds: &TokenStream,
+) -> syn::WherePredicate {
+    syn::parse_quote! { for<'__a> #ty: #bounds }
+}
+
+/// [`event::name::Policy`] specified via `#[event(case = "...")]` and
+/// `#[event(max_len = ...)]` attributes.
+#[derive(Debug, Default)]
+pub struct NamingPolicy {
+    /// Variant of the [`event::name::Case`] to check [`event::Name`] segments
+    /// against, if any.
+    pub case: Option<syn::Ident>,
+
+    /// Maximum length of an [`event::Name`], if any.
+    pub max_len: Option<syn::LitInt>,
+}
+
+impl NamingPolicy {
+    /// Parses a [`NamingPolicy`] out of the provided `case` and `max_len`
+    /// attribute arguments.
+    ///
+    /// # Errors
+    ///
+    /// - If the provided `case` is not a known [`event::name::Case`].
+    /// - If the provided `max_len` cannot be parsed as [`usize`].
+    pub fn parse(
+        case: Option<&syn::LitStr>,
+        max_len: Option<&syn::LitInt>,
+    ) -> syn::Result<Self> {
+        let case = case
+            .map(|lit| {
+                let variant = match lit.value().as_str() {
+                    "snake_case" => "Snake",
+                    "kebab-case" => "Kebab",
+                    "camelCase" => "Camel",
+                    "PascalCase" => "Pascal",
+                    "lowercase" => "Lower",
+                    _ => {
+                        return Err(syn::Error::new(
+                            lit.span(),
+                            "unknown case, expected one of: `snake_case`, \
+                             `kebab-case`, `camelCase`, `PascalCase`, \
+                             `lowercase`",
+                        ));
+                    }
+                };
+                Ok(syn::Ident::new(variant, lit.span()))
+            })
+            .transpose()?;
+        if let Some(len) = max_len {
+            _ = len.base10_parse::<usize>()?;
+        }
+
+        Ok(Self { case, max_len: max_len.cloned() })
+    }
+
+    /// Indicates whether this [`NamingPolicy`] performs the basic checks only.
+    #[must_use]
+    pub const fn is_basic(&self) -> bool {
+        self.case.is_none() && self.max_len.is_none()
+    }
+
+    /// Generates code of constructing this [`NamingPolicy`] as an
+    /// [`event::name::Policy`].
+    #[must_use]
+    pub fn expand(&self) -> TokenStream {
+        let case = self.case.as_ref().map(|c| {
+            quote! { .case(::arcane::es::event::name::Case::#c) }
+        });
+        let max_len = self.max_len.as_ref().map(|l| quote! { .max_len(#l) });
+
+        quote! { ::arcane::es::event::name::Policy::new() #case #max_len }
+    }
+}
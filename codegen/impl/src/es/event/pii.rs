@@ -0,0 +1,306 @@
+//! Personal data machinery of `#[derive(Event)]` macro.
+
+#[cfg(all(doc, feature = "doc"))]
+use arcane_core::es::pii;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{ext::IdentExt as _, spanned::Spanned as _};
+use synthez::ParseAttrs as _;
+
+use super::FieldAttrs;
+
+/// Field of a struct containing personal data.
+#[derive(Debug)]
+pub struct Field {
+    /// [`syn::Ident`](struct@syn::Ident) of this [`Field`].
+    pub ident: syn::Ident,
+
+    /// [`syn::Type`] of this [`Field`].
+    pub ty: syn::Type,
+
+    /// Name of this [`Field`] in the serialized [`Event`], respecting
+    /// `#[serde(rename)]` and `#[serde(rename_all)]` attributes.
+    ///
+    /// [`Event`]: arcane_core::es::Event
+    pub name: String,
+
+    /// Path to the function returning the redacted placeholder value of this
+    /// [`Field`], if any.
+    pub redacted: Option<syn::Path>,
+}
+
+/// Personal data of a struct, used for generating a [`pii::Personal`] trait
+/// implementation.
+#[derive(Debug)]
+pub struct Personal {
+    /// [`syn::Ident`](struct@syn::Ident) of the field containing the ID of
+    /// the subject.
+    pub subject: syn::Ident,
+
+    /// [`Field`]s containing personal data.
+    pub fields: Vec<Field>,
+}
+
+impl Personal {
+    /// Parses [`Personal`] data out of the provided [`syn::Fields`] of a struct
+    /// with the provided [`syn::Attribute`]s, if any of them is marked with
+    /// `#[event(personal)]` attribute.
+    ///
+    /// # Errors
+    ///
+    /// - If [`FieldAttrs`] failed to parse.
+    /// - If `#[serde(rename)]` or `#[serde(rename_all)]` attributes cannot be
+    ///   parsed, or rename the field differently for serialization and
+    ///   deserialization.
+    /// - If `#[event(personal)]` or `#[event(subject)]` attributes are placed
+    ///   on unnamed fields.
+    /// - If `redacted` argument is specified without `personal` argument, or
+    ///   cannot be parsed as a [`syn::Path`].
+    /// - If there are personal fields, but no single subject field.
+    /// - If the subject field is marked as a personal one.
+    pub fn parse(
+        fields: &syn::Fields,
+        struct_attrs: &[syn::Attribute],
+    ) -> syn::Result<Option<Self>> {
+        let rename_all = serde_arg(struct_attrs, "rename_all")?
+            .map(|lit| {
+                RenameRule::parse(&lit.value()).ok_or_else(|| {
+                    syn::Error::new(lit.span(), "unknown `rename_all` rule")
+                })
+            })
+            .transpose()?;
+
+        let mut subject = None;
+        let mut personal = vec![];
+        for f in fields {
+            let attrs = FieldAttrs::parse_attrs("event", f)?;
+            if attrs.personal.is_none() && attrs.subject.is_none() {
+                if let Some(redacted) = &attrs.redacted {
+                    return Err(syn::Error::new(
+                        redacted.span(),
+                        "`redacted` argument requires `personal` argument to \
+                         be present",
+                    ));
+                }
+                continue;
+            }
+
+            let ident = f.ident.clone().ok_or_else(|| {
+                syn::Error::new(
+                    f.span(),
+                    "`personal` and `subject` arguments are allowed on named \
+                     fields only",
+                )
+            })?;
+
+            if let Some(subj) = &attrs.subject {
+                if attrs.personal.is_some() {
+                    return Err(syn::Error::new(
+                        subj.span(),
+                        "`subject` and `personal`/`pii` arguments are \
+                         mutually exclusive",
+                    ));
+                }
+                if subject.replace(ident).is_some() {
+                    return Err(syn::Error::new(
+                        subj.span(),
+                        "only a single field can be marked as `subject`",
+                    ));
+                }
+                continue;
+            }
+
+            let name = serde_arg(&f.attrs, "rename")?.map_or_else(
+                || {
+                    let name = ident.unraw().to_string();
+                    rename_all.map_or_else(|| name.clone(), |r| r.apply(&name))
+                },
+                |lit| lit.value(),
+            );
+            personal.push(Field {
+                ident,
+                ty: f.ty.clone(),
+                name,
+                redacted: attrs
+                    .redacted
+                    .as_ref()
+                    .map(syn::LitStr::parse)
+                    .transpose()?,
+            });
+        }
+
+        let Some(first) = personal.first() else {
+            return Ok(None);
+        };
+        let subject = subject.ok_or_else(|| {
+            syn::Error::new(
+                first.ident.span(),
+                "`personal` fields require a field marked with \
+                 `#[event(subject)]` attribute",
+            )
+        })?;
+        Ok(Some(Self { subject, fields: personal }))
+    }
+
+    /// Generates the body of the [`pii::Personal::redacted()`] method.
+    #[must_use]
+    pub fn redacted(&self) -> TokenStream {
+        let arms = self.fields.iter().map(|f| {
+            let (name, ty) = (&f.name, &f.ty);
+            let value = f.redacted.as_ref().map_or_else(
+                || quote! { <#ty as ::std::default::Default>::default() },
+                |path| quote! { #path() },
+            );
+            quote! {
+                #name => ::arcane::es::pii::serde_json::to_value::<#ty>(#value),
+            }
+        });
+
+        quote! {
+            match field {
+                #( #arms )*
+                _ => ::std::result::Result::Ok(
+                    ::arcane::es::pii::serde_json::Value::Null,
+                ),
+            }
+            .unwrap_or_default()
+        }
+    }
+}
+
+/// Returns the value of the provided `#[serde(...)]` argument, if any.
+///
+/// # Errors
+///
+/// - If `#[serde(...)]` attributes cannot be parsed.
+/// - If the argument has different values for serialization and
+///   deserialization.
+fn serde_arg(
+    attrs: &[syn::Attribute],
+    arg: &str,
+) -> syn::Result<Option<syn::LitStr>> {
+    let mut found = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident(arg) {
+                return skip(&meta);
+            }
+            if meta.input.peek(syn::Token![=]) {
+                found = Some(meta.value()?.parse::<syn::LitStr>()?);
+                return Ok(());
+            }
+            let (mut ser, mut de) = (None, None);
+            meta.parse_nested_meta(|nested| {
+                let value = nested.value()?.parse::<syn::LitStr>()?;
+                if nested.path.is_ident("serialize") {
+                    ser = Some(value);
+                } else if nested.path.is_ident("deserialize") {
+                    de = Some(value);
+                }
+                Ok(())
+            })?;
+            if ser.as_ref().map(syn::LitStr::value)
+                != de.as_ref().map(syn::LitStr::value)
+            {
+                return Err(meta.error(format!(
+                    "`{arg}` must be the same for serialization and \
+                     deserialization of `personal` fields",
+                )));
+            }
+            found = ser;
+            Ok(())
+        })?;
+    }
+    Ok(found)
+}
+
+/// Skips the value of the provided [`syn::meta::ParseNestedMeta`], if any.
+///
+/// # Errors
+///
+/// If the value cannot be parsed.
+fn skip(meta: &syn::meta::ParseNestedMeta<'_>) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        drop(meta.value()?.parse::<syn::Expr>()?);
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        _ = syn::parenthesized!(content in meta.input);
+        drop(content.parse::<TokenStream>()?);
+    }
+    Ok(())
+}
+
+/// Rule of `#[serde(rename_all = "...")]` attribute.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RenameRule {
+    /// `lowercase` rule.
+    Lower,
+
+    /// `UPPERCASE` rule.
+    Upper,
+
+    /// `PascalCase` rule.
+    Pascal,
+
+    /// `camelCase` rule.
+    Camel,
+
+    /// `snake_case` rule.
+    Snake,
+
+    /// `SCREAMING_SNAKE_CASE` rule.
+    ScreamingSnake,
+
+    /// `kebab-case` rule.
+    Kebab,
+
+    /// `SCREAMING-KEBAB-CASE` rule.
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    /// Parses a [`RenameRule`] out of its `serde` name, if it's a known one.
+    #[must_use]
+    pub fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => return None,
+        })
+    }
+
+    /// Applies this [`RenameRule`] to the provided `snake_case` field name, the
+    /// same way `serde` does.
+    #[must_use]
+    pub fn apply(self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_owned(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal | Self::Camel => {
+                let mut out = String::with_capacity(field.len());
+                let mut capitalize = self == Self::Pascal;
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        out.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        out.push(c);
+                    }
+                }
+                out
+            }
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => {
+                field.to_ascii_uppercase().replace('_', "-")
+            }
+        }
+    }
+}
@@ -0,0 +1,548 @@
+//! [Protocol Buffers] machinery of `#[derive(Event)]` macro.
+//!
+//! [Protocol Buffers]: https://protobuf.dev
+
+use std::ops::RangeInclusive;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
+    spanned::Spanned as _,
+};
+use synthez::ParseAttrs as _;
+
+use super::FieldAttrs;
+
+/// Maximum Protobuf tag.
+const MAX_TAG: u32 = (1 << 29) - 1;
+
+/// Protobuf tags reserved for its implementation.
+const RESERVED_TAGS: RangeInclusive<u32> = 19_000..=19_999;
+
+/// Arguments of `#[event(proto(...))]` attribute placed on a struct field or
+/// an enum variant.
+#[derive(Debug)]
+pub struct Attrs {
+    /// Protobuf tag of the field or the `oneof` variant.
+    pub tag: syn::LitInt,
+}
+
+impl Parse for Attrs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let arg = input.parse::<syn::Ident>()?;
+        if arg != "tag" {
+            return Err(syn::Error::new(
+                arg.span(),
+                "unknown `proto` attribute argument",
+            ));
+        }
+        _ = input.parse::<syn::Token![=]>()?;
+        Ok(Self { tag: input.parse()? })
+    }
+}
+
+impl ToTokens for Attrs {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.tag.to_tokens(tokens);
+    }
+}
+
+/// Parses the explicit Protobuf tags of the provided items, given along with
+/// the [`Span`]s to report their absence at.
+///
+/// Tags are required to be explicit, so reordering fields or variants never
+/// changes the wire format silently.
+///
+/// # Errors
+///
+/// - If any item has no tag.
+/// - If any tag is out of the allowed range or is a reserved one.
+/// - If any tag is duplicated.
+pub fn parse_tags(
+    items: impl IntoIterator<Item = (Span, Option<syn::LitInt>)>,
+) -> syn::Result<Vec<u32>> {
+    let mut tags = Vec::<u32>::new();
+    for (span, lit) in items {
+        let lit = lit.ok_or_else(|| {
+            syn::Error::new(
+                span,
+                "`proto` argument requires an explicit \
+                 `#[event(proto(tag = N))]` attribute",
+            )
+        })?;
+        let tag = lit.base10_parse::<u32>()?;
+        if tag == 0 || tag > MAX_TAG || RESERVED_TAGS.contains(&tag) {
+            return Err(syn::Error::new(
+                lit.span(),
+                "Protobuf tag must be in `1..=536870911` range and outside \
+                 the reserved `19000..=19999` one",
+            ));
+        }
+        if tags.contains(&tag) {
+            return Err(syn::Error::new(lit.span(), "duplicate Protobuf tag"));
+        }
+        tags.push(tag);
+    }
+    Ok(tags)
+}
+
+/// Kind of values of a [`Field`].
+#[derive(Debug)]
+pub enum Kind {
+    /// Scalar value of the provided Protobuf type, encoded by the `prost`
+    /// encoding module of the same name.
+    Scalar(&'static str),
+
+    /// Message of the provided name.
+    Message(String),
+}
+
+impl Kind {
+    /// Parses a [`Kind`] of the provided [`syn::Type`], used in a struct with
+    /// the provided [`syn::Generics`].
+    ///
+    /// # Errors
+    ///
+    /// - If the [`syn::Type`] is a primitive one not supported by Protobuf.
+    /// - If the [`syn::Type`] is a generic one or has generic arguments, so
+    ///   its message name is unknown.
+    fn parse(ty: &syn::Type, generics: &syn::Generics) -> syn::Result<Self> {
+        let unsupported =
+            || syn::Error::new(ty.span(), "type is not supported by Protobuf");
+
+        let syn::Type::Path(path) = ty else {
+            return Err(unsupported());
+        };
+        let segment = path.path.segments.last().ok_or_else(unsupported)?;
+        let ident = segment.ident.to_string();
+        Ok(match ident.as_str() {
+            "String" => Self::Scalar("string"),
+            "bool" => Self::Scalar("bool"),
+            "i32" => Self::Scalar("int32"),
+            "i64" => Self::Scalar("int64"),
+            "u32" => Self::Scalar("uint32"),
+            "u64" => Self::Scalar("uint64"),
+            "f32" => Self::Scalar("float"),
+            "f64" => Self::Scalar("double"),
+            "Vec" if generic_arg(segment).is_some_and(|t| is(t, "u8")) => {
+                Self::Scalar("bytes")
+            }
+            "u8" | "u16" | "u128" | "usize" | "i8" | "i16" | "i128"
+            | "isize" | "char" | "str" | "Option" | "Vec" => {
+                return Err(unsupported());
+            }
+            _ if !segment.arguments.is_none()
+                || (path.qself.is_none()
+                    && path.path.segments.len() == 1
+                    && generics.type_params().any(|p| p.ident == ident)) =>
+            {
+                return Err(syn::Error::new(
+                    ty.span(),
+                    "generic types are not supported by Protobuf, as their \
+                     message names are unknown",
+                ));
+            }
+            _ => Self::Message(ident),
+        })
+    }
+
+    /// Returns the Protobuf type of this [`Kind`].
+    fn proto_type(&self) -> &str {
+        match self {
+            Self::Scalar(ty) => ty,
+            Self::Message(name) => name,
+        }
+    }
+
+    /// Indicates whether repeated values of this [`Kind`] are packed.
+    fn is_packed(&self) -> bool {
+        matches!(self, Self::Scalar(ty) if !matches!(*ty, "string" | "bytes"))
+    }
+
+    /// Returns the path to the `prost` encoding module of this [`Kind`].
+    fn module(&self) -> TokenStream {
+        let module = syn::Ident::new(
+            match self {
+                Self::Scalar(ty) => ty,
+                Self::Message(_) => "message",
+            },
+            proc_macro2::Span::call_site(),
+        );
+        quote! { ::arcane::es::protobuf::prost::encoding::#module }
+    }
+}
+
+/// Label of a [`Field`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Label {
+    /// Singular field.
+    Singular,
+
+    /// Field with explicit presence (an [`Option`]).
+    Optional,
+
+    /// Repeated field (a [`Vec`]).
+    Repeated,
+}
+
+/// Field of a struct represented as a Protobuf message field.
+#[derive(Debug)]
+pub struct Field {
+    /// [`syn::Ident`](struct@syn::Ident) of this [`Field`].
+    pub ident: syn::Ident,
+
+    /// [`syn::Type`] of the values of this [`Field`].
+    pub ty: syn::Type,
+
+    /// Protobuf tag of this [`Field`].
+    pub tag: u32,
+
+    /// [`Kind`] of the values of this [`Field`].
+    pub kind: Kind,
+
+    /// [`Label`] of this [`Field`].
+    pub label: Label,
+}
+
+impl Field {
+    /// Parses [`Field`]s out of the provided [`syn::Fields`] of a struct with
+    /// the provided [`syn::Generics`], numbering them with their explicit
+    /// `#[event(proto(tag = N))]` attributes.
+    ///
+    /// # Errors
+    ///
+    /// - If the [`syn::Fields`] are unnamed.
+    /// - If [`FieldAttrs`] failed to parse.
+    /// - If the tags fail to [`parse_tags()`].
+    /// - If any [`syn::Field`] has a type not supported by Protobuf.
+    pub fn parse_all(
+        fields: &syn::Fields,
+        generics: &syn::Generics,
+    ) -> syn::Result<Vec<Self>> {
+        if matches!(fields, syn::Fields::Unnamed(_)) {
+            return Err(syn::Error::new(
+                fields.span(),
+                "`proto` argument requires struct fields to be named",
+            ));
+        }
+        let attrs = fields
+            .iter()
+            .map(|f| FieldAttrs::parse_attrs("event", f))
+            .collect::<syn::Result<Vec<_>>>()?;
+        let tags = parse_tags(fields.iter().zip(&attrs).map(|(f, a)| {
+            (f.span(), a.proto.as_ref().map(|p| p.tag.clone()))
+        }))?;
+        fields
+            .iter()
+            .zip(tags)
+            .map(|(f, tag)| {
+                let ident = f.ident.clone().ok_or_else(|| {
+                    syn::Error::new(f.span(), "field must be named")
+                })?;
+                Self::parse(ident, &f.ty, tag, generics)
+            })
+            .collect()
+    }
+
+    /// Parses a [`Field`] with the provided [`syn::Ident`](struct@syn::Ident),
+    /// [`syn::Type`] and tag, of a struct with the provided [`syn::Generics`].
+    ///
+    /// # Errors
+    ///
+    /// If the [`syn::Type`] is not supported by Protobuf.
+    fn parse(
+        ident: syn::Ident,
+        ty: &syn::Type,
+        tag: u32,
+        generics: &syn::Generics,
+    ) -> syn::Result<Self> {
+        let wrapped = |wrapper| {
+            let syn::Type::Path(path) = ty else { return None };
+            let segment = path.path.segments.last()?;
+            (segment.ident == wrapper).then(|| generic_arg(segment)).flatten()
+        };
+
+        let (label, inner) = wrapped("Option")
+            .map(|inner| (Label::Optional, inner))
+            .or_else(|| {
+                wrapped("Vec")
+                    .filter(|t| !is(t, "u8"))
+                    .map(|inner| (Label::Repeated, inner))
+            })
+            .unwrap_or((Label::Singular, ty));
+
+        Ok(Self {
+            ident,
+            ty: inner.clone(),
+            tag,
+            kind: Kind::parse(inner, generics)?,
+            label,
+        })
+    }
+
+    /// Returns the definition of this [`Field`] in a message.
+    #[must_use]
+    pub fn definition(&self) -> String {
+        let label = match self.label {
+            Label::Singular => "",
+            Label::Optional => "optional ",
+            Label::Repeated => "repeated ",
+        };
+        format!(
+            "  {label}{} {} = {};\n",
+            self.kind.proto_type(),
+            self.ident,
+            self.tag,
+        )
+    }
+
+    /// Returns the `where` clause predicate required by this [`Field`], if
+    /// any.
+    #[must_use]
+    pub fn bound(&self) -> Option<syn::WherePredicate> {
+        let ty = &self.ty;
+        matches!(self.kind, Kind::Message(_)).then(|| {
+            parse_quote! {
+                #ty: ::arcane::es::protobuf::prost::Message
+                   + ::std::default::Default
+            }
+        })
+    }
+
+    /// Generates code encoding this [`Field`] into a `buf`.
+    #[must_use]
+    pub fn encode(&self) -> TokenStream {
+        let (ident, tag, module) = (&self.ident, self.tag, self.kind.module());
+        let ty = &self.ty;
+        match (self.label, &self.kind) {
+            (Label::Singular, Kind::Scalar(_)) => quote! {
+                if self.#ident != <#ty as ::std::default::Default>::default() {
+                    #module::encode(#tag, &self.#ident, buf);
+                }
+            },
+            (Label::Singular, Kind::Message(_)) => quote! {
+                #module::encode(#tag, &self.#ident, buf);
+            },
+            (Label::Optional, _) => quote! {
+                if let ::std::option::Option::Some(v) = &self.#ident {
+                    #module::encode(#tag, v, buf);
+                }
+            },
+            (Label::Repeated, kind) if kind.is_packed() => quote! {
+                #module::encode_packed(#tag, &self.#ident, buf);
+            },
+            (Label::Repeated, _) => quote! {
+                #module::encode_repeated(#tag, &self.#ident, buf);
+            },
+        }
+    }
+
+    /// Generates a `match` arm merging this [`Field`] from a `buf`.
+    #[must_use]
+    pub fn merge(&self) -> TokenStream {
+        let (ident, tag, module) = (&self.ident, self.tag, self.kind.module());
+        let merge = match self.label {
+            Label::Singular => quote! {
+                #module::merge(wire_type, &mut self.#ident, buf, ctx)
+            },
+            Label::Optional => quote! {
+                #module::merge(
+                    wire_type,
+                    self.#ident.get_or_insert_with(
+                        ::std::default::Default::default,
+                    ),
+                    buf,
+                    ctx,
+                )
+            },
+            Label::Repeated => quote! {
+                #module::merge_repeated(wire_type, &mut self.#ident, buf, ctx)
+            },
+        };
+        quote! { #tag => #merge, }
+    }
+
+    /// Generates code calculating the encoded length of this [`Field`].
+    #[must_use]
+    pub fn encoded_len(&self) -> TokenStream {
+        let (ident, tag, module) = (&self.ident, self.tag, self.kind.module());
+        let ty = &self.ty;
+        match (self.label, &self.kind) {
+            (Label::Singular, Kind::Scalar(_)) => quote! {
+                if self.#ident == <#ty as ::std::default::Default>::default() {
+                    0
+                } else {
+                    #module::encoded_len(#tag, &self.#ident)
+                }
+            },
+            (Label::Singular, Kind::Message(_)) => quote! {
+                #module::encoded_len(#tag, &self.#ident)
+            },
+            (Label::Optional, _) => quote! {
+                self.#ident
+                    .as_ref()
+                    .map_or(0, |v| #module::encoded_len(#tag, v))
+            },
+            (Label::Repeated, kind) if kind.is_packed() => quote! {
+                #module::encoded_len_packed(#tag, &self.#ident)
+            },
+            (Label::Repeated, _) => quote! {
+                #module::encoded_len_repeated(#tag, &self.#ident)
+            },
+        }
+    }
+}
+
+/// Converts the provided `PascalCase` identifier into a `snake_case` one.
+#[must_use]
+pub fn snake_case(ident: &str) -> String {
+    let mut out = String::with_capacity(ident.len());
+    for (i, c) in ident.trim_start_matches('_').chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Returns the first generic type argument of the provided
+/// [`syn::PathSegment`], if any.
+fn generic_arg(segment: &syn::PathSegment) -> Option<&syn::Type> {
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|a| {
+        if let syn::GenericArgument::Type(ty) = a { Some(ty) } else { None }
+    })
+}
+
+/// Indicates whether the provided [`syn::Type`] is a path ending with the
+/// provided `ident`.
+fn is(ty: &syn::Type, ident: &str) -> bool {
+    let syn::Type::Path(path) = ty else { return false };
+    path.path.segments.last().is_some_and(|s| s.ident == ident)
+}
+
+#[cfg(test)]
+mod spec {
+    use syn::parse_quote;
+
+    use super::{Field, Label, snake_case};
+
+    #[test]
+    fn maps_field_types() {
+        let fields: syn::Fields = syn::Fields::Named(parse_quote! {{
+            #[event(proto(tag = 1))]
+            text: String,
+            #[event(proto(tag = 2))]
+            data: Vec<u8>,
+            #[event(proto(tag = 3))]
+            tags: Vec<String>,
+            #[event(proto(tag = 4))]
+            scores: Vec<u32>,
+            #[event(proto(tag = 5))]
+            edited: Option<i64>,
+            #[event(proto(tag = 6))]
+            author: Author,
+        }});
+        let fields = Field::parse_all(&fields, &parse_quote! {}).unwrap();
+
+        let defs = fields.iter().map(Field::definition).collect::<String>();
+        assert_eq!(
+            defs,
+            "  string text = 1;\n\
+             \x20 bytes data = 2;\n\
+             \x20 repeated string tags = 3;\n\
+             \x20 repeated uint32 scores = 4;\n\
+             \x20 optional int64 edited = 5;\n\
+             \x20 Author author = 6;\n",
+        );
+        assert_eq!(fields[3].label, Label::Repeated);
+        assert!(fields[5].bound().is_some());
+    }
+
+    #[test]
+    fn numbers_fields_by_explicit_tags() {
+        let fields: syn::Fields = syn::Fields::Named(parse_quote! {{
+            #[event(proto(tag = 2))]
+            text: String,
+            #[event(proto(tag = 1))]
+            id: u64,
+        }});
+        let fields = Field::parse_all(&fields, &parse_quote! {}).unwrap();
+
+        let defs = fields.iter().map(Field::definition).collect::<String>();
+        assert_eq!(defs, "  string text = 2;\n  uint64 id = 1;\n");
+    }
+
+    #[test]
+    fn errors_on_invalid_tags() {
+        for fields in [
+            syn::Fields::Named(parse_quote! {{ text: String }}),
+            syn::Fields::Named(parse_quote! {{
+                #[event(proto(tag = 1))]
+                text: String,
+                id: u64,
+            }}),
+            syn::Fields::Named(parse_quote! {{
+                #[event(proto(tag = 1))]
+                text: String,
+                #[event(proto(tag = 1))]
+                id: u64,
+            }}),
+            syn::Fields::Named(parse_quote! {{
+                #[event(proto(tag = 0))]
+                text: String,
+            }}),
+            syn::Fields::Named(parse_quote! {{
+                #[event(proto(tag = 19000))]
+                text: String,
+            }}),
+            syn::Fields::Named(parse_quote! {{
+                #[event(proto(tag = 536870912))]
+                text: String,
+            }}),
+        ] {
+            assert!(Field::parse_all(&fields, &parse_quote! {}).is_err());
+        }
+    }
+
+    #[test]
+    fn errors_on_unsupported_types() {
+        for fields in [
+            syn::Fields::Named(parse_quote! {{
+                #[event(proto(tag = 1))]
+                count: usize,
+            }}),
+            syn::Fields::Named(parse_quote! {{
+                #[event(proto(tag = 1))]
+                nested: Option<Vec<u32>>,
+            }}),
+            syn::Fields::Named(parse_quote! {{
+                #[event(proto(tag = 1))]
+                map: HashMap<String, u32>,
+            }}),
+            syn::Fields::Named(parse_quote! {{
+                #[event(proto(tag = 1))]
+                inner: Option<T>,
+            }}),
+            syn::Fields::Unnamed(parse_quote! {(String)}),
+        ] {
+            let generics = parse_quote! { <T> };
+            assert!(Field::parse_all(&fields, &generics).is_err());
+        }
+    }
+
+    #[test]
+    fn converts_to_snake_case() {
+        assert_eq!(snake_case("Created"), "created");
+        assert_eq!(snake_case("MessagePosted"), "message_posted");
+    }
+}
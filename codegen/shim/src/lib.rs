@@ -158,7 +158,7 @@ mod only_for_docs_generation {
 #[cfg(feature = "es")]
 use arcane_codegen_impl as codegen;
 #[cfg(all(doc, feature = "doc", feature = "es"))]
-use arcane_core::es::event;
+use arcane_core::es::{event, stream};
 #[cfg(feature = "es")]
 use proc_macro::TokenStream;
 
@@ -179,6 +179,14 @@ use proc_macro::TokenStream;
 /// Also, provides a blanket [`event::Sourced`] implementation for every state,
 /// which can be sourced from all the enum variants.
 ///
+/// With the `proptest` feature enabled, also provides `proptest` strategies
+/// (`event::arbitrary::Arbitrary` and `proptest::arbitrary::Arbitrary`
+/// implementations) generating the enum variants, for enums implementing
+/// [`Debug`], with all the variants implementing
+/// `proptest::arbitrary::Arbitrary`. Use `event::arbitrary::history()` to
+/// generate valid [`Event`] sequences: exactly one `#[event(init)]` variant
+/// first, followed by the other ones.
+///
 /// > **WARNING**: Currently may not work with complex generics using where
 /// >              clause because of `const` evaluation limitations. Should be
 /// >              lifted once [rust-lang/rust#57775] is resolved.
@@ -191,6 +199,33 @@ use proc_macro::TokenStream;
 ///
 /// Indicator whether the [`event::Revisable`] trait should be implemented.
 ///
+/// #### `#[event(prefix = "...")]` (optional)
+///
+/// Namespace prepended to the [`Event::name()`]s of all the variants (and to
+/// the [`event::reflect::Static::NAMES`]). Should usually end with the
+/// [`event::name::SEPARATOR`], like `"chat."`. Cannot be empty or contain
+/// whitespaces. Requires all the variants to be [`event::Static`], so their
+/// prefixed [`Event::name()`]s are computed at compile time.
+///
+/// #### `#[event(case = "...")]` (optional)
+///
+/// [`event::name::Case`] every segment of every (prefixed) variant's
+/// [`Event::name()`] is checked against at compile time. One of:
+/// `snake_case`, `kebab-case`, `camelCase`, `PascalCase`, `lowercase`.
+///
+/// #### `#[event(max_len = <usize>)]` (optional)
+///
+/// Maximum length of every (prefixed) variant's [`Event::name()`], checked at
+/// compile time.
+///
+/// #### `#[event(stream = "...")]` (optional)
+///
+/// [`stream::Category`] of the [`Stream`]s this enum's [`Event`]s belong to,
+/// generating a [`stream::Categorized`] implementation. Allows reading all the
+/// [`Stream`]s of this [`stream::Category`] at once (via
+/// [`EventStore::read_category()`]). Cannot be empty or contain
+/// [`stream::SEPARATOR`] or whitespaces.
+///
 /// ## Variant attributes
 ///
 /// #### `#[event(init)]` (optional)
@@ -260,6 +295,38 @@ use proc_macro::TokenStream;
 /// }
 /// ```
 ///
+/// ```rust
+/// # use arcane::es::{Event, event::reflect};
+/// #
+/// #[derive(Event)]
+/// #[event(name = "created")]
+/// struct Created;
+///
+/// #[derive(Event)]
+/// #[event(prefix = "chat.", case = "snake_case")]
+/// enum ChatEvent {
+///     Created(Created),
+/// }
+///
+/// assert_eq!(ChatEvent::Created(Created).name(), "chat.created");
+/// assert_eq!(<ChatEvent as reflect::Static>::NAMES, ["chat.created"],);
+/// ```
+///
+/// ```rust,compile_fail,E0080
+/// # use arcane::es::Event;
+/// #
+/// #[derive(Event)]
+/// #[event(name = "Created")]
+/// struct Created;
+///
+/// // This fails to compile, as `chat.Created` isn't in `snake_case`.
+/// #[derive(Event)]
+/// #[event(prefix = "chat.", case = "snake_case")]
+/// enum ChatEvent {
+///     Created(Created),
+/// }
+/// ```
+///
 /// # Structs
 ///
 /// This macro generates an [`event::Static`] (and, optionally, an
@@ -271,12 +338,50 @@ use proc_macro::TokenStream;
 ///
 /// Value of the [`event::Static::NAME`] constant.
 ///
+/// #### `#[event(alias = "...")]` (optional, repeatable)
+///
+/// Former name of the [`Event`], added to the [`event::Static::ALIASES`]
+/// constant. Allows to rename an [`Event`] without breaking its already stored
+/// history: [`Event::name()`] always returns the canonical
+/// [`event::Static::NAME`], while [`event::reflect::Static::canonical_name()`]
+/// resolves aliases too.
+///
+/// If a naming policy is specified (via `case` or `max_len` arguments),
+/// aliases are checked against the basic [`event::name::Policy`] only. Aliases
+/// occupy the same name and revision slot as the canonical
+/// [`event::Static::NAME`] when checking an enum for uniqueness.
+///
 /// #### `#[event(revision = <non-zero-u16>)]` (optional)
 ///
 /// Aliases: `#[event(rev = <non-zero-u16>)]`
 ///
 /// Value of the [`event::Concrete::REVISION`] constant.
 ///
+/// #### `#[event(supports = <start>..=<end>)]` (optional)
+///
+/// Aliases: `#[event(supported = <start>..=<end>)]`
+///
+/// Range of historical revisions (containing the current one) this struct is
+/// still able to deserialize, exposed via
+/// [`event::reflect::Compatible::SUPPORTED_REVISIONS`]. Requires the
+/// `revision` argument to be specified. Exclusive ranges (`<start>..<end>`)
+/// are accepted as well.
+///
+/// #### `#[event(case = "...")]` (optional)
+///
+/// [`event::name::Case`] every segment of the [`event::Static::NAME`] is
+/// checked against at compile time. One of: `snake_case`, `kebab-case`,
+/// `camelCase`, `PascalCase`, `lowercase`.
+///
+/// Naming checks are opt-in: if neither this nor the `max_len` argument is
+/// specified, the [`event::Static::NAME`] is not checked at all. Otherwise,
+/// it's also checked to be non-empty, to contain no whitespaces and no empty
+/// segments (see [`event::name::Policy`]).
+///
+/// #### `#[event(max_len = <usize>)]` (optional)
+///
+/// Maximum length of the [`event::Static::NAME`], checked at compile time.
+///
 /// ## Example
 ///
 /// ```rust
@@ -285,9 +390,53 @@ use proc_macro::TokenStream;
 /// #[derive(Event)]
 /// #[event(name = "created", revision = 1)]
 /// struct Created;
+///
+/// #[derive(Event)]
+/// #[event(name = "renamed", revision = 3, supports = 1..=3)]
+/// struct Renamed;
+///
+/// #[derive(Event)]
+/// #[event(name = "chat.message_posted", case = "snake_case", max_len = 32)]
+/// struct MessagePosted;
+///
+/// #[derive(Event)]
+/// #[event(name = "chat.message_edited", alias = "message.edited")]
+/// struct MessageEdited;
+/// ```
+///
+/// ```rust,compile_fail,E0080
+/// # use arcane::es::Event;
+/// #
+/// #[derive(Event)]
+/// #[event(name = "chat.created", alias = "created")]
+/// struct ChatCreated;
+///
+/// #[derive(Event)]
+/// #[event(name = "created")]
+/// struct Created;
+///
+/// // This fails to compile, as the alias of `ChatCreated` occupies the same
+/// // `event::Name` as `Created`.
+/// #[derive(Event)]
+/// enum AnyEvent {
+///     ChatCreated(ChatCreated),
+///     Created(Created),
+/// }
+/// ```
+///
+/// ```rust,compile_fail,E0080
+/// # use arcane::es::Event;
+/// #
+/// // This fails to compile, as the name contains an empty segment, which is
+/// // checked once any naming policy is specified.
+/// #[derive(Event)]
+/// #[event(name = "chat..created", case = "snake_case")]
+/// struct Created;
 /// ```
 ///
 /// [`Event`]: event::Event
+/// [`EventStore::read_category()`]: arcane_core::es::EventStore::read_category
+/// [`Stream`]: arcane_core::es::Stream
 /// [`Event::name`]: event::Event::name()
 /// [`Event::name()`]: event::Event::name()
 /// [rust-lang/rust#57775]: https://github.com/rust-lang/rust/issues/57775
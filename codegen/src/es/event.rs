@@ -1,5 +1,7 @@
 //! Code generation related to [`Event`] and its aiding [`Event`] machinery.
 
+use std::str;
+
 #[doc(inline)]
 pub use arcane_codegen_shim::Event;
 #[cfg(all(doc, feature = "doc"))]
@@ -71,6 +73,334 @@ pub const fn concat_slices<T: Copy, const LEN: usize>(
     out
 }
 
+/// Prefixes the specified slice of [`event::Name`]s with the specified prefix
+/// at `const` evaluation phase.
+///
+/// # Panics
+///
+/// If the specified slice is empty.
+#[macro_export]
+macro_rules! const_prefix_names {
+    ($prefix:expr, $names:expr $(,)?) => {{
+        const PREFIX: &str = $prefix;
+        const NAMES: &[&str] = $names;
+        const BYTES_LEN: usize =
+            $crate::es::event::prefixed_names_len(PREFIX, NAMES);
+        const BYTES: [u8; BYTES_LEN] =
+            $crate::es::event::prefix_names::<BYTES_LEN>(PREFIX, NAMES);
+        const OUT: [&str; NAMES.len()] = $crate::es::event::split_names::<
+            { NAMES.len() },
+        >(&BYTES, PREFIX.len(), NAMES);
+        &OUT
+    }};
+}
+
+/// Prefixes all the [`event::Name`]s in the specified [`Reflect::META`] with
+/// the specified prefix at `const` evaluation phase.
+///
+/// # Panics
+///
+/// If the specified [`Reflect::META`] is empty.
+#[macro_export]
+macro_rules! const_prefix_meta {
+    ($prefix:expr, $meta:expr $(,)?) => {{
+        const META_PREFIX: &str = $prefix;
+        const META: &[(&str, &str, &str)] = $meta;
+        const META_NAMES: [&str; META.len()] =
+            $crate::es::event::meta_names::<{ META.len() }>(META);
+        const PREFIXED: &[&str] =
+            $crate::const_prefix_names!(META_PREFIX, &META_NAMES);
+        const OUT: [(&str, &str, &str); META.len()] =
+            $crate::es::event::with_meta_names::<{ META.len() }>(
+                META, PREFIXED,
+            );
+        &OUT
+    }};
+}
+
+/// Concatenates the specified slices of [`event::Static::ALIASES`] pairs at
+/// `const` evaluation phase.
+///
+/// Unlike the [`const_concat_slices!`] macro, allows all the specified slices
+/// to be empty.
+#[macro_export]
+macro_rules! const_concat_aliases {
+    ($($s:expr),* $(,)?) => {{
+        const LEN: usize = 0 $(+ $s.len())*;
+        &$crate::es::event::concat_aliases::<LEN>(&[$($s),*])
+    }};
+}
+
+/// Prefixes both the aliases and the canonical [`event::Name`]s in the
+/// specified slice of [`event::Static::ALIASES`] pairs with the specified
+/// prefix at `const` evaluation phase.
+#[macro_export]
+macro_rules! const_prefix_aliases {
+    ($prefix:expr, $aliases:expr $(,)?) => {{
+        const ALIASES_PREFIX: &str = $prefix;
+        const ALIASES: &[(&str, &str)] = $aliases;
+        const ALIASES_FROM: [&str; ALIASES.len()] =
+            $crate::es::event::alias_names::<{ ALIASES.len() }>(ALIASES, false);
+        const ALIASES_TO: [&str; ALIASES.len()] =
+            $crate::es::event::alias_names::<{ ALIASES.len() }>(ALIASES, true);
+        const PREFIXED_FROM: &[&str] =
+            $crate::const_prefix_names!(ALIASES_PREFIX, &ALIASES_FROM);
+        const PREFIXED_TO: &[&str] =
+            $crate::const_prefix_names!(ALIASES_PREFIX, &ALIASES_TO);
+        const ALIASES_OUT: [(&str, &str); ALIASES.len()] =
+            $crate::es::event::zip_aliases::<{ ALIASES.len() }>(
+                PREFIXED_FROM,
+                PREFIXED_TO,
+            );
+        &ALIASES_OUT
+    }};
+}
+
+/// Concatenates the specified slice of [`event::Static::ALIASES`] pairs slices
+/// into an array of `LEN` size.
+///
+/// > **NOTE**: This is an inner implementation detail of the
+/// >           [`const_concat_aliases!`] macro.
+///
+/// # Panics
+///
+/// If `LEN` size mismatches the total length of all the specified slices.
+#[must_use]
+pub const fn concat_aliases<const LEN: usize>(
+    input: &[&[(&'static str, &'static str)]],
+) -> [(&'static str, &'static str); LEN] {
+    let mut out = [("", ""); LEN];
+    let (mut i, mut n) = (0, 0);
+    while i < input.len() {
+        let mut j = 0;
+        while j < input[i].len() {
+            // TODO: Use `assert!()` here, once it's allowed in `const`
+            //       context.
+            #[expect(clippy::manual_assert, reason = "const context")]
+            if n >= LEN {
+                panic!("actual slices lengths exceed the specified `LEN` const")
+            }
+            out[n] = input[i][j];
+            n += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    // TODO: Use `assert_eq!()` here, once it's allowed in `const` context.
+    #[expect(clippy::manual_assert, reason = "const context")]
+    if n != LEN {
+        panic!("actual slices lengths mismatches the specified `LEN` const")
+    }
+    out
+}
+
+/// Extracts `N` either aliases or canonical [`event::Name`]s (if `canonical` is
+/// `true`) from the specified [`event::Static::ALIASES`] pairs.
+///
+/// > **NOTE**: This is an inner implementation detail of the
+/// >           [`const_prefix_aliases!`] macro.
+///
+/// # Panics
+///
+/// If `N` mismatches the length of the specified `aliases`.
+#[must_use]
+pub const fn alias_names<const N: usize>(
+    aliases: &[(&'static str, &'static str)],
+    canonical: bool,
+) -> [&'static str; N] {
+    // TODO: Use `assert_eq!()` here, once it's allowed in `const` context.
+    #[expect(clippy::manual_assert, reason = "const context")]
+    if aliases.len() != N {
+        panic!("aliases length mismatches the specified `N` const")
+    }
+
+    let mut out = [""; N];
+    let mut i = 0;
+    while i < N {
+        let (alias, name) = aliases[i];
+        out[i] = if canonical { name } else { alias };
+        i += 1;
+    }
+    out
+}
+
+/// Zips the specified `aliases` and canonical `names` into `N`
+/// [`event::Static::ALIASES`] pairs.
+///
+/// > **NOTE**: This is an inner implementation detail of the
+/// >           [`const_prefix_aliases!`] macro.
+///
+/// # Panics
+///
+/// If `N` mismatches the length of the specified `aliases` or `names`.
+#[must_use]
+pub const fn zip_aliases<const N: usize>(
+    aliases: &[&'static str],
+    names: &[&'static str],
+) -> [(&'static str, &'static str); N] {
+    // TODO: Use `assert_eq!()` here, once it's allowed in `const` context.
+    #[expect(clippy::manual_assert, reason = "const context")]
+    if aliases.len() != N || names.len() != N {
+        panic!("aliases or names length mismatches the specified `N` const")
+    }
+
+    let mut out = [("", ""); N];
+    let mut i = 0;
+    while i < N {
+        out[i] = (aliases[i], names[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Calculates the total length of all the specified `names` with the specified
+/// `prefix` prepended to each of them.
+///
+/// > **NOTE**: This is an inner implementation detail of the
+/// >           [`const_prefix_names!`] macro.
+#[must_use]
+pub const fn prefixed_names_len(prefix: &str, names: &[&str]) -> usize {
+    let (mut i, mut len) = (0, 0);
+    while i < names.len() {
+        len += prefix.len() + names[i].len();
+        i += 1;
+    }
+    len
+}
+
+/// Writes all the specified `names` with the specified `prefix` prepended to
+/// each of them into a single array of `LEN` bytes.
+///
+/// > **NOTE**: This is an inner implementation detail of the
+/// >           [`const_prefix_names!`] macro.
+///
+/// # Panics
+///
+/// If `LEN` mismatches the [`prefixed_names_len()`].
+#[must_use]
+pub const fn prefix_names<const LEN: usize>(
+    prefix: &str,
+    names: &[&str],
+) -> [u8; LEN] {
+    let mut out = [0; LEN];
+    let (mut i, mut n) = (0, 0);
+    while i < names.len() {
+        let (prefix, name) = (prefix.as_bytes(), names[i].as_bytes());
+        let mut j = 0;
+        while j < prefix.len() {
+            out[n] = prefix[j];
+            n += 1;
+            j += 1;
+        }
+        j = 0;
+        while j < name.len() {
+            out[n] = name[j];
+            n += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    // TODO: Use `assert_eq!()` here, once it's allowed in `const` context.
+    #[expect(clippy::manual_assert, reason = "const context")]
+    if n != LEN {
+        panic!("actual names lengths mismatches the specified `LEN` const")
+    }
+    out
+}
+
+/// Splits the specified `bytes` (produced by the [`prefix_names()`]) back into
+/// `N` prefixed [`event::Name`]s.
+///
+/// > **NOTE**: This is an inner implementation detail of the
+/// >           [`const_prefix_names!`] macro.
+///
+/// # Panics
+///
+/// - If all the specified `names` are empty.
+/// - If `N` mismatches the length of the specified `names`.
+#[must_use]
+pub const fn split_names<const N: usize>(
+    mut bytes: &'static [u8],
+    prefix_len: usize,
+    names: &[&str],
+) -> [&'static str; N] {
+    // TODO: Use `assert_eq!()` here, once it's allowed in `const` context.
+    #[expect(clippy::manual_assert, reason = "const context")]
+    if names.len() != N {
+        panic!("names length mismatches the specified `N` const")
+    }
+
+    let mut out = [""; N];
+    let mut i = 0;
+    while i < N {
+        let (name, rest) = bytes.split_at(prefix_len + names[i].len());
+        out[i] = match str::from_utf8(name) {
+            Ok(name) => name,
+            Err(_) => panic!("prefixed name is not a valid UTF-8 string"),
+        };
+        bytes = rest;
+        i += 1;
+    }
+    out
+}
+
+/// Extracts `N` [`event::Name`]s from the specified [`Reflect::META`].
+///
+/// > **NOTE**: This is an inner implementation detail of the
+/// >           [`const_prefix_meta!`] macro.
+///
+/// # Panics
+///
+/// If `N` mismatches the length of the specified [`Reflect::META`].
+#[must_use]
+pub const fn meta_names<const N: usize>(
+    meta: &[(&'static str, &'static str, &'static str)],
+) -> [&'static str; N] {
+    // TODO: Use `assert_eq!()` here, once it's allowed in `const` context.
+    #[expect(clippy::manual_assert, reason = "const context")]
+    if meta.len() != N {
+        panic!("`META` length mismatches the specified `N` const")
+    }
+
+    let mut out = [""; N];
+    let mut i = 0;
+    while i < N {
+        out[i] = meta[i].1;
+        i += 1;
+    }
+    out
+}
+
+/// Replaces [`event::Name`]s in the specified [`Reflect::META`] with the
+/// specified `names`.
+///
+/// > **NOTE**: This is an inner implementation detail of the
+/// >           [`const_prefix_meta!`] macro.
+///
+/// # Panics
+///
+/// If `N` mismatches the length of the specified [`Reflect::META`] or `names`.
+#[must_use]
+pub const fn with_meta_names<const N: usize>(
+    meta: &[(&'static str, &'static str, &'static str)],
+    names: &[&'static str],
+) -> [(&'static str, &'static str, &'static str); N] {
+    // TODO: Use `assert_eq!()` here, once it's allowed in `const` context.
+    #[expect(clippy::manual_assert, reason = "const context")]
+    if meta.len() != N || names.len() != N {
+        panic!("`META` or names length mismatches the specified `N` const")
+    }
+
+    let mut out = [("", "", ""); N];
+    let mut i = 0;
+    while i < N {
+        let (ty, _, rev) = meta[i];
+        out[i] = (ty, names[i], rev);
+        i += 1;
+    }
+    out
+}
+
 /// Compile time reflection of an [`Event`] (either a single or multiple
 /// [`StaticEvent`]s or [`ConcreteEvent`]s).
 ///
@@ -83,15 +413,41 @@ pub const fn concat_slices<T: Copy, const LEN: usize>(
 pub trait Reflect {
     /// Meta information of this [`Event`], containing its all combinations of:
     /// - Unique Rust type identifier.
-    /// - [`StaticEvent::NAME`].
+    /// - [`StaticEvent::NAME`] or any of its [`StaticEvent::ALIASES`].
     /// - Stringified [`ConcreteEvent::REVISION`].
     const META: &'static [(&'static str, &'static str, &'static str)];
+
+    /// Pairs of all the [`StaticEvent::ALIASES`] of this [`Event`] and their
+    /// canonical [`StaticEvent::NAME`]s.
+    const ALIASES: &'static [(&'static str, &'static str)] = &[];
+}
+
+/// Checks whether the specified `name` is one of the [`Reflect::ALIASES`] of
+/// the specified [`Event`].
+///
+/// Used to exclude [`StaticEvent::ALIASES`] from naming policy checks, as
+/// they're allowed to follow some former naming conventions.
+#[must_use]
+pub const fn is_alias<E: Reflect + ?Sized>(name: &str) -> bool {
+    let aliases = <E as Reflect>::ALIASES;
+
+    let mut i = 0;
+    while i < aliases.len() {
+        if str_eq(aliases[i].0, name) {
+            return true;
+        }
+        i += 1;
+    }
+    false
 }
 
 /// Checks whether all the combinations of [`StaticEvent::NAME`] and
 /// [`ConcreteEvent::REVISION`] in [`Reflect::META`] correspond to different
 /// Rust types.
 ///
+/// As [`StaticEvent::ALIASES`] are present in [`Reflect::META`] too, an alias
+/// occupies the same combination slot as a canonical [`StaticEvent::NAME`].
+///
 /// Correctness is checked by asserting this function at compile time in `const`
 /// context.
 #[must_use]
@@ -237,4 +593,19 @@ mod has_different_types_with_same_name_and_revision_spec {
 
         assert!(has_different_types_with_same_name_and_revision::<Ev>());
     }
+
+    #[test]
+    fn yes_when_alias_occupies_name_and_revision_of_different_type() {
+        struct Ev;
+
+        impl Reflect for Ev {
+            const META: &'static [(
+                &'static str,
+                &'static str,
+                &'static str,
+            )] = &[("A", "b", "1"), ("A", "a", "1"), ("B", "a", "1")];
+        }
+
+        assert!(has_different_types_with_same_name_and_revision::<Ev>());
+    }
 }
@@ -0,0 +1,130 @@
+//! Benchmarks of replaying (decoding) large payloads encoded by `Codecs` with
+//! and without compression.
+
+use std::hint::black_box;
+
+#[cfg(any(feature = "lz4", feature = "zstd"))]
+use arcane_core::es::codec::Compression;
+use arcane_core::es::{
+    codec::{Codec as _, Codecs, Format},
+    event::Version,
+};
+use criterion::{
+    BenchmarkId, Criterion, Throughput, criterion_group, criterion_main,
+};
+use serde::{Deserialize, Serialize};
+
+/// Name of the benchmarked events.
+const NAME: &str = "document.uploaded";
+
+/// Number of events in a replayed stream.
+const EVENTS: u64 = 32;
+
+/// Event embedding a large document.
+#[derive(Deserialize, Serialize)]
+struct DocumentUploaded {
+    id: u64,
+    title: String,
+    paragraphs: Vec<String>,
+}
+
+/// Generates an event with a document of roughly the provided size (in
+/// bytes), built from pseudo-random words.
+fn document(id: u64, size: u64) -> DocumentUploaded {
+    const WORDS: &[&str] = &[
+        "event",
+        "stream",
+        "aggregate",
+        "command",
+        "projection",
+        "snapshot",
+        "version",
+        "revision",
+        "payload",
+        "subscription",
+        "saga",
+        "outbox",
+    ];
+
+    let mut seed = id.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut next = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+
+    let mut paragraphs = vec![];
+    let mut len = 0_u64;
+    while len < size {
+        let words = (0..64)
+            .map(|_| {
+                let n = usize::try_from(next() % 12).unwrap_or_default();
+                WORDS[n]
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        len += u64::try_from(words.len()).unwrap();
+        paragraphs.push(words);
+    }
+    DocumentUploaded { id, title: format!("Document #{id}"), paragraphs }
+}
+
+/// Benchmarks replaying a stream of events with large documents.
+fn replay(c: &mut Criterion) {
+    let version = Version::try_new(1).unwrap();
+
+    let codecs = [
+        ("none", Codecs::new(Format::Json)),
+        #[cfg(feature = "lz4")]
+        ("lz4", Codecs::new(Format::Json).compress(Compression::Lz4, 4096)),
+        #[cfg(feature = "zstd")]
+        (
+            "zstd",
+            Codecs::new(Format::Json).compress(Compression::Zstd(3), 4096),
+        ),
+    ];
+
+    for size in [64 * 1024, 256 * 1024, 512 * 1024_u64] {
+        let events =
+            (0..EVENTS).map(|id| document(id, size)).collect::<Vec<_>>();
+
+        let mut group = c.benchmark_group(format!("replay/{}KiB", size / 1024));
+        group.throughput(Throughput::Bytes(EVENTS * size));
+        for (label, codecs) in &codecs {
+            let stored = events
+                .iter()
+                .map(|ev| codecs.encode(NAME, version, ev).unwrap())
+                .collect::<Vec<_>>();
+            let stored_len = stored.iter().map(Vec::len).sum::<usize>();
+            println!(
+                "{label}: {} KiB stored for {} KiB of events",
+                stored_len / 1024,
+                EVENTS * size / 1024,
+            );
+
+            group.bench_function(BenchmarkId::new("decode", label), |b| {
+                b.iter(|| {
+                    for bytes in &stored {
+                        let ev: DocumentUploaded =
+                            codecs.decode(NAME, version, bytes).unwrap();
+                        _ = black_box(ev);
+                    }
+                });
+            });
+            group.bench_function(BenchmarkId::new("encode", label), |b| {
+                b.iter(|| {
+                    for ev in &events {
+                        _ = black_box(
+                            codecs.encode(NAME, version, ev).unwrap(),
+                        );
+                    }
+                });
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, replay);
+criterion_main!(benches);
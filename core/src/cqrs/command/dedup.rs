@@ -0,0 +1,811 @@
+//! [`Idempotent`] execution of [`Command`]s, deduplicated by their
+//! [`Command::id()`]s.
+//!
+//! Clients retry [`Command`]s on timeouts, not knowing whether the first
+//! attempt succeeded. [`Idempotent`] claims the [`Command::id()`] of every
+//! [`Command`] in a deduplication [`Store`] before executing it, appends the
+//! produced [`Event`]s with the [`Command::id()`] as their
+//! [`Metadata::causation`] id, and records them in the [`Store`] afterwards,
+//! so a repeated [`Command`] returns the originally produced [`Event`]s
+//! instead of being executed once again.
+//!
+//! If recording the [`Event`]s fails, the claim stays [`Claim::Pending`], and
+//! the repeated [`Command`] looks up its [`Event`]s in the [`stream`] by their
+//! [`Metadata::causation`] id instead. If there are none (the executor has
+//! crashed before appending them, or the [`Command`] has produced no
+//! [`Event`]s), the [`Claim::Pending`] is taken over and the [`Command`] is
+//! executed once again after the configurable [`Idempotent::lease()`] expires.
+//!
+//! [`Claim`]s are retained for a configurable [`Idempotent::retain()`]
+//! period only, after which the same [`Command::id()`] is executed anew.
+//!
+//! [`Idempotent`] is usually run inside a [`Handler`] registered on a
+//! [`Bus`], so the repeated [`Command`]s dispatched via the [`Bus`] return
+//! the output of the original ones.
+//!
+//! [`Bus`]: super::Bus
+//! [`Handler`]: super::Handler
+
+use std::{
+    collections::{HashMap, hash_map},
+    convert::Infallible,
+    error::Error as StdError,
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+use super::{Command, Error as CommandError};
+#[cfg(doc)]
+use crate::es::Event;
+use crate::es::{
+    event,
+    store::{self, Metadata, Recorded, retry},
+    stream,
+};
+
+/// Record of a [`Command`] executed by [`Idempotent`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Record<Ev> {
+    /// [`Event`]s produced by the [`Command`].
+    pub events: Vec<Recorded<Ev>>,
+
+    /// Version of the [`stream`] after the [`Command`] has been executed.
+    pub version: Option<stream::Position>,
+
+    /// Time the [`Command`] has been executed at.
+    pub at: SystemTime,
+}
+
+/// Claim of a [`Command::id()`] in a deduplication [`Store`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Claim<Ev> {
+    /// [`Command`] is being executed (or its [`Record`] has failed to be
+    /// saved).
+    Pending {
+        /// Time the [`Command`] has been claimed at.
+        at: SystemTime,
+    },
+
+    /// [`Command`] has been executed.
+    Done(Record<Ev>),
+}
+
+impl<Ev> Claim<Ev> {
+    /// Returns the time this [`Claim`] has been made at.
+    #[must_use]
+    pub const fn at(&self) -> SystemTime {
+        match self {
+            Self::Pending { at } | Self::Done(Record { at, .. }) => *at,
+        }
+    }
+}
+
+/// Storage of the [`Claim`]s of [`Command`]s, keyed by their
+/// [`Command::NAME`]s and [`Command::id()`]s.
+pub trait Store<Ev> {
+    /// Error of this [`Store`].
+    type Error;
+
+    /// Atomically claims the [`Command`] with the provided [`Command::NAME`]
+    /// and [`Command::id()`] by inserting a [`Claim::Pending`] made at the
+    /// provided time, unless it's claimed already.
+    ///
+    /// Returns the existing [`Claim`], if any, leaving it untouched.
+    ///
+    /// # Errors
+    ///
+    /// If this [`Store`] fails.
+    fn claim(
+        &mut self,
+        command: &str,
+        id: &str,
+        at: SystemTime,
+    ) -> Result<Option<Claim<Ev>>, Self::Error>;
+
+    /// Atomically takes over the [`Claim::Pending`] of the [`Command`] with the
+    /// provided [`Command::NAME`] and [`Command::id()`] made at the provided
+    /// `claimed` time, by replacing it with the one made at the provided `at`
+    /// time.
+    ///
+    /// Returns `false` if there is no such [`Claim::Pending`] (it has been
+    /// taken over or done already), leaving the existing [`Claim`] untouched.
+    ///
+    /// # Errors
+    ///
+    /// If this [`Store`] fails.
+    fn reclaim(
+        &mut self,
+        command: &str,
+        id: &str,
+        claimed: SystemTime,
+        at: SystemTime,
+    ) -> Result<bool, Self::Error>;
+
+    /// Saves the provided [`Record`] of the [`Command`] with the provided
+    /// [`Command::NAME`] and [`Command::id()`] as its [`Claim::Done`].
+    ///
+    /// # Errors
+    ///
+    /// If this [`Store`] fails.
+    fn save(
+        &mut self,
+        command: &str,
+        id: &str,
+        record: Record<Ev>,
+    ) -> Result<(), Self::Error>;
+
+    /// Removes the [`Claim`] of the [`Command`] with the provided
+    /// [`Command::NAME`] and [`Command::id()`], so it may be executed anew.
+    ///
+    /// # Errors
+    ///
+    /// If this [`Store`] fails.
+    fn release(&mut self, command: &str, id: &str) -> Result<(), Self::Error>;
+
+    /// Removes the [`Claim`]s made before the provided time, returning the
+    /// number of the removed ones.
+    ///
+    /// # Errors
+    ///
+    /// If this [`Store`] fails.
+    fn purge(&mut self, before: SystemTime) -> Result<usize, Self::Error>;
+}
+
+/// In-memory [`Store`] of [`Claim`]s.
+#[derive(Clone, Debug)]
+pub struct Memory<Ev> {
+    /// [`Claim`]s by [`Command::NAME`]s and [`Command::id()`]s.
+    claims: HashMap<(String, String), Claim<Ev>>,
+}
+
+impl<Ev> Default for Memory<Ev> {
+    fn default() -> Self {
+        Self { claims: HashMap::new() }
+    }
+}
+
+impl<Ev> Memory<Ev> {
+    /// Creates a new empty [`Memory`] [`Store`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<Ev: Clone> Store<Ev> for Memory<Ev> {
+    type Error = Infallible;
+
+    fn claim(
+        &mut self,
+        command: &str,
+        id: &str,
+        at: SystemTime,
+    ) -> Result<Option<Claim<Ev>>, Self::Error> {
+        match self.claims.entry((command.to_owned(), id.to_owned())) {
+            hash_map::Entry::Occupied(e) => Ok(Some(e.get().clone())),
+            hash_map::Entry::Vacant(e) => {
+                _ = e.insert(Claim::Pending { at });
+                Ok(None)
+            }
+        }
+    }
+
+    fn reclaim(
+        &mut self,
+        command: &str,
+        id: &str,
+        claimed: SystemTime,
+        at: SystemTime,
+    ) -> Result<bool, Self::Error> {
+        match self.claims.get_mut(&(command.to_owned(), id.to_owned())) {
+            Some(claim @ Claim::Pending { .. }) if claim.at() == claimed => {
+                *claim = Claim::Pending { at };
+                Ok(true)
+            }
+            Some(_) | None => Ok(false),
+        }
+    }
+
+    fn save(
+        &mut self,
+        command: &str,
+        id: &str,
+        record: Record<Ev>,
+    ) -> Result<(), Self::Error> {
+        drop(
+            self.claims.insert(
+                (command.to_owned(), id.to_owned()),
+                Claim::Done(record),
+            ),
+        );
+        Ok(())
+    }
+
+    fn release(&mut self, command: &str, id: &str) -> Result<(), Self::Error> {
+        drop(self.claims.remove(&(command.to_owned(), id.to_owned())));
+        Ok(())
+    }
+
+    fn purge(&mut self, before: SystemTime) -> Result<usize, Self::Error> {
+        let len = self.claims.len();
+        self.claims.retain(|_, c| c.at() >= before);
+        Ok(len - self.claims.len())
+    }
+}
+
+/// Outcome of [`Idempotent::execute()`]ing a [`Command`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Outcome<Ev> {
+    /// [`Record`] of the [`Command`].
+    pub record: Record<Ev>,
+
+    /// Indicator whether the [`Record`] is the original one of the repeated
+    /// [`Command`], so nothing has been executed.
+    pub replayed: bool,
+}
+
+/// Executor of [`Command`]s against a [`stream`], recording the produced
+/// [`Event`]s in a deduplication [`Store`], so the repeated [`Command`]s are
+/// not executed twice.
+///
+/// [`Command`]s are executed via a [`store::Retry`], so are re-run on
+/// [`store::Conflict`]s.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::time::Duration;
+/// #
+/// # use arcane::{
+/// #     cqrs::command::{Command, dedup},
+/// #     es::{
+/// #         Event,
+/// #         event::Sourced,
+/// #         store::{self, Store as _},
+/// #         stream::{self, Position},
+/// #     },
+/// # };
+/// #
+/// #[derive(Clone, Debug, Event, PartialEq)]
+/// #[event(name = "message.posted")]
+/// struct MessagePosted {
+///     text: String,
+/// }
+///
+/// #[derive(Default)]
+/// struct Chat;
+///
+/// impl Sourced<MessagePosted> for Chat {
+///     fn apply(&mut self, _: &MessagePosted) {}
+/// }
+///
+/// struct PostMessage {
+///     id: String,
+///     text: String,
+/// }
+///
+/// impl Command for PostMessage {
+///     const NAME: &'static str = "post_message";
+///     type Output = ();
+///
+///     fn id(&self) -> Option<&str> {
+///         Some(&self.id)
+///     }
+/// }
+///
+/// let mut store = store::Memory::new();
+/// let mut dedup = dedup::Memory::new();
+/// let chat = stream::Id::new("chat", "1").unwrap();
+/// let idempotent = dedup::Idempotent::new().retain(Duration::from_secs(60));
+///
+/// let cmd = PostMessage { id: "a".to_owned(), text: "hi".to_owned() };
+/// let mut post = |cmd: &PostMessage| {
+///     idempotent.execute(&mut store, &mut dedup, cmd, &chat, |_: &Chat| {
+///         Ok::<_, std::convert::Infallible>(vec![MessagePosted {
+///             text: cmd.text.clone(),
+///         }])
+///     })
+/// };
+///
+/// let first = post(&cmd).unwrap();
+/// assert!(!first.replayed);
+/// assert_eq!(first.record.version, Position::new(1));
+/// let causation = &first.record.events[0].metadata.causation;
+/// assert_eq!(causation.as_deref(), Some("a"));
+///
+/// // The retried command returns the originally produced events.
+/// let retried = post(&cmd).unwrap();
+/// assert!(retried.replayed);
+/// assert_eq!(retried.record, first.record);
+///
+/// let other = PostMessage { id: "b".to_owned(), text: "hi".to_owned() };
+/// assert_eq!(post(&other).unwrap().record.version, Position::new(2));
+/// assert_eq!(store.read_stream(&chat).unwrap().len(), 2);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Idempotent<Ev> {
+    /// [`store::Retry`] executing the [`Command`]s.
+    retry: store::Retry<Ev>,
+
+    /// Period the [`Claim`]s are retained for.
+    retention: Duration,
+
+    /// Period the [`Claim::Pending`]s are leased for.
+    lease: Duration,
+}
+
+impl<Ev> Default for Idempotent<Ev> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Ev> Idempotent<Ev> {
+    /// Creates a new [`Idempotent`] executor with the default
+    /// [`store::Retry`], retaining [`Claim`]s for a day and leasing
+    /// [`Claim::Pending`]s for a minute.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            retry: store::Retry::new(),
+            retention: Duration::from_secs(24 * 60 * 60),
+            lease: Duration::from_secs(60),
+        }
+    }
+
+    /// Sets the [`store::Retry`] executing the [`Command`]s.
+    #[must_use]
+    pub const fn retry(mut self, retry: store::Retry<Ev>) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the period the [`Claim`]s are retained for, during which the
+    /// repeated [`Command`]s are not executed.
+    #[must_use]
+    pub const fn retain(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Sets the period the [`Claim::Pending`]s are leased for, after which
+    /// the repeated [`Command`]s without [`Event`]s in the [`stream`] take
+    /// them over and are executed once again.
+    ///
+    /// Should be well below the [`Idempotent::retain()`] period and above the
+    /// time executing a [`Command`] takes.
+    #[must_use]
+    pub const fn lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    /// Executes the provided `handler` of the provided [`Command`] against
+    /// the state sourced from the [`stream`] with the provided [`stream::Id`],
+    /// and records the produced [`Event`]s into the provided `dedup`
+    /// [`Store`], purging the expired [`Claim`]s.
+    ///
+    /// The [`Event`]s are appended with the [`Command::id()`] and
+    /// [`Command::correlation()`] as their [`Metadata`].
+    ///
+    /// If the [`Command`] with the same [`Command::NAME`] and
+    /// [`Command::id()`] is claimed already and not expired, returns its
+    /// [`Record`] without running the `handler`, unless its
+    /// [`Claim::Pending`] has no [`Event`]s in the [`stream`] and its
+    /// [`Idempotent::lease()`] has expired.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::NoId`] if the [`Command`] has no [`Command::id()`].
+    /// - [`Error::Pending`] if the [`Command`] is still being executed (its
+    ///   [`Claim::Pending`] is not expired).
+    /// - [`Error::Execute`] if executing the `handler` fails, in which case
+    ///   the [`Claim`] is released.
+    /// - [`Error::Dedup`] if the `dedup` [`Store`] fails.
+    pub fn execute<St, D, Cmd, S, H, E>(
+        &self,
+        store: &mut St,
+        dedup: &mut D,
+        command: &Cmd,
+        stream: &stream::Id,
+        mut handler: H,
+    ) -> Result<Outcome<Ev>, Error<E, St::Error, D::Error>>
+    where
+        Ev: Clone,
+        St: store::Store<Ev> + ?Sized,
+        D: Store<Ev> + ?Sized,
+        Cmd: Command,
+        S: Default + event::Sourced<Ev>,
+        H: FnMut(&S) -> Result<Vec<Ev>, E>,
+    {
+        let id = command.id().ok_or(Error::NoId { command: Cmd::NAME })?;
+        let now = SystemTime::now();
+        if let Some(before) = now.checked_sub(self.retention) {
+            _ = dedup.purge(before).map_err(Error::Dedup)?;
+        }
+
+        match dedup.claim(Cmd::NAME, id, now).map_err(Error::Dedup)? {
+            None => {}
+            Some(Claim::Done(record)) => {
+                return Ok(Outcome { record, replayed: true });
+            }
+            Some(Claim::Pending { at }) => {
+                if let Some(record) = recover(store, stream, id, at)
+                    .map_err(|e| Error::Execute(retry::Error::Store(e)))?
+                {
+                    dedup
+                        .save(Cmd::NAME, id, record.clone())
+                        .map_err(Error::Dedup)?;
+                    return Ok(Outcome { record, replayed: true });
+                }
+                let expired =
+                    now.duration_since(at).is_ok_and(|d| d >= self.lease);
+                if !expired
+                    || !dedup
+                        .reclaim(Cmd::NAME, id, at, now)
+                        .map_err(Error::Dedup)?
+                {
+                    return Err(Error::Pending {
+                        command: Cmd::NAME,
+                        id: id.to_owned(),
+                    });
+                }
+            }
+        }
+
+        let metadata = Metadata {
+            causation: Some(id.to_owned()),
+            correlation: command.correlation().map(ToOwned::to_owned),
+        };
+        let mut produced = Vec::new();
+        let executed =
+            self.retry.execute_with(store, stream, &metadata, |state: &S| {
+                let events = handler(state)?;
+                produced.clone_from(&events);
+                Ok(events)
+            });
+        let version = match executed {
+            Ok(version) => version,
+            Err(e) => {
+                // Failure of releasing leaves the claim pending, so shouldn't
+                // shadow the original error.
+                drop(dedup.release(Cmd::NAME, id));
+                return Err(Error::Execute(e));
+            }
+        };
+
+        let len = u64::try_from(produced.len()).unwrap_or(u64::MAX);
+        let first =
+            version.map_or(0, stream::Position::get).saturating_sub(len);
+        let events = (first + 1..)
+            .zip(produced)
+            .filter_map(|(pos, event)| {
+                Some(Recorded {
+                    stream: stream.clone(),
+                    position: stream::Position::new(pos)?,
+                    metadata: metadata.clone(),
+                    event,
+                })
+            })
+            .collect();
+        let record = Record { events, version, at: now };
+        dedup.save(Cmd::NAME, id, record.clone()).map_err(Error::Dedup)?;
+        Ok(Outcome { record, replayed: false })
+    }
+}
+
+/// Recovers the [`Record`] of the [`Command`] with the provided
+/// [`Command::id()`] claimed at the provided time, by looking up the
+/// [`Event`]s caused by it in the [`stream`] with the provided
+/// [`stream::Id`].
+///
+/// Returns [`None`] if there are no such [`Event`]s.
+///
+/// # Errors
+///
+/// If the [`stream`] cannot be read.
+fn recover<St, Ev>(
+    store: &St,
+    stream: &stream::Id,
+    id: &str,
+    at: SystemTime,
+) -> Result<Option<Record<Ev>>, St::Error>
+where
+    St: store::Store<Ev> + ?Sized,
+{
+    let mut events = store.read_recorded(stream)?;
+    events.retain(|r| r.metadata.causation.as_deref() == Some(id));
+    let version = events.last().map(|r| r.position);
+    Ok((!events.is_empty()).then_some(Record { events, version, at }))
+}
+
+/// Error of [`Idempotent::execute()`]ing a [`Command`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error<C, S, D> {
+    /// [`Command`] has no [`Command::id()`] to be deduplicated by.
+    NoId {
+        /// [`Command::NAME`] of the [`Command`].
+        command: &'static str,
+    },
+
+    /// [`Command`] with the same [`Command::id()`] is still being executed,
+    /// so its [`Event`]s are not known yet.
+    Pending {
+        /// [`Command::NAME`] of the [`Command`].
+        command: &'static str,
+
+        /// [`Command::id()`] of the [`Command`].
+        id: String,
+    },
+
+    /// Executing the [`Command`] failed.
+    Execute(retry::Error<C, S>),
+
+    /// Deduplication [`Store`] failed.
+    Dedup(D),
+}
+
+impl<C, S, D> fmt::Display for Error<C, S, D>
+where
+    C: fmt::Display,
+    S: fmt::Display,
+    D: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoId { command } => {
+                write!(f, "`{command}` command has no id to be deduplicated by")
+            }
+            Self::Pending { command, id } => {
+                write!(f, "`{command}` command `{id}` is still being executed")
+            }
+            Self::Execute(e) => write!(f, "{e}"),
+            Self::Dedup(e) => write!(f, "deduplication store failed: {e}"),
+        }
+    }
+}
+
+impl<C, S, D> StdError for Error<C, S, D>
+where
+    C: StdError + 'static,
+    S: StdError + 'static,
+    D: StdError + 'static,
+{
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::NoId { .. } | Self::Pending { .. } => None,
+            Self::Execute(e) => Some(e),
+            Self::Dedup(e) => Some(e),
+        }
+    }
+}
+
+impl<C, S, D> From<Error<C, S, D>> for CommandError
+where
+    C: StdError + Send + Sync + 'static,
+    S: StdError + Send + Sync + 'static,
+    D: StdError + Send + Sync + 'static,
+{
+    fn from(e: Error<C, S, D>) -> Self {
+        match e {
+            Error::Execute(retry::Error::Conflict(c)) => Self::Conflict(c),
+            Error::Pending { command, id } => Self::Duplicate { command, id },
+            e @ (Error::NoId { .. } | Error::Execute(_) | Error::Dedup(_)) => {
+                Self::handler(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod spec {
+    use std::{
+        convert::Infallible,
+        time::{Duration, SystemTime},
+    };
+
+    use derive_more::{Display, Error};
+
+    use super::{Claim, Command, Error, Idempotent, Memory, Record, Store};
+    use crate::es::{
+        event::{self, Sourced},
+        store::{self, Store as _},
+        stream,
+    };
+
+    /// Event of the tested stream.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    struct Posted;
+
+    impl event::Static for Posted {
+        const NAME: event::Name = "posted";
+    }
+
+    /// State of the tested stream.
+    #[derive(Default)]
+    struct Chat;
+
+    impl Sourced<Posted> for Chat {
+        fn apply(&mut self, _: &Posted) {}
+    }
+
+    /// Tested [`Command`].
+    struct Post(&'static str);
+
+    impl Command for Post {
+        const NAME: &'static str = "post";
+        type Output = ();
+
+        fn id(&self) -> Option<&str> {
+            Some(self.0)
+        }
+
+        fn correlation(&self) -> Option<&str> {
+            Some("flow")
+        }
+    }
+
+    /// Error of a [`Failing`] [`Store`].
+    #[derive(Clone, Copy, Debug, Display, Error)]
+    #[display("saving failed")]
+    struct SaveFailed;
+
+    /// [`Memory`] [`Store`] failing to save the provided number of
+    /// [`Record`]s.
+    #[derive(Default)]
+    struct Failing(Memory<Posted>, usize);
+
+    impl Store<Posted> for Failing {
+        type Error = SaveFailed;
+
+        fn claim(
+            &mut self,
+            command: &str,
+            id: &str,
+            at: SystemTime,
+        ) -> Result<Option<Claim<Posted>>, Self::Error> {
+            Ok(self.0.claim(command, id, at).unwrap_or_else(|e| match e {}))
+        }
+
+        fn reclaim(
+            &mut self,
+            command: &str,
+            id: &str,
+            claimed: SystemTime,
+            at: SystemTime,
+        ) -> Result<bool, Self::Error> {
+            self.0.reclaim(command, id, claimed, at).map_err(|e| match e {})
+        }
+
+        fn save(
+            &mut self,
+            command: &str,
+            id: &str,
+            record: Record<Posted>,
+        ) -> Result<(), Self::Error> {
+            if self.1 > 0 {
+                self.1 -= 1;
+                return Err(SaveFailed);
+            }
+            self.0.save(command, id, record).map_err(|e| match e {})
+        }
+
+        fn release(
+            &mut self,
+            command: &str,
+            id: &str,
+        ) -> Result<(), Self::Error> {
+            self.0.release(command, id).map_err(|e| match e {})
+        }
+
+        fn purge(&mut self, before: SystemTime) -> Result<usize, Self::Error> {
+            self.0.purge(before).map_err(|e| match e {})
+        }
+    }
+
+    /// Returns the [`stream::Id`] of the tested stream.
+    fn chat() -> stream::Id {
+        stream::Id::new("chat", "1").expect("valid ID")
+    }
+
+    #[test]
+    fn recovers_unsaved_record_from_stream() {
+        let (mut store, mut dedup) = (store::Memory::new(), Failing::default());
+        dedup.1 = 1;
+        let idempotent = Idempotent::new();
+        let mut post = |cmd: &Post| {
+            idempotent.execute(
+                &mut store,
+                &mut dedup,
+                cmd,
+                &chat(),
+                |_: &Chat| Ok::<_, Infallible>(vec![Posted]),
+            )
+        };
+
+        assert!(matches!(post(&Post("a")), Err(Error::Dedup(SaveFailed))));
+        let retried = post(&Post("a")).expect("replays");
+        assert!(retried.replayed);
+        assert_eq!(retried.record.version, stream::Position::new(1));
+        let metadata = &retried.record.events[0].metadata;
+        assert_eq!(metadata.causation.as_deref(), Some("a"));
+        assert_eq!(metadata.correlation.as_deref(), Some("flow"));
+        assert_eq!(post(&Post("a")).expect("replays").record, retried.record);
+
+        assert!(!post(&Post("b")).expect("executes").replayed);
+        assert_eq!(store.read_stream(&chat()).expect("reads").len(), 2);
+    }
+
+    #[test]
+    fn rejects_pending_and_releases_failed() {
+        let (mut store, mut dedup) = (store::Memory::new(), Memory::new());
+        let idempotent = Idempotent::new();
+        assert_eq!(dedup.claim(Post::NAME, "a", SystemTime::now()), Ok(None),);
+
+        let pending = idempotent.execute(
+            &mut store,
+            &mut dedup,
+            &Post("a"),
+            &chat(),
+            |_: &Chat| Ok::<_, Infallible>(vec![Posted]),
+        );
+        assert!(
+            matches!(pending, Err(Error::Pending { command: "post", .. }),)
+        );
+
+        let failed = idempotent.execute(
+            &mut store,
+            &mut dedup,
+            &Post("b"),
+            &chat(),
+            |_: &Chat| Err::<Vec<Posted>, _>(SaveFailed),
+        );
+        assert!(matches!(failed, Err(Error::Execute(_))));
+        assert_eq!(dedup.claim(Post::NAME, "b", SystemTime::now()), Ok(None));
+        assert!(store.read_stream(&chat()).expect("reads").is_empty());
+    }
+
+    #[test]
+    fn reexecutes_expired_pending() {
+        let (mut store, mut dedup) = (store::Memory::new(), Failing::default());
+        let crashed = SystemTime::now() - Duration::from_secs(120);
+        assert!(matches!(dedup.claim(Post::NAME, "a", crashed), Ok(None)));
+
+        let executed = Idempotent::new()
+            .execute(&mut store, &mut dedup, &Post("a"), &chat(), |_: &Chat| {
+                Ok::<_, Infallible>(vec![Posted])
+            })
+            .expect("takes over expired claim");
+        assert!(!executed.replayed);
+        assert_eq!(executed.record.version, stream::Position::new(1));
+        assert_eq!(store.read_stream(&chat()).expect("reads").len(), 1);
+    }
+
+    #[test]
+    fn reexecutes_unsaved_without_events_after_lease() {
+        let (mut store, mut dedup) = (store::Memory::new(), Failing::default());
+        dedup.1 = 1;
+        let mut post = |idempotent: &Idempotent<Posted>| {
+            idempotent.execute(
+                &mut store,
+                &mut dedup,
+                &Post("a"),
+                &chat(),
+                |_: &Chat| Ok::<_, Infallible>(vec![]),
+            )
+        };
+
+        assert!(matches!(
+            post(&Idempotent::new()),
+            Err(Error::Dedup(SaveFailed)),
+        ));
+        assert!(matches!(
+            post(&Idempotent::new()),
+            Err(Error::Pending { command: "post", .. }),
+        ));
+
+        let expired = Idempotent::new().lease(Duration::ZERO);
+        assert!(!post(&expired).expect("takes over expired claim").replayed);
+        assert!(post(&expired).expect("replays").replayed);
+    }
+}
@@ -0,0 +1,451 @@
+//! [`Middleware`] of a [`Bus`].
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    error::Error as StdError,
+    fmt,
+    time::{Duration, Instant, SystemTime},
+};
+
+#[cfg(doc)]
+use super::{Bus, Handler};
+use super::{Command, Envelope, Error, dedup};
+
+/// Link of a [`Bus`] chain, run around handling of every dispatched
+/// [`Command`].
+///
+/// [`Middleware::before()`] hooks are run in the order the [`Middleware`] is
+/// added to a [`Bus`], and [`Middleware::after()`] hooks are run in the
+/// reversed one, so the first added [`Middleware`] wraps all the others.
+pub trait Middleware: Send {
+    /// Runs before the [`Command`] is handled.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Command`] is rejected, in which case neither its [`Handler`]
+    /// nor the following [`Middleware`] is run.
+    fn before(&mut self, envelope: &Envelope<'_>) -> Result<(), Error> {
+        _ = envelope;
+        Ok(())
+    }
+
+    /// Decides whether the [`Command`] should be handled once again after its
+    /// [`Handler`] failed with the provided [`Error`] on the provided
+    /// `attempt` (starting from `1`).
+    fn retry(
+        &mut self,
+        envelope: &Envelope<'_>,
+        error: &Error,
+        attempt: u32,
+    ) -> bool {
+        _ = (envelope, error, attempt);
+        false
+    }
+
+    /// Runs after the [`Command`] is handled or rejected by the following
+    /// [`Middleware`], with the outcome of it.
+    fn after(&mut self, envelope: &Envelope<'_>, outcome: Result<(), &Error>) {
+        _ = (envelope, outcome);
+    }
+}
+
+/// Rule of [`Validate`] or [`Authorize`] over a type-erased [`Command`].
+type Rule<T> = Box<dyn Fn(&Envelope<'_>) -> Option<T> + Send + Sync>;
+
+/// [`Middleware`] rejecting [`Command`]s failing their validation rules with
+/// an [`Error::Invalid`].
+#[derive(Default)]
+pub struct Validate {
+    /// Validation [`Rule`]s by the types of [`Command`]s.
+    rules: HashMap<TypeId, Vec<Rule<Result<(), String>>>>,
+}
+
+impl Validate {
+    /// Creates a new [`Validate`] [`Middleware`] without rules.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the provided validation rule of the specified [`Command`],
+    /// returning the reason of a rejection.
+    #[must_use]
+    pub fn rule<C: Command>(
+        mut self,
+        rule: impl Fn(&C) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.rules
+            .entry(TypeId::of::<C>())
+            .or_default()
+            .push(Box::new(move |env| env.downcast().map(&rule)));
+        self
+    }
+}
+
+impl fmt::Debug for Validate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Validate")
+            .field("commands", &self.rules.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Middleware for Validate {
+    fn before(&mut self, envelope: &Envelope<'_>) -> Result<(), Error> {
+        let Some(rules) = self.rules.get(&Any::type_id(envelope.command))
+        else {
+            return Ok(());
+        };
+        for rule in rules {
+            if let Some(Err(reason)) = rule(envelope) {
+                return Err(Error::Invalid {
+                    command: envelope.name(),
+                    reason,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// [`Middleware`] rejecting [`Command`]s not permitted by their authorization
+/// rules with an [`Error::Unauthorized`].
+///
+/// [`Command`]s without rules are permitted, unless
+/// [`Authorize::deny_unknown()`] is used.
+#[derive(Default)]
+pub struct Authorize {
+    /// Authorization [`Rule`]s by the types of [`Command`]s.
+    rules: HashMap<TypeId, Vec<Rule<bool>>>,
+
+    /// Indicator whether [`Command`]s without rules are rejected.
+    deny_unknown: bool,
+}
+
+impl Authorize {
+    /// Creates a new [`Authorize`] [`Middleware`] without rules.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the provided authorization rule of the specified [`Command`],
+    /// which must return `true` for the [`Command`] to be permitted.
+    #[must_use]
+    pub fn rule<C: Command>(
+        mut self,
+        rule: impl Fn(&C) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.rules
+            .entry(TypeId::of::<C>())
+            .or_default()
+            .push(Box::new(move |env| env.downcast().map(&rule)));
+        self
+    }
+
+    /// Rejects the [`Command`]s without rules.
+    #[must_use]
+    pub const fn deny_unknown(mut self) -> Self {
+        self.deny_unknown = true;
+        self
+    }
+}
+
+impl fmt::Debug for Authorize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Authorize")
+            .field("commands", &self.rules.len())
+            .field("deny_unknown", &self.deny_unknown)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Middleware for Authorize {
+    fn before(&mut self, envelope: &Envelope<'_>) -> Result<(), Error> {
+        let permitted = self
+            .rules
+            .get(&Any::type_id(envelope.command))
+            .map_or(!self.deny_unknown, |rules| {
+                rules.iter().all(|rule| rule(envelope) == Some(true))
+            });
+        if permitted {
+            Ok(())
+        } else {
+            Err(Error::Unauthorized { command: envelope.name() })
+        }
+    }
+}
+
+/// Outcome of handling a [`Command`], reported by [`Trace`].
+#[derive(Clone, Copy, Debug)]
+pub struct Span<'e> {
+    /// [`Command::NAME`] of the [`Command`].
+    pub command: &'static str,
+
+    /// [`Command::id()`] of the [`Command`].
+    pub id: Option<&'e str>,
+
+    /// Time spent on handling the [`Command`] by the following
+    /// [`Middleware`] and its [`Handler`].
+    pub elapsed: Duration,
+
+    /// [`Error`] of the [`Command`], if it has failed.
+    pub error: Option<&'e Error>,
+}
+
+/// [`Middleware`] reporting a [`Span`] of every [`Command`] to the provided
+/// sink.
+///
+/// Should be added first, so it reports the [`Command`]s rejected by the
+/// following [`Middleware`] too.
+pub struct Trace<F> {
+    /// Sink of the reported [`Span`]s.
+    sink: F,
+
+    /// Time handling of the current [`Command`] started at.
+    started: Option<Instant>,
+}
+
+impl<F: FnMut(&Span<'_>) + Send> Trace<F> {
+    /// Creates a new [`Trace`] [`Middleware`] reporting to the provided
+    /// `sink`.
+    #[must_use]
+    pub const fn new(sink: F) -> Self {
+        Self { sink, started: None }
+    }
+}
+
+impl<F> fmt::Debug for Trace<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Trace")
+            .field("started", &self.started)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F: FnMut(&Span<'_>) + Send> Middleware for Trace<F> {
+    fn before(&mut self, _: &Envelope<'_>) -> Result<(), Error> {
+        self.started = Some(Instant::now());
+        Ok(())
+    }
+
+    fn after(&mut self, envelope: &Envelope<'_>, outcome: Result<(), &Error>) {
+        let elapsed =
+            self.started.take().map_or(Duration::ZERO, |t| t.elapsed());
+        (self.sink)(&Span {
+            command: envelope.name(),
+            id: envelope.id(),
+            elapsed,
+            error: outcome.err(),
+        });
+    }
+}
+
+/// [`Middleware`] rejecting [`Command`]s with the [`Command::id()`]s of the
+/// handled (or being handled) already ones with an [`Error::Duplicate`].
+///
+/// Claims the [`Command::id()`]s in the provided [`dedup::Store`] before
+/// handling the [`Command`]s, saves them as [`dedup::Claim::Done`] once
+/// handled successfully and releases them otherwise. Failures of the
+/// [`dedup::Store`] are reported as [`Error::Handler`]s.
+///
+/// [`dedup::Claim`]s are retained for the configurable [`Dedup::retain()`]
+/// period (forever by default), while [`dedup::Claim::Pending`]s left by
+/// crashed handling are taken over once their configurable [`Dedup::lease()`]
+/// (a minute by default) expires.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::time::{Duration, SystemTime};
+/// #
+/// # use arcane::cqrs::command::{
+/// #     self, Bus, Command, Dedup,
+/// #     dedup::{self, Store as _},
+/// # };
+/// #
+/// struct Ban(&'static str);
+///
+/// impl Command for Ban {
+///     const NAME: &'static str = "ban";
+///     type Output = ();
+///
+///     fn id(&self) -> Option<&str> {
+///         Some(self.0)
+///     }
+/// }
+///
+/// // Claim left pending by a crashed handling.
+/// let mut handled = dedup::Memory::new();
+/// let crashed = SystemTime::now() - Duration::from_secs(120);
+/// assert_eq!(handled.claim("ban", "b", crashed), Ok(None));
+///
+/// let mut bus = Bus::new()
+///     .middleware(Dedup::new(handled).lease(Duration::from_secs(60)))
+///     .handler(|cmd: &Ban| match cmd.0 {
+///         "fail" => Err(command::Error::handler("failed")),
+///         _ => Ok(()),
+///     });
+///
+/// bus.dispatch(&Ban("a")).unwrap();
+/// let err = bus.dispatch(&Ban("a")).unwrap_err();
+/// assert!(matches!(err, command::Error::Duplicate { id, .. } if id == "a"));
+///
+/// // Expired pending claims are taken over.
+/// bus.dispatch(&Ban("b")).unwrap();
+///
+/// // Failed commands are released, so may be dispatched once again.
+/// _ = bus.dispatch(&Ban("fail")).unwrap_err();
+/// let err = bus.dispatch(&Ban("fail")).unwrap_err();
+/// assert!(matches!(err, command::Error::Handler(_)));
+/// ```
+pub struct Dedup<S> {
+    /// [`dedup::Store`] of the [`Command::id()`]s [`dedup::Claim`]s.
+    store: S,
+
+    /// Period the [`dedup::Claim`]s are retained for, if limited.
+    retention: Option<Duration>,
+
+    /// Period the [`dedup::Claim::Pending`]s are leased for.
+    lease: Duration,
+
+    /// Time the current [`Command`] has been claimed at.
+    claimed: Option<SystemTime>,
+}
+
+impl<S> Dedup<S>
+where
+    S: dedup::Store<()> + Send,
+    S::Error: StdError + Send + Sync + 'static,
+{
+    /// Creates a new [`Dedup`] [`Middleware`] claiming [`Command::id()`]s in
+    /// the provided [`dedup::Store`].
+    #[must_use]
+    pub const fn new(store: S) -> Self {
+        Self {
+            store,
+            retention: None,
+            lease: Duration::from_secs(60),
+            claimed: None,
+        }
+    }
+
+    /// Sets the period the [`dedup::Claim`]s are retained for, after which
+    /// the [`Command`]s with the same [`Command::id()`]s are handled once
+    /// again.
+    #[must_use]
+    pub const fn retain(mut self, retention: Duration) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// Sets the period the [`dedup::Claim::Pending`]s are leased for, after
+    /// which the [`Command`]s with the same [`Command::id()`]s take them over
+    /// and are handled once again.
+    ///
+    /// Should be well below the [`Dedup::retain()`] period and above the time
+    /// handling a [`Command`] takes.
+    #[must_use]
+    pub const fn lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    /// Returns the [`dedup::Store`] of this [`Dedup`] [`Middleware`].
+    #[must_use]
+    pub const fn store(&self) -> &S {
+        &self.store
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for Dedup<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dedup")
+            .field("store", &self.store)
+            .field("retention", &self.retention)
+            .field("lease", &self.lease)
+            .field("claimed", &self.claimed)
+            .finish()
+    }
+}
+
+impl<S> Middleware for Dedup<S>
+where
+    S: dedup::Store<()> + Send,
+    S::Error: StdError + Send + Sync + 'static,
+{
+    fn before(&mut self, envelope: &Envelope<'_>) -> Result<(), Error> {
+        let Some(id) = envelope.id() else {
+            return Ok(());
+        };
+        let now = SystemTime::now();
+        if let Some(before) = self.retention.and_then(|r| now.checked_sub(r)) {
+            _ = self.store.purge(before).map_err(Error::handler)?;
+        }
+        let claimed = match self
+            .store
+            .claim(envelope.name(), id, now)
+            .map_err(Error::handler)?
+        {
+            None => true,
+            Some(dedup::Claim::Pending { at })
+                if now.duration_since(at).is_ok_and(|d| d >= self.lease) =>
+            {
+                self.store
+                    .reclaim(envelope.name(), id, at, now)
+                    .map_err(Error::handler)?
+            }
+            Some(dedup::Claim::Pending { .. } | dedup::Claim::Done(_)) => false,
+        };
+        if !claimed {
+            return Err(Error::Duplicate {
+                command: envelope.name(),
+                id: id.to_owned(),
+            });
+        }
+        self.claimed = Some(now);
+        Ok(())
+    }
+
+    fn after(&mut self, envelope: &Envelope<'_>, outcome: Result<(), &Error>) {
+        let (Some(id), Some(at)) = (envelope.id(), self.claimed.take()) else {
+            return;
+        };
+        // Failures leave the claim pending, which rejects the repeated
+        // commands anyway, so are not reported.
+        if outcome.is_ok() {
+            let record =
+                dedup::Record { events: Vec::new(), version: None, at };
+            drop(self.store.save(envelope.name(), id, record));
+        } else {
+            drop(self.store.release(envelope.name(), id));
+        }
+    }
+}
+
+/// [`Middleware`] retrying handling of [`Command`]s failed with an
+/// [`Error::Conflict`].
+///
+/// Retries immediately, as [`Handler`]s are expected to reload their state on
+/// every attempt.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Retry {
+    /// Maximum number of retries after the first attempt.
+    max_retries: u32,
+}
+
+impl Retry {
+    /// Creates a new [`Retry`] [`Middleware`] with the provided maximum
+    /// number of retries after the first attempt.
+    #[must_use]
+    pub const fn new(max_retries: u32) -> Self {
+        Self { max_retries }
+    }
+}
+
+impl Middleware for Retry {
+    fn retry(&mut self, _: &Envelope<'_>, error: &Error, attempt: u32) -> bool {
+        matches!(error, Error::Conflict(_)) && attempt <= self.max_retries
+    }
+}
@@ -0,0 +1,665 @@
+//! [`Command`]s dispatched via a [`Bus`] to their [`Handler`]s through a chain
+//! of [`Middleware`].
+//!
+//! A [`Bus`] routes every [`Command`] to the single [`Handler`] registered for
+//! its type, so callers don't need to know which aggregate handles it.
+//! Before reaching its [`Handler`], a [`Command`] passes through the
+//! [`Middleware`] chain, which may reject it (like [`Validate`],
+//! [`Authorize`] or [`Dedup`] do), retry its handling (like [`Retry`] does on
+//! [`Conflict`]s) or observe the outcome (like [`Trace`] does).
+//!
+//! Repeated [`Command`]s are either rejected by [`Dedup`], or deduplicated by
+//! their [`Handler`]s executing them via [`dedup::Idempotent`], so they
+//! return the output of the original ones.
+//!
+//! [`Command`]s are dispatched either synchronously via [`Bus::dispatch()`],
+//! or asynchronously via [`Bus::dispatch_async()`], which also runs the
+//! [`Handler`]s registered via [`Bus::async_handler()`]. Everything runs
+//! in-process, so a [`Bus`] is fully testable along with a [`store::Memory`].
+//!
+//! A [`Bus`] is [`Send`], as well as the [`Future`]s of its
+//! [`Bus::dispatch_async()`], so it may be moved to (or shared behind a
+//! [`Mutex`] between) threads and multi-threaded executors.
+//!
+//! [`Mutex`]: std::sync::Mutex
+//!
+//! # Example
+//!
+//! ```rust
+//! # use std::{
+//! #     convert::Infallible,
+//! #     sync::{Arc, Mutex},
+//! # };
+//! #
+//! # use arcane::{
+//! #     cqrs::command::{
+//! #         self, Bus, Command, Retry, Trace, Validate, dedup,
+//! #     },
+//! #     es::{Event, event::Sourced, store, stream},
+//! # };
+//! # use futures::executor::block_on;
+//! #
+//! #[derive(Clone, Debug, Event)]
+//! #[event(name = "message.posted")]
+//! struct MessagePosted;
+//!
+//! #[derive(Default)]
+//! struct Chat;
+//!
+//! impl Sourced<MessagePosted> for Chat {
+//!     fn apply(&mut self, _: &MessagePosted) {}
+//! }
+//!
+//! struct PostMessage {
+//!     id: String,
+//!     chat: String,
+//!     text: String,
+//! }
+//!
+//! impl Command for PostMessage {
+//!     const NAME: &'static str = "post_message";
+//!     type Output = Option<stream::Position>;
+//!
+//!     fn id(&self) -> Option<&str> {
+//!         Some(&self.id)
+//!     }
+//! }
+//!
+//! let store = Arc::new(Mutex::new(store::Memory::new()));
+//! let traced = Arc::new(Mutex::new(Vec::new()));
+//!
+//! let handler_store = Arc::clone(&store);
+//! let mut handled = dedup::Memory::new();
+//! let idempotent = dedup::Idempotent::new();
+//! let traces = Arc::clone(&traced);
+//! let mut bus = Bus::new()
+//!     .middleware(Trace::new(move |span: &command::Span<'_>| {
+//!         let mut traces = traces.lock().unwrap();
+//!         traces.push((span.command, span.error.is_none()));
+//!     }))
+//!     .middleware(Validate::new().rule(|cmd: &PostMessage| {
+//!         if cmd.text.is_empty() {
+//!             return Err("empty text".to_owned());
+//!         }
+//!         Ok(())
+//!     }))
+//!     .middleware(Retry::new(3))
+//!     .handler(move |cmd: &PostMessage| {
+//!         let chat = stream::Id::new("chat", &cmd.chat).unwrap();
+//!         let mut store = handler_store.lock().unwrap();
+//!         let outcome = idempotent.execute(
+//!             &mut *store,
+//!             &mut handled,
+//!             cmd,
+//!             &chat,
+//!             |_: &Chat| Ok::<_, Infallible>(vec![MessagePosted]),
+//!         )?;
+//!         Ok(outcome.record.version)
+//!     });
+//!
+//! let post = |id: &str, text: &str| PostMessage {
+//!     id: id.to_owned(),
+//!     chat: "1".to_owned(),
+//!     text: text.to_owned(),
+//! };
+//!
+//! assert_eq!(
+//!     bus.dispatch(&post("a", "hi")).unwrap(),
+//!     stream::Position::new(1)
+//! );
+//! let ver = std::thread::spawn(move || {
+//!     let ver = block_on(bus.dispatch_async(&post("b", "hey"))).unwrap();
+//!     (ver, bus)
+//! });
+//! let (ver, mut bus) = ver.join().unwrap();
+//! assert_eq!(ver, stream::Position::new(2));
+//!
+//! // The repeated command returns the original output.
+//! assert_eq!(
+//!     bus.dispatch(&post("a", "hi")).unwrap(),
+//!     stream::Position::new(1)
+//! );
+//! let err = bus.dispatch(&post("c", "")).unwrap_err();
+//! assert_eq!(err.to_string(), "invalid `post_message` command: empty text");
+//!
+//! assert_eq!(store.lock().unwrap().log().count(), 2);
+//! assert_eq!(
+//!     *traced.lock().unwrap(),
+//!     [
+//!         ("post_message", true),
+//!         ("post_message", true),
+//!         ("post_message", true),
+//!         ("post_message", false),
+//!     ],
+//! );
+//! ```
+
+pub mod dedup;
+pub mod middleware;
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    error::Error as StdError,
+    fmt,
+    future::Future,
+    pin::Pin,
+};
+
+#[doc(inline)]
+pub use self::middleware::{
+    Authorize, Dedup, Middleware, Retry, Span, Trace, Validate,
+};
+use crate::es::store::{AppendError, Conflict};
+#[cfg(doc)]
+use crate::es::{Event, store};
+
+/// Boxed [`Send`] [`Future`] of an asynchronous [`Handler`].
+pub type BoxFuture<'f, T> = Pin<Box<dyn Future<Output = T> + Send + 'f>>;
+
+/// Intent to change the state of an application, handled by a single
+/// [`Handler`].
+pub trait Command: Any + Send + Sync {
+    /// Name of this [`Command`], used in [`Error`]s and [`Span`]s.
+    const NAME: &'static str;
+
+    /// Output of handling this [`Command`].
+    type Output: Send + 'static;
+
+    /// Returns the unique identifier of this [`Command`] instance, used to
+    /// deduplicate it via [`dedup::Idempotent`] and as the causation id of
+    /// the [`Event`]s it produces, if any.
+    fn id(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the identifier of the conversation (workflow) this [`Command`]
+    /// belongs to, recorded as the correlation id of the [`Event`]s it
+    /// produces via [`dedup::Idempotent`], if any.
+    fn correlation(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Synchronous handler of a [`Command`].
+///
+/// Implemented for all the [`FnMut`] closures accepting a [`Command`].
+pub trait Handler<C: Command>: Send {
+    /// Handles the provided [`Command`].
+    ///
+    /// May be called several times for the same [`Command`], if its handling
+    /// is [`Retry`]ed.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Command`] cannot be handled.
+    fn handle(&mut self, command: &C) -> Result<C::Output, Error>;
+}
+
+impl<C, F> Handler<C> for F
+where
+    C: Command,
+    F: FnMut(&C) -> Result<C::Output, Error> + Send,
+{
+    fn handle(&mut self, command: &C) -> Result<C::Output, Error> {
+        self(command)
+    }
+}
+
+/// [`Command`] being dispatched, as seen by [`Middleware`].
+#[derive(Clone, Copy)]
+pub struct Envelope<'c> {
+    /// [`Command`] itself.
+    command: &'c (dyn Any + Send + Sync),
+
+    /// [`Command::NAME`] of the [`Command`].
+    name: &'static str,
+
+    /// [`Command::id()`] of the [`Command`].
+    id: Option<&'c str>,
+}
+
+impl<'c> Envelope<'c> {
+    /// Wraps the provided [`Command`] into an [`Envelope`].
+    fn of<C: Command>(command: &'c C) -> Self {
+        Self { command, name: C::NAME, id: command.id() }
+    }
+
+    /// Returns the [`Command::NAME`] of the enveloped [`Command`].
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the [`Command::id()`] of the enveloped [`Command`].
+    #[must_use]
+    pub const fn id(&self) -> Option<&'c str> {
+        self.id
+    }
+
+    /// Returns the enveloped [`Command`], if it's of the specified type.
+    #[must_use]
+    pub fn downcast<C: Command>(&self) -> Option<&'c C> {
+        self.command.downcast_ref()
+    }
+}
+
+impl fmt::Debug for Envelope<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Envelope")
+            .field("name", &self.name)
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Asynchronous handler of a [`Command`].
+type AsyncHandler<C> =
+    dyn FnMut(&C) -> BoxFuture<'static, HandlerResult<C>> + Send;
+
+/// [`Result`] of handling a [`Command`].
+type HandlerResult<C> = Result<<C as Command>::Output, Error>;
+
+/// [`Handler`] a [`Command`] is routed to.
+enum Route<C: Command> {
+    /// Synchronous [`Handler`].
+    Sync(Box<dyn Handler<C>>),
+
+    /// Asynchronous [`Handler`].
+    Async(Box<AsyncHandler<C>>),
+}
+
+/// Bus routing [`Command`]s to their [`Handler`]s by type, through a chain of
+/// [`Middleware`].
+///
+/// See the [module-level documentation](self) for details.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::{
+/// #     cqrs::command::{self, Authorize, Bus, Command, Retry},
+/// #     es::{store, stream},
+/// # };
+/// # use futures::executor::block_on;
+/// #
+/// struct Ban {
+///     user: String,
+///     by: String,
+/// }
+///
+/// impl Command for Ban {
+///     const NAME: &'static str = "ban";
+///     type Output = u32;
+/// }
+///
+/// struct Unban;
+///
+/// impl Command for Unban {
+///     const NAME: &'static str = "unban";
+///     type Output = ();
+/// }
+///
+/// let mut attempts = 0;
+/// let mut bus = Bus::new()
+///     .middleware(Authorize::new().rule(|cmd: &Ban| cmd.by == "admin"))
+///     .middleware(Retry::new(1))
+///     .async_handler(move |cmd: &Ban| {
+///         // Fails with a conflict on the first attempt only.
+///         attempts += 1;
+///         let res = if attempts == 1 {
+///             Err(command::Error::Conflict(store::Conflict {
+///                 stream: stream::Id::new("user", &cmd.user).unwrap(),
+///                 expected: stream::ExpectedVersion::NoStream,
+///                 actual: stream::Position::new(1),
+///             }))
+///         } else {
+///             Ok(attempts)
+///         };
+///         async move { res }
+///     });
+///
+/// let ban = |by: &str| Ban { user: "bob".to_owned(), by: by.to_owned() };
+/// let by_admin = ban("admin");
+/// let dispatched = bus.dispatch_async(&by_admin);
+/// fn assert_send(_: &impl Send) {}
+/// assert_send(&dispatched);
+/// assert_eq!(block_on(dispatched).unwrap(), 2);
+///
+/// let err = block_on(bus.dispatch_async(&ban("bob"))).unwrap_err();
+/// assert!(matches!(err, command::Error::Unauthorized { command: "ban" }));
+///
+/// let err = bus.dispatch(&ban("admin")).unwrap_err();
+/// assert!(matches!(err, command::Error::Async { command: "ban" }));
+///
+/// assert!(!bus.handles::<Unban>());
+/// let err = bus.dispatch(&Unban).unwrap_err();
+/// assert!(matches!(err, command::Error::NoHandler { command: "unban" }));
+/// ```
+#[derive(Default)]
+pub struct Bus {
+    /// [`Route`]s of [`Command`]s by their types.
+    routes: HashMap<TypeId, Box<dyn Any + Send>>,
+
+    /// Chain of [`Middleware`] in the order it's run.
+    middleware: Vec<Box<dyn Middleware>>,
+}
+
+impl Bus {
+    /// Creates a new [`Bus`] without [`Handler`]s and [`Middleware`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the provided [`Handler`] of the specified [`Command`],
+    /// replacing the one registered before, if any.
+    #[must_use]
+    pub fn handler<C: Command>(
+        mut self,
+        handler: impl Handler<C> + 'static,
+    ) -> Self {
+        self.route(Route::Sync(Box::new(handler)));
+        self
+    }
+
+    /// Registers the provided asynchronous [`Handler`] of the specified
+    /// [`Command`], replacing the one registered before, if any.
+    ///
+    /// The [`Command`] can be dispatched via [`Bus::dispatch_async()`] only.
+    #[must_use]
+    pub fn async_handler<C, F, Fut>(mut self, mut handler: F) -> Self
+    where
+        C: Command,
+        F: FnMut(&C) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<C::Output, Error>> + Send + 'static,
+    {
+        self.route(Route::Async(Box::new(move |cmd: &C| {
+            let fut: BoxFuture<'static, _> = Box::pin(handler(cmd));
+            fut
+        })));
+        self
+    }
+
+    /// Appends the provided [`Middleware`] to the end of the chain.
+    #[must_use]
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    /// Indicates whether a [`Handler`] of the specified [`Command`] is
+    /// registered.
+    #[must_use]
+    pub fn handles<C: Command>(&self) -> bool {
+        self.routes.contains_key(&TypeId::of::<C>())
+    }
+
+    /// Dispatches the provided [`Command`] to its synchronous [`Handler`]
+    /// through the [`Middleware`] chain.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::NoHandler`] if no [`Handler`] of the [`Command`] is
+    ///   registered.
+    /// - [`Error::Async`] if the [`Handler`] of the [`Command`] is an
+    ///   asynchronous one.
+    /// - If any [`Middleware`] rejects the [`Command`].
+    /// - If the [`Handler`] fails and no [`Middleware`] retries it.
+    pub fn dispatch<C: Command>(
+        &mut self,
+        command: &C,
+    ) -> Result<C::Output, Error> {
+        let envelope = Envelope::of(command);
+        before(&mut self.middleware, &envelope)?;
+        let res = match route::<C>(&mut self.routes) {
+            Some(Route::Sync(handler)) => {
+                let mut attempt = 1;
+                loop {
+                    match handler.handle(command) {
+                        Err(e)
+                            if retry(
+                                &mut self.middleware,
+                                &envelope,
+                                &e,
+                                attempt,
+                            ) =>
+                        {
+                            attempt += 1;
+                        }
+                        res => break res,
+                    }
+                }
+            }
+            Some(Route::Async(_)) => Err(Error::Async { command: C::NAME }),
+            None => Err(Error::NoHandler { command: C::NAME }),
+        };
+        after(&mut self.middleware, &envelope, res.as_ref().map(drop));
+        res
+    }
+
+    /// Dispatches the provided [`Command`] to its [`Handler`] (either
+    /// synchronous or asynchronous one) through the [`Middleware`] chain.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::NoHandler`] if no [`Handler`] of the [`Command`] is
+    ///   registered.
+    /// - If any [`Middleware`] rejects the [`Command`].
+    /// - If the [`Handler`] fails and no [`Middleware`] retries it.
+    pub async fn dispatch_async<C: Command>(
+        &mut self,
+        command: &C,
+    ) -> Result<C::Output, Error> {
+        let envelope = Envelope::of(command);
+        before(&mut self.middleware, &envelope)?;
+        let res = match route::<C>(&mut self.routes) {
+            Some(route) => {
+                let mut attempt = 1;
+                loop {
+                    let res = match route {
+                        Route::Sync(handler) => handler.handle(command),
+                        Route::Async(handler) => handler(command).await,
+                    };
+                    match res {
+                        Err(e)
+                            if retry(
+                                &mut self.middleware,
+                                &envelope,
+                                &e,
+                                attempt,
+                            ) =>
+                        {
+                            attempt += 1;
+                        }
+                        res => break res,
+                    }
+                }
+            }
+            None => Err(Error::NoHandler { command: C::NAME }),
+        };
+        after(&mut self.middleware, &envelope, res.as_ref().map(drop));
+        res
+    }
+
+    /// Registers the provided [`Route`] of the specified [`Command`].
+    fn route<C: Command>(&mut self, route: Route<C>) {
+        drop(self.routes.insert(TypeId::of::<C>(), Box::new(route)));
+    }
+}
+
+impl fmt::Debug for Bus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bus")
+            .field("handlers", &self.routes.len())
+            .field("middleware", &self.middleware.len())
+            .finish()
+    }
+}
+
+/// Returns the [`Route`] of the specified [`Command`] out of the provided
+/// ones, if it's registered.
+fn route<C: Command>(
+    routes: &mut HashMap<TypeId, Box<dyn Any + Send>>,
+) -> Option<&mut Route<C>> {
+    routes.get_mut(&TypeId::of::<C>())?.downcast_mut()
+}
+
+/// Runs [`Middleware::before()`] of the provided chain in its order.
+///
+/// # Errors
+///
+/// If any [`Middleware`] rejects the [`Command`], in which case
+/// [`Middleware::after()`] of the preceding ones is run.
+fn before(
+    chain: &mut [Box<dyn Middleware>],
+    envelope: &Envelope<'_>,
+) -> Result<(), Error> {
+    for i in 0..chain.len() {
+        let Some(Err(e)) = chain.get_mut(i).map(|m| m.before(envelope)) else {
+            continue;
+        };
+        after(chain.get_mut(..i).unwrap_or_default(), envelope, Err(&e));
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Runs [`Middleware::retry()`] of the provided chain in its order, until any
+/// [`Middleware`] decides to retry.
+fn retry(
+    chain: &mut [Box<dyn Middleware>],
+    envelope: &Envelope<'_>,
+    error: &Error,
+    attempt: u32,
+) -> bool {
+    chain.iter_mut().any(|m| m.retry(envelope, error, attempt))
+}
+
+/// Runs [`Middleware::after()`] of the provided chain in its reversed order.
+fn after(
+    chain: &mut [Box<dyn Middleware>],
+    envelope: &Envelope<'_>,
+    outcome: Result<(), &Error>,
+) {
+    for m in chain.iter_mut().rev() {
+        m.after(envelope, outcome);
+    }
+}
+
+/// Error of dispatching a [`Command`].
+#[derive(Debug)]
+pub enum Error {
+    /// No [`Handler`] of the [`Command`] is registered.
+    NoHandler {
+        /// [`Command::NAME`] of the [`Command`].
+        command: &'static str,
+    },
+
+    /// [`Handler`] of the [`Command`] is an asynchronous one, so it can be
+    /// dispatched via [`Bus::dispatch_async()`] only.
+    Async {
+        /// [`Command::NAME`] of the [`Command`].
+        command: &'static str,
+    },
+
+    /// [`Command`] is rejected by [`Validate`].
+    Invalid {
+        /// [`Command::NAME`] of the [`Command`].
+        command: &'static str,
+
+        /// Reason of the rejection.
+        reason: String,
+    },
+
+    /// [`Command`] is rejected by [`Authorize`].
+    Unauthorized {
+        /// [`Command::NAME`] of the [`Command`].
+        command: &'static str,
+    },
+
+    /// [`Command`] is rejected by [`Dedup`] as a handled (or being handled)
+    /// already one, or the one with the same [`Command::id()`] is still being
+    /// executed (see [`dedup::Error::Pending`]).
+    Duplicate {
+        /// [`Command::NAME`] of the [`Command`].
+        command: &'static str,
+
+        /// [`Command::id()`] of the [`Command`].
+        id: String,
+    },
+
+    /// [`Handler`] hit a version [`Conflict`], so may be [`Retry`]ed.
+    Conflict(Conflict),
+
+    /// [`Handler`] failed.
+    Handler(Box<dyn StdError + Send + Sync>),
+}
+
+impl Error {
+    /// Wraps the provided error of a [`Handler`] into an [`Error::Handler`].
+    #[must_use]
+    pub fn handler(error: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        Self::Handler(error.into())
+    }
+}
+
+impl<E> From<AppendError<E>> for Error
+where
+    E: StdError + Send + Sync + 'static,
+{
+    fn from(e: AppendError<E>) -> Self {
+        match e {
+            AppendError::Conflict(c) => Self::Conflict(c),
+            AppendError::Tombstoned(t) => Self::handler(t),
+            AppendError::Reserved(r) => Self::handler(r),
+            AppendError::Store(e) => Self::handler(e),
+        }
+    }
+}
+
+impl From<Conflict> for Error {
+    fn from(c: Conflict) -> Self {
+        Self::Conflict(c)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoHandler { command } => {
+                write!(f, "no handler of `{command}` command is registered")
+            }
+            Self::Async { command } => write!(
+                f,
+                "`{command}` command has an asynchronous handler, so must be \
+                 dispatched asynchronously",
+            ),
+            Self::Invalid { command, reason } => {
+                write!(f, "invalid `{command}` command: {reason}")
+            }
+            Self::Unauthorized { command } => {
+                write!(f, "unauthorized `{command}` command")
+            }
+            Self::Duplicate { command, id } => {
+                write!(f, "duplicate `{command}` command `{id}`")
+            }
+            Self::Conflict(c) => write!(f, "{c}"),
+            Self::Handler(e) => write!(f, "command handler failed: {e}"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::NoHandler { .. }
+            | Self::Async { .. }
+            | Self::Invalid { .. }
+            | Self::Unauthorized { .. }
+            | Self::Duplicate { .. } => None,
+            Self::Conflict(c) => Some(c),
+            Self::Handler(e) => Some(&**e),
+        }
+    }
+}
@@ -0,0 +1,14 @@
+//! [CQRS] machinery.
+//!
+//! [CQRS]: https://martinfowler.com/bliki/CQRS.html
+
+pub mod command;
+pub mod query;
+pub mod saga;
+
+#[doc(inline)]
+pub use self::{
+    command::{Bus as CommandBus, Command},
+    query::{Bus as QueryBus, Query},
+    saga::Saga,
+};
@@ -0,0 +1,660 @@
+//! [`Query`]s over read models dispatched via a [`Bus`] to their
+//! [`Handler`]s.
+//!
+//! A [`Bus`] routes every [`Query`] to the single [`Handler`] registered for
+//! its type, reading a read model maintained by a projection (usually, a
+//! [`Group`] consuming the global [`Log`]).
+//!
+//! As projections lag behind the [`Log`], a [`Query`] issued right after a
+//! command may miss its [`Event`]s. For read-your-writes, the command should
+//! return the [`Log::end()`] after appending its [`Event`]s, and the [`Query`]
+//! should be asked with [`Consistency::After`] it, so it waits until the
+//! projection's checkpoint reaches this [`Offset`]. The checkpoints are
+//! tracked by the [`Progress`] of a [`Bus`] (usually, the [`Watched`] ones
+//! the projection saves its checkpoints into). Waiting is bounded by the
+//! [`Consistency::within()`] timeout, if any, after which the [`Query`] fails
+//! as [`Error::Stale`].
+//!
+//! A [`Bus`] is [`Send`] and [`Sync`], so it may be shared between threads
+//! via an [`Arc`].
+//!
+//! # Example
+//!
+//! ```rust
+//! # use std::{
+//! #     collections::HashMap,
+//! #     sync::{Arc, RwLock},
+//! #     thread,
+//! #     time::{Duration, Instant},
+//! # };
+//! #
+//! # use arcane::{
+//! #     cqrs::query::{self, Bus, Consistency, Query, Watched},
+//! #     es::{
+//! #         Event,
+//! #         store::{self, Store as _},
+//! #         stream::{self, ExpectedVersion},
+//! #         subscription::{Group, Log as _, Shared, group},
+//! #     },
+//! # };
+//! # use futures::{FutureExt as _, StreamExt as _, executor::block_on};
+//! #
+//! #[derive(Clone, Debug, Event)]
+//! #[event(name = "message.posted")]
+//! struct MessagePosted;
+//!
+//! #[derive(Clone)]
+//! struct MessageCount {
+//!     chat: String,
+//! }
+//!
+//! impl Query for MessageCount {
+//!     const NAME: &'static str = "message_count";
+//!     type Output = usize;
+//! }
+//!
+//! // Read model maintained by the `counter` projection.
+//! let counts = Arc::new(RwLock::new(HashMap::<String, usize>::new()));
+//! let checkpoints = Watched::new(group::Memory::new());
+//!
+//! let read_model = Arc::clone(&counts);
+//! let bus = Arc::new(Bus::new().progress(checkpoints.clone()).handler(
+//!     move |q: &MessageCount| {
+//!         let counts = read_model.read().unwrap();
+//!         Ok(counts.get(&q.chat).copied().unwrap_or_default())
+//!     },
+//! ));
+//!
+//! // Command appends its events and returns the end of the log.
+//! let mut store = Shared::new(store::Memory::new());
+//! let chat = stream::Id::new("chat", "1").unwrap();
+//! store.append(&chat, ExpectedVersion::Any, vec![MessagePosted]).unwrap();
+//! let written = store.end().unwrap();
+//!
+//! let count = MessageCount { chat: chat.to_string() };
+//! let fresh = Consistency::after("counter", written);
+//! assert_eq!(bus.ask(&count, &Consistency::Eventual).unwrap(), 0);
+//! let err = bus.ask(&count, &fresh).unwrap_err();
+//! assert!(matches!(err, query::Error::Stale { .. }));
+//! let bounded = fresh.clone().within(Duration::from_millis(10));
+//! let err = block_on(bus.ask_async(&count, &bounded)).unwrap_err();
+//! assert!(matches!(err, query::Error::Stale { .. }));
+//!
+//! // Timeouts may be driven by another timer (usually, the executor's one).
+//! let timed = Bus::new()
+//!     .progress(checkpoints.clone())
+//!     .timer_with(|deadline, waker| {
+//!         let delay = deadline.saturating_duration_since(Instant::now());
+//!         drop(thread::spawn(move || {
+//!             thread::sleep(delay);
+//!             waker.wake();
+//!         }));
+//!     })
+//!     .handler(|_: &MessageCount| Ok(0));
+//! let err = block_on(timed.ask_async(&count, &bounded)).unwrap_err();
+//! assert!(matches!(err, query::Error::Stale { .. }));
+//!
+//! // Another thread waits for the projection to catch up.
+//! let waiting = thread::spawn({
+//!     let (bus, count, fresh) =
+//!         (Arc::clone(&bus), count.clone(), fresh.clone());
+//!     move || block_on(bus.ask_async(&count, &fresh)).unwrap()
+//! });
+//!
+//! // Projection catches up.
+//! let (counter, mut cps) = (Group::new("counter"), checkpoints.clone());
+//! let mut sub = counter.subscribe(store.clone(), &cps).unwrap();
+//! while let Some(Some(Ok((offset, entry)))) = sub.next().now_or_never() {
+//!     let chat = entry.stream().to_string();
+//!     *counts.write().unwrap().entry(chat).or_default() += 1;
+//!     counter.ack(&mut cps, offset).unwrap();
+//! }
+//!
+//! assert_eq!(bus.ask(&count, &fresh).unwrap(), 1);
+//! assert_eq!(waiting.join().unwrap(), 1);
+//! ```
+//!
+//! [`Event`]: crate::es::Event
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    error::Error as StdError,
+    fmt, future, mem,
+    sync::{Arc, Condvar, Mutex, MutexGuard, Once, PoisonError},
+    task::{Context, Poll, Waker},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::es::subscription::{Checkpoints, Offset};
+#[cfg(doc)]
+use crate::es::subscription::{Group, Log};
+
+/// Request of data from a read model, handled by a single [`Handler`].
+pub trait Query: Any + Send + Sync {
+    /// Name of this [`Query`], used in [`Error`]s.
+    const NAME: &'static str;
+
+    /// Result of handling this [`Query`].
+    type Output: Send + 'static;
+}
+
+/// Handler of a [`Query`].
+///
+/// Implemented for all the [`Fn`] closures accepting a [`Query`].
+pub trait Handler<Q: Query>: Send + Sync {
+    /// Handles the provided [`Query`].
+    ///
+    /// # Errors
+    ///
+    /// If the [`Query`] cannot be handled.
+    fn handle(&self, query: &Q) -> Result<Q::Output, Error>;
+}
+
+impl<Q, F> Handler<Q> for F
+where
+    Q: Query,
+    F: Fn(&Q) -> Result<Q::Output, Error> + Send + Sync,
+{
+    fn handle(&self, query: &Q) -> Result<Q::Output, Error> {
+        self(query)
+    }
+}
+
+/// Consistency of a read model required by a [`Query`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Consistency {
+    /// Read model is read as is.
+    #[default]
+    Eventual,
+
+    /// Read model must reflect everything before the provided [`Offset`] in
+    /// the global [`Log`] (read-your-writes).
+    After {
+        /// Name of the consumer maintaining the read model, which checkpoints
+        /// are tracked by the [`Progress`].
+        consumer: String,
+
+        /// [`Offset`] the checkpoint of the `consumer` must reach (usually,
+        /// the [`Log::end()`] after a command is handled).
+        offset: Offset,
+
+        /// Maximum time to wait for the checkpoint of the `consumer` in
+        /// [`Bus::ask_async()`], if any.
+        timeout: Option<Duration>,
+    },
+}
+
+impl Consistency {
+    /// Creates a new [`Consistency::After`] the provided [`Offset`] for the
+    /// provided `consumer`, waited for without a timeout.
+    #[must_use]
+    pub fn after(consumer: impl Into<String>, offset: Offset) -> Self {
+        Self::After { consumer: consumer.into(), offset, timeout: None }
+    }
+
+    /// Bounds waiting for this [`Consistency::After`] by the provided
+    /// `timeout`.
+    ///
+    /// Does nothing for [`Consistency::Eventual`].
+    #[must_use]
+    pub const fn within(mut self, timeout: Duration) -> Self {
+        if let Self::After { timeout: t, .. } = &mut self {
+            *t = Some(timeout);
+        }
+        self
+    }
+}
+
+/// Progress of consumers through the global [`Log`], which [`Query`]s may wait
+/// for.
+pub trait Progress: Send + Sync {
+    /// Checks whether the checkpoint of the provided `consumer` has reached
+    /// the provided [`Offset`].
+    ///
+    /// # Errors
+    ///
+    /// If the checkpoint cannot be loaded.
+    fn reached(&self, consumer: &str, offset: Offset) -> Result<bool, Error>;
+
+    /// Checks whether the checkpoint of the provided `consumer` has reached
+    /// the provided [`Offset`], otherwise scheduling the current task to be
+    /// woken once any checkpoint is saved.
+    ///
+    /// # Errors
+    ///
+    /// If the checkpoint cannot be loaded.
+    fn poll_reached(
+        &self,
+        consumer: &str,
+        offset: Offset,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Error>>;
+}
+
+/// [`Checkpoints`] shared between their consumers and [`Query`]s, notifying
+/// the latter about saved checkpoints, so making them the [`Progress`].
+///
+/// Cloning [`Watched`] [`Checkpoints`] gives another handle to the same
+/// [`Checkpoints`].
+pub struct Watched<C> {
+    /// Shared [`Checkpoints`] themselves.
+    checkpoints: Arc<Mutex<C>>,
+
+    /// [`Waker`]s of the [`Query`]s waiting for checkpoints.
+    wakers: Arc<Mutex<Vec<Waker>>>,
+}
+
+impl<C> Watched<C> {
+    /// Wraps the provided [`Checkpoints`] into [`Watched`] ones.
+    #[must_use]
+    pub fn new(checkpoints: C) -> Self {
+        Self {
+            checkpoints: Arc::new(Mutex::new(checkpoints)),
+            wakers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Locks the underlying [`Checkpoints`] for an exclusive access.
+    ///
+    /// Not exposed, as checkpoints saved directly to the locked
+    /// [`Checkpoints`] wouldn't wake the waiting [`Query`]s.
+    fn lock(&self) -> MutexGuard<'_, C> {
+        self.checkpoints.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Wakes all the [`Query`]s waiting for checkpoints.
+    fn wake_all(&self) {
+        let wakers = mem::take(
+            &mut *self.wakers.lock().unwrap_or_else(PoisonError::into_inner),
+        );
+        for w in wakers {
+            w.wake();
+        }
+    }
+}
+
+impl<C> Clone for Watched<C> {
+    fn clone(&self) -> Self {
+        Self {
+            checkpoints: Arc::clone(&self.checkpoints),
+            wakers: Arc::clone(&self.wakers),
+        }
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for Watched<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watched")
+            .field("checkpoints", &*self.lock())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: Checkpoints> Checkpoints for Watched<C> {
+    type Error = C::Error;
+
+    fn load(&self, consumer: &str) -> Result<Option<Offset>, Self::Error> {
+        self.lock().load(consumer)
+    }
+
+    fn save(
+        &mut self,
+        consumer: &str,
+        next: Offset,
+    ) -> Result<(), Self::Error> {
+        let res = self.lock().save(consumer, next);
+        if res.is_ok() {
+            self.wake_all();
+        }
+        res
+    }
+}
+
+impl<C> Progress for Watched<C>
+where
+    C: Checkpoints + Send,
+    C::Error: StdError + Send + Sync + 'static,
+{
+    fn reached(&self, consumer: &str, offset: Offset) -> Result<bool, Error> {
+        let loaded = self.load(consumer).map_err(|e| Error::Progress(e.into()));
+        Ok(loaded?.is_some_and(|next| next >= offset))
+    }
+
+    fn poll_reached(
+        &self,
+        consumer: &str,
+        offset: Offset,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Error>> {
+        // Registered before loading, so a checkpoint saved in-between still
+        // wakes the task.
+        {
+            let mut wakers =
+                self.wakers.lock().unwrap_or_else(PoisonError::into_inner);
+            if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+        }
+        match self.reached(consumer, offset) {
+            Ok(false) => Poll::Pending,
+            Ok(true) => Poll::Ready(Ok(())),
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+}
+
+/// Hook waking the provided [`Waker`] once the provided deadline passes,
+/// without blocking the current thread.
+///
+/// Waking it earlier or more than once is harmless, as the woken task checks
+/// the deadline itself.
+pub type Timer = fn(Instant, Waker);
+
+/// Bus routing [`Query`]s to their [`Handler`]s by type.
+///
+/// See the [module-level documentation](self) for details.
+pub struct Bus {
+    /// [`Handler`]s of [`Query`]s by their types.
+    routes: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+
+    /// [`Progress`] of consumers, if any.
+    progress: Option<Box<dyn Progress>>,
+
+    /// [`Timer`] hook bounding waiting in [`Bus::ask_async()`].
+    timer: Timer,
+}
+
+impl Bus {
+    /// Creates a new [`Bus`] without [`Handler`]s and [`Progress`], waking
+    /// the timed out [`Query`]s on a single helper thread shared by all the
+    /// [`Bus`]es.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { routes: HashMap::new(), progress: None, timer: wake_at }
+    }
+
+    /// Registers the provided [`Handler`] of the specified [`Query`],
+    /// replacing the one registered before, if any.
+    #[must_use]
+    pub fn handler<Q: Query>(
+        mut self,
+        handler: impl Handler<Q> + 'static,
+    ) -> Self {
+        let handler: Box<dyn Handler<Q>> = Box::new(handler);
+        drop(self.routes.insert(TypeId::of::<Q>(), Box::new(handler)));
+        self
+    }
+
+    /// Sets the [`Progress`] of consumers, required for [`Query`]s asked with
+    /// [`Consistency::After`].
+    #[must_use]
+    pub fn progress(mut self, progress: impl Progress + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Sets the [`Timer`] hook waking the [`Query`]s waiting in
+    /// [`Bus::ask_async()`] once their [`Consistency::within()`] timeouts
+    /// elapse (usually, via the timer of the used executor).
+    #[must_use]
+    pub const fn timer_with(mut self, timer: Timer) -> Self {
+        self.timer = timer;
+        self
+    }
+
+    /// Indicates whether a [`Handler`] of the specified [`Query`] is
+    /// registered.
+    #[must_use]
+    pub fn handles<Q: Query>(&self) -> bool {
+        self.routes.contains_key(&TypeId::of::<Q>())
+    }
+
+    /// Asks the provided [`Query`] with the provided [`Consistency`], without
+    /// waiting for it.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::NoHandler`] if no [`Handler`] of the [`Query`] is
+    ///   registered.
+    /// - [`Error::NoProgress`] if [`Consistency::After`] is required, but
+    ///   no [`Progress`] is set.
+    /// - [`Error::Stale`] if the read model doesn't satisfy the required
+    ///   [`Consistency`] yet.
+    /// - [`Error::Progress`] if the [`Progress`] fails.
+    /// - If the [`Handler`] fails.
+    pub fn ask<Q: Query>(
+        &self,
+        query: &Q,
+        consistency: &Consistency,
+    ) -> Result<Q::Output, Error> {
+        let handler = self.route::<Q>()?;
+        if let Consistency::After { consumer, offset, .. } = consistency {
+            if !self.progress_for::<Q>()?.reached(consumer, *offset)? {
+                return Err(Error::Stale {
+                    query: Q::NAME,
+                    consumer: consumer.clone(),
+                });
+            }
+        }
+        handler.handle(query)
+    }
+
+    /// Asks the provided [`Query`] with the provided [`Consistency`], waiting
+    /// until the read model satisfies it or its [`Consistency::within()`]
+    /// timeout elapses.
+    ///
+    /// While waiting with a timeout, the task is woken once it elapses via
+    /// the [`Timer`] hook set by [`Bus::timer_with()`], regardless of the
+    /// executor.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::NoHandler`] if no [`Handler`] of the [`Query`] is
+    ///   registered.
+    /// - [`Error::NoProgress`] if [`Consistency::After`] is required, but
+    ///   no [`Progress`] is set.
+    /// - [`Error::Stale`] if the read model doesn't satisfy the required
+    ///   [`Consistency`] within its timeout.
+    /// - [`Error::Progress`] if the [`Progress`] fails.
+    /// - If the [`Handler`] fails.
+    pub async fn ask_async<Q: Query>(
+        &self,
+        query: &Q,
+        consistency: &Consistency,
+    ) -> Result<Q::Output, Error> {
+        let handler = self.route::<Q>()?;
+        if let Consistency::After { consumer, offset, timeout } = consistency {
+            let progress = self.progress_for::<Q>()?;
+            let deadline = timeout.map(|t| Instant::now() + t);
+            let mut scheduled = None::<Waker>;
+            future::poll_fn(|cx| {
+                let reached = progress.poll_reached(consumer, *offset, cx);
+                let Some(deadline) = deadline else {
+                    return reached;
+                };
+                if reached.is_ready() {
+                    return reached;
+                }
+                if Instant::now() >= deadline {
+                    return Poll::Ready(Err(Error::Stale {
+                        query: Q::NAME,
+                        consumer: consumer.clone(),
+                    }));
+                }
+                // Rescheduled only once the task is moved to another `Waker`.
+                if !scheduled.as_ref().is_some_and(|w| w.will_wake(cx.waker()))
+                {
+                    (self.timer)(deadline, cx.waker().clone());
+                    scheduled = Some(cx.waker().clone());
+                }
+                Poll::Pending
+            })
+            .await?;
+        }
+        handler.handle(query)
+    }
+
+    /// Returns the [`Handler`] of the specified [`Query`].
+    ///
+    /// # Errors
+    ///
+    /// [`Error::NoHandler`] if no [`Handler`] of the [`Query`] is registered.
+    fn route<Q: Query>(&self) -> Result<&dyn Handler<Q>, Error> {
+        self.routes
+            .get(&TypeId::of::<Q>())
+            .and_then(|h| h.downcast_ref::<Box<dyn Handler<Q>>>())
+            .map(AsRef::as_ref)
+            .ok_or(Error::NoHandler { query: Q::NAME })
+    }
+
+    /// Returns the [`Progress`] of consumers for the specified [`Query`].
+    ///
+    /// # Errors
+    ///
+    /// [`Error::NoProgress`] if no [`Progress`] is set.
+    fn progress_for<Q: Query>(&self) -> Result<&dyn Progress, Error> {
+        self.progress.as_deref().ok_or(Error::NoProgress { query: Q::NAME })
+    }
+}
+
+impl Default for Bus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Bus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bus")
+            .field("handlers", &self.routes.len())
+            .field("progress", &self.progress.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// [`Waker`]s registered by the default [`Timer`] hook along with their
+/// deadlines, and the [`Condvar`] notifying its helper thread about the new
+/// ones.
+static ALARMS: (Mutex<Vec<(Instant, Waker)>>, Condvar) =
+    (Mutex::new(Vec::new()), Condvar::new());
+
+/// Default [`Timer`] hook, waking the provided [`Waker`] on a single helper
+/// thread shared by all the [`Bus`]es, started on the first call.
+fn wake_at(deadline: Instant, waker: Waker) {
+    /// Indicator whether the helper thread is started.
+    static STARTED: Once = Once::new();
+
+    STARTED.call_once(|| drop(thread::spawn(ring)));
+    let (alarms, added) = &ALARMS;
+    alarms
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push((deadline, waker));
+    added.notify_one();
+}
+
+/// Wakes the [`ALARMS`] once their deadlines pass, forever.
+fn ring() -> ! {
+    let (alarms, added) = &ALARMS;
+    loop {
+        let mut pending = alarms.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        let (due, rest): (Vec<_>, _) = mem::take(&mut *pending)
+            .into_iter()
+            .partition(|(at, _)| *at <= now);
+        *pending = rest;
+        if due.is_empty() {
+            // Waited while still locked, so no new alarm is missed.
+            let next = pending.iter().map(|(at, _)| *at).min();
+            drop(match next {
+                Some(at) => {
+                    added
+                        .wait_timeout(pending, at - now)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                None => {
+                    added.wait(pending).unwrap_or_else(PoisonError::into_inner)
+                }
+            });
+            continue;
+        }
+        drop(pending);
+        for (_, w) in due {
+            w.wake();
+        }
+    }
+}
+
+/// Error of asking a [`Query`].
+#[derive(Debug)]
+pub enum Error {
+    /// No [`Handler`] of the [`Query`] is registered.
+    NoHandler {
+        /// [`Query::NAME`] of the [`Query`].
+        query: &'static str,
+    },
+
+    /// [`Consistency::After`] is required, but no [`Progress`] is set.
+    NoProgress {
+        /// [`Query::NAME`] of the [`Query`].
+        query: &'static str,
+    },
+
+    /// Read model doesn't satisfy the required [`Consistency`] yet.
+    Stale {
+        /// [`Query::NAME`] of the [`Query`].
+        query: &'static str,
+
+        /// Consumer maintaining the read model.
+        consumer: String,
+    },
+
+    /// [`Progress`] failed.
+    Progress(Box<dyn StdError + Send + Sync>),
+
+    /// [`Handler`] failed.
+    Handler(Box<dyn StdError + Send + Sync>),
+}
+
+impl Error {
+    /// Wraps the provided error of a [`Handler`] into an [`Error::Handler`].
+    #[must_use]
+    pub fn handler(error: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        Self::Handler(error.into())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoHandler { query } => {
+                write!(f, "no handler of `{query}` query is registered")
+            }
+            Self::NoProgress { query } => write!(
+                f,
+                "`{query}` query requires consistency, but no progress is set",
+            ),
+            Self::Stale { query, consumer } => write!(
+                f,
+                "`{query}` query is stale: `{consumer}` consumer hasn't \
+                 caught up yet",
+            ),
+            Self::Progress(e) => write!(f, "progress failed: {e}"),
+            Self::Handler(e) => write!(f, "query handler failed: {e}"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::NoHandler { .. }
+            | Self::NoProgress { .. }
+            | Self::Stale { .. } => None,
+            Self::Progress(e) | Self::Handler(e) => Some(&**e),
+        }
+    }
+}
@@ -0,0 +1,770 @@
+//! [`Saga`]s (process managers) reacting to [`Event`]s of several [`Stream`]s
+//! and issuing commands.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use std::{convert::Infallible, time::{Duration, SystemTime}};
+//! #
+//! # use arcane::{
+//! #     cqrs::saga::{self, Effects, Saga},
+//! #     es::{
+//! #         Event,
+//! #         event::Sourced,
+//! #         store::{self, Store as _},
+//! #         stream::{self, ExpectedVersion},
+//! #     },
+//! # };
+//! #
+//! #[derive(Clone, Debug, Event)]
+//! #[event(name = "message.reported", revision = 1)]
+//! struct MessageReported {
+//!     chat: String,
+//! }
+//!
+//! #[derive(Clone, Debug, Event)]
+//! #[event(name = "report.reviewed", revision = 1)]
+//! struct ReportReviewed {
+//!     chat: String,
+//! }
+//!
+//! #[derive(Clone, Debug, Event)]
+//! enum ModerationEvent {
+//!     Reported(MessageReported),
+//!     Reviewed(ReportReviewed),
+//! }
+//!
+//! #[derive(Debug, Eq, PartialEq)]
+//! enum Command {
+//!     Escalate { chat: String },
+//! }
+//!
+//! /// Escalates reports not reviewed within an hour.
+//! #[derive(Clone, Default)]
+//! struct Escalation {
+//!     pending: bool,
+//! }
+//!
+//! impl Sourced<ModerationEvent> for Escalation {
+//!     fn apply(&mut self, ev: &ModerationEvent) {
+//!         self.pending = matches!(ev, ModerationEvent::Reported(_));
+//!     }
+//! }
+//!
+//! impl Saga for Escalation {
+//!     type Event = ModerationEvent;
+//!     type Command = Command;
+//!     type Timeout = String;
+//!
+//!     fn correlate(ev: &ModerationEvent) -> Option<saga::Key> {
+//!         match ev {
+//!             ModerationEvent::Reported(ev) => Some(ev.chat.clone()),
+//!             ModerationEvent::Reviewed(ev) => Some(ev.chat.clone()),
+//!         }
+//!     }
+//!
+//!     fn react(
+//!         &self,
+//!         ev: &ModerationEvent,
+//!         fx: &mut Effects<Command, String>,
+//!     ) {
+//!         if let ModerationEvent::Reported(ev) = ev {
+//!             fx.schedule_in(Duration::from_secs(3600), ev.chat.clone());
+//!         }
+//!     }
+//!
+//!     fn on_timeout(&self, chat: &String, fx: &mut Effects<Command, String>) {
+//!         if self.pending {
+//!             fx.command(Command::Escalate { chat: chat.clone() });
+//!         }
+//!     }
+//! }
+//!
+//! let mut store = store::Memory::new();
+//! let (chat1, chat2) = ("chat1".to_owned(), "chat2".to_owned());
+//! for (id, events) in [
+//!     (
+//!         "1",
+//!         vec![ModerationEvent::Reported(MessageReported {
+//!             chat: chat1.clone(),
+//!         })],
+//!     ),
+//!     (
+//!         "2",
+//!         vec![
+//!             ModerationEvent::Reported(MessageReported {
+//!                 chat: chat2.clone(),
+//!             }),
+//!             ModerationEvent::Reviewed(ReportReviewed { chat: chat2 }),
+//!         ],
+//!     ),
+//! ] {
+//!     let id = stream::Id::new("report", id).unwrap();
+//!     store.append(&id, ExpectedVersion::Any, events).unwrap();
+//! }
+//!
+//! let mut runner = saga::Runner::<Escalation, _>::new(saga::Memory::new());
+//! let mut commands = vec![];
+//! let mut dispatch = |cmd| {
+//!     commands.push(cmd);
+//!     Ok::<_, Infallible>(())
+//! };
+//!
+//! let now = SystemTime::UNIX_EPOCH;
+//! let processed = runner.poll(&store, "report", now, &mut dispatch).unwrap();
+//! assert_eq!(processed, 3);
+//! // Already processed events are skipped.
+//! assert_eq!(runner.poll(&store, "report", now, &mut dispatch).unwrap(), 0);
+//!
+//! // Not due yet.
+//! assert_eq!(runner.fire(now, &mut dispatch).unwrap(), 0);
+//!
+//! let later = now + Duration::from_secs(3600);
+//! // Timeouts failed to be dispatched remain due.
+//! assert!(runner.fire(later, |_| Err("unavailable")).is_err());
+//! assert_eq!(runner.persistence().pending_timeouts(), 2);
+//!
+//! assert_eq!(runner.fire(later, &mut dispatch).unwrap(), 2);
+//! assert_eq!(runner.persistence().pending_timeouts(), 0);
+//! assert_eq!(commands, [Command::Escalate { chat: chat1 }]);
+//! ```
+//!
+//! [`Stream`]: crate::es::Stream
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::Infallible,
+    error::Error as StdError,
+    fmt,
+    marker::PhantomData,
+    time::{Duration, SystemTime},
+};
+
+#[cfg(doc)]
+use crate::es::{Event, store::Store};
+use crate::es::{
+    event::Sourced,
+    store::Recorded,
+    subscription::{self, Log, Offset},
+};
+
+/// Correlation key of a [`Saga`] instance.
+pub type Key = String;
+
+/// Identifier of a scheduled timeout, unique within its [`Persistence`].
+pub type TimerId = u64;
+
+/// Timeout of a [`Saga`] instance scheduled in a [`Persistence`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Timer<T> {
+    /// [`TimerId`] of this [`Timer`].
+    pub id: TimerId,
+
+    /// [`Key`] of the [`Saga`] instance scheduled this [`Timer`].
+    pub key: Key,
+
+    /// Scheduled timeout itself.
+    pub timeout: T,
+}
+
+/// Process manager, which state is [`Sourced`] from [`Event`]s correlated by
+/// a [`Key`] (possibly from several [`Stream`]s), reacting to them with
+/// commands and scheduled timeouts.
+///
+/// [`Stream`]: crate::es::Stream
+pub trait Saga: Default + Sourced<Self::Event> {
+    /// Type of [`Event`]s this [`Saga`] reacts to.
+    type Event;
+
+    /// Type of commands this [`Saga`] issues.
+    type Command;
+
+    /// Type of timeouts this [`Saga`] schedules.
+    type Timeout;
+
+    /// Extracts the [`Key`] of the [`Saga`] instance the provided [`Event`]
+    /// belongs to, or [`None`] if it should be ignored.
+    fn correlate(event: &Self::Event) -> Option<Key>;
+
+    /// Reacts to the provided [`Event`], already applied to this [`Saga`]'s
+    /// state.
+    fn react(
+        &self,
+        event: &Self::Event,
+        effects: &mut Effects<Self::Command, Self::Timeout>,
+    );
+
+    /// Reacts to the provided timeout, previously scheduled via
+    /// [`Effects::schedule()`], becoming due.
+    ///
+    /// Does nothing by default.
+    fn on_timeout(
+        &self,
+        timeout: &Self::Timeout,
+        effects: &mut Effects<Self::Command, Self::Timeout>,
+    ) {
+        _ = (timeout, effects);
+    }
+}
+
+/// Commands and timeouts produced by a [`Saga`] in a single reaction.
+#[derive(Clone, Debug)]
+pub struct Effects<C, T> {
+    /// Moment the reaction happens at.
+    now: SystemTime,
+
+    /// Issued commands.
+    commands: Vec<C>,
+
+    /// Scheduled timeouts along with their deadlines.
+    timeouts: Vec<(SystemTime, T)>,
+}
+
+impl<C, T> Effects<C, T> {
+    /// Creates new empty [`Effects`] of a reaction happening at the provided
+    /// moment.
+    #[must_use]
+    pub const fn new(now: SystemTime) -> Self {
+        Self { now, commands: Vec::new(), timeouts: Vec::new() }
+    }
+
+    /// Returns the moment the reaction happens at.
+    #[must_use]
+    pub const fn now(&self) -> SystemTime {
+        self.now
+    }
+
+    /// Issues the provided command.
+    pub fn command(&mut self, command: C) {
+        self.commands.push(command);
+    }
+
+    /// Schedules the provided `timeout` to become due at the provided
+    /// `deadline`.
+    pub fn schedule(&mut self, deadline: SystemTime, timeout: T) {
+        self.timeouts.push((deadline, timeout));
+    }
+
+    /// Schedules the provided `timeout` to become due after the provided
+    /// `delay`.
+    pub fn schedule_in(&mut self, delay: Duration, timeout: T) {
+        self.schedule(self.now + delay, timeout);
+    }
+
+    /// Returns the issued commands.
+    #[must_use]
+    pub fn commands(&self) -> &[C] {
+        &self.commands
+    }
+
+    /// Returns the scheduled timeouts along with their deadlines.
+    #[must_use]
+    pub fn timeouts(&self) -> &[(SystemTime, T)] {
+        &self.timeouts
+    }
+}
+
+/// Storage of [`Saga`] states, the checkpoint of processed [`Event`]s and
+/// scheduled timeouts.
+pub trait Persistence<G: Saga> {
+    /// Error of this [`Persistence`].
+    type Error;
+
+    /// Loads the state of the [`Saga`] instance with the provided [`Key`], if
+    /// any.
+    ///
+    /// # Errors
+    ///
+    /// If the state cannot be loaded.
+    fn load(&self, key: &str) -> Result<Option<G>, Self::Error>;
+
+    /// Saves the state of the [`Saga`] instance with the provided [`Key`].
+    ///
+    /// # Errors
+    ///
+    /// If the state cannot be saved.
+    fn save(&mut self, key: &str, saga: &G) -> Result<(), Self::Error>;
+
+    /// Loads the [`Offset`] of the next [`Event`] to be processed in the
+    /// global log, if any.
+    ///
+    /// # Errors
+    ///
+    /// If the checkpoint cannot be loaded.
+    fn checkpoint(&self) -> Result<Option<Offset>, Self::Error>;
+
+    /// Saves the [`Offset`] of the next [`Event`] to be processed in the
+    /// global log.
+    ///
+    /// # Errors
+    ///
+    /// If the checkpoint cannot be saved.
+    fn save_checkpoint(&mut self, next: Offset) -> Result<(), Self::Error>;
+
+    /// Schedules the provided `timeout` of the [`Saga`] instance with the
+    /// provided [`Key`] to become due at the provided `deadline`.
+    ///
+    /// # Errors
+    ///
+    /// If the timeout cannot be scheduled.
+    fn schedule(
+        &mut self,
+        key: &str,
+        deadline: SystemTime,
+        timeout: G::Timeout,
+    ) -> Result<(), Self::Error>;
+
+    /// Returns all the [`Timer`]s due at the provided moment, in the order of
+    /// their deadlines, without removing them.
+    ///
+    /// # Errors
+    ///
+    /// If the timeouts cannot be read.
+    fn due(
+        &self,
+        now: SystemTime,
+    ) -> Result<Vec<Timer<G::Timeout>>, Self::Error>;
+
+    /// Removes the fired [`Timer`] with the provided [`TimerId`], so it's not
+    /// due anymore.
+    ///
+    /// # Errors
+    ///
+    /// If the timeout cannot be removed.
+    fn complete(&mut self, timer: TimerId) -> Result<(), Self::Error>;
+}
+
+/// In-memory [`Persistence`] with a local timer store.
+#[derive(Clone, Debug)]
+pub struct Memory<G: Saga> {
+    /// States of [`Saga`] instances by their [`Key`]s.
+    states: HashMap<Key, G>,
+
+    /// [`Offset`] of the next [`Event`] to be processed.
+    checkpoint: Option<Offset>,
+
+    /// Scheduled timeouts ordered by their deadlines and scheduling order.
+    timers: BTreeMap<(SystemTime, TimerId), (Key, G::Timeout)>,
+
+    /// Deadlines of the scheduled timeouts by their [`TimerId`]s.
+    deadlines: HashMap<TimerId, SystemTime>,
+
+    /// [`TimerId`] of the next scheduled timeout.
+    seq: TimerId,
+}
+
+impl<G: Saga> Memory<G> {
+    /// Creates a new empty [`Memory`] [`Persistence`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            states: HashMap::new(),
+            checkpoint: None,
+            timers: BTreeMap::new(),
+            deadlines: HashMap::new(),
+            seq: 0,
+        }
+    }
+
+    /// Returns the number of the pending timeouts.
+    #[must_use]
+    pub fn pending_timeouts(&self) -> usize {
+        self.timers.len()
+    }
+}
+
+impl<G: Saga> Default for Memory<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G> Persistence<G> for Memory<G>
+where
+    G: Saga + Clone,
+    G::Timeout: Clone,
+{
+    type Error = Infallible;
+
+    fn load(&self, key: &str) -> Result<Option<G>, Self::Error> {
+        Ok(self.states.get(key).cloned())
+    }
+
+    fn save(&mut self, key: &str, saga: &G) -> Result<(), Self::Error> {
+        drop(self.states.insert(key.to_owned(), saga.clone()));
+        Ok(())
+    }
+
+    fn checkpoint(&self) -> Result<Option<Offset>, Self::Error> {
+        Ok(self.checkpoint)
+    }
+
+    fn save_checkpoint(&mut self, next: Offset) -> Result<(), Self::Error> {
+        self.checkpoint = Some(next);
+        Ok(())
+    }
+
+    fn schedule(
+        &mut self,
+        key: &str,
+        deadline: SystemTime,
+        timeout: G::Timeout,
+    ) -> Result<(), Self::Error> {
+        drop(
+            self.timers.insert((deadline, self.seq), (key.to_owned(), timeout)),
+        );
+        _ = self.deadlines.insert(self.seq, deadline);
+        self.seq += 1;
+        Ok(())
+    }
+
+    fn due(
+        &self,
+        now: SystemTime,
+    ) -> Result<Vec<Timer<G::Timeout>>, Self::Error> {
+        Ok(self
+            .timers
+            .range(..=(now, TimerId::MAX))
+            .map(|((_, id), (key, timeout))| Timer {
+                id: *id,
+                key: key.clone(),
+                timeout: timeout.clone(),
+            })
+            .collect())
+    }
+
+    fn complete(&mut self, timer: TimerId) -> Result<(), Self::Error> {
+        if let Some(deadline) = self.deadlines.remove(&timer) {
+            drop(self.timers.remove(&(deadline, timer)));
+        }
+        Ok(())
+    }
+}
+
+/// Runner of a [`Saga`], feeding it with [`Event`]s read from a [`Store`],
+/// and firing its due timeouts.
+///
+/// Delivers [`Event`]s and timeouts with at-least-once semantics: the issued
+/// commands are dispatched before the [`Saga`] state and checkpoint are
+/// saved (or the fired timeout is removed), so they may be dispatched again
+/// if the dispatching or saving fails.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::time::{Duration, SystemTime};
+/// #
+/// # use arcane::{
+/// #     cqrs::saga::{self, Effects, Persistence as _, Saga},
+/// #     es::{
+/// #         Event,
+/// #         event::Sourced,
+/// #         store::{self, Store as _},
+/// #         stream::{self, ExpectedVersion},
+/// #         subscription::Offset,
+/// #     },
+/// # };
+/// #
+/// #[derive(Clone, Debug, Event)]
+/// #[event(name = "message.posted", revision = 1)]
+/// struct MessagePosted {
+///     chat: String,
+/// }
+///
+/// #[derive(Debug, Eq, PartialEq)]
+/// enum Command {
+///     Notify(String),
+///     Remind(String),
+/// }
+///
+/// /// Reminds about posted messages twice, a minute apart.
+/// #[derive(Clone, Default)]
+/// struct Reminder;
+///
+/// impl Sourced<MessagePosted> for Reminder {
+///     fn apply(&mut self, _: &MessagePosted) {}
+/// }
+///
+/// impl Saga for Reminder {
+///     type Event = MessagePosted;
+///     type Command = Command;
+///     type Timeout = (String, u8);
+///
+///     fn correlate(ev: &MessagePosted) -> Option<saga::Key> {
+///         Some(ev.chat.clone())
+///     }
+///
+///     fn react(
+///         &self,
+///         ev: &MessagePosted,
+///         fx: &mut Effects<Command, (String, u8)>,
+///     ) {
+///         fx.command(Command::Notify(ev.chat.clone()));
+///         fx.schedule_in(Duration::from_secs(60), (ev.chat.clone(), 1));
+///     }
+///
+///     fn on_timeout(
+///         &self,
+///         (chat, attempt): &(String, u8),
+///         fx: &mut Effects<Command, (String, u8)>,
+///     ) {
+///         fx.command(Command::Remind(chat.clone()));
+///         if *attempt < 2 {
+///             fx.schedule_in(Duration::from_secs(60), (chat.clone(), 2));
+///         }
+///     }
+/// }
+///
+/// let mut store = store::Memory::new();
+/// for chat in ["1", "2"] {
+///     let id = stream::Id::new("chat", chat).unwrap();
+///     let ev = MessagePosted { chat: chat.to_owned() };
+///     store.append(&id, ExpectedVersion::Any, vec![ev]).unwrap();
+/// }
+///
+/// let now = SystemTime::UNIX_EPOCH;
+/// let mut runner = saga::Runner::<Reminder, _>::new(saga::Memory::new());
+/// let mut commands = vec![];
+///
+/// // Dispatching fails on the second event, so it remains unprocessed.
+/// let err = runner.poll(&store, "chat", now, |cmd| match cmd {
+///     Command::Notify(chat) if chat == "2" => Err("unavailable"),
+///     cmd => {
+///         commands.push(cmd);
+///         Ok(())
+///     }
+/// });
+/// assert!(matches!(err, Err(saga::Error::Dispatch("unavailable"))));
+/// let checkpoint = runner.persistence().checkpoint().unwrap();
+/// assert_eq!(checkpoint, Some(Offset::START.next()));
+///
+/// // Restarted runner resumes from the checkpoint.
+/// let mut runner =
+///     saga::Runner::<Reminder, _>::new(runner.persistence().clone());
+/// let mut dispatch = |cmd| {
+///     commands.push(cmd);
+///     Ok::<_, &str>(())
+/// };
+/// assert_eq!(runner.poll(&store, "chat", now, &mut dispatch).unwrap(), 1);
+/// assert_eq!(runner.persistence().pending_timeouts(), 2);
+///
+/// // Timeouts become due at their deadlines exactly, in scheduling order,
+/// // and may reschedule themselves.
+/// let minute = Duration::from_secs(60);
+/// let almost = now + minute - Duration::from_millis(1);
+/// assert_eq!(runner.fire(almost, &mut dispatch).unwrap(), 0);
+/// assert_eq!(runner.fire(now + minute, &mut dispatch).unwrap(), 2);
+/// assert_eq!(runner.persistence().pending_timeouts(), 2);
+/// assert_eq!(runner.fire(now + minute * 2, &mut dispatch).unwrap(), 2);
+/// assert_eq!(runner.persistence().pending_timeouts(), 0);
+///
+/// let remind = |chat: &str| Command::Remind(chat.to_owned());
+/// let notify = |chat: &str| Command::Notify(chat.to_owned());
+/// assert_eq!(
+///     commands,
+///     [
+///         notify("1"),
+///         notify("2"),
+///         remind("1"),
+///         remind("2"),
+///         remind("1"),
+///         remind("2"),
+///     ],
+/// );
+/// ```
+#[derive(Debug)]
+pub struct Runner<G, P> {
+    /// [`Persistence`] of the [`Saga`].
+    persistence: P,
+
+    /// Type of the run [`Saga`].
+    _saga: PhantomData<fn() -> G>,
+}
+
+impl<G, P> Runner<G, P> {
+    /// Creates a new [`Runner`] backed by the provided [`Persistence`].
+    #[must_use]
+    pub const fn new(persistence: P) -> Self {
+        Self { persistence, _saga: PhantomData }
+    }
+
+    /// Returns the [`Persistence`] of this [`Runner`].
+    #[must_use]
+    pub const fn persistence(&self) -> &P {
+        &self.persistence
+    }
+}
+
+impl<G, P> Runner<G, P>
+where
+    G: Saga,
+    P: Persistence<G>,
+{
+    /// Feeds the [`Saga`] with the [`Event`]s of the provided [`Store`]
+    /// category, resuming from the [`Persistence::checkpoint()`] in the
+    /// global [`Log`], dispatching the issued commands, and returns the
+    /// number of the processed [`Event`]s.
+    ///
+    /// The checkpoint is shared by all the categories, so the same category
+    /// should be polled by a [`Runner`] every time.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Store`], the [`Persistence`] or the `dispatch`ing fails.
+    /// The [`Event`] being processed remains unprocessed in this case.
+    pub fn poll<St, D, E>(
+        &mut self,
+        store: &St,
+        category: &str,
+        now: SystemTime,
+        mut dispatch: D,
+    ) -> Result<usize, Error<St::Error, P::Error, E>>
+    where
+        St: Log<G::Event> + ?Sized,
+        D: FnMut(G::Command) -> Result<(), E>,
+    {
+        let mut next = self
+            .persistence
+            .checkpoint()
+            .map_err(Error::Persistence)?
+            .unwrap_or(Offset::START);
+        let mut processed = 0;
+        loop {
+            let batch = store
+                .read_log(next, subscription::DEFAULT_BATCH_SIZE)
+                .map_err(Error::Store)?;
+            if batch.is_empty() {
+                return Ok(processed);
+            }
+            for (offset, entry) in batch {
+                // Tombstones and markers of deleted `Stream`s are skipped.
+                let rec =
+                    entry.into_event().filter(|r| r.stream.is_in(category));
+                if let Some(Recorded { event, .. }) = rec {
+                    if let Some(key) = G::correlate(&event) {
+                        let mut saga = self.load(&key)?;
+                        saga.apply(&event);
+                        let mut effects = Effects::new(now);
+                        saga.react(&event, &mut effects);
+                        self.commit(&key, &saga, effects, &mut dispatch)?;
+                    }
+                    processed += 1;
+                }
+
+                // Saved after every `Event` (including the skipped ones), so
+                // no `Event` is applied to a saved `Saga` state twice.
+                next = offset.next();
+                self.persistence
+                    .save_checkpoint(next)
+                    .map_err(Error::Persistence)?;
+            }
+        }
+    }
+
+    /// Fires all the [`Saga`] timeouts due at the provided moment,
+    /// dispatching the issued commands, and returns the number of the fired
+    /// timeouts.
+    ///
+    /// A timeout is removed only once its commands are dispatched.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Persistence`] or the `dispatch`ing fails. The timeout being
+    /// fired remains due in this case.
+    pub fn fire<D, E>(
+        &mut self,
+        now: SystemTime,
+        mut dispatch: D,
+    ) -> Result<usize, Error<Infallible, P::Error, E>>
+    where
+        D: FnMut(G::Command) -> Result<(), E>,
+    {
+        let due = self.persistence.due(now).map_err(Error::Persistence)?;
+        let count = due.len();
+        for Timer { id, key, timeout } in due {
+            let saga = self.load(&key)?;
+            let mut effects = Effects::new(now);
+            saga.on_timeout(&timeout, &mut effects);
+            self.commit(&key, &saga, effects, &mut dispatch)?;
+            self.persistence.complete(id).map_err(Error::Persistence)?;
+        }
+        Ok(count)
+    }
+
+    /// Loads the state of the [`Saga`] instance with the provided [`Key`], or
+    /// a [`Default`] one.
+    fn load<S, E>(&self, key: &str) -> Result<G, Error<S, P::Error, E>> {
+        Ok(self
+            .persistence
+            .load(key)
+            .map_err(Error::Persistence)?
+            .unwrap_or_default())
+    }
+
+    /// Dispatches the issued commands, schedules the timeouts and saves the
+    /// state of the [`Saga`] instance with the provided [`Key`].
+    fn commit<S, D, E>(
+        &mut self,
+        key: &str,
+        saga: &G,
+        effects: Effects<G::Command, G::Timeout>,
+        dispatch: &mut D,
+    ) -> Result<(), Error<S, P::Error, E>>
+    where
+        D: FnMut(G::Command) -> Result<(), E>,
+    {
+        let Effects { commands, timeouts, .. } = effects;
+        for cmd in commands {
+            dispatch(cmd).map_err(Error::Dispatch)?;
+        }
+        for (deadline, timeout) in timeouts {
+            self.persistence
+                .schedule(key, deadline, timeout)
+                .map_err(Error::Persistence)?;
+        }
+        self.persistence.save(key, saga).map_err(Error::Persistence)
+    }
+}
+
+/// Error of a [`Runner`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error<S, P, D> {
+    /// [`Store`] failed.
+    Store(S),
+
+    /// [`Persistence`] failed.
+    Persistence(P),
+
+    /// Dispatching a command failed.
+    Dispatch(D),
+}
+
+impl<S, P, D> fmt::Display for Error<S, P, D>
+where
+    S: fmt::Display,
+    P: fmt::Display,
+    D: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Store(e) => write!(f, "store failed: {e}"),
+            Self::Persistence(e) => write!(f, "persistence failed: {e}"),
+            Self::Dispatch(e) => write!(f, "dispatching command failed: {e}"),
+        }
+    }
+}
+
+impl<S, P, D> StdError for Error<S, P, D>
+where
+    S: StdError + 'static,
+    P: StdError + 'static,
+    D: StdError + 'static,
+{
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Store(e) => Some(e),
+            Self::Persistence(e) => Some(e),
+            Self::Dispatch(e) => Some(e),
+        }
+    }
+}
@@ -0,0 +1,1327 @@
+//! Portable archives of [`Event`]s for moving histories between [`Store`]s.
+//!
+//! An archive is a newline-delimited JSON document consisting of:
+//! - a header, recording the version of the archive format and the
+//!   [`Catalog`] of the [`event::Name`]s and [`event::Version`]s of the
+//!   archived [`Event`]s;
+//! - an [`Entry`] per archived [`Event`] (or tombstone with the reserved
+//!   [`TOMBSTONE`] name, not required to be in the [`Catalog`]) in the order
+//!   they were appended, carrying its encoded payload (as a hexadecimal
+//!   string), [`Metadata`] and [CRC-32] checksum;
+//! - a marker of every truncated [`Stream`] preceding its first [`Entry`], so
+//!   the [`Stream`] is [`import()`]ed starting from the same position;
+//! - a trailer, recording the number of the [`Entry`]s and a checksum of all
+//!   of them, so a truncated archive is detected.
+//!
+//! [`export()`] streams all the [`Entry`]s of a [`Source`] into an archive via
+//! a [`Writer`], while [`import()`] streams the [`Entry`]s of an archive read
+//! via a [`Reader`] into a [`Target`]. [`Entry`]s already present in the
+//! [`Target`] (by their [`stream::Id`] and [`stream::Position`]) are skipped,
+//! so an interrupted [`import()`] may be simply re-run. However, an [`Entry`]
+//! differing from the one present at its position is rejected as
+//! [`Error::Diverged`], as its history doesn't match the [`Target`]'s one.
+//!
+//! > **NOTE**: [`import()`] is not atomic: [`Entry`]s are appended while the
+//! >           archive is being read, so the ones preceding an invalid
+//! >           [`Entry`] (or the end of a truncated archive) remain imported.
+//! >           [`verify()`] an archive beforehand, if it can be read twice.
+//!
+//! [`Stream`]: stream::Stream
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[cfg(feature = "file")]
+//! # {
+//! # use arcane::es::{
+//! #     Event,
+//! #     archive::{self, Catalog, Imported},
+//! #     event,
+//! #     store::{self, Store as _, file::Fsync},
+//! #     stream::{self, ExpectedVersion},
+//! # };
+//! # use serde::{Deserialize, Serialize};
+//! #
+//! #[derive(Debug, Deserialize, Event, PartialEq, Serialize)]
+//! #[event(name = "chat.created", revision = 1)]
+//! struct ChatCreated;
+//!
+//! #[derive(Debug, Deserialize, Event, PartialEq, Serialize)]
+//! #[event(name = "message.posted", revision = 2)]
+//! struct MessagePosted {
+//!     text: String,
+//! }
+//!
+//! #[derive(Debug, Deserialize, Event, PartialEq, Serialize)]
+//! #[event(revision)]
+//! enum ChatEvent {
+//!     Created(ChatCreated),
+//!     MessagePosted(MessagePosted),
+//! }
+//!
+//! let tmp = std::env::temp_dir()
+//!     .join(format!("arcane-archive-{}", std::process::id()));
+//! let open = |name: &str| {
+//!     store::file::Options::new()
+//!         .fsync(Fsync::Never)
+//!         .open::<ChatEvent>(tmp.join(name))
+//!         .unwrap()
+//! };
+//! let chat = stream::Id::new("chat", "1").unwrap();
+//!
+//! let mut source = open("source");
+//! let events = vec![
+//!     ChatEvent::Created(ChatCreated),
+//!     ChatEvent::MessagePosted(MessagePosted { text: "hi".into() }),
+//! ];
+//! let caused = store::Metadata::new().caused_by("create-chat");
+//! source
+//!     .append_with(&chat, ExpectedVersion::NoStream, events, &caused)
+//!     .unwrap();
+//!
+//! let mut bytes = Vec::new();
+//! let catalog = Catalog::of::<ChatEvent>();
+//! assert_eq!(archive::export(&source, catalog, &mut bytes).unwrap(), 2);
+//!
+//! let v2 = event::Version::try_new(2).unwrap();
+//! let reader = archive::Reader::new(bytes.as_slice()).unwrap();
+//! assert!(reader.catalog().contains("message.posted", v2));
+//! assert_eq!(reader.catalog().unsupported_by::<ChatEvent>().count(), 0);
+//!
+//! // Re-importing skips the already imported events.
+//! let mut target = open("target");
+//! let imported = archive::import(bytes.as_slice(), &mut target).unwrap();
+//! assert_eq!(imported, Imported { appended: 2, skipped: 0 });
+//! let imported = archive::import(bytes.as_slice(), &mut target).unwrap();
+//! assert_eq!(imported, Imported { appended: 0, skipped: 2 });
+//! assert_eq!(
+//!     target.read_stream(&chat).unwrap(),
+//!     source.read_stream(&chat).unwrap(),
+//! );
+//! let recorded = target.read_category("chat").unwrap();
+//! assert_eq!(recorded, source.read_category("chat").unwrap());
+//! assert_eq!(recorded[1].metadata, caused);
+//!
+//! // But diverged histories are rejected.
+//! let mut other = open("other");
+//! let events = vec![
+//!     ChatEvent::Created(ChatCreated),
+//!     ChatEvent::MessagePosted(MessagePosted { text: "ho".into() }),
+//! ];
+//! other
+//!     .append_with(&chat, ExpectedVersion::NoStream, events, &caused)
+//!     .unwrap();
+//! assert!(matches!(
+//!     archive::import(bytes.as_slice(), &mut other),
+//!     Err(archive::Error::Diverged { position, .. }) if position.get() == 2,
+//! ));
+//!
+//! // Corrupted and truncated archives are detected.
+//! let text = String::from_utf8(bytes).unwrap();
+//! let corrupted = text.replace("226869227d", "22686f227d"); // "hi" -> "ho"
+//! assert!(matches!(
+//!     archive::verify(corrupted.as_bytes()),
+//!     Err(archive::Error::Checksum { line: 3 }),
+//! ));
+//! let (truncated, _) = text.trim_end().rsplit_once('\n').unwrap();
+//! assert!(matches!(
+//!     archive::verify(truncated.as_bytes()),
+//!     Err(archive::Error::Truncated),
+//! ));
+//! assert_eq!(archive::verify(text.as_bytes()).unwrap(), 2);
+//! # std::fs::remove_dir_all(&tmp).unwrap();
+//! # }
+//! ```
+//!
+//! [CRC-32]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    convert::Infallible,
+    error::Error as StdError,
+    fmt,
+    io::{self, BufRead},
+    mem,
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "reflect")]
+use super::event::reflect;
+#[cfg(feature = "file")]
+use super::store::file;
+#[cfg(doc)]
+use super::{Event, codec::Codecs, store::Store};
+use super::{
+    event,
+    store::{AppendError, Conflict, Metadata, Reserved, TOMBSTONE, Tombstoned},
+    stream::{self, ExpectedVersion},
+};
+use crate::util::{hex, micros, unhex};
+
+/// Name of the archive format, recorded into the header of an archive.
+const FORMAT: &str = "arcane-archive";
+
+/// Version of the archive format written by a [`Writer`].
+///
+/// Archives of the previous versions (without [`Metadata`] of [`Entry`]s or
+/// markers of truncated [`Stream`]s) are read too.
+///
+/// [`Stream`]: stream::Stream
+pub const FORMAT_VERSION: u16 = 3;
+
+/// Maximum number of [`Entry`]s appended to a [`Target`] at once by
+/// [`import()`].
+const BATCH_SIZE: usize = 100;
+
+/// Archived [`Event`] along with its metadata.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    /// [`stream::Id`] of the [`Stream`] the [`Event`] was appended to.
+    ///
+    /// [`Stream`]: stream::Stream
+    pub stream: stream::Id,
+
+    /// [`stream::Position`] of the [`Event`] in its [`Stream`].
+    ///
+    /// [`Stream`]: stream::Stream
+    pub position: stream::Position,
+
+    /// [`event::Name`] of the [`Event`].
+    pub name: String,
+
+    /// [`event::Version`] of the [`Event`].
+    pub version: event::Version,
+
+    /// Time the [`Event`] was appended at.
+    ///
+    /// Archived with a microsecond precision.
+    pub timestamp: SystemTime,
+
+    /// [`Metadata`] the [`Event`] was appended with.
+    pub metadata: Metadata,
+
+    /// [`Event`] encoded with the [`Codecs`] of its [`Store`].
+    pub payload: Vec<u8>,
+}
+
+impl Entry {
+    /// Checks whether this [`Entry`] is a tombstone, or is contained in the
+    /// provided [`Catalog`].
+    fn is_catalogued_in(&self, catalog: &Catalog) -> bool {
+        self.name == TOMBSTONE || catalog.contains(&self.name, self.version)
+    }
+
+    /// Calculates the [CRC-32] checksum of this [`Entry`].
+    ///
+    /// [CRC-32]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+    fn checksum(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        for s in [&self.stream.to_string(), &self.name] {
+            hasher.update(&u64::try_from(s.len()).unwrap_or(0).to_le_bytes());
+            hasher.update(s.as_bytes());
+        }
+        hasher.update(&self.position.get().to_le_bytes());
+        hasher.update(&self.version.get().to_le_bytes());
+        hasher.update(&micros(self.timestamp).to_le_bytes());
+        hasher.update(&self.payload);
+        // Hashed only when present, so checksums of the `Entry`s without
+        // `Metadata` stay the same as in the previous format versions.
+        let metadata = [&self.metadata.causation, &self.metadata.correlation];
+        for (tag, id) in (0_u8..).zip(metadata) {
+            if let Some(id) = id {
+                hasher.update(&[tag]);
+                hasher.update(
+                    &u64::try_from(id.len()).unwrap_or(0).to_le_bytes(),
+                );
+                hasher.update(id.as_bytes());
+            }
+        }
+        hasher.finalize()
+    }
+}
+
+#[cfg(feature = "file")]
+impl From<file::Record> for Entry {
+    fn from(rec: file::Record) -> Self {
+        Self {
+            stream: rec.stream,
+            position: rec.position,
+            name: rec.name,
+            version: rec.version,
+            timestamp: rec.timestamp,
+            metadata: rec.metadata,
+            payload: rec.payload,
+        }
+    }
+}
+
+/// Catalog of the [`event::Name`]s and [`event::Version`]s of the [`Event`]s
+/// in an archive.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Catalog(BTreeSet<(String, event::Version)>);
+
+impl Catalog {
+    /// Creates a new empty [`Catalog`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(BTreeSet::new())
+    }
+
+    /// Creates a new [`Catalog`] of all the [`event::Name`]s and
+    /// [`event::Version`]s the provided [`Event`] is composed of.
+    #[cfg(feature = "reflect")]
+    #[must_use]
+    pub fn of<Ev>() -> Self
+    where
+        Ev: reflect::Concrete<Revision = event::Version> + ?Sized,
+    {
+        Ev::names_and_revisions_iter()
+            .map(|(name, version)| ((*name).to_owned(), *version))
+            .collect()
+    }
+
+    /// Adds the provided [`event::Name`] and [`event::Version`] to this
+    /// [`Catalog`].
+    ///
+    /// Returns `false` if this [`Catalog`] contains them already.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        version: event::Version,
+    ) -> bool {
+        self.0.insert((name.into(), version))
+    }
+
+    /// Checks whether this [`Catalog`] contains the provided [`event::Name`]
+    /// and [`event::Version`].
+    #[must_use]
+    pub fn contains(&self, name: &str, version: event::Version) -> bool {
+        self.iter().any(|(n, v)| n == name && v == version)
+    }
+
+    /// Returns an [`Iterator`] over all the [`event::Name`]s and
+    /// [`event::Version`]s of this [`Catalog`] in their order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, event::Version)> {
+        self.0.iter().map(|(n, v)| (n.as_str(), *v))
+    }
+
+    /// Returns the number of pairs of [`event::Name`]s and [`event::Version`]s
+    /// in this [`Catalog`].
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Indicates whether this [`Catalog`] is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an [`Iterator`] over all the [`event::Name`]s and
+    /// [`event::Version`]s of this [`Catalog`] the provided [`Event`] is
+    /// unable to deserialize.
+    ///
+    /// Allows to check whether an archive is compatible with the [`Event`]s
+    /// of a [`Target`] before [`import()`]ing it.
+    #[cfg(feature = "reflect")]
+    pub fn unsupported_by<Ev>(
+        &self,
+    ) -> impl Iterator<Item = (&str, event::Version)>
+    where
+        Ev: reflect::Compatible + ?Sized,
+    {
+        self.iter().filter(|(name, version)| {
+            *name != TOMBSTONE && !Ev::supports(name, *version)
+        })
+    }
+}
+
+impl FromIterator<(String, event::Version)> for Catalog {
+    fn from_iter<I: IntoIterator<Item = (String, event::Version)>>(
+        iter: I,
+    ) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Extend<(String, event::Version)> for Catalog {
+    fn extend<I: IntoIterator<Item = (String, event::Version)>>(
+        &mut self,
+        iter: I,
+    ) {
+        self.0.extend(iter);
+    }
+}
+
+/// Line of an archive.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Line {
+    /// Header of an archive.
+    Header {
+        /// Name of the archive format.
+        format: String,
+
+        /// Version of the archive format.
+        version: u16,
+
+        /// [`Catalog`] of the archived [`Event`]s.
+        catalog: Vec<CatalogItem>,
+    },
+
+    /// Archived [`Event`].
+    Event {
+        /// [`Entry::stream`].
+        stream: stream::Id,
+
+        /// [`Entry::position`].
+        position: stream::Position,
+
+        /// [`Entry::name`].
+        name: String,
+
+        /// [`Entry::version`].
+        revision: event::Version,
+
+        /// [`Entry::timestamp`] as an [RFC 3339] UTC timestamp.
+        ///
+        /// [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339
+        timestamp: String,
+
+        /// [`Entry::payload`] as a hexadecimal string.
+        payload: String,
+
+        /// [`Metadata::causation`] of the [`Entry::metadata`].
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        causation: Option<String>,
+
+        /// [`Metadata::correlation`] of the [`Entry::metadata`].
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        correlation: Option<String>,
+
+        /// [CRC-32] checksum of the [`Entry`].
+        ///
+        /// [CRC-32]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+        checksum: u32,
+    },
+
+    /// Marker of a truncated [`Stream`], preceding its first archived
+    /// [`Entry`].
+    ///
+    /// [`Stream`]: stream::Stream
+    Truncated {
+        /// [`stream::Id`] of the [`Stream`].
+        ///
+        /// [`Stream`]: stream::Stream
+        stream: stream::Id,
+
+        /// [`stream::Position`] of the first archived [`Entry`] of the
+        /// [`Stream`], as the preceding ones are truncated.
+        ///
+        /// [`Stream`]: stream::Stream
+        position: stream::Position,
+
+        /// [CRC-32] checksum of this marker.
+        ///
+        /// [CRC-32]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+        checksum: u32,
+    },
+
+    /// Trailer of an archive.
+    End {
+        /// Number of the archived [`Event`]s.
+        count: u64,
+
+        /// [CRC-32] checksum of the checksums of all the archived [`Entry`]s
+        /// and markers.
+        ///
+        /// [CRC-32]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+        checksum: u32,
+    },
+}
+
+/// Item of a [`Catalog`] in the header of an archive.
+#[derive(Debug, Deserialize, Serialize)]
+struct CatalogItem {
+    /// [`event::Name`] of the archived [`Event`]s.
+    name: String,
+
+    /// [`event::Version`] of the archived [`Event`]s.
+    revision: event::Version,
+}
+
+/// Streaming writer of an archive.
+#[derive(Debug)]
+pub struct Writer<W> {
+    /// Output the archive is written into.
+    out: W,
+
+    /// [`Catalog`] of the [`Entry`]s allowed to be written.
+    catalog: Catalog,
+
+    /// Number of the written [`Entry`]s.
+    count: u64,
+
+    /// Checksum of the checksums of the written [`Entry`]s and markers.
+    checksum: crc32fast::Hasher,
+
+    /// [`stream::Id`]s of the [`Stream`]s with the written [`Entry`]s.
+    ///
+    /// [`Stream`]: stream::Stream
+    streams: HashSet<stream::Id>,
+}
+
+impl<W: io::Write> Writer<W> {
+    /// Creates a new [`Writer`] of an archive of the [`Entry`]s from the
+    /// provided [`Catalog`] into the provided `out`put, and writes its header.
+    ///
+    /// # Errors
+    ///
+    /// If the header cannot be written.
+    pub fn new(out: W, catalog: Catalog) -> Result<Self, Error> {
+        let mut this = Self {
+            out,
+            catalog,
+            count: 0,
+            checksum: crc32fast::Hasher::new(),
+            streams: HashSet::new(),
+        };
+        let header = Line::Header {
+            format: FORMAT.to_owned(),
+            version: FORMAT_VERSION,
+            catalog: this
+                .catalog
+                .iter()
+                .map(|(name, revision)| CatalogItem {
+                    name: name.to_owned(),
+                    revision,
+                })
+                .collect(),
+        };
+        this.line(&header)?;
+        Ok(this)
+    }
+
+    /// Writes the provided [`Entry`] into the archive.
+    ///
+    /// If it's the first [`Entry`] of its [`Stream`], but not at the
+    /// [`stream::Position::FIRST`], the [`Stream`] is marked as truncated
+    /// before it.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Uncatalogued`] if the [`Entry`] is missing in the [`Catalog`]
+    ///   of this [`Writer`].
+    /// - [`Error::Io`] if the [`Entry`] cannot be written.
+    ///
+    /// [`Stream`]: stream::Stream
+    pub fn write(&mut self, entry: &Entry) -> Result<(), Error> {
+        if !entry.is_catalogued_in(&self.catalog) {
+            return Err(Error::Uncatalogued {
+                name: entry.name.clone(),
+                version: entry.version,
+            });
+        }
+        if !self.streams.contains(&entry.stream) {
+            if entry.position > stream::Position::FIRST {
+                let checksum = marker_checksum(&entry.stream, entry.position);
+                self.line(&Line::Truncated {
+                    stream: entry.stream.clone(),
+                    position: entry.position,
+                    checksum,
+                })?;
+                self.checksum.update(&checksum.to_le_bytes());
+            }
+            _ = self.streams.insert(entry.stream.clone());
+        }
+        let checksum = entry.checksum();
+        self.line(&Line::Event {
+            stream: entry.stream.clone(),
+            position: entry.position,
+            name: entry.name.clone(),
+            revision: entry.version,
+            timestamp: humantime::format_rfc3339_micros(entry.timestamp)
+                .to_string(),
+            payload: hex(&entry.payload),
+            causation: entry.metadata.causation.clone(),
+            correlation: entry.metadata.correlation.clone(),
+            checksum,
+        })?;
+        self.count += 1;
+        self.checksum.update(&checksum.to_le_bytes());
+        Ok(())
+    }
+
+    /// Returns the number of the [`Entry`]s written so far.
+    #[must_use]
+    pub const fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Writes the trailer of the archive and flushes it, returning the
+    /// `out`put of this [`Writer`].
+    ///
+    /// > **NOTE**: Archives not [`Writer::finish()`]ed are considered
+    /// >           truncated by a [`Reader`].
+    ///
+    /// # Errors
+    ///
+    /// If the trailer cannot be written or flushed.
+    pub fn finish(mut self) -> Result<W, Error> {
+        let trailer = Line::End {
+            count: self.count,
+            checksum: self.checksum.clone().finalize(),
+        };
+        self.line(&trailer)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    /// Writes the provided [`Line`] into the archive.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Line`] cannot be written.
+    fn line(&mut self, line: &Line) -> Result<(), Error> {
+        serde_json::to_writer(&mut self.out, line).map_err(io::Error::from)?;
+        self.out.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+/// Streaming reader of an archive, verifying the checksums of its [`Entry`]s
+/// while reading them.
+#[derive(Debug)]
+pub struct Reader<R> {
+    /// Input the archive is read from.
+    input: R,
+
+    /// [`Catalog`] from the header of the archive.
+    catalog: Catalog,
+
+    /// Number of the read lines.
+    line: u64,
+
+    /// Number of the read [`Entry`]s.
+    count: u64,
+
+    /// Checksum of the checksums of the read [`Entry`]s and markers.
+    checksum: crc32fast::Hasher,
+
+    /// [`stream::Position`]s of the first [`Entry`]s of the truncated
+    /// [`Stream`]s marked so far.
+    ///
+    /// [`Stream`]: stream::Stream
+    truncated: HashMap<stream::Id, stream::Position>,
+
+    /// Indicator whether the archive is read till its end, or has failed to be
+    /// read.
+    done: bool,
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Creates a new [`Reader`] of an archive from the provided `input`, and
+    /// reads its header.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Malformed`] if the header is invalid, or the version of the
+    ///   archive format is not supported.
+    /// - [`Error::Truncated`] if the `input` is empty.
+    /// - [`Error::Io`] if the header cannot be read.
+    pub fn new(input: R) -> Result<Self, Error> {
+        let mut this = Self {
+            input,
+            catalog: Catalog::new(),
+            line: 0,
+            count: 0,
+            checksum: crc32fast::Hasher::new(),
+            truncated: HashMap::new(),
+            done: false,
+        };
+        match this.line()?.ok_or(Error::Truncated)? {
+            Line::Header { format, version, catalog } if format == FORMAT => {
+                if !(1..=FORMAT_VERSION).contains(&version) {
+                    return Err(this.malformed(format!(
+                        "unsupported archive format version `{version}`",
+                    )));
+                }
+                this.catalog =
+                    catalog.into_iter().map(|i| (i.name, i.revision)).collect();
+                Ok(this)
+            }
+            Line::Header { .. }
+            | Line::Event { .. }
+            | Line::Truncated { .. }
+            | Line::End { .. } => Err(this.malformed("missing archive header")),
+        }
+    }
+
+    /// Returns the [`Catalog`] from the header of the archive.
+    #[must_use]
+    pub const fn catalog(&self) -> &Catalog {
+        &self.catalog
+    }
+
+    /// Returns the [`stream::Position`] of the first archived [`Entry`] of
+    /// the [`Stream`] with the provided [`stream::Id`], if the [`Stream`] is
+    /// marked as truncated in the archive read so far.
+    ///
+    /// [`Stream`]: stream::Stream
+    #[must_use]
+    pub fn truncated(&self, stream: &stream::Id) -> Option<stream::Position> {
+        self.truncated.get(stream).copied()
+    }
+
+    /// Reads the next non-empty [`Line`] of the archive.
+    ///
+    /// Returns [`None`] if the archive has ended.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Malformed`] if the [`Line`] is invalid.
+    /// - [`Error::Io`] if the [`Line`] cannot be read.
+    fn line(&mut self) -> Result<Option<Line>, Error> {
+        let mut buf = String::new();
+        loop {
+            buf.clear();
+            if self.input.read_line(&mut buf)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+            if !buf.trim().is_empty() {
+                break;
+            }
+        }
+        serde_json::from_str(&buf).map(Some).map_err(|e| self.malformed(e))
+    }
+
+    /// Reads the next [`Entry`] of the archive (along with the markers
+    /// preceding it), verifying its checksum.
+    ///
+    /// Returns [`None`] if the trailer of the archive is reached.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Checksum`] if the [`Entry`], a marker or the trailer doesn't
+    ///   match its checksum.
+    /// - [`Error::Uncatalogued`] if the [`Entry`] is missing in the
+    ///   [`Catalog`].
+    /// - [`Error::Malformed`] if the [`Entry`] is invalid, or anything
+    ///   follows the trailer.
+    /// - [`Error::Truncated`] if the archive ends without the trailer.
+    /// - [`Error::Io`] if the [`Entry`] cannot be read.
+    fn entry(&mut self) -> Result<Option<Entry>, Error> {
+        let mut line = self.line()?.ok_or(Error::Truncated)?;
+        while let Line::Truncated { stream, position, checksum } = line {
+            if marker_checksum(&stream, position) != checksum {
+                return Err(Error::Checksum { line: self.line });
+            }
+            _ = self.truncated.insert(stream, position);
+            self.checksum.update(&checksum.to_le_bytes());
+            line = self.line()?.ok_or(Error::Truncated)?;
+        }
+        match line {
+            Line::Event {
+                stream,
+                position,
+                name,
+                revision,
+                timestamp,
+                payload,
+                causation,
+                correlation,
+                checksum,
+            } => {
+                let timestamp = humantime::parse_rfc3339(&timestamp)
+                    .map_err(|e| self.malformed(e))?;
+                let payload = unhex(&payload).ok_or_else(|| {
+                    self.malformed("payload is not a hexadecimal string")
+                })?;
+                let entry = Entry {
+                    stream,
+                    position,
+                    name,
+                    version: revision,
+                    timestamp,
+                    metadata: Metadata { causation, correlation },
+                    payload,
+                };
+                if entry.checksum() != checksum {
+                    return Err(Error::Checksum { line: self.line });
+                }
+                if !entry.is_catalogued_in(&self.catalog) {
+                    return Err(Error::Uncatalogued {
+                        name: entry.name,
+                        version: entry.version,
+                    });
+                }
+                self.count += 1;
+                self.checksum.update(&checksum.to_le_bytes());
+                Ok(Some(entry))
+            }
+            Line::End { count, checksum } => {
+                if count != self.count
+                    || checksum != mem::take(&mut self.checksum).finalize()
+                {
+                    return Err(Error::Checksum { line: self.line });
+                }
+                if self.line()?.is_some() {
+                    return Err(self.malformed("data after archive trailer"));
+                }
+                Ok(None)
+            }
+            Line::Header { .. } => Err(self.malformed("duplicate header")),
+            // Markers are read above already.
+            Line::Truncated { .. } => Err(self.malformed("unexpected marker")),
+        }
+    }
+
+    /// Creates an [`Error::Malformed`] at the current line.
+    fn malformed(&self, reason: impl fmt::Display) -> Error {
+        Error::Malformed { line: self.line, reason: reason.to_string() }
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = Result<Entry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let entry = self.entry().transpose();
+        self.done = !matches!(entry, Some(Ok(_)));
+        entry
+    }
+}
+
+/// [`Store`] which [`Entry`]s can be [`export()`]ed.
+pub trait Source {
+    /// Error of this [`Source`].
+    type Error;
+
+    /// Lazily reads all the [`Entry`]s of this [`Source`] in the order they
+    /// were appended.
+    fn entries(&self) -> impl Iterator<Item = Result<Entry, Self::Error>>;
+}
+
+/// [`Store`] which [`Entry`]s can be [`import()`]ed into.
+pub trait Target {
+    /// Error of this [`Target`].
+    type Error;
+
+    /// Returns the current version of the [`Stream`] with the provided
+    /// [`stream::Id`], or [`None`] if it's empty.
+    ///
+    /// # Errors
+    ///
+    /// If the version cannot be read.
+    ///
+    /// [`Stream`]: stream::Stream
+    fn stream_version(
+        &self,
+        stream: &stream::Id,
+    ) -> Result<Option<stream::Position>, Self::Error>;
+
+    /// Returns the [`Entry`] at the provided [`stream::Position`] of the
+    /// [`Stream`] with the provided [`stream::Id`], or [`None`] if there is
+    /// no such [`Entry`] (or it's truncated).
+    ///
+    /// # Errors
+    ///
+    /// If the [`Entry`] cannot be read.
+    ///
+    /// [`Stream`]: stream::Stream
+    fn stream_entry(
+        &self,
+        stream: &stream::Id,
+        position: stream::Position,
+    ) -> Result<Option<Entry>, Self::Error>;
+
+    /// Appends the provided [`Entry`]s as is to the end of the [`Stream`] with
+    /// the provided [`stream::Id`], preserving their timestamps and
+    /// [`Metadata`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Store::append()`].
+    ///
+    /// [`Stream`]: stream::Stream
+    fn append_entries(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+        entries: Vec<Entry>,
+    ) -> Result<Option<stream::Position>, AppendError<Self::Error>>;
+
+    /// Appends the provided [`Entry`]s as is to the empty [`Stream`] with the
+    /// provided [`stream::Id`], starting it from the [`stream::Position`] of
+    /// the first [`Entry`] as if the preceding ones were truncated.
+    ///
+    /// # Errors
+    ///
+    /// - [`AppendError::Conflict`] if the [`Stream`] is not empty.
+    /// - Same as [`Target::append_entries()`] otherwise.
+    ///
+    /// [`Stream`]: stream::Stream
+    fn append_truncated(
+        &mut self,
+        stream: &stream::Id,
+        entries: Vec<Entry>,
+    ) -> Result<Option<stream::Position>, AppendError<Self::Error>>;
+}
+
+#[cfg(feature = "file")]
+impl<Ev> Source for file::File<Ev> {
+    type Error = file::Error;
+
+    fn entries(&self) -> impl Iterator<Item = Result<Entry, Self::Error>> {
+        self.log().map(|rec| rec.map(Entry::from))
+    }
+}
+
+#[cfg(feature = "file")]
+impl<Ev> Target for file::File<Ev> {
+    type Error = file::Error;
+
+    fn stream_version(
+        &self,
+        stream: &stream::Id,
+    ) -> Result<Option<stream::Position>, Self::Error> {
+        Ok(self.version(stream))
+    }
+
+    fn stream_entry(
+        &self,
+        stream: &stream::Id,
+        position: stream::Position,
+    ) -> Result<Option<Entry>, Self::Error> {
+        Ok(self.record(stream, position)?.map(Entry::from))
+    }
+
+    fn append_entries(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+        entries: Vec<Entry>,
+    ) -> Result<Option<stream::Position>, AppendError<Self::Error>> {
+        self.restore_encoded(stream, expected, encoded(entries))
+    }
+
+    fn append_truncated(
+        &mut self,
+        stream: &stream::Id,
+        entries: Vec<Entry>,
+    ) -> Result<Option<stream::Position>, AppendError<Self::Error>> {
+        let Some(first) = entries.first().map(|e| e.position) else {
+            return self.append_entries(stream, ExpectedVersion::Any, entries);
+        };
+        self.restore_encoded_from(stream, first, encoded(entries))
+    }
+}
+
+/// Converts the provided [`Entry`]s into [`file::Encoded`] [`Event`]s as is,
+/// preserving their timestamps and [`Metadata`].
+#[cfg(feature = "file")]
+fn encoded(entries: Vec<Entry>) -> Vec<file::Encoded> {
+    entries
+        .into_iter()
+        .map(|e| file::Encoded {
+            name: e.name,
+            version: e.version,
+            payload: e.payload,
+            timestamp: Some(e.timestamp),
+            metadata: e.metadata,
+        })
+        .collect()
+}
+
+/// Exports all the [`Entry`]s of the provided [`Source`] into an archive
+/// written into the provided `out`put, returning their number.
+///
+/// # Errors
+///
+/// - [`Error::Uncatalogued`] if any [`Entry`] is missing in the provided
+///   [`Catalog`].
+/// - [`Error::Store`] if the [`Source`] fails to be read.
+/// - [`Error::Io`] if the archive cannot be written.
+pub fn export<S: Source + ?Sized>(
+    source: &S,
+    catalog: Catalog,
+    out: impl io::Write,
+) -> Result<u64, Error<S::Error>> {
+    let mut writer = Writer::new(out, catalog).map_err(Error::widen)?;
+    for entry in source.entries() {
+        writer.write(&entry.map_err(Error::Store)?).map_err(Error::widen)?;
+    }
+    let count = writer.count();
+    drop(writer.finish().map_err(Error::widen)?);
+    Ok(count)
+}
+
+/// Result of [`import()`]ing an archive.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Imported {
+    /// Number of the [`Entry`]s appended to the [`Target`].
+    pub appended: u64,
+
+    /// Number of the [`Entry`]s skipped as already present in the [`Target`].
+    pub skipped: u64,
+}
+
+/// Imports all the [`Entry`]s of the archive read from the provided `input`
+/// into the provided [`Target`], skipping the ones already present in it.
+///
+/// [`Entry`]s are imported while being read, so the ones preceding an invalid
+/// [`Entry`] (or the end of a truncated archive) are imported anyway, making
+/// the import partial. Use [`verify()`] to check the whole archive beforehand.
+///
+/// # Errors
+///
+/// - [`Error::Diverged`] if an [`Entry`] differs from the one present at its
+///   position in the [`Target`].
+/// - [`Error::Conflict`] if an [`Entry`] doesn't follow the current version of
+///   its [`Stream`] in the [`Target`].
+/// - [`Error::Tombstoned`] if an [`Entry`] follows the tombstone of its
+///   [`Stream`] in the [`Target`].
+/// - [`Error::Reserved`] if the [`Target`] rejects an [`Entry`] with the
+///   reserved [`TOMBSTONE`] name.
+/// - [`Error::Store`] if the [`Target`] fails.
+/// - Same as [`Reader`] otherwise.
+///
+/// # Example
+///
+/// Truncated and tombstoned [`Stream`]s are imported as they are.
+///
+/// ```rust
+/// # #[cfg(feature = "file")]
+/// # {
+/// # use arcane::es::{
+/// #     Event,
+/// #     archive::{self, Catalog, Imported},
+/// #     store::{self, AppendError, Lifecycle as _, Store as _, file::Fsync},
+/// #     stream::{self, ExpectedVersion},
+/// # };
+/// # use serde::{Deserialize, Serialize};
+/// #
+/// #[derive(Clone, Debug, Deserialize, Event, PartialEq, Serialize)]
+/// #[event(name = "message.posted", revision = 1)]
+/// struct MessagePosted;
+///
+/// let tmp = std::env::temp_dir()
+///     .join(format!("arcane-archive-import-{}", std::process::id()));
+/// let open = |name: &str| {
+///     store::file::Options::new()
+///         .fsync(Fsync::Never)
+///         .open::<MessagePosted>(tmp.join(name))
+///         .unwrap()
+/// };
+/// let (chat1, chat2) = (
+///     stream::Id::new("chat", "1").unwrap(),
+///     stream::Id::new("chat", "2").unwrap(),
+/// );
+///
+/// let mut source = open("source");
+/// for chat in [&chat1, &chat2] {
+///     let events = vec![MessagePosted; 3];
+///     source.append(chat, ExpectedVersion::NoStream, events).unwrap();
+/// }
+/// let third = stream::Position::new(3).unwrap();
+/// assert_eq!(source.truncate(&chat1, third).unwrap(), 2);
+/// let tombstone = source.tombstone(&chat2, ExpectedVersion::Any).unwrap();
+///
+/// let mut bytes = Vec::new();
+/// let catalog = Catalog::of::<MessagePosted>();
+/// assert_eq!(archive::export(&source, catalog, &mut bytes).unwrap(), 5);
+///
+/// let mut target = open("target");
+/// let imported = archive::import(bytes.as_slice(), &mut target).unwrap();
+/// assert_eq!(imported, Imported { appended: 5, skipped: 0 });
+/// let imported = archive::import(bytes.as_slice(), &mut target).unwrap();
+/// assert_eq!(imported, Imported { appended: 0, skipped: 5 });
+///
+/// let chat = target.read_stream(&chat1).unwrap();
+/// assert!(chat.is_truncated());
+/// assert_eq!((chat.first(), chat.version()), (Some(third), Some(third)));
+/// let chat = target.read_stream(&chat2).unwrap();
+/// assert_eq!((chat.len(), chat.tombstone()), (3, Some(tombstone)));
+/// let err = target.append(&chat2, ExpectedVersion::Any, vec![MessagePosted]);
+/// assert!(matches!(err, Err(AppendError::Tombstoned(_))));
+///
+/// // Truncated streams are not imported into non-empty ones.
+/// let mut other = open("other");
+/// let events = vec![MessagePosted];
+/// other.append(&chat1, ExpectedVersion::NoStream, events).unwrap();
+/// assert!(matches!(
+///     archive::import(bytes.as_slice(), &mut other),
+///     Err(archive::Error::Conflict(_)),
+/// ));
+/// # std::fs::remove_dir_all(&tmp).unwrap();
+/// # }
+/// ```
+///
+/// [`Stream`]: stream::Stream
+pub fn import<T: Target + ?Sized>(
+    input: impl BufRead,
+    target: &mut T,
+) -> Result<Imported, Error<T::Error>> {
+    let mut imported = Imported::default();
+    let mut batch = Vec::<Entry>::new();
+    // Indicator whether the `batch` starts a truncated `Stream`.
+    let mut starts = false;
+    let mut reader = Reader::new(input).map_err(Error::widen)?;
+    while let Some(entry) = reader.next() {
+        let entry = entry.map_err(Error::widen)?;
+        let continues = batch.len() < BATCH_SIZE
+            && batch.last().is_some_and(|last| {
+                last.stream == entry.stream
+                    && stream::Position::following(Some(last.position))
+                        == entry.position
+            });
+        if !continues {
+            imported.appended += append(target, mem::take(&mut batch), starts)?;
+            let version =
+                target.stream_version(&entry.stream).map_err(Error::Store)?;
+            starts = version.is_none()
+                && reader.truncated(&entry.stream) == Some(entry.position);
+            if version.is_some_and(|v| entry.position <= v) {
+                let present = target
+                    .stream_entry(&entry.stream, entry.position)
+                    .map_err(Error::Store)?;
+                // Truncated entries cannot be compared anymore.
+                if present.is_some_and(|p| {
+                    (&p.name, p.version, &p.metadata, &p.payload)
+                        != (
+                            &entry.name,
+                            entry.version,
+                            &entry.metadata,
+                            &entry.payload,
+                        )
+                }) {
+                    return Err(Error::Diverged {
+                        stream: entry.stream,
+                        position: entry.position,
+                    });
+                }
+                imported.skipped += 1;
+                continue;
+            }
+        }
+        batch.push(entry);
+    }
+    imported.appended += append(target, batch, starts)?;
+    Ok(imported)
+}
+
+/// Appends the provided batch of sequential [`Entry`]s of a single [`Stream`]
+/// to the provided [`Target`], returning their number.
+///
+/// If the batch `starts` a truncated [`Stream`], it's appended to the empty
+/// [`Stream`] via [`Target::append_truncated()`].
+///
+/// # Errors
+///
+/// - [`Error::Conflict`] if the batch doesn't follow the current version of its
+///   [`Stream`].
+/// - [`Error::Tombstoned`] if the [`Stream`] is tombstoned.
+/// - [`Error::Reserved`] if the [`Target`] rejects a tombstone in the batch.
+/// - [`Error::Store`] if the [`Target`] fails.
+///
+/// [`Stream`]: stream::Stream
+fn append<T: Target + ?Sized>(
+    target: &mut T,
+    batch: Vec<Entry>,
+    starts: bool,
+) -> Result<u64, Error<T::Error>> {
+    let Some(first) = batch.first() else {
+        return Ok(0);
+    };
+    let stream = first.stream.clone();
+    let expected = ExpectedVersion::from(stream::Position::new(
+        first.position.get().saturating_sub(1),
+    ));
+    let count = u64::try_from(batch.len()).unwrap_or(u64::MAX);
+    let appended = if starts {
+        target.append_truncated(&stream, batch)
+    } else {
+        target.append_entries(&stream, expected, batch)
+    };
+    match appended {
+        Ok(_) => Ok(count),
+        Err(AppendError::Conflict(c)) => Err(Error::Conflict(c)),
+        Err(AppendError::Tombstoned(t)) => Err(Error::Tombstoned(t)),
+        Err(AppendError::Reserved(r)) => Err(Error::Reserved(r)),
+        Err(AppendError::Store(e)) => Err(Error::Store(e)),
+    }
+}
+
+/// Verifies the checksums of all the [`Entry`]s of the archive read from the
+/// provided `input`, returning their number.
+///
+/// # Errors
+///
+/// Same as [`Reader`].
+pub fn verify(input: impl BufRead) -> Result<u64, Error> {
+    let mut reader = Reader::new(input)?;
+    for entry in &mut reader {
+        drop(entry?);
+    }
+    Ok(reader.count)
+}
+
+/// Error of reading, writing, [`export()`]ing or [`import()`]ing an archive.
+#[derive(Debug)]
+pub enum Error<E = Infallible> {
+    /// I/O operation failed.
+    Io(io::Error),
+
+    /// Line of the archive is malformed.
+    Malformed {
+        /// Number of the line, starting from `1`.
+        line: u64,
+
+        /// Reason of the line being malformed.
+        reason: String,
+    },
+
+    /// [`Entry`] or the whole archive doesn't match its checksum.
+    Checksum {
+        /// Number of the line of the [`Entry`] or the trailer, starting from
+        /// `1`.
+        line: u64,
+    },
+
+    /// [`Entry`] is missing in the [`Catalog`] of the archive.
+    Uncatalogued {
+        /// [`Entry::name`].
+        name: String,
+
+        /// [`Entry::version`].
+        version: event::Version,
+    },
+
+    /// Archive ends without its trailer.
+    Truncated,
+
+    /// [`Entry`] differs from the one present at its position in a
+    /// [`Target`].
+    Diverged {
+        /// [`Entry::stream`].
+        stream: stream::Id,
+
+        /// [`Entry::position`].
+        position: stream::Position,
+    },
+
+    /// [`Entry`] conflicts with the current version of its [`Stream`] in a
+    /// [`Target`].
+    ///
+    /// [`Stream`]: stream::Stream
+    Conflict(Conflict),
+
+    /// [`Entry`] follows the tombstone of its [`Stream`] in a [`Target`].
+    ///
+    /// [`Stream`]: stream::Stream
+    Tombstoned(Tombstoned),
+
+    /// [`Target`] rejects an [`Entry`] with the reserved [`TOMBSTONE`] name.
+    Reserved(Reserved),
+
+    /// [`Source`] or [`Target`] failed.
+    Store(E),
+}
+
+impl Error {
+    /// Converts this [`Error`] into the one of a [`Source`] or a [`Target`].
+    fn widen<E>(self) -> Error<E> {
+        match self {
+            Self::Io(e) => Error::Io(e),
+            Self::Malformed { line, reason } => {
+                Error::Malformed { line, reason }
+            }
+            Self::Checksum { line } => Error::Checksum { line },
+            Self::Uncatalogued { name, version } => {
+                Error::Uncatalogued { name, version }
+            }
+            Self::Truncated => Error::Truncated,
+            Self::Diverged { stream, position } => {
+                Error::Diverged { stream, position }
+            }
+            Self::Conflict(c) => Error::Conflict(c),
+            Self::Tombstoned(t) => Error::Tombstoned(t),
+            Self::Reserved(r) => Error::Reserved(r),
+            Self::Store(e) => match e {},
+        }
+    }
+}
+
+impl<E> From<io::Error> for Error<E> {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O operation failed: {e}"),
+            Self::Malformed { line, reason } => {
+                write!(f, "malformed archive at line `{line}`: {reason}")
+            }
+            Self::Checksum { line } => {
+                write!(f, "checksum mismatch at line `{line}` of archive")
+            }
+            Self::Uncatalogued { name, version } => write!(
+                f,
+                "event `{name}` of revision `{version}` is missing in archive \
+                 catalog",
+            ),
+            Self::Truncated => write!(f, "archive is truncated"),
+            Self::Diverged { stream, position } => write!(
+                f,
+                "event at position `{position}` of stream `{stream}` differs \
+                 from the archived one",
+            ),
+            Self::Conflict(c) => write!(f, "{c}"),
+            Self::Tombstoned(t) => write!(f, "{t}"),
+            Self::Reserved(r) => write!(f, "{r}"),
+            Self::Store(e) => write!(f, "store failed: {e}"),
+        }
+    }
+}
+
+impl<E: StdError + 'static> StdError for Error<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Conflict(c) => Some(c),
+            Self::Tombstoned(t) => Some(t),
+            Self::Reserved(r) => Some(r),
+            Self::Store(e) => Some(e),
+            Self::Malformed { .. }
+            | Self::Checksum { .. }
+            | Self::Uncatalogued { .. }
+            | Self::Truncated
+            | Self::Diverged { .. } => None,
+        }
+    }
+}
+
+/// Calculates the [CRC-32] checksum of the marker of the truncated [`Stream`]
+/// with the provided [`stream::Id`], which first archived [`Entry`] is at the
+/// provided [`stream::Position`].
+///
+/// [CRC-32]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+/// [`Stream`]: stream::Stream
+fn marker_checksum(stream: &stream::Id, position: stream::Position) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    let stream = stream.to_string();
+    hasher.update(&u64::try_from(stream.len()).unwrap_or(0).to_le_bytes());
+    hasher.update(stream.as_bytes());
+    hasher.update(&position.get().to_le_bytes());
+    hasher.finalize()
+}
@@ -0,0 +1,854 @@
+//! [CloudEvents 1.0] envelopes of [`Event`]s for interoperating with external
+//! systems.
+//!
+//! [`Event`]s are mapped onto [`CloudEvent`] attributes as follows:
+//! - [`Event::name()`] becomes the `type` attribute;
+//! - [`Revisable::revision()`] becomes the `revision` extension attribute
+//!   (see [`REVISION`]);
+//! - [`stream::Id`] becomes the `subject` attribute;
+//! - [`Metadata`] provides the `id`, `source` and `time` attributes, along
+//!   with the `causationid` and `correlationid` extension attributes (see
+//!   [`CAUSATION`] and [`CORRELATION`]) from the [`store::Metadata`] the
+//!   [`Event`] was recorded with, if any;
+//! - the concrete [`Event`] itself (not the enum wrapping it) is serialized as
+//!   JSON into the `data` attribute via [`Encode`].
+//!
+//! Both structured (see [`CloudEvent::to_structured()`]) and binary (see
+//! [`CloudEvent::to_binary()`]) content modes are supported, preserving any
+//! [`CloudEvent::extensions`]. Decoding back into [`Event`]s is done via
+//! [`CloudEvent::decode()`] for [`Decode`] implementers.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use std::time::{Duration, SystemTime};
+//! #
+//! # use arcane::es::{
+//! #     Event,
+//! #     cloudevents::{CloudEvent, Metadata},
+//! #     store, stream,
+//! # };
+//! # use serde::{Deserialize, Serialize};
+//! #
+//! #[derive(Debug, Deserialize, Event, PartialEq, Serialize)]
+//! #[event(name = "chat.created", revision = 1)]
+//! struct ChatCreated;
+//!
+//! #[derive(Debug, Deserialize, Event, PartialEq, Serialize)]
+//! #[event(name = "message.posted", revision = 2)]
+//! struct MessagePosted {
+//!     text: String,
+//! }
+//!
+//! #[derive(Debug, Event, PartialEq)]
+//! #[event(revision)]
+//! enum ChatEvent {
+//!     #[event(init)]
+//!     Created(ChatCreated),
+//!     Posted(MessagePosted),
+//! }
+//!
+//! let chat = stream::Id::new("chat", "1").unwrap();
+//! let ev = ChatEvent::Posted(MessagePosted { text: "hello".into() });
+//! let time =
+//!     SystemTime::UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+//! let recorded = store::Metadata::new().caused_by("post-1");
+//! let meta =
+//!     Metadata::new("42", "/chats").at(time).recorded(recorded.clone());
+//! let ce = CloudEvent::encode(&ev, &chat, meta).unwrap();
+//!
+//! assert_eq!(ce.ty, "message.posted");
+//! assert_eq!(ce.metadata(), recorded);
+//! assert_eq!(ce.revision.as_deref(), Some("2"));
+//! assert_eq!(ce.subject.as_deref(), Some("chat-1"));
+//! assert_eq!(ce.data, Some(serde_json::json!({"text": "hello"})));
+//!
+//! let ce = ce.extension("traceparent", "00-4bf92f3577b34da6-01");
+//! let json = ce.to_structured().unwrap();
+//! let structured = CloudEvent::from_structured(&json).unwrap();
+//! assert_eq!(structured, ce);
+//!
+//! let binary = CloudEvent::from_binary(&ce.to_binary().unwrap()).unwrap();
+//! assert_eq!(binary, ce);
+//!
+//! assert_eq!(binary.decode::<ChatEvent>().unwrap(), ev);
+//! assert!(binary.decode::<ChatCreated>().is_err());
+//!
+//! // Non-JSON data is kept as is, and header values are percent-encoded.
+//! let text = CloudEvent {
+//!     content_type: Some("text/plain; charset=utf-8".into()),
+//!     data: Some("héllo".into()),
+//!     ..ce
+//! }
+//! .extension("comment", "50% \"off\"");
+//! let binary = text.to_binary().unwrap();
+//! assert_eq!(binary.body, "héllo".as_bytes());
+//! let header = ("ce-comment".to_owned(), "50%25%20%22off%22".to_owned());
+//! assert!(binary.headers.contains(&header));
+//! assert_eq!(CloudEvent::from_binary(&binary).unwrap(), text);
+//! ```
+//!
+//! [CloudEvents 1.0]: https://cloudevents.io
+
+use std::{
+    collections::BTreeMap, error::Error as StdError, fmt, time::SystemTime,
+};
+
+#[doc(hidden)]
+pub use serde;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+#[doc(hidden)]
+pub use serde_json;
+use serde_json::Value as Json;
+
+#[cfg(doc)]
+use super::Event;
+#[cfg(feature = "outbox")]
+use super::outbox;
+use super::{
+    event::{self, Concrete, Revisable, Version},
+    store, stream,
+};
+
+/// Supported version of the [CloudEvents] specification.
+///
+/// [CloudEvents]: https://cloudevents.io
+pub const SPEC_VERSION: &str = "1.0";
+
+/// Name of the extension attribute holding [`Revisable::revision()`] of an
+/// [`Event`].
+pub const REVISION: &str = "revision";
+
+/// Name of the extension attribute holding [`store::Metadata::causation`] of
+/// an [`Event`].
+pub const CAUSATION: &str = "causationid";
+
+/// Name of the extension attribute holding [`store::Metadata::correlation`]
+/// of an [`Event`].
+pub const CORRELATION: &str = "correlationid";
+
+/// Media type of the `data` attribute of [`CloudEvent`]s.
+pub const CONTENT_TYPE: &str = "application/json";
+
+/// Prefix of [`Binary`] headers holding [`CloudEvent`] attributes.
+pub const HEADER_PREFIX: &str = "ce-";
+
+/// Names of the [`CloudEvent`] attributes represented by its own fields in the
+/// binary content mode, so not being [`CloudEvent::extensions`].
+const ATTRIBUTES: [&str; 8] = [
+    "specversion",
+    "id",
+    "source",
+    "type",
+    "subject",
+    "time",
+    "dataschema",
+    REVISION,
+];
+
+/// Metadata of an [`Event`] not carried by the [`Event`] itself.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Metadata {
+    /// Identifier of the [`Event`], unique within its `source`.
+    pub id: String,
+
+    /// URI reference of the context the [`Event`] happened in.
+    pub source: String,
+
+    /// Time the [`Event`] happened at, if known.
+    pub time: Option<SystemTime>,
+
+    /// [`store::Metadata`] the [`Event`] was recorded with.
+    pub recorded: store::Metadata,
+}
+
+impl Metadata {
+    /// Creates new [`Metadata`] with the provided `id` and `source`.
+    #[must_use]
+    pub fn new(id: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            source: source.into(),
+            time: None,
+            recorded: store::Metadata::new(),
+        }
+    }
+
+    /// Sets the time the [`Event`] happened at.
+    #[must_use]
+    pub const fn at(mut self, time: SystemTime) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Sets the [`store::Metadata`] the [`Event`] was recorded with.
+    #[must_use]
+    pub fn recorded(mut self, metadata: store::Metadata) -> Self {
+        self.recorded = metadata;
+        self
+    }
+}
+
+/// [CloudEvent] envelope of an [`Event`].
+///
+/// Its `serde` representation is the structured JSON one.
+///
+/// [CloudEvent]: https://cloudevents.io
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CloudEvent {
+    /// Version of the [CloudEvents] specification (see [`SPEC_VERSION`]).
+    ///
+    /// [CloudEvents]: https://cloudevents.io
+    #[serde(rename = "specversion")]
+    pub spec_version: String,
+
+    /// Identifier of the [`Event`], unique within its [`CloudEvent::source`].
+    pub id: String,
+
+    /// URI reference of the context the [`Event`] happened in.
+    pub source: String,
+
+    /// [`event::Name`] of the [`Event`].
+    #[serde(rename = "type")]
+    pub ty: String,
+
+    /// Stringified [`stream::Id`] the [`Event`] belongs to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+
+    /// Time the [`Event`] happened at, if known.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "rfc3339"
+    )]
+    pub time: Option<SystemTime>,
+
+    /// Media type of the [`CloudEvent::data`] (see [`CONTENT_TYPE`]).
+    #[serde(
+        default,
+        rename = "datacontenttype",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub content_type: Option<String>,
+
+    /// URI of the schema the [`CloudEvent::data`] adheres to, if any.
+    #[serde(
+        default,
+        rename = "dataschema",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub schema: Option<String>,
+
+    /// Stringified [`event::Revision`] of the [`Event`] (see [`REVISION`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+
+    /// Serialized [`Event`] itself.
+    ///
+    /// If the [`CloudEvent::content_type`] is not a JSON one, holds a JSON
+    /// string with the data as is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Json>,
+
+    /// Extension attributes not known to this library, preserved as is.
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, Json>,
+}
+
+impl CloudEvent {
+    /// Encodes the provided [`Event`] of the [`stream::Id`] into a
+    /// [`CloudEvent`] with the provided [`Metadata`].
+    ///
+    /// # Errors
+    ///
+    /// If the provided [`Event`] fails to be serialized.
+    pub fn encode<Ev>(
+        event: &Ev,
+        stream: &stream::Id,
+        meta: Metadata,
+    ) -> Result<Self, serde_json::Error>
+    where
+        Ev: Encode + Revisable + ?Sized,
+        event::RevisionOf<Ev>: fmt::Display,
+    {
+        let store::Metadata { causation, correlation } = meta.recorded;
+        let extensions = [(CAUSATION, causation), (CORRELATION, correlation)]
+            .into_iter()
+            .filter_map(|(name, id)| Some((name.to_owned(), id?.into())))
+            .collect();
+        Ok(Self {
+            spec_version: SPEC_VERSION.to_owned(),
+            id: meta.id,
+            source: meta.source,
+            ty: event.name().to_owned(),
+            subject: Some(stream.to_string()),
+            time: meta.time,
+            content_type: Some(CONTENT_TYPE.to_owned()),
+            schema: None,
+            revision: Some(event.revision().to_string()),
+            data: Some(event.encode()?),
+            extensions,
+        })
+    }
+
+    /// Returns the [`store::Metadata`] from the [`CAUSATION`] and
+    /// [`CORRELATION`] extension attributes of this [`CloudEvent`].
+    ///
+    /// Non-string values of these attributes are ignored.
+    #[must_use]
+    pub fn metadata(&self) -> store::Metadata {
+        let id = |name| {
+            self.extensions.get(name).and_then(Json::as_str).map(str::to_owned)
+        };
+        store::Metadata {
+            causation: id(CAUSATION),
+            correlation: id(CORRELATION),
+        }
+    }
+
+    /// Sets the extension attribute with the provided `name` to the provided
+    /// `value`.
+    #[must_use]
+    pub fn extension(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<Json>,
+    ) -> Self {
+        drop(self.extensions.insert(name.into(), value.into()));
+        self
+    }
+
+    /// Converts the provided [`outbox::Envelope`] into a [`CloudEvent`]
+    /// originated from the provided `source`, using its
+    /// [`outbox::Envelope::key`] as the identifier.
+    #[cfg(feature = "outbox")]
+    #[must_use]
+    pub fn from_envelope(
+        envelope: &outbox::Envelope,
+        source: impl Into<String>,
+    ) -> Self {
+        Self {
+            spec_version: SPEC_VERSION.to_owned(),
+            id: envelope.key.clone(),
+            source: source.into(),
+            ty: envelope.name.clone(),
+            subject: Some(envelope.stream.to_string()),
+            time: None,
+            content_type: Some(CONTENT_TYPE.to_owned()),
+            schema: None,
+            revision: Some(envelope.revision.clone()),
+            data: Some(envelope.payload.clone()),
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    /// Decodes the [`Event`] from this [`CloudEvent`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Missing`] if this [`CloudEvent`] has no [`REVISION`].
+    /// - [`Error::Invalid`] if the [`REVISION`] is not an [`event::Version`].
+    /// - [`Error::Unknown`] if the [`Event`] is not [`Decode`]d by the
+    ///   specified type.
+    /// - [`Error::Json`] if the [`CloudEvent::data`] fails to be deserialized.
+    pub fn decode<Ev: Decode>(&self) -> Result<Ev, Error> {
+        let revision =
+            self.revision.as_deref().ok_or(Error::Missing(REVISION))?;
+        let revision = revision.parse::<Version>().ok().ok_or_else(|| {
+            Error::Invalid { attribute: REVISION, value: revision.to_owned() }
+        })?;
+        let data = self.data.as_ref().unwrap_or(&Json::Null);
+        Ev::decode(&self.ty, revision, data)
+            .ok_or_else(|| Error::Unknown { ty: self.ty.clone(), revision })?
+            .map_err(Error::Json)
+    }
+
+    /// Encodes this [`CloudEvent`] in the structured JSON content mode.
+    ///
+    /// # Errors
+    ///
+    /// If this [`CloudEvent`] fails to be serialized.
+    pub fn to_structured(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(self)
+    }
+
+    /// Decodes a [`CloudEvent`] encoded in the structured JSON content mode.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Json`] if the provided `bytes` are not a [`CloudEvent`].
+    /// - [`Error::Invalid`] if the [`CloudEvent::spec_version`] is not the
+    ///   [`SPEC_VERSION`].
+    pub fn from_structured(bytes: &[u8]) -> Result<Self, Error> {
+        let ce = serde_json::from_slice::<Self>(bytes).map_err(Error::Json)?;
+        ce.check_spec_version()?;
+        Ok(ce)
+    }
+
+    /// Encodes this [`CloudEvent`] in the binary content mode.
+    ///
+    /// Header values are percent-encoded as required by the [HTTP binding].
+    /// [`CloudEvent::extensions`] are represented as headers too, with the
+    /// non-string values being serialized as JSON. If the
+    /// [`CloudEvent::content_type`] is not a JSON one, and the
+    /// [`CloudEvent::data`] is a JSON string, the [`Binary::body`] holds this
+    /// string as is.
+    ///
+    /// # Errors
+    ///
+    /// If the [`CloudEvent::data`] fails to be serialized.
+    ///
+    /// [HTTP binding]: https://github.com/cloudevents/spec
+    pub fn to_binary(&self) -> Result<Binary, serde_json::Error> {
+        let header = |name: &str, value: &str| {
+            (format!("{HEADER_PREFIX}{name}"), percent::encode(value))
+        };
+
+        let mut headers = vec![
+            header("specversion", &self.spec_version),
+            header("id", &self.id),
+            header("source", &self.source),
+            header("type", &self.ty),
+        ];
+        if let Some(subject) = &self.subject {
+            headers.push(header("subject", subject));
+        }
+        if let Some(time) = self.time {
+            headers.push(header("time", &rfc3339::format(time)));
+        }
+        if let Some(schema) = &self.schema {
+            headers.push(header("dataschema", schema));
+        }
+        if let Some(revision) = &self.revision {
+            headers.push(header(REVISION, revision));
+        }
+        for (name, value) in &self.extensions {
+            if let Json::String(value) = value {
+                headers.push(header(name, value));
+            } else {
+                headers.push(header(name, &value.to_string()));
+            }
+        }
+        if let Some(content_type) = &self.content_type {
+            headers.push(("content-type".to_owned(), content_type.clone()));
+        }
+
+        let body = match &self.data {
+            Some(Json::String(data))
+                if !is_json(self.content_type.as_deref()) =>
+            {
+                data.clone().into_bytes()
+            }
+            data => data
+                .as_ref()
+                .map(serde_json::to_vec)
+                .transpose()?
+                .unwrap_or_default(),
+        };
+
+        Ok(Binary { headers, body })
+    }
+
+    /// Decodes a [`CloudEvent`] encoded in the binary content mode.
+    ///
+    /// Header names are matched case-insensitively, and header values are
+    /// percent-decoded. Headers of unknown attributes are collected into the
+    /// [`CloudEvent::extensions`] as JSON strings. The [`Binary::body`] is
+    /// parsed as JSON only if the `content-type` is absent or a JSON one,
+    /// otherwise it's kept as a JSON string.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Missing`] if any of the required attributes is missing.
+    /// - [`Error::Invalid`] if the [`CloudEvent::spec_version`] is not the
+    ///   [`SPEC_VERSION`], the `time` attribute is not an [RFC 3339] one, any
+    ///   header value is not properly percent-encoded, or a non-JSON
+    ///   [`Binary::body`] is not a UTF-8 one.
+    /// - [`Error::Json`] if the JSON [`Binary::body`] is not a JSON.
+    ///
+    /// [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339
+    pub fn from_binary(binary: &Binary) -> Result<Self, Error> {
+        let attributes = binary.headers.iter().filter_map(|(n, v)| {
+            n.get(..HEADER_PREFIX.len())
+                .filter(|p| p.eq_ignore_ascii_case(HEADER_PREFIX))
+                .and_then(|_| n.get(HEADER_PREFIX.len()..))
+                .map(|n| (n, v))
+        });
+        let header = |name: &'static str| {
+            attributes
+                .clone()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, v)| {
+                    percent::decode(v).ok_or_else(|| Error::Invalid {
+                        attribute: name,
+                        value: v.clone(),
+                    })
+                })
+                .transpose()
+        };
+        let required =
+            |name: &'static str| header(name)?.ok_or(Error::Missing(name));
+
+        let extensions = attributes
+            .clone()
+            .filter(|(n, _)| {
+                !ATTRIBUTES.iter().any(|a| n.eq_ignore_ascii_case(a))
+            })
+            .map(|(n, v)| {
+                let value = percent::decode(v).ok_or_else(|| {
+                    Error::Invalid { attribute: "extension", value: v.clone() }
+                })?;
+                Ok((n.to_ascii_lowercase(), Json::String(value)))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        let time = header("time")?
+            .map(|t| {
+                rfc3339::parse(&t)
+                    .ok_or(Error::Invalid { attribute: "time", value: t })
+            })
+            .transpose()?;
+        let content_type = binary
+            .headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case("content-type"))
+            .map(|(_, v)| v.clone());
+        let data = if binary.body.is_empty() {
+            None
+        } else if is_json(content_type.as_deref()) {
+            Some(serde_json::from_slice(&binary.body).map_err(Error::Json)?)
+        } else {
+            let data = String::from_utf8(binary.body.clone()).map_err(|e| {
+                Error::Invalid {
+                    attribute: "data",
+                    value: String::from_utf8_lossy(e.as_bytes()).into_owned(),
+                }
+            })?;
+            Some(Json::String(data))
+        };
+
+        let ce = Self {
+            spec_version: required("specversion")?,
+            id: required("id")?,
+            source: required("source")?,
+            ty: required("type")?,
+            subject: header("subject")?,
+            time,
+            content_type,
+            schema: header("dataschema")?,
+            revision: header(REVISION)?,
+            data,
+            extensions,
+        };
+        ce.check_spec_version()?;
+        Ok(ce)
+    }
+
+    /// Checks whether the [`CloudEvent::spec_version`] is the
+    /// [`SPEC_VERSION`].
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Invalid`] if it's not.
+    fn check_spec_version(&self) -> Result<(), Error> {
+        if self.spec_version == SPEC_VERSION {
+            Ok(())
+        } else {
+            Err(Error::Invalid {
+                attribute: "specversion",
+                value: self.spec_version.clone(),
+            })
+        }
+    }
+}
+
+/// [CloudEvent] encoded in the binary content mode.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::{
+/// #     cloudevents::{Binary, CloudEvent, Error},
+/// #     store,
+/// # };
+/// #
+/// let header = |name: &str, value: &str| (name.to_owned(), value.to_owned());
+/// let mut binary = Binary {
+///     headers: vec![
+///         header("CE-SpecVersion", "1.0"),
+///         header("ce-id", "42"),
+///         header("ce-source", "/chats"),
+///         header("ce-type", "chat.archived"),
+///         header("ce-causationid", "archive-1"),
+///         header("Ce-Priority", "5"),
+///         header("ce-traceparent", "00-4bf9%2001"),
+///         header("x-request-id", "7"),
+///     ],
+///     body: Vec::new(),
+/// };
+///
+/// // Extensions are matched case-insensitively and decoded as strings, while
+/// // non-prefixed headers are ignored.
+/// let ce = CloudEvent::from_binary(&binary).unwrap();
+/// assert_eq!(ce.extensions.len(), 3);
+/// assert_eq!(ce.extensions["priority"], "5");
+/// assert_eq!(ce.extensions["traceparent"], "00-4bf9 01");
+/// assert_eq!(ce.metadata(), store::Metadata::new().caused_by("archive-1"));
+/// assert_eq!(ce.data, None);
+///
+/// // Non-string extensions are encoded as JSON, so come back as strings.
+/// let ce = ce.extension("priority", 5).extension("urgent", true);
+/// let decoded = CloudEvent::from_binary(&ce.to_binary().unwrap()).unwrap();
+/// assert_eq!(decoded.extensions["priority"], "5");
+/// assert_eq!(decoded.extensions["urgent"], "true");
+///
+/// binary.headers.push(header("ce-broken", "%zz"));
+/// let err = CloudEvent::from_binary(&binary);
+/// assert!(matches!(err, Err(Error::Invalid { attribute: "extension", .. })));
+///
+/// binary.headers.retain(|(n, _)| n != "ce-broken" && n != "ce-id");
+/// let err = CloudEvent::from_binary(&binary);
+/// assert!(matches!(err, Err(Error::Missing("id"))));
+/// ```
+///
+/// [CloudEvent]: https://cloudevents.io
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Binary {
+    /// Headers (like HTTP ones or message properties) holding the attributes
+    /// prefixed with the [`HEADER_PREFIX`], and the `content-type`.
+    pub headers: Vec<(String, String)>,
+
+    /// Body holding the serialized [`CloudEvent::data`].
+    pub body: Vec<u8>,
+}
+
+/// Indicates whether the provided [`CloudEvent::content_type`] is a JSON one.
+///
+/// An absent one is considered as JSON, as the [CloudEvents] JSON format
+/// implies.
+///
+/// [CloudEvents]: https://cloudevents.io
+fn is_json(content_type: Option<&str>) -> bool {
+    content_type.is_none_or(|ct| {
+        let media = ct.split(';').next().unwrap_or_default().trim();
+        media.eq_ignore_ascii_case(CONTENT_TYPE)
+            || media.eq_ignore_ascii_case("text/json")
+            || media
+                .get(media.len().saturating_sub(5)..)
+                .is_some_and(|s| s.eq_ignore_ascii_case("+json"))
+    })
+}
+
+/// [`Event`] encodable into the `data` of a [`CloudEvent`].
+///
+/// > **NOTE**: Implementations of this trait are automatically generated by
+/// >           `#[derive(Event)]` macro for [`Concrete`] structs and enums
+/// >           composed of them, whenever the composed [`Event`]s are
+/// >           [`Serialize`]. Enums encode their variants only, so the `data`
+/// >           is [`Decode`]d back by the variant's [`Event`] type.
+pub trait Encode {
+    /// Encodes this [`Event`] into the `data` of a [`CloudEvent`].
+    ///
+    /// # Errors
+    ///
+    /// If this [`Event`] fails to be serialized.
+    fn encode(&self) -> Result<Json, serde_json::Error>;
+}
+
+/// [`Event`] decodable from a [`CloudEvent`].
+///
+/// > **NOTE**: Implementations of this trait are automatically generated by
+/// >           `#[derive(Event)]` macro for [`Concrete`] structs and enums
+/// >           composed of them, whenever the composed [`Event`]s are
+/// >           [`DeserializeOwned`].
+pub trait Decode: Sized {
+    /// Decodes this [`Event`] from the provided `data` of the [`Event`] with
+    /// the provided `ty`pe and `revision`.
+    ///
+    /// Returns [`None`] if this [`Event`] is not (and is not composed of) the
+    /// one with the provided `ty`pe and `revision`.
+    fn decode(
+        ty: &str,
+        revision: Version,
+        data: &Json,
+    ) -> Option<Result<Self, serde_json::Error>>;
+}
+
+/// Decodes the specified [`Concrete`] [`Event`] from the provided `data`, if
+/// its [`Static::NAME`] (or any of [`Static::ALIASES`]) and
+/// [`Concrete::REVISION`] match the provided `ty`pe and `revision`.
+///
+/// Used by the [`Decode`] implementations of structs generated by
+/// `#[derive(Event)]` macro.
+///
+/// [`Static::ALIASES`]: event::Static::ALIASES
+/// [`Static::NAME`]: event::Static::NAME
+#[must_use]
+pub fn decode_concrete<Ev>(
+    ty: &str,
+    revision: Version,
+    data: &Json,
+) -> Option<Result<Ev, serde_json::Error>>
+where
+    Ev: Concrete + Revisable<Revision = Version> + DeserializeOwned,
+{
+    let is_named = ty == Ev::NAME || Ev::ALIASES.contains(&ty);
+    (is_named && revision == Ev::REVISION).then(|| Ev::deserialize(data))
+}
+
+/// Error of decoding a [`CloudEvent`].
+#[derive(Debug)]
+pub enum Error {
+    /// Required attribute is missing.
+    Missing(&'static str),
+
+    /// Attribute has an invalid value.
+    Invalid {
+        /// Name of the attribute.
+        attribute: &'static str,
+
+        /// Invalid value of the attribute.
+        value: String,
+    },
+
+    /// [`Event`] of the type and revision is unknown to the [`Decode`]
+    /// implementer.
+    Unknown {
+        /// [`CloudEvent::ty`] of the [`Event`].
+        ty: String,
+
+        /// [`Version`] of the [`Event`].
+        revision: Version,
+    },
+
+    /// JSON (de)serialization failed.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(attr) => {
+                write!(f, "missing `{attr}` CloudEvent attribute")
+            }
+            Self::Invalid { attribute, value } => write!(
+                f,
+                "invalid value `{value}` of `{attribute}` CloudEvent attribute",
+            ),
+            Self::Unknown { ty, revision } => {
+                write!(f, "unknown event `{ty}` of revision `{revision}`")
+            }
+            Self::Json(e) => write!(f, "JSON (de)serialization failed: {e}"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Missing(_) | Self::Invalid { .. } | Self::Unknown { .. } => {
+                None
+            }
+            Self::Json(e) => Some(e),
+        }
+    }
+}
+
+/// Percent-encoding of [`Binary`] header values, as required by the
+/// [HTTP binding].
+///
+/// [HTTP binding]: https://github.com/cloudevents/spec
+mod percent {
+    use std::{fmt::Write as _, str};
+
+    /// Percent-encodes the provided header `value`.
+    ///
+    /// Encodes the space, double-quote and percent characters, along with the
+    /// ones outside the printable ASCII range, as their UTF-8 bytes.
+    pub(super) fn encode(value: &str) -> String {
+        let mut out = String::with_capacity(value.len());
+        for b in value.bytes() {
+            if matches!(b, b'!'..=b'~') && !matches!(b, b'"' | b'%') {
+                out.push(char::from(b));
+            } else {
+                _ = write!(out, "%{b:02X}");
+            }
+        }
+        out
+    }
+
+    /// Percent-decodes the provided header `value`.
+    ///
+    /// Returns [`None`] if the `value` contains invalid percent-encoded
+    /// sequences, or they don't form a UTF-8 string.
+    pub(super) fn decode(value: &str) -> Option<String> {
+        let mut bytes = Vec::with_capacity(value.len());
+        let mut iter = value.bytes();
+        while let Some(b) = iter.next() {
+            if b == b'%' {
+                let hex = [iter.next()?, iter.next()?];
+                let hex = str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            } else {
+                bytes.push(b);
+            }
+        }
+        String::from_utf8(bytes).ok()
+    }
+}
+
+/// [RFC 3339] (de)serialization of [`SystemTime`]s.
+///
+/// [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339
+mod rfc3339 {
+    use std::time::SystemTime;
+
+    use serde::{Deserialize as _, Deserializer, Serializer, de::Error as _};
+
+    /// Formats the provided [`SystemTime`] as an [RFC 3339] UTC timestamp.
+    ///
+    /// [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339
+    pub(super) fn format(time: SystemTime) -> String {
+        humantime::format_rfc3339_nanos(time).to_string()
+    }
+
+    /// Parses the provided [RFC 3339] UTC timestamp into a [`SystemTime`].
+    ///
+    /// [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339
+    pub(super) fn parse(time: &str) -> Option<SystemTime> {
+        humantime::parse_rfc3339(time).ok()
+    }
+
+    /// Serializes the provided optional [`SystemTime`] as an [RFC 3339] one.
+    ///
+    /// [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339
+    #[expect(
+        clippy::ref_option,
+        reason = "signature is dictated by `serde(with)`"
+    )]
+    pub(super) fn serialize<S: Serializer>(
+        time: &Option<SystemTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match time {
+            Some(t) => serializer.serialize_some(&format(*t)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserializes an optional [RFC 3339] [`SystemTime`].
+    ///
+    /// [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<SystemTime>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|t| {
+                parse(&t).ok_or_else(|| {
+                    D::Error::custom(format!("invalid RFC 3339 time `{t}`"))
+                })
+            })
+            .transpose()
+    }
+}
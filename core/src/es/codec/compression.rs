@@ -0,0 +1,175 @@
+//! [`Compression`] of encoded payloads.
+
+#[cfg(feature = "zstd")]
+use std::io::Read as _;
+use std::{fmt, io};
+
+/// Default maximum size (in bytes) of a decompressed payload.
+pub const DEFAULT_MAX_SIZE: usize = 64 * 1024 * 1024;
+
+/// Compression algorithm of encoded payloads.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::codec::compression::{self, Compression};
+/// #
+/// let max = compression::DEFAULT_MAX_SIZE;
+/// let oversized = vec![0; max + 1];
+///
+/// # #[cfg(feature = "zstd")]
+/// # {
+/// let zstd = Compression::Zstd(3);
+/// let compressed = zstd.compress(&oversized).unwrap();
+/// assert!(compressed.len() < 64 * 1024);
+/// assert!(zstd.decompress(&compressed, max).is_err());
+///
+/// let compressed = zstd.compress(&oversized[..max]).unwrap();
+/// assert_eq!(zstd.decompress(&compressed, max).unwrap().len(), max);
+/// assert!(zstd.decompress(&compressed, max - 1).is_err());
+/// assert!(zstd.decompress(b"not zstd", max).is_err());
+/// # }
+///
+/// # #[cfg(feature = "lz4")]
+/// # {
+/// let lz4 = Compression::Lz4;
+/// let compressed = lz4.compress(&oversized).unwrap();
+/// assert!(lz4.decompress(&compressed, max).is_err());
+///
+/// // Forged sizes are rejected before decompressing anything.
+/// let mut forged = u32::try_from(max + 1).unwrap().to_le_bytes().to_vec();
+/// forged.extend(b"bomb");
+/// assert!(lz4.decompress(&forged, max).is_err());
+/// assert!(lz4.decompress(&[1, 0], max).is_err());
+///
+/// let compressed = lz4.compress(&oversized[..1024]).unwrap();
+/// assert_eq!(lz4.decompress(&compressed, 1024).unwrap().len(), 1024);
+/// assert!(lz4.decompress(&compressed, 1023).is_err());
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Compression {
+    /// [Zstandard](https://facebook.github.io/zstd) with the provided
+    /// compression level.
+    #[cfg(feature = "zstd")]
+    Zstd(i32),
+
+    /// [LZ4](https://lz4.org) block format.
+    #[cfg(feature = "lz4")]
+    Lz4,
+}
+
+impl Compression {
+    /// Returns the tag of this [`Compression`] prepended to the payloads
+    /// compressed by [`Codecs`].
+    ///
+    /// Tags of [`Compression`]s are uppercase ASCII letters, so never clash
+    /// with [`Format::tag()`]s.
+    ///
+    /// [`Codecs`]: super::Codecs
+    /// [`Format::tag()`]: super::Format::tag
+    #[must_use]
+    pub const fn tag(self) -> u8 {
+        match self {
+            #[cfg(feature = "zstd")]
+            Self::Zstd(_) => b'Z',
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => b'L',
+        }
+    }
+
+    /// Returns the [`Compression`] with the provided [`Compression::tag()`], if
+    /// it's known and enabled.
+    ///
+    /// The returned [`Compression`] uses the default level, as it doesn't
+    /// matter for decompression.
+    #[must_use]
+    pub const fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            #[cfg(feature = "zstd")]
+            b'Z' => Some(Self::Zstd(zstd::DEFAULT_COMPRESSION_LEVEL)),
+            #[cfg(feature = "lz4")]
+            b'L' => Some(Self::Lz4),
+            _ => None,
+        }
+    }
+
+    /// Compresses the provided `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// If the `bytes` fail to be compressed.
+    pub fn compress(self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "zstd")]
+            Self::Zstd(level) => zstd::encode_all(bytes, level),
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => Ok(lz4_flex::compress_prepend_size(bytes)),
+        }
+    }
+
+    /// Decompresses the provided `bytes`, unless they decompress into more
+    /// than `max_size` bytes.
+    ///
+    /// # Errors
+    ///
+    /// - If the `bytes` are not a valid compressed payload.
+    /// - If the decompressed payload exceeds the `max_size`.
+    pub fn decompress(
+        self,
+        bytes: &[u8],
+        max_size: usize,
+    ) -> io::Result<Vec<u8>> {
+        let too_large = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("decompressed payload exceeds {max_size} bytes"),
+            )
+        };
+        match self {
+            #[cfg(feature = "zstd")]
+            Self::Zstd(_) => {
+                let limit = u64::try_from(max_size)
+                    .unwrap_or(u64::MAX)
+                    .saturating_add(1);
+                let mut out = vec![];
+                _ = zstd::Decoder::new(bytes)?
+                    .take(limit)
+                    .read_to_end(&mut out)?;
+                if out.len() > max_size {
+                    return Err(too_large());
+                }
+                Ok(out)
+            }
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => {
+                let size = bytes
+                    .first_chunk::<4>()
+                    .map(|s| u32::from_le_bytes(*s))
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "missing size of LZ4 payload",
+                        )
+                    })?;
+                if usize::try_from(size).map_or(true, |s| s > max_size) {
+                    return Err(too_large());
+                }
+                lz4_flex::decompress_size_prepended(bytes)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            #[cfg(feature = "zstd")]
+            Self::Zstd(_) => "zstd",
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => "LZ4",
+        })
+    }
+}
@@ -0,0 +1,508 @@
+//! [`Codec`]s (de)serializing payloads of [`Event`]s.
+//!
+//! Besides JSON, the following binary [`Format`]s are supported, each behind
+//! its own feature:
+//! - [MessagePack] (`msgpack` feature);
+//! - [postcard] (`postcard` feature);
+//! - [bincode] (`bincode` feature).
+//!
+//! > **NOTE**: [postcard] and [bincode] are not self-describing, so they don't
+//! >           support `#[serde(untagged)]`, `#[serde(flatten)]` and
+//! >           `#[serde(skip_serializing_if)]` attributes.
+//!
+//! [`Codecs`] allow choosing a [`Format`] per [`event::Name`], and tag every
+//! encoded payload with its [`Format`], so the [`Format`] of an [`Event`] may
+//! be changed gradually: the payloads encoded before are still decoded with
+//! their original [`Format`].
+//!
+//! [`Codecs`] may also compress large payloads with a [`Compression`] (see
+//! [`Codecs::compress()`]), behind its own feature:
+//! - [Zstandard] (`zstd` feature);
+//! - [LZ4] (`lz4` feature).
+//!
+//! Compressed payloads are tagged with their [`Compression`] too, so they're
+//! decompressed transparently, regardless of the current settings. To protect
+//! from decompression bombs, decompressed payloads are limited in size (see
+//! [`Codecs::max_decompressed_size()`]).
+//!
+//! Tags are the leading bytes of the encoded payloads themselves, rather than
+//! a metadata stored separately, so [`Codecs`] require no support from
+//! stores. As JSON never starts with a tag, payloads stored as plain untagged
+//! JSON (before [`Codecs`] were adopted) are still decoded as JSON.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use arcane::es::{
+//! #     Event,
+//! #     codec::{Codec as _, Codecs, Format},
+//! #     event,
+//! # };
+//! # #[cfg(feature = "zstd")]
+//! # use arcane::es::codec::Compression;
+//! # use serde::{Deserialize, Serialize};
+//! #
+//! #[derive(Debug, Deserialize, Event, PartialEq, Serialize)]
+//! #[event(name = "message.posted", revision = 1)]
+//! struct MessagePosted {
+//!     text: String,
+//! }
+//!
+//! let v1 = event::Version::try_new(1).unwrap();
+//! let ev = MessagePosted { text: "hello".into() };
+//!
+//! let json = Codecs::new(Format::Json);
+//! let old = json.encode("message.posted", v1, &ev).unwrap();
+//!
+//! # #[cfg(feature = "msgpack")]
+//! # {
+//! let msgpack =
+//!     Codecs::new(Format::Json).with("message.posted", Format::MessagePack);
+//! let new = msgpack.encode("message.posted", v1, &ev).unwrap();
+//! assert!(new.len() < old.len());
+//!
+//! // Payloads encoded before the migration are still decoded.
+//! for bytes in [&old, &new] {
+//!     let decoded =
+//!         msgpack.decode::<MessagePosted>("message.posted", v1, bytes);
+//!     assert_eq!(decoded.unwrap(), ev);
+//! }
+//! # }
+//!
+//! for &format in Format::ALL {
+//!     let codecs = Codecs::new(format);
+//!     let bytes = codecs.encode("message.posted", v1, &ev).unwrap();
+//!     let decoded =
+//!         codecs.decode::<MessagePosted>("message.posted", v1, &bytes);
+//!     assert_eq!(decoded.unwrap(), ev);
+//! }
+//!
+//! # #[cfg(feature = "zstd")]
+//! # {
+//! let doc = MessagePosted { text: "lorem ipsum ".repeat(1000) };
+//! let codecs = Codecs::new(Format::Json).compress(Compression::Zstd(3), 1024);
+//! let bytes = codecs.encode("message.posted", v1, &doc).unwrap();
+//! assert!(bytes.len() < doc.text.len() / 10);
+//!
+//! // Compressed payloads are decoded regardless of the current settings.
+//! let decoded = json.decode::<MessagePosted>("message.posted", v1, &bytes);
+//! assert_eq!(decoded.unwrap(), doc);
+//!
+//! // Unless they decompress into too many bytes.
+//! let limited = Codecs::new(Format::Json).max_decompressed_size(1024);
+//! let decoded = limited.decode::<MessagePosted>("message.posted", v1, &bytes);
+//! assert!(decoded.is_err());
+//! # }
+//!
+//! // Untagged JSON payloads are decoded too.
+//! let legacy = br#"{"text":"hello"}"#;
+//! let decoded = json.decode::<MessagePosted>("message.posted", v1, legacy);
+//! assert_eq!(decoded.unwrap(), ev);
+//! ```
+//!
+//! [bincode]: https://docs.rs/bincode
+//! [LZ4]: https://lz4.org
+//! [MessagePack]: https://msgpack.org
+//! [postcard]: https://docs.rs/postcard
+//! [Zstandard]: https://facebook.github.io/zstd
+
+#[cfg(any(feature = "lz4", feature = "zstd"))]
+pub mod compression;
+
+#[cfg(any(feature = "lz4", feature = "zstd"))]
+use std::io;
+use std::{collections::HashMap, error::Error as StdError, fmt};
+
+#[cfg(feature = "bincode")]
+use bincode::{
+    config,
+    serde::{decode_from_slice, encode_to_vec},
+};
+use serde::{Serialize, de::DeserializeOwned};
+
+#[cfg(any(feature = "lz4", feature = "zstd"))]
+#[doc(inline)]
+pub use self::compression::Compression;
+use super::event::Version;
+#[cfg(doc)]
+use super::{Event, event};
+
+/// Boxed error of a [`Format`].
+pub type BoxError = Box<dyn StdError + Send + Sync>;
+
+/// Codec (de)serializing payloads of [`Event`]s, keyed by their
+/// [`event::Name`]s and [`Version`]s.
+pub trait Codec {
+    /// Encodes the provided [`Event`] with the provided [`event::Name`] and
+    /// [`Version`].
+    ///
+    /// # Errors
+    ///
+    /// If the [`Event`] fails to be serialized.
+    fn encode<Ev: Serialize + ?Sized>(
+        &self,
+        name: &str,
+        version: Version,
+        event: &Ev,
+    ) -> Result<Vec<u8>, Error>;
+
+    /// Decodes the [`Event`] with the provided [`event::Name`] and [`Version`]
+    /// from the provided `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// If the `bytes` fail to be deserialized into the specified [`Event`].
+    fn decode<Ev: DeserializeOwned>(
+        &self,
+        name: &str,
+        version: Version,
+        bytes: &[u8],
+    ) -> Result<Ev, Error>;
+}
+
+/// Serialization format of payloads.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+#[repr(u8)]
+pub enum Format {
+    /// [JSON](https://www.json.org).
+    #[default]
+    Json = b'j',
+
+    /// [MessagePack](https://msgpack.org), with structs encoded as maps.
+    #[cfg(feature = "msgpack")]
+    MessagePack = b'm',
+
+    /// [postcard](https://docs.rs/postcard).
+    #[cfg(feature = "postcard")]
+    Postcard = b'p',
+
+    /// [bincode](https://docs.rs/bincode) with its standard configuration.
+    #[cfg(feature = "bincode")]
+    Bincode = b'b',
+}
+
+impl Format {
+    /// All the enabled [`Format`]s.
+    pub const ALL: &'static [Self] = &[
+        Self::Json,
+        #[cfg(feature = "msgpack")]
+        Self::MessagePack,
+        #[cfg(feature = "postcard")]
+        Self::Postcard,
+        #[cfg(feature = "bincode")]
+        Self::Bincode,
+    ];
+
+    /// Returns the tag of this [`Format`] prepended to the payloads encoded by
+    /// [`Codecs`].
+    #[expect(clippy::as_conversions, reason = "`Format` is `#[repr(u8)]`")]
+    #[must_use]
+    pub const fn tag(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the [`Format`] with the provided [`Format::tag()`], if it's
+    /// known and enabled.
+    #[must_use]
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        Self::ALL.iter().copied().find(|f| f.tag() == tag)
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Json => "JSON",
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => "MessagePack",
+            #[cfg(feature = "postcard")]
+            Self::Postcard => "postcard",
+            #[cfg(feature = "bincode")]
+            Self::Bincode => "bincode",
+        })
+    }
+}
+
+impl Codec for Format {
+    fn encode<Ev: Serialize + ?Sized>(
+        &self,
+        _: &str,
+        _: Version,
+        event: &Ev,
+    ) -> Result<Vec<u8>, Error> {
+        let encoded = match self {
+            Self::Json => serde_json::to_vec(event).map_err(BoxError::from),
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => {
+                rmp_serde::to_vec_named(event).map_err(BoxError::from)
+            }
+            #[cfg(feature = "postcard")]
+            Self::Postcard => {
+                postcard::to_allocvec(event).map_err(BoxError::from)
+            }
+            #[cfg(feature = "bincode")]
+            Self::Bincode => {
+                encode_to_vec(event, config::standard()).map_err(BoxError::from)
+            }
+        };
+        encoded.map_err(|e| Error::Encode { format: *self, source: e })
+    }
+
+    fn decode<Ev: DeserializeOwned>(
+        &self,
+        _: &str,
+        _: Version,
+        bytes: &[u8],
+    ) -> Result<Ev, Error> {
+        let decoded = match self {
+            Self::Json => serde_json::from_slice(bytes).map_err(BoxError::from),
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => {
+                rmp_serde::from_slice(bytes).map_err(BoxError::from)
+            }
+            #[cfg(feature = "postcard")]
+            Self::Postcard => {
+                postcard::from_bytes(bytes).map_err(BoxError::from)
+            }
+            #[cfg(feature = "bincode")]
+            Self::Bincode => decode_from_slice(bytes, config::standard())
+                .map(|(ev, _)| ev)
+                .map_err(BoxError::from),
+        };
+        decoded.map_err(|e| Error::Decode { format: *self, source: e })
+    }
+}
+
+/// [`Codec`] choosing a [`Format`] per [`event::Name`], and tagging the encoded
+/// payloads with it.
+///
+/// Intended to be used as a store-level setting: payloads are always decoded
+/// with the [`Format`] they were encoded with, so changing the [`Format`] of
+/// an [`Event`] affects only the newly encoded payloads.
+///
+/// Compressed payloads are prefixed with a [`Compression::tag()`] followed by
+/// a [`Format::tag()`], while uncompressed ones are prefixed with a
+/// [`Format::tag()`] only.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Codecs {
+    /// [`Format`] of the [`Event`]s without a specific one.
+    default: Format,
+
+    /// Specific [`Format`]s of [`Event`]s by their [`event::Name`]s.
+    by_name: HashMap<String, Format>,
+
+    /// [`Compression`] of the payloads, along with the minimal size of the
+    /// payloads to be compressed, if any.
+    #[cfg(any(feature = "lz4", feature = "zstd"))]
+    compression: Option<(Compression, usize)>,
+
+    /// Maximum size of decompressed payloads (in bytes).
+    #[cfg(any(feature = "lz4", feature = "zstd"))]
+    max_decompressed_size: usize,
+}
+
+impl Codecs {
+    /// Creates new [`Codecs`] encoding all the [`Event`]s with the provided
+    /// [`Format`] by default.
+    #[must_use]
+    pub fn new(default: Format) -> Self {
+        Self {
+            default,
+            by_name: HashMap::new(),
+            #[cfg(any(feature = "lz4", feature = "zstd"))]
+            compression: None,
+            #[cfg(any(feature = "lz4", feature = "zstd"))]
+            max_decompressed_size: compression::DEFAULT_MAX_SIZE,
+        }
+    }
+
+    /// Makes these [`Codecs`] encode the [`Event`]s with the provided
+    /// [`event::Name`] with the provided [`Format`].
+    #[must_use]
+    pub fn with(mut self, name: impl Into<String>, format: Format) -> Self {
+        _ = self.by_name.insert(name.into(), format);
+        self
+    }
+
+    /// Makes these [`Codecs`] compress the encoded payloads with the provided
+    /// [`Compression`], if their size is at least the provided `threshold`
+    /// (in bytes).
+    ///
+    /// Payloads not shrinking after the compression are stored uncompressed.
+    #[cfg(any(feature = "lz4", feature = "zstd"))]
+    #[must_use]
+    pub const fn compress(
+        mut self,
+        compression: Compression,
+        threshold: usize,
+    ) -> Self {
+        self.compression = Some((compression, threshold));
+        self
+    }
+
+    /// Limits the size of decompressed payloads (in bytes), so a malicious or
+    /// corrupted payload cannot exhaust memory.
+    ///
+    /// [`compression::DEFAULT_MAX_SIZE`] by default.
+    #[cfg(any(feature = "lz4", feature = "zstd"))]
+    #[must_use]
+    pub const fn max_decompressed_size(mut self, max_size: usize) -> Self {
+        self.max_decompressed_size = max_size;
+        self
+    }
+
+    /// Returns the [`Format`] the [`Event`]s with the provided
+    /// [`event::Name`] are encoded with.
+    #[must_use]
+    pub fn format_of(&self, name: &str) -> Format {
+        self.by_name.get(name).copied().unwrap_or(self.default)
+    }
+}
+
+impl Codec for Codecs {
+    fn encode<Ev: Serialize + ?Sized>(
+        &self,
+        name: &str,
+        version: Version,
+        event: &Ev,
+    ) -> Result<Vec<u8>, Error> {
+        let format = self.format_of(name);
+        let payload = format.encode(name, version, event)?;
+
+        #[cfg(any(feature = "lz4", feature = "zstd"))]
+        if let Some((compression, threshold)) = self.compression {
+            if payload.len() >= threshold {
+                let compressed =
+                    compression.compress(&payload).map_err(|e| {
+                        Error::Compression { compression, source: e }
+                    })?;
+                if compressed.len() < payload.len() {
+                    let mut bytes = vec![compression.tag(), format.tag()];
+                    bytes.extend(compressed);
+                    return Ok(bytes);
+                }
+            }
+        }
+
+        let mut bytes = vec![format.tag()];
+        bytes.extend(payload);
+        Ok(bytes)
+    }
+
+    fn decode<Ev: DeserializeOwned>(
+        &self,
+        name: &str,
+        version: Version,
+        bytes: &[u8],
+    ) -> Result<Ev, Error> {
+        let (&tag, payload) = bytes.split_first().ok_or(Error::Untagged)?;
+
+        #[cfg(any(feature = "lz4", feature = "zstd"))]
+        if let Some(compression) = Compression::from_tag(tag) {
+            let (&format_tag, compressed) =
+                payload.split_first().ok_or(Error::Untagged)?;
+            let format = Format::from_tag(format_tag)
+                .ok_or(Error::UnknownTag(format_tag))?;
+            let decompressed = compression
+                .decompress(compressed, self.max_decompressed_size)
+                .map_err(|e| Error::Compression { compression, source: e })?;
+            return format.decode(name, version, &decompressed);
+        }
+
+        match Format::from_tag(tag) {
+            Some(format) => format.decode(name, version, payload),
+            None if is_json_start(tag) => {
+                Format::Json.decode(name, version, bytes)
+            }
+            None => Err(Error::UnknownTag(tag)),
+        }
+    }
+}
+
+/// Indicates whether the provided first byte of a payload may start an
+/// untagged JSON value.
+const fn is_json_start(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'{' | b'[' | b'"' | b'-' | b'0'
+            ..=b'9' | b't' | b'f' | b'n' | b' ' | b'\t' | b'\n' | b'\r',
+    )
+}
+
+/// Error of a [`Codec`].
+#[derive(Debug)]
+pub enum Error {
+    /// [`Event`] failed to be encoded.
+    Encode {
+        /// [`Format`] the [`Event`] was encoded with.
+        format: Format,
+
+        /// Error of the [`Format`].
+        source: BoxError,
+    },
+
+    /// [`Event`] failed to be decoded.
+    Decode {
+        /// [`Format`] the [`Event`] was decoded with.
+        format: Format,
+
+        /// Error of the [`Format`].
+        source: BoxError,
+    },
+
+    /// Payload has no [`Format::tag()`].
+    Untagged,
+
+    /// Payload is tagged with an unknown (or disabled) [`Format`] or
+    /// [`Compression`], and is not an untagged JSON either.
+    UnknownTag(u8),
+
+    /// Payload failed to be compressed or decompressed.
+    #[cfg(any(feature = "lz4", feature = "zstd"))]
+    Compression {
+        /// [`Compression`] of the payload.
+        compression: Compression,
+
+        /// Error of the [`Compression`].
+        source: io::Error,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Encode { format, source } => {
+                write!(f, "failed to encode event as {format}: {source}")
+            }
+            Self::Decode { format, source } => {
+                write!(f, "failed to decode event from {format}: {source}")
+            }
+            Self::Untagged => write!(f, "payload is not tagged with a format"),
+            Self::UnknownTag(tag) => {
+                write!(f, "payload is tagged with unknown format `{tag:#x}`")
+            }
+            #[cfg(any(feature = "lz4", feature = "zstd"))]
+            Self::Compression { compression, source } => {
+                write!(
+                    f,
+                    "failed to (de)compress payload with {compression}: \
+                           {source}"
+                )
+            }
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Encode { source, .. } | Self::Decode { source, .. } => {
+                Some(&**source)
+            }
+            #[cfg(any(feature = "lz4", feature = "zstd"))]
+            Self::Compression { source, .. } => Some(source),
+            Self::Untagged | Self::UnknownTag(_) => None,
+        }
+    }
+}
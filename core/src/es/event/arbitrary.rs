@@ -0,0 +1,225 @@
+//! [`proptest`] strategies generating valid [`Event`] sequences.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use arcane::es::event::{self, Event, Initialized, Sourced};
+//! # use proptest::prelude::*;
+//! #
+//! #[derive(Clone, Debug, Event, PartialEq)]
+//! #[event(name = "chat.created", revision = 1)]
+//! struct ChatCreated;
+//!
+//! impl Arbitrary for ChatCreated {
+//!     type Parameters = ();
+//!     type Strategy = Just<Self>;
+//!
+//!     fn arbitrary_with((): ()) -> Self::Strategy {
+//!         Just(Self)
+//!     }
+//! }
+//!
+//! #[derive(Debug, Event, PartialEq)]
+//! #[event(name = "message.posted", revision = 1)]
+//! struct MessagePosted(u8);
+//!
+//! impl Arbitrary for MessagePosted {
+//!     type Parameters = ();
+//!     type Strategy = BoxedStrategy<Self>;
+//!
+//!     fn arbitrary_with((): ()) -> Self::Strategy {
+//!         any::<u8>().prop_map(Self).boxed()
+//!     }
+//! }
+//!
+//! #[derive(Debug, Event, PartialEq)]
+//! enum ChatEvent {
+//!     #[event(init)]
+//!     Created(ChatCreated),
+//!     MessagePosted(MessagePosted),
+//! }
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct Chat {
+//!     total: u32,
+//! }
+//!
+//! impl Initialized<ChatCreated> for Chat {
+//!     fn init(_: &ChatCreated) -> Self {
+//!         Self { total: 0 }
+//!     }
+//! }
+//!
+//! impl Sourced<MessagePosted> for Chat {
+//!     fn apply(&mut self, ev: &MessagePosted) {
+//!         self.total += u32::from(ev.0);
+//!     }
+//! }
+//!
+//! proptest!(|(events in event::arbitrary::history::<ChatEvent>(0..16))| {
+//!     prop_assert!(matches!(events[0], ChatEvent::Created(_)));
+//!     event::arbitrary::check_replay::<Option<Chat>, _>(&events)?;
+//! });
+//! ```
+
+use std::{any::type_name, fmt};
+
+#[doc(hidden)]
+pub use proptest;
+use proptest::{
+    arbitrary::{Arbitrary as ProptestArbitrary, any},
+    collection::{SizeRange, vec},
+    prop_assert_eq,
+    strategy::{BoxedStrategy, LazyJust, Strategy as _, Union},
+    test_runner::TestCaseError,
+};
+
+use super::{Event, Sourced};
+
+/// [`Event`] capable of generating its arbitrary values, split into the
+/// initializing and sourcing ones.
+///
+/// Derived by `#[derive(Event)]` for enums, along with the
+/// [`proptest::arbitrary::Arbitrary`] implementation, with the `proptest`
+/// feature enabled. Requires every enum variant to implement
+/// [`proptest::arbitrary::Arbitrary`].
+pub trait Arbitrary: Event + fmt::Debug + Sized + 'static {
+    /// Returns a strategy generating [`Event`]s initializing a state, if any.
+    ///
+    /// For a derived enum, these are its `#[event(init)]` variants.
+    fn initial() -> Option<BoxedStrategy<Self>>;
+
+    /// Returns a strategy generating [`Event`]s applied to an already
+    /// initialized state, if any.
+    ///
+    /// For a derived enum, these are its non-`#[event(init)]` variants.
+    fn sourced() -> Option<BoxedStrategy<Self>>;
+}
+
+/// Returns a strategy generating [`Event`]s of any kind.
+///
+/// # Panics
+///
+/// If the [`Event`] has neither [`Arbitrary::initial()`] nor
+/// [`Arbitrary::sourced()`] strategy.
+pub fn any_event<Ev: Arbitrary>() -> BoxedStrategy<Ev> {
+    one_of(Ev::initial().into_iter().chain(Ev::sourced()).collect())
+        .unwrap_or_else(|| {
+            panic!("`{}` cannot be generated", type_name::<Ev>())
+        })
+}
+
+/// Returns a strategy generating valid [`Event`] histories: exactly one
+/// [`Arbitrary::initial()`] [`Event`] first (if there are any), followed by
+/// the `len` number of [`Arbitrary::sourced()`] ones.
+pub fn history<Ev: Arbitrary>(
+    len: impl Into<SizeRange>,
+) -> BoxedStrategy<Vec<Ev>> {
+    let len = len.into();
+    let sourced = Ev::sourced().map(|s| vec(s, len));
+    match (Ev::initial(), sourced) {
+        (Some(init), Some(rest)) => (init, rest)
+            .prop_map(|(init, mut rest)| {
+                rest.insert(0, init);
+                rest
+            })
+            .boxed(),
+        (Some(init), None) => init.prop_map(|init| vec![init]).boxed(),
+        (None, Some(rest)) => rest.boxed(),
+        (None, None) => LazyJust::new(Vec::new).boxed(),
+    }
+}
+
+/// Asserts that replaying the provided [`Event`]s is deterministic, and that
+/// a state snapshotted at any point of the history and then replayed till its
+/// end equals to the state replayed from scratch.
+///
+/// # Errors
+///
+/// If any of the assertions fails.
+///
+/// # Example
+///
+/// States losing anything on snapshotting are rejected:
+///
+/// ```rust
+/// # use arcane::es::event::{self, Event, Sourced};
+/// #
+/// #[derive(Debug, Event)]
+/// #[event(name = "message.posted", revision = 1)]
+/// struct MessagePosted;
+///
+/// #[derive(Debug, Default, PartialEq)]
+/// struct Chat {
+///     total: u32,
+/// }
+///
+/// impl Clone for Chat {
+///     fn clone(&self) -> Self {
+///         Self::default()
+///     }
+/// }
+///
+/// impl Sourced<MessagePosted> for Chat {
+///     fn apply(&mut self, _: &MessagePosted) {
+///         self.total += 1;
+///     }
+/// }
+///
+/// let events = [MessagePosted, MessagePosted];
+/// assert!(event::arbitrary::check_replay::<Chat, _>(&events).is_err());
+/// ```
+pub fn check_replay<S, Ev>(events: &[Ev]) -> Result<(), TestCaseError>
+where
+    S: Clone + Default + PartialEq + fmt::Debug + Sourced<Ev>,
+{
+    let full = replay(S::default(), events);
+    prop_assert_eq!(&replay(S::default(), events), &full, "non-deterministic");
+
+    for at in 0..=events.len() {
+        let (head, tail) = events.split_at(at);
+        let state = replay(S::default(), head);
+        let resumed = replay(state.clone(), tail);
+        prop_assert_eq!(
+            &resumed,
+            &full,
+            "snapshot at {} diverges from the full replay",
+            at,
+        );
+        prop_assert_eq!(
+            &replay(state, tail),
+            &resumed,
+            "snapshot at {} diverges from the original state",
+            at,
+        );
+    }
+    Ok(())
+}
+
+/// Returns a strategy generating the provided enum variant.
+///
+/// Used by `#[derive(Event)]` for enums.
+pub fn variant<T, Ev>(wrap: fn(T) -> Ev) -> BoxedStrategy<Ev>
+where
+    T: ProptestArbitrary + 'static,
+    Ev: fmt::Debug + 'static,
+{
+    any::<T>().prop_map(wrap).boxed()
+}
+
+/// Returns a strategy generating values of any of the provided ones, or
+/// [`None`] if none are provided.
+#[must_use]
+pub fn one_of<T: fmt::Debug + 'static>(
+    strategies: Vec<BoxedStrategy<T>>,
+) -> Option<BoxedStrategy<T>> {
+    (!strategies.is_empty()).then(|| Union::new(strategies).boxed())
+}
+
+/// Applies the provided [`Event`]s to the provided `state`.
+fn replay<S: Sourced<Ev>, Ev>(mut state: S, events: &[Ev]) -> S {
+    for ev in events {
+        state.apply(ev);
+    }
+    state
+}
@@ -0,0 +1,661 @@
+//! [`Event`] machinery.
+
+use std::{
+    fmt,
+    num::{NonZero, ParseIntError},
+    ops::RangeInclusive,
+    str::FromStr,
+};
+
+use derive_more::{Deref, DerefMut, Display, Error, From, Into};
+use ref_cast::RefCast;
+use sealed::sealed;
+
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+pub mod name;
+#[cfg(feature = "registry")]
+pub mod registry;
+
+/// Fully qualified name of an [`Event`].
+///
+/// See [`name`] module for the [`name::Policy`] it's expected to conform to
+/// and the [`name::Parsed`] representation.
+pub type Name = &'static str;
+
+/// Abstracted [`Revision`] number of an [`Event`].
+pub trait Revision: Copy {}
+
+impl Revision for &str {}
+
+impl Revision for Version {}
+
+/// [`NonZero`] [`u16`] incremental [`Revision`] number of an [`Event`].
+#[derive(
+    Clone, Copy, Debug, Display, Eq, Hash, Into, Ord, PartialEq, PartialOrd,
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(transparent),
+    expect(
+        clippy::unsafe_derive_deserialize,
+        reason = "`NonZero` invariant is checked by its `Deserialize` impl"
+    )
+)]
+pub struct Version(NonZero<u16>);
+
+impl Version {
+    /// Smallest possible [`Version`] (`1`).
+    pub const MIN: Self = Self(NonZero::<u16>::MIN);
+
+    /// Largest possible [`Version`] ([`u16::MAX`]).
+    pub const MAX: Self = Self(NonZero::<u16>::MAX);
+
+    /// Creates a new [`Version`] out of the provided `value`.
+    ///
+    /// The provided `value` should not be `0` (zero) and fit into [`u16`] size.
+    #[must_use]
+    pub fn try_new<N>(value: N) -> Option<Self>
+    where
+        u16: TryFrom<N>,
+    {
+        Some(Self(NonZero::new(u16::try_from(value).ok()?)?))
+    }
+
+    /// Creates a new [`Version`] out of the provided `value` without checking
+    /// its invariants.
+    ///
+    /// # Safety
+    ///
+    /// The provided `value` must not be `0` (zero).
+    #[inline]
+    #[must_use]
+    pub const unsafe fn new_unchecked(value: u16) -> Self {
+        // SAFETY: Safety invariants are the same as for this method.
+        Self(unsafe { NonZero::new_unchecked(value) })
+    }
+
+    /// Returns the value of this [`Version`] as a primitive type.
+    #[inline]
+    #[must_use]
+    pub const fn get(self) -> u16 {
+        self.0.get()
+    }
+
+    /// Returns the [`Version`] following this one, or [`None`] if this is the
+    /// [`Version::MAX`].
+    #[inline]
+    #[must_use]
+    pub const fn next(self) -> Option<Self> {
+        self.checked_add(1)
+    }
+
+    /// Returns the [`Version`] preceding this one, or [`None`] if this is the
+    /// [`Version::MIN`].
+    #[inline]
+    #[must_use]
+    pub const fn prev(self) -> Option<Self> {
+        self.checked_sub(1)
+    }
+
+    /// Adds the provided `rhs` to this [`Version`], returning [`None`] on
+    /// overflow.
+    #[must_use]
+    pub const fn checked_add(self, rhs: u16) -> Option<Self> {
+        match self.0.checked_add(rhs) {
+            Some(v) => Some(Self(v)),
+            None => None,
+        }
+    }
+
+    /// Subtracts the provided `rhs` from this [`Version`], returning [`None`]
+    /// if the result is `0` (zero) or underflows.
+    #[must_use]
+    pub const fn checked_sub(self, rhs: u16) -> Option<Self> {
+        let Some(v) = self.get().checked_sub(rhs) else {
+            return None;
+        };
+        match NonZero::new(v) {
+            Some(v) => Some(Self(v)),
+            None => None,
+        }
+    }
+}
+
+impl FromStr for Version {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+/// Inclusive range of [`Version`]s an [`Event`] is compatible with.
+///
+/// Displayed and parsed in the `<start>..=<end>` form, while a single
+/// [`Version`] (`<version>`) is parsed as a range of itself.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::event::{Version, VersionRange};
+/// #
+/// let range: VersionRange = "1..=3".parse().unwrap();
+///
+/// assert_eq!(range.start(), Version::MIN);
+/// assert!(range.contains(Version::try_new(2).unwrap()));
+/// assert!(!range.contains(Version::try_new(4).unwrap()));
+/// assert_eq!(range.iter().count(), 3);
+/// assert_eq!(range.to_string(), "1..=3");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(try_from = "serde_repr::VersionRange"),
+    expect(
+        clippy::unsafe_derive_deserialize,
+        reason = "invariants are checked by `serde_repr::VersionRange`"
+    )
+)]
+pub struct VersionRange {
+    /// First [`Version`] of this [`VersionRange`].
+    start: Version,
+
+    /// Last [`Version`] of this [`VersionRange`] (inclusive).
+    end: Version,
+}
+
+impl VersionRange {
+    /// Creates a new [`VersionRange`] out of the provided `start` and `end`
+    /// (inclusive) [`Version`]s.
+    ///
+    /// Returns [`None`] if `start` is greater than `end`.
+    #[must_use]
+    pub const fn new(start: Version, end: Version) -> Option<Self> {
+        if start.get() > end.get() {
+            return None;
+        }
+        Some(Self { start, end })
+    }
+
+    /// Creates a new [`VersionRange`] out of the provided `start` and `end`
+    /// (inclusive) [`Version`]s without checking its invariants.
+    ///
+    /// # Safety
+    ///
+    /// The provided `start` must not be greater than the provided `end`.
+    #[inline]
+    #[must_use]
+    pub const unsafe fn new_unchecked(start: Version, end: Version) -> Self {
+        Self { start, end }
+    }
+
+    /// Creates a new [`VersionRange`] containing the single provided
+    /// [`Version`] only.
+    #[inline]
+    #[must_use]
+    pub const fn single(version: Version) -> Self {
+        Self { start: version, end: version }
+    }
+
+    /// Returns the first [`Version`] of this [`VersionRange`].
+    #[inline]
+    #[must_use]
+    pub const fn start(self) -> Version {
+        self.start
+    }
+
+    /// Returns the last [`Version`] of this [`VersionRange`] (inclusive).
+    #[inline]
+    #[must_use]
+    pub const fn end(self) -> Version {
+        self.end
+    }
+
+    /// Checks whether the provided [`Version`] belongs to this
+    /// [`VersionRange`].
+    #[inline]
+    #[must_use]
+    pub const fn contains(self, version: Version) -> bool {
+        self.start.get() <= version.get() && version.get() <= self.end.get()
+    }
+
+    /// Returns an [`Iterator`] over all the [`Version`]s of this
+    /// [`VersionRange`] in ascending order.
+    pub fn iter(self) -> impl Iterator<Item = Version> {
+        (self.start.get()..=self.end.get()).filter_map(Version::try_new)
+    }
+}
+
+impl From<Version> for VersionRange {
+    fn from(version: Version) -> Self {
+        Self::single(version)
+    }
+}
+
+impl TryFrom<RangeInclusive<Version>> for VersionRange {
+    type Error = ParseVersionRangeError;
+
+    fn try_from(range: RangeInclusive<Version>) -> Result<Self, Self::Error> {
+        Self::new(*range.start(), *range.end())
+            .ok_or(ParseVersionRangeError::Reversed)
+    }
+}
+
+impl From<VersionRange> for RangeInclusive<Version> {
+    fn from(range: VersionRange) -> Self {
+        range.start..=range.end
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..={}", self.start, self.end)
+    }
+}
+
+impl FromStr for VersionRange {
+    type Err = ParseVersionRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((start, end)) = s.split_once("..=") else {
+            return Ok(Self::single(s.trim().parse()?));
+        };
+        Self::new(start.trim().parse()?, end.trim().parse()?)
+            .ok_or(ParseVersionRangeError::Reversed)
+    }
+}
+
+/// Error of parsing a [`VersionRange`].
+#[derive(Clone, Debug, Display, Error, Eq, From, PartialEq)]
+pub enum ParseVersionRangeError {
+    /// One of the bounds is not a valid [`Version`].
+    #[display("invalid `Version` bound: {_0}")]
+    Version(ParseIntError),
+
+    /// Start of the range is greater than its end.
+    #[display("`VersionRange` start cannot be greater than its end")]
+    Reversed,
+}
+
+#[cfg(feature = "serde")]
+mod serde_repr {
+    //! Helper representations of types for [`serde`] deserialization.
+
+    use super::{ParseVersionRangeError, Version};
+
+    /// Unchecked representation of a [`super::VersionRange`].
+    #[derive(serde::Deserialize)]
+    pub(super) struct VersionRange {
+        /// First [`Version`] of the range.
+        start: Version,
+
+        /// Last [`Version`] of the range (inclusive).
+        end: Version,
+    }
+
+    impl TryFrom<VersionRange> for super::VersionRange {
+        type Error = ParseVersionRangeError;
+
+        fn try_from(range: VersionRange) -> Result<Self, Self::Error> {
+            (range.start..=range.end).try_into()
+        }
+    }
+}
+
+/// [Event Sourcing] event describing something that has occurred (happened
+/// fact).
+///
+/// [Event Sourcing]: https://martinfowler.com/eaaDev/EventSourcing.html
+pub trait Event {
+    /// Returns [`Name`] of this [`Event`].
+    ///
+    /// > **NOTE:** This should effectively be a constant value, and should
+    /// >           never change.
+    #[must_use]
+    fn name(&self) -> Name;
+}
+
+/// Concrete [`Event`] defined statically.
+pub trait Static: Event {
+    /// Concrete [`Name`] of this [`Event`].
+    const NAME: Name;
+
+    /// Former [`Name`]s of this [`Event`], still carried by its already stored
+    /// history.
+    ///
+    /// [`Event::name()`] always returns the canonical [`Static::NAME`], while
+    /// these aliases are only used to recognize the stored [`Event`]s.
+    const ALIASES: &'static [Name] = &[];
+}
+
+impl<Ev: Static + ?Sized> Event for Ev {
+    fn name(&self) -> Name {
+        <Self as Static>::NAME
+    }
+}
+
+/// [`Event`] capable of evolving with time.
+pub trait Revisable: Event {
+    /// Type of this [`Event`]'s [`Revision`] number.
+    type Revision: Revision;
+
+    /// Returns [`Revision`] of this [`Event`].
+    #[must_use]
+    fn revision(&self) -> Self::Revision;
+}
+
+/// Shortcut for naming a [`Revision`] of a [`RevisableEvent`].
+///
+/// [`RevisableEvent`]: Revisable
+pub type RevisionOf<Ev> = <Ev as Revisable>::Revision;
+
+/// [`StaticEvent`] of a concrete [`Revision`].
+///
+/// [`StaticEvent`]: Static
+pub trait Concrete: Revisable + Static {
+    /// Type of this [`StaticEvent`]'s [`Revision`] number.
+    ///
+    /// [`StaticEvent`]: Static
+    type Revision: Revision;
+
+    /// Concrete [`Revision`] of this [`Event`].
+    const REVISION: RevisionOf<Self>;
+}
+
+impl<Ev: Concrete + ?Sized> Revisable for Ev {
+    type Revision = <Self as Concrete>::Revision;
+
+    fn revision(&self) -> Self::Revision {
+        <Self as Concrete>::REVISION
+    }
+}
+
+/// State that can be calculated by applying the specified [`Event`].
+pub trait Sourced<Ev: ?Sized> {
+    /// Applies the specified [`Event`] to the current state.
+    fn apply(&mut self, event: &Ev);
+}
+
+impl<Ev, S> Sourced<Ev> for Option<S>
+where
+    Ev: Concrete + ?Sized,
+    S: Sourced<Ev>,
+{
+    fn apply(&mut self, event: &Ev) {
+        if let Some(state) = self {
+            state.apply(event);
+        }
+    }
+}
+
+impl<'e, S> Sourced<dyn Event + 'e> for Option<S>
+where
+    S: Sourced<dyn Event + 'e>,
+{
+    fn apply(&mut self, event: &(dyn Event + 'e)) {
+        if let Some(state) = self {
+            state.apply(event);
+        }
+    }
+}
+
+impl<'e, S> Sourced<dyn Event + Send + 'e> for Option<S>
+where
+    S: Sourced<dyn Event + Send + 'e>,
+{
+    fn apply(&mut self, event: &(dyn Event + Send + 'e)) {
+        if let Some(state) = self {
+            state.apply(event);
+        }
+    }
+}
+
+impl<'e, S> Sourced<dyn Event + Send + Sync + 'e> for Option<S>
+where
+    S: Sourced<dyn Event + Send + Sync + 'e>,
+{
+    fn apply(&mut self, event: &(dyn Event + Send + Sync + 'e)) {
+        if let Some(state) = self {
+            state.apply(event);
+        }
+    }
+}
+
+/// [`Event`] sourcing the specified state.
+///
+/// This is a reversed version of [`Sourced`] trait intended to simplify usage
+/// of trait objects describing sets of [`Event`]s. Shouldn't be implemented
+/// manually, but rather used as blanket impl.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::event::{self, Event, Sourced as _};
+/// #
+/// #[derive(Debug, Eq, PartialEq)]
+/// struct Chat;
+///
+/// #[derive(Event)]
+/// #[event(name = "chat", revision = 1)]
+/// struct ChatEvent;
+///
+/// impl event::Initialized<ChatEvent> for Chat {
+///     fn init(_: &ChatEvent) -> Self {
+///         Self
+///     }
+/// }
+///
+/// let mut chat = Option::<Chat>::None;
+/// let ev = event::Initial(ChatEvent);
+/// let ev: &dyn event::Sourcing<Option<Chat>> = &ev;
+/// chat.apply(ev);
+/// assert_eq!(chat, Some(Chat));
+/// ```
+#[sealed]
+pub trait Sourcing<S: ?Sized> {
+    /// Applies this [`Event`] to the specified `state`.
+    fn apply_to(&self, state: &mut S);
+}
+
+#[sealed]
+impl<Ev: ?Sized, S: Sourced<Ev> + ?Sized> Sourcing<S> for Ev {
+    fn apply_to(&self, state: &mut S) {
+        state.apply(self);
+    }
+}
+
+impl<'e, S: ?Sized> Sourced<dyn Sourcing<S> + 'e> for S {
+    fn apply(&mut self, event: &(dyn Sourcing<S> + 'e)) {
+        event.apply_to(self);
+    }
+}
+
+impl<'e, S: ?Sized> Sourced<dyn Sourcing<S> + Send + 'e> for S {
+    fn apply(&mut self, event: &(dyn Sourcing<S> + Send + 'e)) {
+        event.apply_to(self);
+    }
+}
+
+impl<'e, S: ?Sized> Sourced<dyn Sourcing<S> + Send + Sync + 'e> for S {
+    fn apply(&mut self, event: &(dyn Sourcing<S> + Send + Sync + 'e)) {
+        event.apply_to(self);
+    }
+}
+
+/// Before a state can be [`Sourced`] it needs to be [`Initialized`].
+pub trait Initialized<Ev: ?Sized> {
+    /// Creates an initial state from the given [`Event`].
+    #[must_use]
+    fn init(event: &Ev) -> Self;
+}
+
+/// Wrapper type to mark an [`Event`] that makes some [`Sourced`] state being
+/// [`Initialized`].
+///
+/// Exists solely to solve specialization problems.
+#[derive(Clone, Copy, Debug, Deref, DerefMut, Display, RefCast)]
+#[repr(transparent)]
+pub struct Initial<Ev: ?Sized>(pub Ev);
+
+// Manual implementation due to `derive_more::From` not being able to strip
+// `?Sized` trait bound.
+impl<Ev> From<Ev> for Initial<Ev> {
+    fn from(ev: Ev) -> Self {
+        Self(ev)
+    }
+}
+
+impl<Ev, S> Sourced<Initial<Ev>> for Option<S>
+where
+    Ev: Event + ?Sized,
+    S: Initialized<Ev>,
+{
+    fn apply(&mut self, event: &Initial<Ev>) {
+        *self = Some(S::init(&event.0));
+    }
+}
+
+#[cfg(feature = "reflect")]
+pub mod reflect {
+    //! Compile-time reflection for [`Event`] machinery.
+
+    use std::iter;
+
+    use super::{
+        super::{Event, event},
+        VersionRange,
+    };
+
+    /// Compile-time reflection of a single or multiple [`StaticEvent`]s.
+    ///
+    /// > **NOTE**: Implementations of this trait are automatically generated by
+    /// >           `#[derive(Event)]` macro, and shouldn't be written manually.
+    /// >           The trait is exposed for usage purposes only.
+    ///
+    /// [`StaticEvent`]: event::Static
+    pub trait Static: Event {
+        /// List of all [`StaticEvent::NAME`]s this [`Event`] is composed of
+        /// (including multiple levels of composition).
+        ///
+        /// > **NOTE**: May contain duplicates if the same [`event::Name`] is
+        /// >           used by multiple nested [`StaticEvent`]s.
+        ///
+        /// [`StaticEvent`]: event::Static
+        /// [`StaticEvent::NAME`]: event::Static::NAME
+        const NAMES: &'static [event::Name];
+
+        /// List of all the pairs of [`StaticEvent::ALIASES`] and their
+        /// canonical [`StaticEvent::NAME`]s this [`Event`] is composed of
+        /// (including multiple levels of composition).
+        ///
+        /// [`StaticEvent::ALIASES`]: event::Static::ALIASES
+        /// [`StaticEvent::NAME`]: event::Static::NAME
+        const ALIASES: &'static [(event::Name, event::Name)] = &[];
+
+        /// Resolves the provided [`event::Name`] (either a canonical one, or
+        /// an alias) into the canonical [`event::Name`] of this [`Event`].
+        ///
+        /// Returns [`None`] if this [`Event`] doesn't know the provided
+        /// [`event::Name`].
+        #[must_use]
+        fn canonical_name(name: &str) -> Option<event::Name> {
+            Self::NAMES.iter().copied().find(|n| *n == name).or_else(|| {
+                Self::ALIASES
+                    .iter()
+                    .find_map(|(alias, n)| (*alias == name).then_some(*n))
+            })
+        }
+    }
+
+    /// Compile-time reflection of a single or multiple [`ConcreteEvent`]s.
+    ///
+    /// > **NOTE**: Implementations of this trait are automatically generated by
+    /// >           `#[derive(Event)]` macro, and shouldn't be written manually.
+    /// >           The trait is exposed for usage purposes only.
+    ///
+    /// [`ConcreteEvent`]: event::Concrete
+    pub trait Concrete: event::Revisable + Static
+    where
+        event::RevisionOf<Self>: 'static,
+    {
+        /// List of all [`ConcreteEvent::REVISION`]s this [`RevisableEvent`] is
+        /// composed of (including multiple levels of composition).
+        ///
+        /// > **NOTE**: May contain duplicates if the same [`event::Revision`]
+        /// >           is used by multiple nested [`ConcreteEvent`]s.
+        ///
+        /// [`ConcreteEvent`]: event::Concrete
+        /// [`ConcreteEvent::REVISION`]: event::Concrete::REVISION
+        /// [`RevisableEvent`]: event::Revisable
+        const REVISIONS: &'static [event::RevisionOf<Self>];
+
+        /// Returns an [`Iterator`] over all the pairs of [`StaticEvent::NAME`]
+        /// and [`ConcreteEvent::REVISION`] this [`RevisableEvent`] is composed
+        /// of (including multiple levels of composition).
+        ///
+        /// > **NOTE**: May contain duplicates if the same [`ConcreteEvent`]
+        /// >           is used multiple times in this [`RevisableEvent`].
+        ///
+        /// [`ConcreteEvent`]: event::Concrete
+        /// [`ConcreteEvent::REVISION`]: event::Concrete::REVISION
+        /// [`RevisableEvent`]: event::Revisable
+        /// [`StaticEvent::NAME`]: event::Static::NAME
+        // TODO: Make `const` once `const fn` is allowed in traits.
+        fn names_and_revisions_iter() -> impl Iterator<
+            Item = (&'static event::Name, &'static event::RevisionOf<Self>),
+        > {
+            iter::zip(Self::NAMES, Self::REVISIONS)
+        }
+    }
+
+    /// Compile-time reflection of [`event::VersionRange`]s a single or multiple
+    /// [`ConcreteEvent`]s are able to deserialize.
+    ///
+    /// > **NOTE**: Implementations of this trait are automatically generated by
+    /// >           `#[derive(Event)]` macro, and shouldn't be written manually.
+    /// >           The trait is exposed for usage purposes only.
+    ///
+    /// [`ConcreteEvent`]: event::Concrete
+    pub trait Compatible: Concrete<Revision = event::Version> {
+        /// List of all [`event::VersionRange`]s of historical revisions this
+        /// [`RevisableEvent`] is composed of (including multiple levels of
+        /// composition), in the same order as [`Static::NAMES`].
+        ///
+        /// For a [`ConcreteEvent`] not declaring `#[event(supports = ...)]`
+        /// this is a range of its [`ConcreteEvent::REVISION`] only.
+        ///
+        /// [`ConcreteEvent`]: event::Concrete
+        /// [`ConcreteEvent::REVISION`]: event::Concrete::REVISION
+        /// [`RevisableEvent`]: event::Revisable
+        const SUPPORTED_REVISIONS: &'static [VersionRange];
+
+        /// Returns an [`Iterator`] over all the pairs of [`StaticEvent::NAME`]
+        /// and its supported [`event::VersionRange`] this [`RevisableEvent`] is
+        /// composed of (including multiple levels of composition).
+        ///
+        /// [`RevisableEvent`]: event::Revisable
+        /// [`StaticEvent::NAME`]: event::Static::NAME
+        #[must_use]
+        fn names_and_supported_revisions_iter()
+        -> impl Iterator<Item = (&'static event::Name, &'static VersionRange)>
+        {
+            iter::zip(Self::NAMES, Self::SUPPORTED_REVISIONS)
+        }
+
+        /// Checks whether this [`RevisableEvent`] is able to deserialize an
+        /// [`Event`] with the provided [`event::Name`] (or its alias) and
+        /// [`event::Version`].
+        ///
+        /// [`RevisableEvent`]: event::Revisable
+        #[must_use]
+        fn supports(name: &str, revision: event::Version) -> bool {
+            Self::canonical_name(name).is_some_and(|name| {
+                Self::names_and_supported_revisions_iter()
+                    .any(|(n, range)| *n == name && range.contains(revision))
+            })
+        }
+    }
+}
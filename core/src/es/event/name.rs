@@ -0,0 +1,336 @@
+//! [`Name`] machinery.
+//!
+//! [`Name`]: super::Name
+
+use std::fmt;
+
+use derive_more::Display;
+
+#[cfg(doc)]
+use super::{Event, Name};
+
+/// Separator of [`Name`] segments.
+pub const SEPARATOR: char = '.';
+
+/// [`SEPARATOR`] as a byte, for usage in `const` context.
+#[expect(clippy::as_conversions, reason = "ASCII char always fits into `u8`")]
+const SEPARATOR_BYTE: u8 = SEPARATOR as u8;
+
+/// Case convention of [`Name`] segments.
+#[derive(Clone, Copy, Debug, Display, Eq, Hash, PartialEq)]
+pub enum Case {
+    /// `snake_case`: lowercase ASCII letters, digits and underscores,
+    /// starting with a letter.
+    #[display("snake_case")]
+    Snake,
+
+    /// `kebab-case`: lowercase ASCII letters, digits and hyphens, starting
+    /// with a letter.
+    #[display("kebab-case")]
+    Kebab,
+
+    /// `camelCase`: ASCII letters and digits, starting with a lowercase
+    /// letter.
+    #[display("camelCase")]
+    Camel,
+
+    /// `PascalCase`: ASCII letters and digits, starting with an uppercase
+    /// letter.
+    #[display("PascalCase")]
+    Pascal,
+
+    /// `lowercase`: lowercase ASCII letters and digits, starting with a
+    /// letter.
+    #[display("lowercase")]
+    Lower,
+}
+
+impl Case {
+    /// Parses a [`Case`] from its [`Display`]ed representation.
+    ///
+    /// [`Display`]: std::fmt::Display
+    #[must_use]
+    pub const fn from_name(name: &str) -> Option<Self> {
+        Some(match name.as_bytes() {
+            b"snake_case" => Self::Snake,
+            b"kebab-case" => Self::Kebab,
+            b"camelCase" => Self::Camel,
+            b"PascalCase" => Self::Pascal,
+            b"lowercase" => Self::Lower,
+            _ => return None,
+        })
+    }
+
+    /// Checks whether the provided [`Name`] `segment` matches this [`Case`].
+    #[must_use]
+    pub const fn matches(self, segment: &str) -> bool {
+        self.matches_bytes(segment.as_bytes())
+    }
+
+    /// Checks whether the provided [`Name`] segment `bytes` match this
+    /// [`Case`].
+    const fn matches_bytes(self, bytes: &[u8]) -> bool {
+        let Some(first) = bytes.first() else {
+            return false;
+        };
+        let starts_ok = match self {
+            Self::Snake | Self::Kebab | Self::Camel | Self::Lower => {
+                first.is_ascii_lowercase()
+            }
+            Self::Pascal => first.is_ascii_uppercase(),
+        };
+        if !starts_ok {
+            return false;
+        }
+
+        let mut i = 1;
+        while i < bytes.len() {
+            let b = bytes[i];
+            let ok = match self {
+                Self::Snake => {
+                    b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_'
+                }
+                Self::Kebab => {
+                    b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-'
+                }
+                Self::Camel | Self::Pascal => b.is_ascii_alphanumeric(),
+                Self::Lower => b.is_ascii_lowercase() || b.is_ascii_digit(),
+            };
+            if !ok {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+}
+
+/// Naming policy [`Name`]s of [`Event`]s should conform to.
+///
+/// Regardless of the configuration, a [`Name`] is always required to be
+/// non-empty, to not contain whitespaces, and to consist of non-empty
+/// [`SEPARATOR`]-separated segments.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::event::name::{Case, Error, Policy};
+/// #
+/// let policy = Policy::new().case(Case::Snake).max_len(32);
+///
+/// assert_eq!(policy.validate("chat.message_posted"), Ok(()));
+/// assert_eq!(policy.validate("chat.MessagePosted"), Err(Error::Case));
+/// assert_eq!(policy.validate("chat..created"), Err(Error::EmptySegment));
+/// assert_eq!(policy.validate(""), Err(Error::Empty));
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Policy {
+    /// [`Case`] every segment of a [`Name`] should match, if any.
+    case: Option<Case>,
+
+    /// Maximum length of a [`Name`] in bytes, if any.
+    max_len: Option<usize>,
+}
+
+impl Policy {
+    /// Creates a new [`Policy`] performing the basic checks only.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { case: None, max_len: None }
+    }
+
+    /// Requires every segment of a [`Name`] to match the provided [`Case`].
+    #[must_use]
+    pub const fn case(mut self, case: Case) -> Self {
+        self.case = Some(case);
+        self
+    }
+
+    /// Requires a [`Name`] to be no longer than the provided `len` in bytes.
+    #[must_use]
+    pub const fn max_len(mut self, len: usize) -> Self {
+        self.max_len = Some(len);
+        self
+    }
+
+    /// Validates the provided [`Name`] against this [`Policy`].
+    ///
+    /// # Errors
+    ///
+    /// If the provided [`Name`] violates this [`Policy`].
+    pub const fn validate(&self, name: &str) -> Result<(), Error> {
+        let bytes = name.as_bytes();
+        if bytes.is_empty() {
+            return Err(Error::Empty);
+        }
+        if let Some(max) = self.max_len {
+            if bytes.len() > max {
+                return Err(Error::TooLong);
+            }
+        }
+
+        let (mut start, mut i) = (0, 0);
+        while i <= bytes.len() {
+            if i == bytes.len() || bytes[i] == SEPARATOR_BYTE {
+                if i == start {
+                    return Err(Error::EmptySegment);
+                }
+                if let Some(case) = self.case {
+                    let (_, rest) = bytes.split_at(start);
+                    let (segment, _) = rest.split_at(i - start);
+                    if !case.matches_bytes(segment) {
+                        return Err(Error::Case);
+                    }
+                }
+                start = i + 1;
+            } else if bytes[i].is_ascii_whitespace()
+                || bytes[i].is_ascii_control()
+            {
+                return Err(Error::Whitespace);
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+
+    /// Asserts the provided [`Name`] conforms to this [`Policy`].
+    ///
+    /// Intended for usage in `const` context, so a violation is reported at
+    /// compile time.
+    ///
+    /// # Panics
+    ///
+    /// If the provided [`Name`] violates this [`Policy`].
+    pub const fn assert(&self, name: &str) {
+        if let Err(e) = self.validate(name) {
+            panic!("{}", e.as_str());
+        }
+    }
+}
+
+/// Error of a [`Name`] violating a [`Policy`].
+#[derive(Clone, Copy, Debug, Eq, derive_more::Error, Hash, PartialEq)]
+pub enum Error {
+    /// [`Name`] is empty.
+    Empty,
+
+    /// [`Name`] contains an empty segment.
+    EmptySegment,
+
+    /// [`Name`] contains whitespace or control characters.
+    Whitespace,
+
+    /// [`Name`] segment doesn't match the [`Case`] of a [`Policy`].
+    Case,
+
+    /// [`Name`] exceeds the maximum length of a [`Policy`].
+    TooLong,
+}
+
+impl Error {
+    /// Returns the description of this [`Error`] usable in `const` context.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Empty => "`Event` name cannot be empty",
+            Self::EmptySegment => "`Event` name cannot contain empty segments",
+            Self::Whitespace => "`Event` name cannot contain whitespaces",
+            Self::Case => {
+                "`Event` name segment doesn't match the required case"
+            }
+            Self::TooLong => "`Event` name exceeds the maximum length",
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// [`Name`] split into its namespace and the last segment.
+///
+/// Useful for routing [`Event`]s by their namespaces.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::event::name::Parsed;
+/// #
+/// let name = Parsed::parse("chat.message.posted").unwrap();
+///
+/// assert_eq!(name.namespace(), Some("chat.message"));
+/// assert_eq!(name.segment(), "posted");
+/// assert!(name.is_in("chat"));
+/// assert!(name.is_in("chat.message"));
+/// assert!(!name.is_in("chat.mess"));
+/// assert_eq!(
+///     name.segments().collect::<Vec<_>>(),
+///     ["chat", "message", "posted"],
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Parsed<'n> {
+    /// Full [`Name`] being parsed.
+    full: &'n str,
+
+    /// Namespace of this [`Parsed`] [`Name`], if any.
+    namespace: Option<&'n str>,
+
+    /// Last segment of this [`Parsed`] [`Name`].
+    segment: &'n str,
+}
+
+impl<'n> Parsed<'n> {
+    /// Parses the provided [`Name`], validating it against the basic
+    /// [`Policy`].
+    ///
+    /// # Errors
+    ///
+    /// If the provided [`Name`] violates the basic [`Policy`].
+    pub fn parse(name: &'n str) -> Result<Self, Error> {
+        Policy::new().validate(name)?;
+
+        let (namespace, segment) = name
+            .rsplit_once(SEPARATOR)
+            .map_or((None, name), |(ns, s)| (Some(ns), s));
+        Ok(Self { full: name, namespace, segment })
+    }
+
+    /// Returns the full [`Name`].
+    #[must_use]
+    pub const fn as_str(&self) -> &'n str {
+        self.full
+    }
+
+    /// Returns the namespace of this [`Name`] (everything before its last
+    /// segment), if any.
+    #[must_use]
+    pub const fn namespace(&self) -> Option<&'n str> {
+        self.namespace
+    }
+
+    /// Returns the last segment of this [`Name`].
+    #[must_use]
+    pub const fn segment(&self) -> &'n str {
+        self.segment
+    }
+
+    /// Returns an [`Iterator`] over all the segments of this [`Name`].
+    pub fn segments(&self) -> impl Iterator<Item = &'n str> + use<'n> {
+        self.full.split(SEPARATOR)
+    }
+
+    /// Checks whether this [`Name`] belongs to the provided `namespace`
+    /// (respecting segments boundaries).
+    #[must_use]
+    pub fn is_in(&self, namespace: &str) -> bool {
+        let namespace = namespace.trim_end_matches(SEPARATOR);
+        self.namespace.is_some_and(|ns| {
+            ns.strip_prefix(namespace).is_some_and(|rest| {
+                rest.is_empty() || rest.starts_with(SEPARATOR)
+            })
+        })
+    }
+}
@@ -0,0 +1,356 @@
+//! [`Registry`] of decoders of stored [`Event`]s into [`Sourcing`] trait
+//! objects.
+//!
+//! Allows to replay heterogeneous [`Stream`]s into a state, without defining
+//! a single enum of all the [`Event`]s for every consumer.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use arcane::es::event::{
+//! #     self, Event, Initialized, Sourced,
+//! #     registry::{Json, Registry},
+//! # };
+//! # use serde::Deserialize;
+//! # use serde_json::json;
+//! #
+//! #[derive(Debug, Deserialize, Event)]
+//! #[event(name = "chat.created", revision = 1)]
+//! struct ChatCreated;
+//!
+//! #[derive(Debug, Deserialize, Event)]
+//! #[event(name = "message.posted", revision = 1, alias = "msg.posted")]
+//! struct MessagePosted {
+//!     text: String,
+//! }
+//!
+//! #[derive(Debug, Event)]
+//! enum ChatEvent {
+//!     #[event(init)]
+//!     Created(ChatCreated),
+//!     Posted(MessagePosted),
+//! }
+//!
+//! #[derive(Debug, Deserialize, Event)]
+//! #[event(name = "chat.archived", revision = 2)]
+//! struct ChatArchived;
+//!
+//! #[derive(Debug, Default, PartialEq)]
+//! struct Chat {
+//!     messages: Vec<String>,
+//!     archived: bool,
+//! }
+//!
+//! impl Initialized<ChatCreated> for Chat {
+//!     fn init(_: &ChatCreated) -> Self {
+//!         Self::default()
+//!     }
+//! }
+//!
+//! impl Sourced<MessagePosted> for Chat {
+//!     fn apply(&mut self, ev: &MessagePosted) {
+//!         self.messages.push(ev.text.clone());
+//!     }
+//! }
+//!
+//! impl Sourced<ChatArchived> for Chat {
+//!     fn apply(&mut self, _: &ChatArchived) {
+//!         self.archived = true;
+//!     }
+//! }
+//!
+//! let mut registry = Registry::<Chat, Json>::new();
+//! registry.register::<ChatEvent>().register::<ChatArchived>();
+//!
+//! let v = |n| event::Version::try_new(n).unwrap();
+//! let stored = [
+//!     ("chat.created", v(1), json!(null)),
+//!     ("msg.posted", v(1), json!({"text": "hello"})),
+//!     ("chat.archived", v(2), json!(null)),
+//! ];
+//!
+//! let mut chat = None;
+//! for (name, version, payload) in &stored {
+//!     let ev = registry.decode(name, *version, payload).unwrap();
+//!     ev.apply_to(&mut chat);
+//! }
+//! assert_eq!(
+//!     chat,
+//!     Some(Chat { messages: vec!["hello".into()], archived: true }),
+//! );
+//!
+//! assert!(registry.decode("chat.archived", v(1), &json!(null)).is_err());
+//! ```
+//!
+//! Registering another [`Event`] type under the same [`Name`] and [`Version`]
+//! panics, rather than silently replacing the registered decoder:
+//!
+//! ```rust,should_panic
+//! # use arcane::es::event::{Event, Sourced, registry::{Json, Registry}};
+//! # use serde::Deserialize;
+//! #
+//! #[derive(Debug, Deserialize, Event)]
+//! #[event(name = "chat.created", revision = 1)]
+//! struct ChatCreated;
+//!
+//! #[derive(Debug, Deserialize, Event)]
+//! #[event(name = "chat.created", revision = 1)]
+//! struct ChatOpened;
+//!
+//! #[derive(Debug)]
+//! struct Chat;
+//!
+//! impl Sourced<ChatCreated> for Option<Chat> {
+//!     fn apply(&mut self, _: &ChatCreated) {}
+//! }
+//!
+//! impl Sourced<ChatOpened> for Option<Chat> {
+//!     fn apply(&mut self, _: &ChatOpened) {}
+//! }
+//!
+//! let mut registry = Registry::<Chat, Json>::new();
+//! registry.register::<ChatCreated>().register::<ChatOpened>();
+//! ```
+//!
+//! [`Stream`]: crate::es::Stream
+
+use std::{
+    any::{TypeId, type_name},
+    collections::{HashMap, hash_map::Entry},
+    error::Error as StdError,
+    fmt, iter,
+};
+
+#[doc(hidden)]
+pub use serde;
+use serde::de::DeserializeOwned;
+
+use super::{Concrete, Revisable, Sourcing, Version};
+#[cfg(doc)]
+use super::{Event, Initial, Name, Static};
+
+/// Type-erased [`Event`] decoded by a [`Registry`], sourcing the optional
+/// state `S`.
+pub type Decoded<S> = Box<dyn Sourcing<Option<S>> + Send>;
+
+/// Decoder of a single [`Event`] type from the [`Format::Payload`].
+pub type Decoder<S, F> =
+    fn(&<F as Format>::Payload) -> Result<Decoded<S>, <F as Format>::Error>;
+
+/// [`Decoder`] registered in a [`Registry`], along with the [`TypeId`] of the
+/// type it decodes into.
+type Registered<S, F> = (TypeId, Decoder<S, F>);
+
+/// Format of stored [`Event`]s payloads.
+pub trait Format {
+    /// Type of the stored payloads.
+    type Payload: ?Sized;
+
+    /// Error of decoding a payload.
+    type Error;
+
+    /// Decodes the provided payload into a value of the specified type.
+    ///
+    /// # Errors
+    ///
+    /// If the payload doesn't represent a value of the specified type.
+    fn decode<T: DeserializeOwned>(
+        payload: &Self::Payload,
+    ) -> Result<T, Self::Error>;
+}
+
+/// JSON [`Format`] of payloads.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Json;
+
+impl Format for Json {
+    type Payload = serde_json::Value;
+    type Error = serde_json::Error;
+
+    fn decode<T: DeserializeOwned>(
+        payload: &Self::Payload,
+    ) -> Result<T, Self::Error> {
+        T::deserialize(payload)
+    }
+}
+
+/// [`Event`] able to register its decoders in a [`Registry`].
+///
+/// > **NOTE**: Implementations of this trait are automatically generated by
+/// >           `#[derive(Event)]` macro for [`Concrete`] structs and enums
+/// >           composed of them, whenever the composed [`Event`]s are
+/// >           [`DeserializeOwned`] and source the state `S`.
+pub trait Register<S> {
+    /// Registers the decoders of all the [`Concrete`] [`Event`]s this
+    /// [`Event`] is composed of (including multiple levels of composition)
+    /// in the provided [`Registry`], prepending the provided `prefix` to
+    /// their [`Name`]s.
+    fn register<F: Format>(registry: &mut Registry<S, F>, prefix: &str);
+}
+
+/// Registry of [`Decoder`]s of stored [`Event`]s by their [`Name`]s and
+/// [`Version`]s.
+pub struct Registry<S, F: Format> {
+    /// [`Decoder`]s by [`Name`]s and [`Version`]s of [`Event`]s, along with
+    /// the types they decode into.
+    decoders: HashMap<String, HashMap<Version, Registered<S, F>>>,
+}
+
+impl<S, F: Format> Registry<S, F> {
+    /// Creates a new empty [`Registry`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self { decoders: HashMap::new() }
+    }
+
+    /// Creates a new [`Registry`] with the decoders of the specified
+    /// [`Event`].
+    #[must_use]
+    pub fn of<Ev: Register<S> + ?Sized>() -> Self {
+        let mut registry = Self::new();
+        _ = registry.register::<Ev>();
+        registry
+    }
+
+    /// Registers the decoders of the specified [`Event`] in this
+    /// [`Registry`].
+    ///
+    /// # Panics
+    ///
+    /// If a decoder of another [`Event`] type is registered already under the
+    /// same [`Name`] and [`Version`].
+    pub fn register<Ev: Register<S> + ?Sized>(&mut self) -> &mut Self {
+        Ev::register(self, "");
+        self
+    }
+
+    /// Registers the decoder of the specified [`Concrete`] [`Event`] under its
+    /// [`Static::NAME`] and [`Static::ALIASES`] prefixed with the provided
+    /// `prefix`, wrapping it into the specified `W` type (like [`Initial`]).
+    ///
+    /// Registering the same `W` type again is a no-op.
+    ///
+    /// # Panics
+    ///
+    /// If a decoder into another type is registered already under the same
+    /// [`Name`] and [`Version`], as silently replacing it would make the
+    /// stored [`Event`]s being decoded into a wrong type.
+    pub fn insert<Ev, W>(&mut self, prefix: &str) -> &mut Self
+    where
+        Ev: Concrete + Revisable<Revision = Version> + DeserializeOwned,
+        W: From<Ev> + Sourcing<Option<S>> + Send + 'static,
+    {
+        let names = iter::once(Ev::NAME).chain(Ev::ALIASES.iter().copied());
+        for name in names {
+            let name = format!("{prefix}{name}");
+            let decoders = self.decoders.entry(name.clone()).or_default();
+            match decoders.entry(Ev::REVISION) {
+                Entry::Vacant(e) => {
+                    _ = e.insert((TypeId::of::<W>(), decode::<S, F, Ev, W>));
+                }
+                Entry::Occupied(e) => assert!(
+                    e.get().0 == TypeId::of::<W>(),
+                    "decoder of `{name}` event of `{}` revision is registered \
+                     already for another type than `{}`",
+                    Ev::REVISION,
+                    type_name::<W>(),
+                ),
+            }
+        }
+        self
+    }
+
+    /// Checks whether this [`Registry`] contains a decoder of the [`Event`]
+    /// with the provided [`Name`] and [`Version`].
+    #[must_use]
+    pub fn contains(&self, name: &str, version: Version) -> bool {
+        self.decoders.get(name).is_some_and(|d| d.contains_key(&version))
+    }
+
+    /// Decodes the provided payload of the [`Event`] with the provided [`Name`]
+    /// and [`Version`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Unknown`] if there is no decoder of the [`Event`] in this
+    ///   [`Registry`].
+    /// - [`Error::Format`] if the payload fails to be decoded.
+    pub fn decode(
+        &self,
+        name: &str,
+        version: Version,
+        payload: &F::Payload,
+    ) -> Result<Decoded<S>, Error<F::Error>> {
+        let (_, decoder) =
+            self.decoders.get(name).and_then(|d| d.get(&version)).ok_or_else(
+                || Error::Unknown { name: name.to_owned(), version },
+            )?;
+        decoder(payload).map_err(Error::Format)
+    }
+}
+
+impl<S, F: Format> Default for Registry<S, F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, F: Format> fmt::Debug for Registry<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut events = self
+            .decoders
+            .iter()
+            .flat_map(|(n, d)| d.keys().map(move |v| format!("{n}@{v}")))
+            .collect::<Vec<_>>();
+        events.sort_unstable();
+        f.debug_struct("Registry").field("events", &events).finish()
+    }
+}
+
+/// Decodes the specified [`Event`] from the provided payload and wraps it into
+/// the specified `W` type.
+fn decode<S, F, Ev, W>(payload: &F::Payload) -> Result<Decoded<S>, F::Error>
+where
+    F: Format,
+    Ev: DeserializeOwned,
+    W: From<Ev> + Sourcing<Option<S>> + Send + 'static,
+{
+    let ev = F::decode::<Ev>(payload)?;
+    Ok(Box::new(W::from(ev)))
+}
+
+/// Error of [`Registry::decode()`]ing an [`Event`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error<E> {
+    /// [`Registry`] has no decoder of the [`Event`].
+    Unknown {
+        /// [`Name`] of the [`Event`].
+        name: String,
+
+        /// [`Version`] of the [`Event`].
+        version: Version,
+    },
+
+    /// Payload of the [`Event`] failed to be decoded.
+    Format(E),
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown { name, version } => {
+                write!(f, "unknown event `{name}` of revision `{version}`")
+            }
+            Self::Format(e) => write!(f, "failed to decode event: {e}"),
+        }
+    }
+}
+
+impl<E: StdError + 'static> StdError for Error<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Unknown { .. } => None,
+            Self::Format(e) => Some(e),
+        }
+    }
+}
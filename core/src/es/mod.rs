@@ -2,7 +2,27 @@
 //!
 //! [Event Sourcing]: https://martinfowler.com/eaaDev/EventSourcing.html
 
+#[cfg(feature = "archive")]
+pub mod archive;
+#[cfg(feature = "cloudevents")]
+pub mod cloudevents;
+#[cfg(feature = "codec")]
+pub mod codec;
 pub mod event;
+#[cfg(feature = "outbox")]
+pub mod outbox;
+#[cfg(feature = "pii")]
+pub mod pii;
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
+pub mod store;
+pub mod stream;
+#[cfg(feature = "subscription")]
+pub mod subscription;
+#[cfg(feature = "temporal")]
+pub mod temporal;
+#[cfg(feature = "tenant")]
+pub mod tenant;
 
 #[doc(inline)]
 pub use self::event::{
@@ -10,4 +30,13 @@ pub use self::event::{
     Name as EventName, Revisable as RevisableEvent, Revision as EventRevision,
     RevisionOf as EventRevisionOf, Sourced as EventSourced,
     Sourcing as EventSourcing, Static as StaticEvent, Version as EventVersion,
+    VersionRange as EventVersionRange,
+};
+#[cfg(feature = "subscription")]
+#[doc(inline)]
+pub use self::subscription::Subscription;
+#[doc(inline)]
+pub use self::{
+    store::Store as EventStore,
+    stream::{Id as StreamId, Stream},
 };
@@ -0,0 +1,423 @@
+//! Transactional outbox for reliable publishing of [`Event`]s to external
+//! message brokers.
+//!
+//! [`Envelope`]s of [`Event`]s are written into an [`Outbox`] in the same
+//! transaction as the [`Event`]s are appended to a [`Store`] (see
+//! [`Transactional`]), and then are drained from the [`Outbox`] to a
+//! [`Publisher`] by a [`Relay`] with at-least-once semantics.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use arcane::es::{
+//! #     Event,
+//! #     outbox::{self, Relay, publisher},
+//! #     store,
+//! #     stream::{self, ExpectedVersion},
+//! # };
+//! #
+//! #[derive(Clone, Debug, Event, serde::Serialize)]
+//! #[event(name = "chat.created", revision = 1)]
+//! struct ChatCreated;
+//!
+//! let mut store = store::Memory::new();
+//! let id = stream::Id::new("chat", "1").unwrap();
+//! outbox::append(
+//!     &mut store,
+//!     &id,
+//!     ExpectedVersion::NoStream,
+//!     vec![ChatCreated],
+//! )
+//! .unwrap();
+//!
+//! let mut relay = Relay::new(publisher::Memory::new());
+//! assert_eq!(relay.drain(&mut store).unwrap(), 1);
+//! assert_eq!(relay.drain(&mut store).unwrap(), 0);
+//!
+//! let published = relay.publisher().published();
+//! assert_eq!(published[0].key, "0");
+//! assert_eq!(published[0].name, "chat.created");
+//! assert_eq!(published[0].revision, "1");
+//! ```
+
+pub mod publisher;
+
+use std::{
+    convert::Infallible, error::Error as StdError, fmt, thread, time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as Json;
+
+#[doc(inline)]
+pub use self::publisher::Publisher;
+use super::{
+    Event, event,
+    store::{self, AppendError, Store, retry::Sleep},
+    stream::{self, ExpectedVersion, Position},
+};
+
+/// [`Event`] prepared for publishing to an external message broker.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Envelope {
+    /// Idempotency key of this [`Envelope`], unique for every [`Event`]
+    /// recorded into a [`Store`] (its stringified [`Envelope::offset`]).
+    ///
+    /// Allows [`Publisher`]s and consumers to deduplicate [`Envelope`]s, as
+    /// they're delivered with at-least-once semantics.
+    pub key: String,
+
+    /// Global offset of the [`Event`] in the log of its [`Store`].
+    pub offset: u64,
+
+    /// [`stream::Id`] of the [`Stream`] the [`Event`] was appended to.
+    ///
+    /// [`Stream`]: stream::Stream
+    pub stream: stream::Id,
+
+    /// [`Position`] of the [`Event`] in its [`Stream`].
+    ///
+    /// [`Stream`]: stream::Stream
+    pub position: Position,
+
+    /// [`event::Name`] of the [`Event`].
+    pub name: String,
+
+    /// Stringified [`event::Revision`] of the [`Event`].
+    pub revision: String,
+
+    /// Serialized [`Event`] itself.
+    pub payload: Json,
+}
+
+/// [`Envelope`] of an [`Event`] not appended to a [`Store`] yet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Draft {
+    /// [`event::Name`] of the [`Event`].
+    pub name: String,
+
+    /// Stringified [`event::Revision`] of the [`Event`].
+    pub revision: String,
+
+    /// Serialized [`Event`] itself.
+    pub payload: Json,
+}
+
+impl Draft {
+    /// Encodes the provided [`Event`] into a [`Draft`].
+    ///
+    /// # Errors
+    ///
+    /// If the provided [`Event`] fails to be serialized.
+    pub fn encode<Ev>(event: &Ev) -> Result<Self, serde_json::Error>
+    where
+        Ev: event::Revisable + Serialize + ?Sized,
+        event::RevisionOf<Ev>: fmt::Display,
+    {
+        Ok(Self {
+            name: event.name().to_owned(),
+            revision: event.revision().to_string(),
+            payload: serde_json::to_value(event)?,
+        })
+    }
+
+    /// Seals this [`Draft`] into an [`Envelope`] of an [`Event`] appended to
+    /// the provided [`stream::Id`] at the provided [`Position`] and global
+    /// `offset`.
+    #[must_use]
+    pub fn seal(
+        self,
+        stream: &stream::Id,
+        position: Position,
+        offset: u64,
+    ) -> Envelope {
+        Envelope {
+            key: offset.to_string(),
+            offset,
+            stream: stream.clone(),
+            position,
+            name: self.name,
+            revision: self.revision,
+            payload: self.payload,
+        }
+    }
+}
+
+/// Table of [`Envelope`]s pending to be published.
+pub trait Outbox {
+    /// Error of this [`Outbox`].
+    type Error;
+
+    /// Returns up to `limit` oldest [`Envelope`]s pending to be published.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Envelope`]s cannot be read.
+    fn pending(&self, limit: usize) -> Result<Vec<Envelope>, Self::Error>;
+
+    /// Acknowledges the [`Envelope`] with the provided idempotency `key` as
+    /// published, so it's not pending anymore.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Envelope`] cannot be acknowledged.
+    fn ack(&mut self, key: &str) -> Result<(), Self::Error>;
+}
+
+/// [`Store`] writing [`Envelope`]s into its [`Outbox`] in the same transaction
+/// as appending [`Event`]s.
+pub trait Transactional<Ev>: Store<Ev> + Outbox {
+    /// Appends the provided [`Event`]s to the end of the [`Stream`] with the
+    /// provided [`stream::Id`], and writes their [`Draft`]s [`Draft::seal`]ed
+    /// into this [`Outbox`] atomically.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Store::append()`]. Nothing is written in this case.
+    ///
+    /// [`Stream`]: stream::Stream
+    fn append_outboxed(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+        events: Vec<(Ev, Draft)>,
+    ) -> Result<Option<Position>, AppendError<<Self as Store<Ev>>::Error>>;
+}
+
+/// Appends the provided [`Event`]s to the provided [`Transactional`] [`Store`]
+/// along with their [`Envelope`]s.
+///
+/// # Errors
+///
+/// - [`AppendError`] if [`Transactional::append_outboxed()`] fails.
+/// - [`Error::Encode`] if any of the [`Event`]s fails to be serialized, before
+///   anything is appended.
+pub fn append<St, Ev>(
+    store: &mut St,
+    stream: &stream::Id,
+    expected: ExpectedVersion,
+    events: Vec<Ev>,
+) -> Result<Option<Position>, Error<<St as Store<Ev>>::Error>>
+where
+    St: Transactional<Ev> + ?Sized,
+    Ev: event::Revisable + Serialize,
+    event::RevisionOf<Ev>: fmt::Display,
+{
+    let events = events
+        .into_iter()
+        .map(|ev| Draft::encode(&ev).map(|d| (ev, d)))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::Encode)?;
+    store.append_outboxed(stream, expected, events).map_err(Error::Append)
+}
+
+/// Error of [`append()`]ing [`Event`]s along with their [`Envelope`]s.
+#[derive(Debug)]
+pub enum Error<E> {
+    /// [`Event`] failed to be serialized.
+    Encode(serde_json::Error),
+
+    /// [`Event`]s failed to be appended.
+    Append(AppendError<E>),
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Encode(e) => write!(f, "failed to encode event: {e}"),
+            Self::Append(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl<E: StdError + 'static> StdError for Error<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Encode(e) => Some(e),
+            Self::Append(e) => Some(e),
+        }
+    }
+}
+
+impl<Ev> Outbox for store::Memory<Ev> {
+    type Error = Infallible;
+
+    fn pending(&self, limit: usize) -> Result<Vec<Envelope>, Self::Error> {
+        Ok(self.outbox.iter().take(limit).cloned().collect())
+    }
+
+    fn ack(&mut self, key: &str) -> Result<(), Self::Error> {
+        self.outbox.retain(|e| e.key != key);
+        Ok(())
+    }
+}
+
+impl<Ev: Clone + Event> Transactional<Ev> for store::Memory<Ev> {
+    fn append_outboxed(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+        events: Vec<(Ev, Draft)>,
+    ) -> Result<Option<Position>, AppendError<<Self as Store<Ev>>::Error>> {
+        let (events, drafts): (Vec<_>, Vec<_>) = events.into_iter().unzip();
+        let mut position = self.version(stream);
+        let from = u64::try_from(self.log.len()).unwrap_or(u64::MAX);
+        let version = self.append(stream, expected, events)?;
+        for (draft, offset) in drafts.into_iter().zip(from..) {
+            let pos = Position::following(position);
+            self.outbox.push_back(draft.seal(stream, pos, offset));
+            position = Some(pos);
+        }
+        Ok(version)
+    }
+}
+
+/// Worker draining [`Envelope`]s from an [`Outbox`] to a [`Publisher`] with
+/// at-least-once semantics.
+///
+/// An [`Envelope`] is acknowledged in the [`Outbox`] only after it has been
+/// published successfully, so it may be published more than once if the
+/// acknowledgement fails. Use [`Envelope::key`]s to deduplicate them.
+///
+/// [`Relay`] is blocking: [`Relay::run()`] blocks the current thread between
+/// polls via [`thread::sleep()`] by default (or via the [`Sleep`] hook set by
+/// [`Relay::sleep_with()`]). In asynchronous contexts, either run it on a
+/// thread allowed to block, or call [`Relay::drain()`] from an own polling
+/// loop.
+#[derive(Clone, Debug)]
+pub struct Relay<P> {
+    /// [`Publisher`] to publish [`Envelope`]s to.
+    publisher: P,
+
+    /// Maximum number of [`Envelope`]s read from an [`Outbox`] at once.
+    batch_size: usize,
+
+    /// [`Sleep`] hook delaying polls in [`Relay::run()`].
+    sleep: Sleep,
+}
+
+impl<P> Relay<P> {
+    /// Creates a new [`Relay`] publishing to the provided [`Publisher`] in
+    /// batches of `100` [`Envelope`]s and sleeping via [`thread::sleep()`].
+    #[must_use]
+    pub const fn new(publisher: P) -> Self {
+        Self { publisher, batch_size: 100, sleep: thread::sleep }
+    }
+
+    /// Sets the maximum number of [`Envelope`]s read from an [`Outbox`] at
+    /// once.
+    #[must_use]
+    pub const fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size;
+        self
+    }
+
+    /// Sets the [`Sleep`] hook delaying polls in [`Relay::run()`].
+    #[must_use]
+    pub const fn sleep_with(mut self, sleep: Sleep) -> Self {
+        self.sleep = sleep;
+        self
+    }
+
+    /// Returns the [`Publisher`] of this [`Relay`].
+    #[must_use]
+    pub const fn publisher(&self) -> &P {
+        &self.publisher
+    }
+
+    /// Returns the [`Publisher`] of this [`Relay`], consuming it.
+    #[must_use]
+    pub fn into_publisher(self) -> P {
+        self.publisher
+    }
+
+    /// Publishes all the [`Envelope`]s pending in the provided [`Outbox`],
+    /// returning the number of the published ones.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Outbox`] or the [`Publisher`] fails. The [`Envelope`]s being
+    /// published at the moment remain pending.
+    pub fn drain<O>(
+        &mut self,
+        outbox: &mut O,
+    ) -> Result<usize, RelayError<O::Error, P::Error>>
+    where
+        O: Outbox + ?Sized,
+        P: Publisher,
+    {
+        let mut count = 0;
+        loop {
+            let batch = outbox
+                .pending(self.batch_size.max(1))
+                .map_err(RelayError::Outbox)?;
+            if batch.is_empty() {
+                return Ok(count);
+            }
+            for envelope in batch {
+                self.publisher
+                    .publish(&envelope)
+                    .map_err(RelayError::Publisher)?;
+                outbox.ack(&envelope.key).map_err(RelayError::Outbox)?;
+                count += 1;
+            }
+        }
+    }
+
+    /// Runs this [`Relay`] in the current thread, [`Relay::drain()`]ing the
+    /// provided [`Outbox`] every `poll_interval`, until `should_stop` returns
+    /// `true`.
+    ///
+    /// # Errors
+    ///
+    /// If [`Relay::drain()`] fails.
+    pub fn run<O>(
+        &mut self,
+        outbox: &mut O,
+        poll_interval: Duration,
+        mut should_stop: impl FnMut() -> bool,
+    ) -> Result<(), RelayError<O::Error, P::Error>>
+    where
+        O: Outbox + ?Sized,
+        P: Publisher,
+    {
+        while !should_stop() {
+            if self.drain(outbox)? == 0 {
+                (self.sleep)(poll_interval);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error of a [`Relay`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RelayError<O, P> {
+    /// [`Outbox`] failed.
+    Outbox(O),
+
+    /// [`Publisher`] failed.
+    Publisher(P),
+}
+
+impl<O: fmt::Display, P: fmt::Display> fmt::Display for RelayError<O, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Outbox(e) => write!(f, "outbox failed: {e}"),
+            Self::Publisher(e) => write!(f, "publisher failed: {e}"),
+        }
+    }
+}
+
+impl<O, P> StdError for RelayError<O, P>
+where
+    O: StdError + 'static,
+    P: StdError + 'static,
+{
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Outbox(e) => Some(e),
+            Self::Publisher(e) => Some(e),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! [`Publisher`]s of [`Envelope`]s to external message brokers.
+
+use std::{
+    collections::HashSet,
+    convert::Infallible,
+    fs,
+    io::{self, BufRead as _, Write as _},
+    path::Path,
+};
+
+use super::Envelope;
+
+/// Publisher of [`Envelope`]s to an external message broker.
+pub trait Publisher {
+    /// Error of this [`Publisher`].
+    type Error;
+
+    /// Publishes the provided [`Envelope`].
+    ///
+    /// May be called several times with the same [`Envelope`], so should
+    /// deduplicate them by their [`Envelope::key`]s, if possible.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Envelope`] cannot be published.
+    fn publish(&mut self, envelope: &Envelope) -> Result<(), Self::Error>;
+}
+
+/// In-memory [`Publisher`], collecting the published [`Envelope`]s.
+///
+/// Deduplicates [`Envelope`]s by their [`Envelope::key`]s.
+#[derive(Clone, Debug, Default)]
+pub struct Memory {
+    /// Published [`Envelope`]s in the order they were published.
+    published: Vec<Envelope>,
+
+    /// [`Envelope::key`]s of the published [`Envelope`]s.
+    keys: HashSet<String>,
+}
+
+impl Memory {
+    /// Creates a new empty [`Memory`] [`Publisher`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the published [`Envelope`]s in the order they were published.
+    #[must_use]
+    pub fn published(&self) -> &[Envelope] {
+        &self.published
+    }
+}
+
+impl Publisher for Memory {
+    type Error = Infallible;
+
+    fn publish(&mut self, envelope: &Envelope) -> Result<(), Self::Error> {
+        if self.keys.insert(envelope.key.clone()) {
+            self.published.push(envelope.clone());
+        }
+        Ok(())
+    }
+}
+
+/// [`Publisher`] appending [`Envelope`]s to a file as JSON lines.
+///
+/// Deduplicates [`Envelope`]s by their [`Envelope::key`]s, including the ones
+/// already written to the file before it was [`File::open()`]ed.
+#[derive(Debug)]
+pub struct File {
+    /// File the [`Envelope`]s are appended to.
+    file: fs::File,
+
+    /// [`Envelope::key`]s of the published [`Envelope`]s.
+    keys: HashSet<String>,
+}
+
+impl File {
+    /// Opens the file at the provided `path` for appending [`Envelope`]s,
+    /// creating it if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be opened, or contains malformed [`Envelope`]s.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+
+        let mut keys = HashSet::new();
+        for line in io::BufReader::new(&file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let envelope: Envelope = serde_json::from_str(&line)?;
+            _ = keys.insert(envelope.key);
+        }
+
+        Ok(Self { file, keys })
+    }
+}
+
+impl Publisher for File {
+    type Error = io::Error;
+
+    fn publish(&mut self, envelope: &Envelope) -> Result<(), Self::Error> {
+        if self.keys.contains(&envelope.key) {
+            return Ok(());
+        }
+        let mut line = serde_json::to_vec(envelope)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.flush()?;
+        _ = self.keys.insert(envelope.key.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+//! [`KeyStore`]s of encryption [`Key`]s of subjects.
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt as _;
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{self, Read as _, Write as _},
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest as _, Sha256};
+
+use crate::util;
+
+/// Encryption [`Key`] of a subject's personal data.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct Key([u8; 32]);
+
+impl Key {
+    /// Generates a new random [`Key`].
+    ///
+    /// # Errors
+    ///
+    /// If the system's random number generator fails.
+    pub fn generate() -> io::Result<Self> {
+        let mut bytes = [0; 32];
+        getrandom::fill(&mut bytes)?;
+        Ok(Self(bytes))
+    }
+
+    /// Creates a [`Key`] out of the provided raw `bytes`.
+    #[must_use]
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Returns raw bytes of this [`Key`].
+    #[must_use]
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+/// Store of [`Key`]s by IDs of the subjects they encrypt personal data of.
+///
+/// Once a [`Key`] is [`KeyStore::shred()`]ded, it's never returned again, and
+/// no new [`Key`] is created for its subject.
+pub trait KeyStore {
+    /// Error of this [`KeyStore`].
+    type Error;
+
+    /// Returns the [`Key`] of the provided `subject`, if it exists and hasn't
+    /// been shredded.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Key`] cannot be read.
+    fn key(&self, subject: &str) -> Result<Option<Key>, Self::Error>;
+
+    /// Returns the [`Key`] of the provided `subject`, generating a new one if
+    /// it doesn't exist yet, or [`None`] if it has been shredded.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Key`] cannot be read or generated.
+    fn create(&mut self, subject: &str) -> Result<Option<Key>, Self::Error>;
+
+    /// Shreds the [`Key`] of the provided `subject`, making its personal data
+    /// undecryptable forever.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Key`] cannot be shredded.
+    fn shred(&mut self, subject: &str) -> Result<(), Self::Error>;
+}
+
+/// In-memory [`KeyStore`].
+#[derive(Clone, Debug, Default)]
+pub struct Memory {
+    /// [`Key`]s by subjects, with [`None`] for the shredded ones.
+    keys: HashMap<String, Option<Key>>,
+}
+
+impl Memory {
+    /// Creates a new empty [`Memory`] [`KeyStore`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl KeyStore for Memory {
+    type Error = io::Error;
+
+    fn key(&self, subject: &str) -> Result<Option<Key>, Self::Error> {
+        Ok(self.keys.get(subject).copied().flatten())
+    }
+
+    fn create(&mut self, subject: &str) -> Result<Option<Key>, Self::Error> {
+        if let Some(key) = self.keys.get(subject) {
+            return Ok(*key);
+        }
+        let key = Key::generate()?;
+        _ = self.keys.insert(subject.to_owned(), Some(key));
+        Ok(Some(key))
+    }
+
+    fn shred(&mut self, subject: &str) -> Result<(), Self::Error> {
+        _ = self.keys.insert(subject.to_owned(), None);
+        Ok(())
+    }
+}
+
+/// [`KeyStore`] keeping every [`Key`] in a separate file of a directory.
+///
+/// Files are named by the [SHA-256] hashes of the subjects, and are readable
+/// by their owner only (on Unix). A new [`Key`] is written into a temporary
+/// file first, and then linked under its name only if no other [`Key`] has
+/// been created for the subject meanwhile, so a crash never leaves a partially
+/// written [`Key`], and concurrent writers never overwrite each other.
+///
+/// Shredding a [`Key`] overwrites its file with zeros and truncates it
+/// durably, so the [`Key`] doesn't linger on disk, while the subject is
+/// remembered as a shredded one.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::pii::{self, KeyStore as _};
+/// #
+/// let dir = std::env::temp_dir()
+///     .join(format!("arcane-pii-keys-{}", std::process::id()));
+/// let mut keys = pii::File::open(&dir).unwrap();
+///
+/// // Subjects of any length are stored.
+/// let subject = "s".repeat(1000);
+/// let key = keys.create(&subject).unwrap().unwrap();
+/// assert_eq!(keys.create(&subject).unwrap(), Some(key));
+/// assert_eq!(
+///     pii::File::open(&dir).unwrap().key(&subject).unwrap(),
+///     Some(key)
+/// );
+///
+/// #[cfg(unix)]
+/// for entry in std::fs::read_dir(&dir).unwrap() {
+///     use std::os::unix::fs::PermissionsExt as _;
+///
+///     let mode = entry.unwrap().metadata().unwrap().permissions().mode();
+///     assert_eq!(mode & 0o777, 0o600);
+/// }
+///
+/// keys.shred(&subject).unwrap();
+/// assert_eq!(keys.key(&subject).unwrap(), None);
+/// assert_eq!(keys.create(&subject).unwrap(), None);
+/// # std::fs::remove_dir_all(dir).unwrap();
+/// ```
+///
+/// [SHA-256]: https://en.wikipedia.org/wiki/SHA-2
+#[derive(Clone, Debug)]
+pub struct File {
+    /// Directory the [`Key`]s are stored in.
+    dir: PathBuf,
+}
+
+impl File {
+    /// Opens the directory at the provided `path` for storing [`Key`]s,
+    /// creating it if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// If the directory cannot be created.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = path.as_ref().to_owned();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Returns the path of the file storing the [`Key`] of the provided
+    /// `subject`.
+    fn path(&self, subject: &str) -> PathBuf {
+        let hash = Sha256::digest(subject.as_bytes());
+        self.dir.join(format!("{}.key", util::hex(&hash)))
+    }
+
+    /// Writes the provided [`Key`] into the provided `path` durably, unless a
+    /// file exists there already.
+    ///
+    /// # Errors
+    ///
+    /// - [`io::ErrorKind::AlreadyExists`] if the file exists already.
+    /// - If the file cannot be written.
+    fn write_new(&self, path: &Path, key: &Key) -> io::Result<()> {
+        let mut suffix = [0; 8];
+        getrandom::fill(&mut suffix)?;
+        let tmp = path.with_extension(format!("{}.tmp", util::hex(&suffix)));
+
+        let mut options = fs::OpenOptions::new();
+        _ = options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            _ = options.mode(0o600);
+        }
+        let written = options.open(&tmp).and_then(|mut file| {
+            file.write_all(key.as_bytes())?;
+            file.sync_all()
+        });
+        // Unlike renaming, linking never replaces an existing file.
+        let linked = written.and_then(|()| fs::hard_link(&tmp, path));
+        drop(fs::remove_file(&tmp));
+        linked?;
+
+        #[cfg(unix)]
+        fs::File::open(&self.dir)?.sync_all()?;
+        Ok(())
+    }
+
+    /// Reads the [`Stored`] [`Key`] of the provided `subject`.
+    fn read(&self, subject: &str) -> io::Result<Stored> {
+        let bytes = match fs::read(self.path(subject)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Stored::Absent);
+            }
+            Err(e) => return Err(e),
+        };
+        if bytes.is_empty() {
+            return Ok(Stored::Shredded);
+        }
+        let key = <[u8; 32]>::try_from(bytes).ok().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "malformed key file")
+        })?;
+        Ok(Stored::Active(Key(key)))
+    }
+}
+
+/// State of a [`Key`] stored in a [`File`] [`KeyStore`].
+#[derive(Clone, Copy, Debug)]
+enum Stored {
+    /// [`Key`] doesn't exist.
+    Absent,
+
+    /// [`Key`] exists.
+    Active(Key),
+
+    /// [`Key`] has been shredded.
+    Shredded,
+}
+
+impl KeyStore for File {
+    type Error = io::Error;
+
+    fn key(&self, subject: &str) -> Result<Option<Key>, Self::Error> {
+        Ok(match self.read(subject)? {
+            Stored::Active(key) => Some(key),
+            Stored::Absent | Stored::Shredded => None,
+        })
+    }
+
+    fn create(&mut self, subject: &str) -> Result<Option<Key>, Self::Error> {
+        match self.read(subject)? {
+            Stored::Active(key) => return Ok(Some(key)),
+            Stored::Shredded => return Ok(None),
+            Stored::Absent => {}
+        }
+        let key = Key::generate()?;
+        match self.write_new(&self.path(subject), &key) {
+            Ok(()) => Ok(Some(key)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                self.key(subject)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn shred(&mut self, subject: &str) -> Result<(), Self::Error> {
+        let mut options = fs::OpenOptions::new();
+        _ = options.write(true).create(true).truncate(false);
+        #[cfg(unix)]
+        {
+            _ = options.mode(0o600);
+        }
+        let mut file = options.open(self.path(subject))?;
+
+        // Overwritten in place first, so the truncated blocks don't keep it.
+        let len = file.metadata()?.len();
+        _ = io::copy(&mut io::repeat(0).take(len), &mut file)?;
+        file.sync_all()?;
+        file.set_len(0)?;
+        file.sync_all()?;
+
+        #[cfg(unix)]
+        fs::File::open(&self.dir)?.sync_all()?;
+        Ok(())
+    }
+}
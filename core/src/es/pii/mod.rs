@@ -0,0 +1,409 @@
+//! Encryption of personal data in [`Event`]s, allowing to "forget" it by
+//! [crypto-shredding].
+//!
+//! Fields of structs deriving `#[derive(Event)]` marked with
+//! `#[event(personal)]` (or `#[event(pii)]`) attribute are [`encrypt()`]ed with
+//! a [`Key`] of the subject the [`Event`] is about (the field marked with
+//! `#[event(subject)]` attribute), stored in a [`KeyStore`]. Once the [`Key`]
+//! is [`KeyStore::shred()`]ded, the encrypted fields cannot be decrypted
+//! anymore, so they're [`decrypt()`]ed into redacted placeholder values:
+//! - the [`Default`] value of the field type;
+//! - or the value returned by the function specified via
+//!   `#[event(personal, redacted = "path::to::fn")]` attribute.
+//!
+//! Personal fields are addressed by their serialized names, respecting
+//! `#[serde(rename)]` and `#[serde(rename_all)]` attributes, and should always
+//! be serialized, so [`encrypt()`] never leaves them in plaintext.
+//!
+//! With `codec` feature, `pii::Encrypted` encrypts personal fields
+//! transparently while encoding [`Event`]s with a `codec::Codec`.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use arcane::es::{Event, pii::{self, KeyStore as _}};
+//! # use serde::{Deserialize, Serialize};
+//! #
+//! #[derive(Debug, Deserialize, Event, PartialEq, Serialize)]
+//! #[event(name = "user.registered", revision = 1)]
+//! struct UserRegistered {
+//!     #[event(subject)]
+//!     user_id: String,
+//!     #[event(personal)]
+//!     email: String,
+//!     #[event(pii, redacted = "forgotten")]
+//!     name: String,
+//!     plan: String,
+//! }
+//!
+//! fn forgotten() -> String {
+//!     "<forgotten>".into()
+//! }
+//!
+//! let mut keys = pii::Memory::new();
+//! let ev = UserRegistered {
+//!     user_id: "u-1".into(),
+//!     email: "alice@example.com".into(),
+//!     name: "Alice".into(),
+//!     plan: "pro".into(),
+//! };
+//!
+//! let stored = pii::encrypt(&mut keys, &ev).unwrap();
+//! assert_eq!(stored["plan"], "pro");
+//! assert!(!stored.to_string().contains("alice@example.com"));
+//! assert_eq!(
+//!     pii::decrypt::<UserRegistered, _>(&keys, stored.clone()).unwrap(),
+//!     ev,
+//! );
+//!
+//! keys.shred("u-1").unwrap();
+//! assert_eq!(
+//!     pii::decrypt::<UserRegistered, _>(&keys, stored).unwrap(),
+//!     UserRegistered {
+//!         user_id: "u-1".into(),
+//!         email: String::new(),
+//!         name: "<forgotten>".into(),
+//!         plan: "pro".into(),
+//!     },
+//! );
+//! ```
+//!
+//! [crypto-shredding]: https://en.wikipedia.org/wiki/Crypto-shredding
+
+pub mod keys;
+
+use std::{error::Error as StdError, fmt, io};
+
+use chacha20poly1305::{
+    ChaCha20Poly1305, KeyInit as _, Nonce,
+    aead::{Aead as _, Payload},
+};
+use serde::{Serialize, de::DeserializeOwned};
+#[doc(hidden)]
+pub use serde_json;
+use serde_json::{Map, Value as Json};
+
+#[doc(inline)]
+pub use self::keys::{File, Key, KeyStore, Memory};
+#[cfg(doc)]
+use super::Event;
+#[cfg(feature = "codec")]
+use super::{
+    codec::{self, Codec},
+    event::Version,
+};
+use crate::util;
+
+/// Name of the JSON object member holding the ciphertext of an encrypted
+/// field.
+pub const CIPHERTEXT: &str = "$pii";
+
+/// Name of the JSON object member holding the subject of an encrypted field.
+pub const SUBJECT: &str = "$subject";
+
+/// Length of a nonce prepended to a ciphertext.
+const NONCE_LEN: usize = 12;
+
+/// [`Event`] containing personal data of a single subject.
+///
+/// > **NOTE**: Implementations of this trait are automatically generated by
+/// >           `#[derive(Event)]` macro for structs having fields marked with
+/// >           `#[event(personal)]` attribute.
+pub trait Personal {
+    /// Names of the fields containing personal data.
+    const FIELDS: &'static [&'static str];
+
+    /// Returns the ID of the subject the personal data belongs to.
+    fn subject(&self) -> String;
+
+    /// Returns the redacted placeholder value of the provided personal
+    /// `field`, used once the [`Key`] of its subject is shredded.
+    fn redacted(field: &str) -> Json;
+}
+
+/// Serializes the provided [`Event`] into JSON, encrypting its
+/// [`Personal::FIELDS`] with the [`Key`] of its [`Personal::subject()`].
+///
+/// # Errors
+///
+/// - [`Error::Shredded`] if the [`Key`] of the subject has been shredded.
+/// - [`Error::Keys`] if the [`KeyStore`] fails.
+/// - [`Error::Json`] if the [`Event`] fails to be serialized.
+/// - [`Error::Missing`] if any of [`Personal::FIELDS`] is not serialized.
+/// - [`Error::Random`] if a nonce fails to be generated.
+pub fn encrypt<Ev, K>(keys: &mut K, event: &Ev) -> Result<Json, Error<K::Error>>
+where
+    Ev: Personal + Serialize + ?Sized,
+    K: KeyStore + ?Sized,
+{
+    let mut json = serde_json::to_value(event).map_err(Error::Json)?;
+    let subject = event.subject();
+    let key = keys
+        .create(&subject)
+        .map_err(Error::Keys)?
+        .ok_or_else(|| Error::Shredded { subject: subject.clone() })?;
+    let cipher = ChaCha20Poly1305::new(key.as_bytes().into());
+
+    for &field in Ev::FIELDS {
+        let value = json.get_mut(field).ok_or(Error::Missing { field })?;
+        let plaintext = serde_json::to_vec(value).map_err(Error::Json)?;
+
+        let mut nonce = [0; NONCE_LEN];
+        getrandom::fill(&mut nonce).map_err(|e| Error::Random(e.into()))?;
+        let aad = aad(&subject, field);
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload { msg: &plaintext, aad: aad.as_bytes() },
+            )
+            .ok()
+            .ok_or(Error::Malformed { field })?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        *value = Json::Object(Map::from_iter([
+            (CIPHERTEXT.into(), util::hex(&sealed).into()),
+            (SUBJECT.into(), subject.clone().into()),
+        ]));
+    }
+    Ok(json)
+}
+
+/// Deserializes an [`Event`] from the provided JSON, decrypting its
+/// [`Personal::FIELDS`] with the [`Key`]s of their subjects.
+///
+/// Fields whose [`Key`] has been shredded are replaced with their
+/// [`Personal::redacted()`] values. Fields not being encrypted are left as is.
+///
+/// # Errors
+///
+/// - [`Error::Keys`] if the [`KeyStore`] fails.
+/// - [`Error::Malformed`] if an encrypted field cannot be decrypted with the
+///   [`Key`] of its subject.
+/// - [`Error::Json`] if the [`Event`] fails to be deserialized.
+pub fn decrypt<Ev, K>(keys: &K, mut json: Json) -> Result<Ev, Error<K::Error>>
+where
+    Ev: Personal + DeserializeOwned,
+    K: KeyStore + ?Sized,
+{
+    for &field in Ev::FIELDS {
+        let Some(value) = json.get_mut(field) else {
+            continue;
+        };
+        let (Some(sealed), Some(subject)) = (
+            value.get(CIPHERTEXT).and_then(Json::as_str),
+            value.get(SUBJECT).and_then(Json::as_str),
+        ) else {
+            continue;
+        };
+
+        let Some(key) = keys.key(subject).map_err(Error::Keys)? else {
+            *value = Ev::redacted(field);
+            continue;
+        };
+        let cipher = ChaCha20Poly1305::new(key.as_bytes().into());
+
+        let sealed = util::unhex(sealed).ok_or(Error::Malformed { field })?;
+        if sealed.len() < NONCE_LEN {
+            return Err(Error::Malformed { field });
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let aad = aad(subject, field);
+        let plaintext = cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload { msg: ciphertext, aad: aad.as_bytes() },
+            )
+            .ok()
+            .ok_or(Error::Malformed { field })?;
+        *value = serde_json::from_slice(&plaintext).map_err(Error::Json)?;
+    }
+    serde_json::from_value(json).map_err(Error::Json)
+}
+
+/// [`Codec`] wrapper [`encrypt()`]ing personal data of [`Event`]s with the
+/// [`Key`]s of its [`KeyStore`] before encoding, and [`decrypt()`]ing it after
+/// decoding.
+///
+/// Encrypted [`Event`]s are encoded as JSON values, so the wrapped [`Codec`]
+/// should use a self-describing [`codec::Format`].
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::{Event, codec::{Codecs, Format}, event, pii};
+/// # use serde::{Deserialize, Serialize};
+/// #
+/// #[derive(Debug, Deserialize, Event, PartialEq, Serialize)]
+/// #[event(name = "user.registered", revision = 1)]
+/// #[serde(rename_all = "camelCase")]
+/// struct UserRegistered {
+///     #[event(subject)]
+///     user_id: String,
+///     #[event(personal)]
+///     home_address: String,
+/// }
+///
+/// let v1 = event::Version::try_new(1).unwrap();
+/// let ev = UserRegistered {
+///     user_id: "u-1".into(),
+///     home_address: "Baker Street".into(),
+/// };
+///
+/// let mut codec =
+///     pii::Encrypted::new(Codecs::new(Format::Json), pii::Memory::new());
+/// let bytes = codec.encode("user.registered", v1, &ev).unwrap();
+/// assert!(!String::from_utf8_lossy(&bytes).contains("Baker"));
+///
+/// let decoded = codec.decode("user.registered", v1, &bytes).unwrap();
+/// assert_eq!(ev, decoded);
+/// ```
+#[cfg(feature = "codec")]
+#[derive(Clone, Debug, Default)]
+pub struct Encrypted<C, K> {
+    /// Wrapped [`Codec`].
+    codec: C,
+
+    /// [`KeyStore`] of the [`Key`]s.
+    keys: K,
+}
+
+#[cfg(feature = "codec")]
+impl<C, K> Encrypted<C, K> {
+    /// Wraps the provided [`Codec`] to encrypt personal data with the [`Key`]s
+    /// of the provided [`KeyStore`].
+    #[must_use]
+    pub const fn new(codec: C, keys: K) -> Self {
+        Self { codec, keys }
+    }
+
+    /// Returns the [`KeyStore`] of these [`Encrypted`].
+    #[must_use]
+    pub const fn keys(&self) -> &K {
+        &self.keys
+    }
+
+    /// Returns the mutable [`KeyStore`] of these [`Encrypted`], allowing to
+    /// [`KeyStore::shred()`] [`Key`]s.
+    pub const fn keys_mut(&mut self) -> &mut K {
+        &mut self.keys
+    }
+}
+
+#[cfg(feature = "codec")]
+impl<C: Codec, K: KeyStore> Encrypted<C, K> {
+    /// [`encrypt()`]s the provided [`Event`] and encodes it with the wrapped
+    /// [`Codec`].
+    ///
+    /// # Errors
+    ///
+    /// - If the [`Event`] fails to be [`encrypt()`]ed.
+    /// - [`Error::Codec`] if the encrypted [`Event`] fails to be encoded.
+    pub fn encode<Ev: Personal + Serialize + ?Sized>(
+        &mut self,
+        name: &str,
+        version: Version,
+        event: &Ev,
+    ) -> Result<Vec<u8>, Error<K::Error>> {
+        let json = encrypt(&mut self.keys, event)?;
+        self.codec.encode(name, version, &json).map_err(Error::Codec)
+    }
+
+    /// Decodes the [`Event`] from the provided `bytes` with the wrapped
+    /// [`Codec`] and [`decrypt()`]s it.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Codec`] if the `bytes` fail to be decoded.
+    /// - If the [`Event`] fails to be [`decrypt()`]ed.
+    pub fn decode<Ev: Personal + DeserializeOwned>(
+        &self,
+        name: &str,
+        version: Version,
+        bytes: &[u8],
+    ) -> Result<Ev, Error<K::Error>> {
+        let json = self
+            .codec
+            .decode::<Json>(name, version, bytes)
+            .map_err(Error::Codec)?;
+        decrypt(&self.keys, json)
+    }
+}
+
+/// Returns the additional authenticated data binding a ciphertext to the
+/// provided `subject` and `field`.
+fn aad(subject: &str, field: &str) -> String {
+    format!("{subject}\0{field}")
+}
+
+/// Error of [`encrypt()`]ing or [`decrypt()`]ing an [`Event`].
+#[derive(Debug)]
+pub enum Error<E> {
+    /// [`KeyStore`] failed.
+    Keys(E),
+
+    /// [`Key`] of the subject has been shredded, so no new personal data can
+    /// be encrypted for it.
+    Shredded {
+        /// ID of the subject.
+        subject: String,
+    },
+
+    /// Encrypted field cannot be decrypted with the [`Key`] of its subject.
+    Malformed {
+        /// Name of the field.
+        field: &'static str,
+    },
+
+    /// [`Event`] failed to be (de)serialized.
+    Json(serde_json::Error),
+
+    /// Personal field is absent in the serialized [`Event`].
+    Missing {
+        /// Name of the field.
+        field: &'static str,
+    },
+
+    /// Encrypted [`Event`] failed to be encoded or decoded.
+    #[cfg(feature = "codec")]
+    Codec(codec::Error),
+
+    /// Nonce failed to be generated.
+    Random(io::Error),
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Keys(e) => write!(f, "key store failed: {e}"),
+            Self::Shredded { subject } => {
+                write!(f, "key of subject `{subject}` has been shredded")
+            }
+            Self::Malformed { field } => {
+                write!(f, "field `{field}` cannot be decrypted")
+            }
+            Self::Json(e) => write!(f, "failed to (de)serialize event: {e}"),
+            Self::Missing { field } => {
+                write!(f, "personal field `{field}` is not serialized")
+            }
+            #[cfg(feature = "codec")]
+            Self::Codec(e) => write!(f, "failed to encode event: {e}"),
+            Self::Random(e) => write!(f, "failed to generate nonce: {e}"),
+        }
+    }
+}
+
+impl<E: StdError + 'static> StdError for Error<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Keys(e) => Some(e),
+            Self::Json(e) => Some(e),
+            Self::Random(e) => Some(e),
+            #[cfg(feature = "codec")]
+            Self::Codec(e) => Some(e),
+            Self::Shredded { .. }
+            | Self::Malformed { .. }
+            | Self::Missing { .. } => None,
+        }
+    }
+}
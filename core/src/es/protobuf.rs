@@ -0,0 +1,340 @@
+//! [Protocol Buffers] schemas and encoding of [`Event`]s.
+//!
+//! Structs and enums deriving `#[derive(Event)]` with `#[event(proto)]`
+//! attribute implement [`Proto`], so the same Rust type definitions are the
+//! source of truth for consumers in all languages:
+//! - a struct becomes a `message` with its [`event::Name`] and
+//!   [`event::Revision`] as the `(arcane.event_name)` and
+//!   `(arcane.event_revision)` options (see [`OPTIONS`]), and implements
+//!   [`prost::Message`], so it's encoded compatibly with [`prost`];
+//! - an enum becomes an envelope `message` with a single `oneof event` field
+//!   of its variants.
+//!
+//! Fields of structs and variants of enums are numbered explicitly with
+//! `#[event(proto(tag = N))]` attributes, which are required, so reordering
+//! them never changes the wire format. Tags should never be reused, once the
+//! field or variant is removed.
+//!
+//! Fields are mapped as follows:
+//! - [`String`] to `string`;
+//! - [`Vec`]`<`[`u8`]`>` to `bytes`;
+//! - [`bool`], [`i32`], [`i64`], [`u32`], [`u64`], [`f32`], [`f64`] to `bool`,
+//!   `int32`, `int64`, `uint32`, `uint64`, `float`, `double` respectively;
+//! - [`Option`]`<T>` to `optional T`;
+//! - [`Vec`]`<T>` to `repeated T`;
+//! - any other non-generic type `T` to the message `T`.
+//!
+//! > **NOTE**: Types of message fields should implement [`prost::Message`]
+//! >           and [`Default`], and should be defined in `.proto` files
+//! >           separately.
+//!
+//! With `codec` feature, `protobuf::Message` wraps a [`Proto`] event to be
+//! encoded by any `codec::Codec`, so its payloads are tagged and compressed
+//! as usual.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use arcane::es::{Event, protobuf::{self, Proto as _}};
+//! #
+//! #[derive(Debug, Event, PartialEq)]
+//! #[event(name = "chat.created", revision = 1, proto)]
+//! struct ChatCreated;
+//!
+//! #[derive(Debug, Event, PartialEq)]
+//! #[event(name = "message.posted", revision = 2, proto)]
+//! struct MessagePosted {
+//!     #[event(proto(tag = 1))]
+//!     text: String,
+//!     #[event(proto(tag = 3))]
+//!     tags: Vec<String>,
+//!     #[event(proto(tag = 2))]
+//!     edited_at: Option<u64>,
+//! }
+//!
+//! #[derive(Debug, Event, PartialEq)]
+//! #[event(proto)]
+//! enum ChatEvent {
+//!     #[event(init, proto(tag = 1))]
+//!     Created(ChatCreated),
+//!     #[event(proto(tag = 2))]
+//!     Posted(MessagePosted),
+//! }
+//!
+//! assert_eq!(
+//!     protobuf::file::<ChatEvent>("chat.v1"),
+//!     r#"syntax = "proto3";
+//!
+//! package chat.v1;
+//!
+//! import "arcane/options.proto";
+//!
+//! message ChatCreated {
+//!   option (arcane.event_name) = "chat.created";
+//!   option (arcane.event_revision) = 1;
+//! }
+//!
+//! message MessagePosted {
+//!   option (arcane.event_name) = "message.posted";
+//!   option (arcane.event_revision) = 2;
+//!   string text = 1;
+//!   repeated string tags = 3;
+//!   optional uint64 edited_at = 2;
+//! }
+//!
+//! message ChatEvent {
+//!   oneof event {
+//!     ChatCreated created = 1;
+//!     MessagePosted posted = 2;
+//!   }
+//! }
+//! "#,
+//! );
+//!
+//! let ev = ChatEvent::Posted(MessagePosted {
+//!     text: "hello".into(),
+//!     tags: vec!["greeting".into()],
+//!     edited_at: None,
+//! });
+//! let bytes = ev.to_proto();
+//! assert_eq!(ChatEvent::from_proto(&bytes).unwrap(), ev);
+//!
+//! # #[cfg(feature = "codec")]
+//! # {
+//! # use arcane::es::{codec::{Codec as _, Codecs, Format}, event};
+//! #
+//! let v1 = event::Version::try_new(1).unwrap();
+//! let codecs = Codecs::new(Format::Json);
+//! let bytes =
+//!     codecs.encode("message.posted", v1, &protobuf::Message(ev)).unwrap();
+//! let decoded = codecs
+//!     .decode::<protobuf::Message<ChatEvent>>("message.posted", v1, &bytes)
+//!     .unwrap();
+//! assert!(matches!(decoded.0, ChatEvent::Posted(_)));
+//! # }
+//! ```
+//!
+//! [Protocol Buffers]: https://protobuf.dev
+
+use std::fmt::Write as _;
+#[cfg(feature = "codec")]
+use std::{fmt, marker::PhantomData};
+
+#[doc(hidden)]
+pub use prost;
+use prost::{
+    DecodeError,
+    bytes::Buf as _,
+    encoding::{
+        DecodeContext, WireType, decode_key, decode_varint, encode_key,
+        encode_varint, skip_field,
+    },
+};
+#[cfg(feature = "codec")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+#[cfg(all(doc, feature = "codec"))]
+use super::codec::Codec;
+#[cfg(doc)]
+use super::{Event, event};
+
+/// Path of the `.proto` file with the [`OPTIONS`].
+pub const OPTIONS_PATH: &str = "arcane/options.proto";
+
+/// Contents of the `.proto` file declaring the custom options of messages
+/// generated for [`Event`]s.
+pub const OPTIONS: &str = r#"syntax = "proto3";
+
+package arcane;
+
+import "google/protobuf/descriptor.proto";
+
+extend google.protobuf.MessageOptions {
+  string event_name = 50501;
+  uint32 event_revision = 50502;
+}
+"#;
+
+/// [`Event`] having a [Protocol Buffers] message representation.
+///
+/// > **NOTE**: Implementations of this trait are automatically generated by
+/// >           `#[derive(Event)]` macro with `#[event(proto)]` attribute.
+///
+/// [Protocol Buffers]: https://protobuf.dev
+pub trait Proto: Sized {
+    /// Name of the message of this [`Event`].
+    const MESSAGE: &'static str;
+
+    /// Pushes definitions of the message of this [`Event`] (along with the
+    /// ones of the [`Event`]s it's composed of) into the provided `defs`,
+    /// unless they're present there already.
+    fn definitions(defs: &mut Vec<String>);
+
+    /// Encodes this [`Event`] into its message.
+    #[must_use]
+    fn to_proto(&self) -> Vec<u8>;
+
+    /// Decodes an [`Event`] from its message.
+    ///
+    /// # Errors
+    ///
+    /// If the provided `bytes` are not a valid message of this [`Event`].
+    fn from_proto(bytes: &[u8]) -> Result<Self, DecodeError>;
+}
+
+/// Renders the `.proto` file of the specified [`Event`] in the provided
+/// `package`.
+#[must_use]
+pub fn file<Ev: Proto>(package: &str) -> String {
+    let mut defs = vec![];
+    Ev::definitions(&mut defs);
+
+    let mut file = format!(
+        "syntax = \"proto3\";\n\npackage {package};\n\n\
+         import \"{OPTIONS_PATH}\";\n",
+    );
+    for def in defs {
+        file.push('\n');
+        file.push_str(&def);
+    }
+    file
+}
+
+/// Pushes the provided message `definition` into the provided `defs`, unless
+/// it's present there already.
+///
+/// Used by the [`Proto`] implementations generated by `#[derive(Event)]`
+/// macro.
+pub fn define(defs: &mut Vec<String>, definition: impl Into<String>) {
+    let definition = definition.into();
+    if !defs.contains(&definition) {
+        defs.push(definition);
+    }
+}
+
+/// Pushes the definition of an envelope `message` into the provided `defs`,
+/// unless it's present there already.
+///
+/// The envelope `message` has the provided name and a single `oneof event`
+/// of the provided `(message, field, tag)` `variants`.
+///
+/// Used by the [`Proto`] implementations of enums generated by
+/// `#[derive(Event)]` macro.
+pub fn define_oneof(
+    defs: &mut Vec<String>,
+    message: &str,
+    variants: &[(&str, &str, u32)],
+) {
+    let mut def = format!("message {message} {{\n  oneof event {{\n");
+    for (ty, field, tag) in variants {
+        _ = writeln!(def, "    {ty} {field} = {tag};");
+    }
+    def.push_str("  }\n}\n");
+    define(defs, def);
+}
+
+/// Encodes the provided `message` as the `oneof` field with the provided
+/// `tag`.
+///
+/// Used by the [`Proto`] implementations of enums generated by
+/// `#[derive(Event)]` macro.
+#[must_use]
+pub fn encode_oneof(tag: u32, message: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(message.len().saturating_add(10));
+    encode_key(tag, WireType::LengthDelimited, &mut buf);
+    encode_varint(u64::try_from(message.len()).unwrap_or(u64::MAX), &mut buf);
+    buf.extend_from_slice(message);
+    buf
+}
+
+/// Decodes a `oneof` field out of the provided `bytes`, with the provided
+/// `variant` function decoding the message of a known tag.
+///
+/// Fields of unknown tags are skipped, and the last field of a known tag wins.
+///
+/// Used by the [`Proto`] implementations of enums generated by
+/// `#[derive(Event)]` macro.
+///
+/// # Errors
+///
+/// - If the `bytes` are not a valid message.
+/// - If the `bytes` contain no field of a known tag.
+pub fn decode_oneof<T>(
+    mut bytes: &[u8],
+    variant: impl Fn(u32, &[u8]) -> Option<Result<T, DecodeError>>,
+) -> Result<T, DecodeError> {
+    let mut decoded = None;
+    while bytes.has_remaining() {
+        let (tag, wire_type) = decode_key(&mut bytes)?;
+        if wire_type == WireType::LengthDelimited {
+            let len = usize::try_from(decode_varint(&mut bytes)?)
+                .ok()
+                .filter(|l| *l <= bytes.len())
+                .ok_or_else(|| DecodeError::new("buffer underflow"))?;
+            let (message, rest) = bytes.split_at(len);
+            bytes = rest;
+            if let Some(ev) = variant(tag, message) {
+                decoded = Some(ev?);
+            }
+        } else {
+            skip_field(wire_type, tag, &mut bytes, DecodeContext::default())?;
+        }
+    }
+    decoded.ok_or_else(|| DecodeError::new("no `oneof event` field is set"))
+}
+
+/// [`Proto`] [`Event`] (de)serialized as the bytes of its message, so it may
+/// be encoded by any [`Codec`].
+///
+/// > **NOTE**: Self-describing text formats (like JSON) represent the bytes as
+/// >           an array of numbers, so binary formats are preferred.
+#[cfg(feature = "codec")]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Message<Ev>(pub Ev);
+
+#[cfg(feature = "codec")]
+impl<Ev: Proto> Serialize for Message<Ev> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0.to_proto())
+    }
+}
+
+#[cfg(feature = "codec")]
+impl<'de, Ev: Proto> Deserialize<'de> for Message<Ev> {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        deserializer.deserialize_bytes(MessageVisitor(PhantomData))
+    }
+}
+
+/// [`de::Visitor`] of a [`Message`].
+#[cfg(feature = "codec")]
+struct MessageVisitor<Ev>(PhantomData<Ev>);
+
+#[cfg(feature = "codec")]
+impl<'de, Ev: Proto> de::Visitor<'de> for MessageVisitor<Ev> {
+    type Value = Message<Ev>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "bytes of `{}` message", Ev::MESSAGE)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ev::from_proto(v).map(Message).map_err(E::custom)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(b) = seq.next_element()? {
+            bytes.push(b);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
@@ -0,0 +1,334 @@
+//! [`Frame`]s of segment files.
+//!
+//! Every segment file starts with the [`MAGIC`] bytes identifying the version
+//! of its format, followed by its [`Frame`]s, each laid out as:
+//! - length of its body (`u32`, little-endian);
+//! - [CRC-32] of its body (`u32`, little-endian);
+//! - body itself, starting with its kind (`u8`) and timestamp (microseconds
+//!   since [`UNIX_EPOCH`] as `u64`), followed by the [`stream::Id`] and, for
+//!   [`Event`]s only, their [`Position`], offset in the global log (`u64`),
+//!   [`event::Version`], [`event::Name`], causation and correlation ids of
+//!   their [`Metadata`] and payload, or, for truncations only, the
+//!   [`Position`] the [`Stream`] is truncated before.
+//!
+//! Strings are prefixed with their length (`u16`), optional strings are
+//! additionally prefixed with their presence (`u8`), and the payload occupies
+//! the rest of the body.
+//!
+//! [`Event`]: crate::es::Event
+//! [CRC-32]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+//! [`Position`]: stream::Position
+//! [`Stream`]: stream::Stream
+
+use std::{
+    io,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{Error, Record};
+use crate::{
+    es::{
+        event,
+        store::{Metadata, TOMBSTONE},
+        stream,
+    },
+    util::micros,
+};
+
+/// Magic bytes starting every segment file, identifying the version of its
+/// format.
+pub(super) const MAGIC: &[u8; 8] = b"ARCSEG01";
+
+/// Length of the [`MAGIC`] bytes.
+pub(super) const MAGIC_LEN: u64 = 8;
+
+/// Length of a [`Frame`] header.
+pub(super) const HEADER_LEN: u64 = 8;
+
+/// Kind of an event [`Frame`] followed by other ones of the same append.
+const EVENT: u8 = 0;
+
+/// Kind of an event [`Frame`] completing its append.
+const EVENT_LAST: u8 = 1;
+
+/// Kind of a [`Frame`] marking a deleted [`Stream`].
+///
+/// [`Stream`]: stream::Stream
+const DELETED: u8 = 2;
+
+/// Kind of a [`Frame`] truncating a [`Stream`].
+///
+/// [`Stream`]: stream::Stream
+const TRUNCATED: u8 = 3;
+
+/// Frame of a segment file.
+#[derive(Clone, Debug)]
+pub(super) enum Frame {
+    /// Event [`Record`].
+    Event {
+        /// [`Record`] itself.
+        record: Record,
+
+        /// Indicator whether this [`Frame`] completes its append.
+        last: bool,
+    },
+
+    /// Marker of a deleted [`Stream`].
+    ///
+    /// [`Stream`]: stream::Stream
+    Deleted {
+        /// [`stream::Id`] of the deleted [`Stream`].
+        ///
+        /// [`Stream`]: stream::Stream
+        stream: stream::Id,
+
+        /// Time the [`Stream`] was deleted at.
+        ///
+        /// [`Stream`]: stream::Stream
+        timestamp: SystemTime,
+    },
+
+    /// Truncation of a [`Stream`].
+    ///
+    /// [`Stream`]: stream::Stream
+    Truncated {
+        /// [`stream::Id`] of the truncated [`Stream`].
+        ///
+        /// [`Stream`]: stream::Stream
+        stream: stream::Id,
+
+        /// [`stream::Position`] the [`Stream`] is truncated before.
+        ///
+        /// [`Stream`]: stream::Stream
+        before: stream::Position,
+
+        /// Time the [`Stream`] was truncated at.
+        ///
+        /// [`Stream`]: stream::Stream
+        timestamp: SystemTime,
+    },
+}
+
+impl Frame {
+    /// Indicates whether this [`Frame`] completes its append.
+    pub(super) const fn is_last(&self) -> bool {
+        match self {
+            Self::Event { last, .. } => *last,
+            Self::Deleted { .. } | Self::Truncated { .. } => true,
+        }
+    }
+
+    /// Indicates whether this [`Frame`] is a tombstone [`Record`].
+    pub(super) fn is_tombstone(&self) -> bool {
+        match self {
+            Self::Event { record, .. } => record.name == TOMBSTONE,
+            Self::Deleted { .. } | Self::Truncated { .. } => false,
+        }
+    }
+
+    /// Encodes this [`Frame`] to the end of the provided `out`put.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Oversized`] if any part of this [`Frame`] doesn't fit into its
+    /// length prefix.
+    pub(super) fn encode(&self, out: &mut Vec<u8>) -> Result<(), Error> {
+        let mut body = Vec::new();
+        match self {
+            Self::Event { record, last } => {
+                body.push(if *last { EVENT_LAST } else { EVENT });
+                body.extend(micros(record.timestamp).to_le_bytes());
+                put_str(&mut body, &record.stream.to_string())?;
+                body.extend(record.position.get().to_le_bytes());
+                body.extend(record.offset.to_le_bytes());
+                body.extend(record.version.get().to_le_bytes());
+                put_str(&mut body, &record.name)?;
+                put_opt_str(&mut body, record.metadata.causation.as_deref())?;
+                put_opt_str(&mut body, record.metadata.correlation.as_deref())?;
+                body.extend(&record.payload);
+            }
+            Self::Deleted { stream, timestamp } => {
+                body.push(DELETED);
+                body.extend(micros(*timestamp).to_le_bytes());
+                put_str(&mut body, &stream.to_string())?;
+            }
+            Self::Truncated { stream, before, timestamp } => {
+                body.push(TRUNCATED);
+                body.extend(micros(*timestamp).to_le_bytes());
+                put_str(&mut body, &stream.to_string())?;
+                body.extend(before.get().to_le_bytes());
+            }
+        }
+
+        let len = u32::try_from(body.len()).ok().ok_or(Error::Oversized)?;
+        out.extend(len.to_le_bytes());
+        out.extend(crc32fast::hash(&body).to_le_bytes());
+        out.extend(body);
+        Ok(())
+    }
+
+    /// Decodes a [`Frame`] from the provided `body`, if it's a valid one.
+    fn decode(body: &[u8]) -> Option<Self> {
+        let mut body = Cursor(body);
+        let kind = body.u8()?;
+        let timestamp = UNIX_EPOCH + Duration::from_micros(body.u64()?);
+        let stream = body.str()?.parse().ok()?;
+        if kind == DELETED {
+            return body
+                .0
+                .is_empty()
+                .then_some(Self::Deleted { stream, timestamp });
+        }
+        if kind == TRUNCATED {
+            let before = stream::Position::new(body.u64()?)?;
+            return body.0.is_empty().then_some(Self::Truncated {
+                stream,
+                before,
+                timestamp,
+            });
+        }
+        let last = match kind {
+            EVENT => false,
+            EVENT_LAST => true,
+            _ => return None,
+        };
+        let position = stream::Position::new(body.u64()?)?;
+        let offset = body.u64()?;
+        let version = event::Version::try_new(body.u16()?)?;
+        let name = body.str()?;
+        let causation = if body.flag()? { Some(body.str()?) } else { None };
+        let correlation = if body.flag()? { Some(body.str()?) } else { None };
+        let metadata = Metadata { causation, correlation };
+        let record = Record {
+            stream,
+            position,
+            offset,
+            name,
+            version,
+            timestamp,
+            metadata,
+            payload: body.0.to_vec(),
+        };
+        Some(Self::Event { record, last })
+    }
+}
+
+/// Result of [`read()`]ing a [`Frame`].
+#[derive(Debug)]
+pub(super) enum Read {
+    /// [`Frame`] along with its total length in bytes.
+    Frame(Box<Frame>, u64),
+
+    /// Clean end of a segment file.
+    End,
+
+    /// Torn or corrupted [`Frame`].
+    Invalid,
+}
+
+/// Reads the next [`Frame`] from the provided `reader` having the provided
+/// number of `remaining` bytes.
+///
+/// # Errors
+///
+/// If the `reader` fails.
+pub(super) fn read(
+    reader: &mut impl io::Read,
+    remaining: u64,
+) -> io::Result<Read> {
+    if remaining == 0 {
+        return Ok(Read::End);
+    }
+    if remaining < HEADER_LEN {
+        return Ok(Read::Invalid);
+    }
+
+    let mut header = [0; 8];
+    reader.read_exact(&mut header)?;
+    let [l0, l1, l2, l3, c0, c1, c2, c3] = header;
+    let (len, crc) = (
+        u32::from_le_bytes([l0, l1, l2, l3]),
+        u32::from_le_bytes([c0, c1, c2, c3]),
+    );
+    if u64::from(len) > remaining - HEADER_LEN {
+        return Ok(Read::Invalid);
+    }
+
+    let total = HEADER_LEN + u64::from(len);
+
+    let Ok(len) = usize::try_from(len) else {
+        return Ok(Read::Invalid);
+    };
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    if crc32fast::hash(&body) != crc {
+        return Ok(Read::Invalid);
+    }
+    Ok(Frame::decode(&body)
+        .map_or(Read::Invalid, |f| Read::Frame(Box::new(f), total)))
+}
+
+/// Appends the provided string prefixed with its length to the `out`put.
+///
+/// # Errors
+///
+/// [`Error::Oversized`] if the string is longer than [`u16::MAX`] bytes.
+fn put_str(out: &mut Vec<u8>, s: &str) -> Result<(), Error> {
+    let len = u16::try_from(s.len()).ok().ok_or(Error::Oversized)?;
+    out.extend(len.to_le_bytes());
+    out.extend(s.as_bytes());
+    Ok(())
+}
+
+/// Appends the provided optional string prefixed with its presence to the
+/// `out`put.
+///
+/// # Errors
+///
+/// [`Error::Oversized`] if the string is longer than [`u16::MAX`] bytes.
+fn put_opt_str(out: &mut Vec<u8>, s: Option<&str>) -> Result<(), Error> {
+    out.push(s.is_some().into());
+    s.map_or(Ok(()), |s| put_str(out, s))
+}
+
+/// Cursor over the body of a [`Frame`].
+struct Cursor<'b>(&'b [u8]);
+
+impl<'b> Cursor<'b> {
+    /// Takes the provided number of bytes, if there are enough of them.
+    fn take(&mut self, n: usize) -> Option<&'b [u8]> {
+        let (head, tail) = self.0.split_at_checked(n)?;
+        self.0 = tail;
+        Some(head)
+    }
+
+    /// Takes a [`u8`].
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1)?.first().copied()
+    }
+
+    /// Takes a little-endian [`u16`].
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    /// Takes a little-endian [`u64`].
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    /// Takes a UTF-8 string prefixed with its length.
+    fn str(&mut self) -> Option<String> {
+        let len = self.u16()?;
+        String::from_utf8(self.take(usize::from(len))?.to_vec()).ok()
+    }
+
+    /// Takes a presence flag of an optional value.
+    fn flag(&mut self) -> Option<bool> {
+        match self.u8()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Per-[`Stream`] [`Index`] of segment files.
+//!
+//! [`Stream`]: stream::Stream
+
+use std::{collections::HashMap, fs, io, iter, path::Path};
+
+use super::{frame::Frame, len_u64};
+use crate::es::stream;
+
+/// Magic bytes an [`Index`] file starts with.
+const MAGIC: &[u8; 8] = b"ARCIDX04";
+
+/// Location of a [`Frame`] in segment files.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub(super) struct Location {
+    /// Number of the segment file.
+    pub(super) segment: u64,
+
+    /// Offset of the [`Frame`] in the segment file.
+    pub(super) offset: u64,
+}
+
+/// Indexed non-empty [`Stream`].
+///
+/// [`Stream`]: stream::Stream
+#[derive(Clone, Debug, Default)]
+pub(super) struct Indexed {
+    /// Number of the truncated event [`Frame`]s preceding the `locations`.
+    pub(super) truncated: u64,
+
+    /// [`Location`]s of the not truncated event [`Frame`]s in their order.
+    pub(super) locations: Vec<Location>,
+
+    /// Indicator whether the last event [`Frame`] is a tombstone.
+    pub(super) tombstoned: bool,
+}
+
+impl Indexed {
+    /// Returns the current version of this [`Indexed`] [`Stream`].
+    ///
+    /// [`Stream`]: stream::Stream
+    pub(super) fn version(&self) -> Option<stream::Position> {
+        let len = u64::try_from(self.locations.len()).ok()?;
+        stream::Position::new(self.truncated.checked_add(len)?)
+    }
+
+    /// Returns the [`Location`]s of the event [`Frame`]s following the
+    /// provided [`stream::Position`].
+    pub(super) fn after(
+        &self,
+        position: Option<stream::Position>,
+    ) -> &[Location] {
+        let skip =
+            position.map_or(0, |p| p.get().saturating_sub(self.truncated));
+        let skip = usize::try_from(skip).unwrap_or(usize::MAX);
+        self.locations.get(skip..).unwrap_or_default()
+    }
+}
+
+/// Index of the event [`Frame`]s of every non-empty [`Stream`].
+///
+/// [`Stream`]: stream::Stream
+#[derive(Clone, Debug, Default)]
+pub(super) struct Index {
+    /// Every non-empty [`Indexed`] [`Stream`].
+    ///
+    /// [`Stream`]: stream::Stream
+    pub(super) streams: HashMap<stream::Id, Indexed>,
+
+    /// [`Location`] up to which the segment files are indexed.
+    pub(super) watermark: Location,
+
+    /// Offset in the global log to be assigned to the next event [`Frame`].
+    pub(super) next_offset: u64,
+}
+
+impl Index {
+    /// Returns the current version of the [`Stream`] with the provided
+    /// [`stream::Id`], or [`None`] if it's empty.
+    ///
+    /// [`Stream`]: stream::Stream
+    pub(super) fn version(
+        &self,
+        stream: &stream::Id,
+    ) -> Option<stream::Position> {
+        self.streams.get(stream)?.version()
+    }
+
+    /// Applies the provided committed [`Frame`] located at the provided
+    /// [`Location`] to this [`Index`].
+    ///
+    /// Returns `false` if the [`Frame`] doesn't follow the current version of
+    /// its [`Stream`], or follows its tombstone.
+    ///
+    /// [`Stream`]: stream::Stream
+    pub(super) fn apply(&mut self, frame: &Frame, at: Location) -> bool {
+        match frame {
+            Frame::Event { record, .. } => {
+                let indexed =
+                    self.streams.entry(record.stream.clone()).or_default();
+                if indexed.locations.is_empty() {
+                    // The first retained event `Frame` of a `Stream`, which
+                    // truncated ones are compacted away.
+                    indexed.truncated = record.position.get() - 1;
+                }
+                let expected = stream::Position::following(indexed.version());
+                if record.position != expected || indexed.tombstoned {
+                    return false;
+                }
+                indexed.locations.push(at);
+                indexed.tombstoned = frame.is_tombstone();
+                self.next_offset =
+                    self.next_offset.max(record.offset.saturating_add(1));
+            }
+            Frame::Deleted { stream, .. } => drop(self.streams.remove(stream)),
+            Frame::Truncated { stream, before, .. } => {
+                let count = self.truncation(stream, *before);
+                if let Some(indexed) = self.streams.get_mut(stream) {
+                    drop(indexed.locations.drain(..count));
+                    indexed.truncated += len_u64(count);
+                }
+            }
+        }
+        true
+    }
+
+    /// Returns the number of the event [`Frame`]s of the [`Stream`] with the
+    /// provided [`stream::Id`] to be truncated before the provided
+    /// [`stream::Position`].
+    ///
+    /// The last event [`Frame`] is never truncated.
+    ///
+    /// [`Stream`]: stream::Stream
+    pub(super) fn truncation(
+        &self,
+        stream: &stream::Id,
+        before: stream::Position,
+    ) -> usize {
+        self.streams.get(stream).map_or(0, |s| {
+            let retained = s.after(
+                before.get().checked_sub(1).and_then(stream::Position::new),
+            );
+            (s.locations.len() - retained.len())
+                .min(s.locations.len().saturating_sub(1))
+        })
+    }
+
+    /// Loads an [`Index`] from the file at the provided `path`.
+    ///
+    /// Returns [`None`] if the file doesn't exist or is malformed.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be read.
+    pub(super) fn load(path: &Path) -> io::Result<Option<Self>> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        Ok(Self::decode(&bytes))
+    }
+
+    /// Saves this [`Index`] into the file at the provided `path` atomically.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be written.
+    pub(super) fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, self.encode())?;
+        fs::File::open(&tmp)?.sync_all()?;
+        fs::rename(tmp, path)
+    }
+
+    /// Encodes this [`Index`] into bytes, ending with their [CRC-32].
+    ///
+    /// [CRC-32]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+    fn encode(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        put_location(&mut out, self.watermark);
+        out.extend(self.next_offset.to_le_bytes());
+        let mut streams = self.streams.iter().collect::<Vec<_>>();
+        streams.sort_unstable_by_key(|(id, _)| *id);
+        for (id, indexed) in streams {
+            let id = id.to_string();
+            put_len(&mut out, id.len());
+            out.extend(id.as_bytes());
+            out.extend(indexed.truncated.to_le_bytes());
+            out.push(indexed.tombstoned.into());
+            put_len(&mut out, indexed.locations.len());
+            for &l in &indexed.locations {
+                put_location(&mut out, l);
+            }
+        }
+        out.extend(crc32fast::hash(&out).to_le_bytes());
+        out
+    }
+
+    /// Decodes an [`Index`] from the provided `bytes`, if they're valid.
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let (body, crc) = bytes.split_last_chunk::<4>()?;
+        if crc32fast::hash(body) != u32::from_le_bytes(*crc) {
+            return None;
+        }
+        let mut body = body.strip_prefix(MAGIC)?;
+
+        let watermark = take_location(&mut body)?;
+        let next_offset = take_u64(&mut body)?;
+        let mut streams = HashMap::new();
+        while !body.is_empty() {
+            let len = usize::try_from(take_u64(&mut body)?).ok()?;
+            let (id, rest) = body.split_at_checked(len)?;
+            body = rest;
+            let id = String::from_utf8(id.to_vec()).ok()?.parse().ok()?;
+
+            let truncated = take_u64(&mut body)?;
+            let (&tombstoned, tail) = body.split_first()?;
+            body = tail;
+            let count = usize::try_from(take_u64(&mut body)?).ok()?;
+            let locations = iter::repeat_with(|| take_location(&mut body))
+                .take(count)
+                .collect::<Option<Vec<_>>>()?;
+            let indexed =
+                Indexed { truncated, locations, tombstoned: tombstoned != 0 };
+            drop(streams.insert(id, indexed));
+        }
+        Some(Self { streams, watermark, next_offset })
+    }
+}
+
+/// Appends the provided [`usize`] as a little-endian [`u64`] to the `out`put.
+fn put_len(out: &mut Vec<u8>, len: usize) {
+    out.extend(u64::try_from(len).unwrap_or(u64::MAX).to_le_bytes());
+}
+
+/// Appends the provided [`Location`] to the `out`put.
+fn put_location(out: &mut Vec<u8>, location: Location) {
+    out.extend(location.segment.to_le_bytes());
+    out.extend(location.offset.to_le_bytes());
+}
+
+/// Takes a [`Location`] from the start of the provided `bytes`.
+fn take_location(bytes: &mut &[u8]) -> Option<Location> {
+    Some(Location { segment: take_u64(bytes)?, offset: take_u64(bytes)? })
+}
+
+/// Takes a little-endian [`u64`] from the start of the provided `bytes`.
+fn take_u64(bytes: &mut &[u8]) -> Option<u64> {
+    let (word, rest) = bytes.split_first_chunk::<8>()?;
+    *bytes = rest;
+    Some(u64::from_le_bytes(*word))
+}
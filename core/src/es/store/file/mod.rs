@@ -0,0 +1,2106 @@
+//! Append-only [`File`] [`Store`] keeping [`Event`]s in segment files on the
+//! local filesystem.
+//!
+//! [`Event`]s are encoded with [`Codecs`] and appended as [CRC]-framed
+//! [`Record`]s to the last (active) segment file of a directory, which is
+//! rolled over to a new one once it exceeds the [`Options::segment_size()`].
+//! [`Record`]s of a single [`Store::append()`] are committed together: a torn
+//! tail of the last segment file, left by a crash in the middle of an append,
+//! is truncated on opening. How often the appended [`Record`]s are `fsync`ed
+//! is defined by the [`Fsync`] policy.
+//!
+//! Every segment file starts with a header identifying the version of its
+//! format, so a segment file of another version is rejected with
+//! [`Error::Unsupported`] rather than truncated as a torn tail.
+//!
+//! Locations of the [`Record`]s of every [`Stream`] are kept in an index,
+//! saved into the `index` file on every roll over and [`File::sync()`], and
+//! caught up with the segment files on opening. If the `index` file is missing
+//! or malformed, the index is rebuilt from the segment files (see also
+//! [`File::rebuild_index()`]).
+//!
+//! [`Lifecycle::tombstone()`]s are recorded as [`Record`]s with the reserved
+//! [`TOMBSTONE`] name. [`Lifecycle::delete()`]d and [`Lifecycle::truncate()`]d
+//! [`Record`]s are only hidden by markers, until [`File::compact()`] drops
+//! them from the segment files.
+//!
+//! A directory is locked by the [`File`] [`Store`] opened in it, so opening
+//! it again fails with [`Error::Locked`] until the former is dropped. If a
+//! segment file fails to be `fsync`ed, the [`File`] [`Store`] is poisoned and
+//! rejects further writes with [`Error::Poisoned`], as it's unknown what has
+//! reached the disk, so it must be reopened to recover.
+//!
+//! A [`File`] [`Store`] may also be opened [read-only](Options::read_only())
+//! for inspecting it, which neither locks, creates nor repairs anything, and
+//! sees the [`Record`]s committed by the time of opening.
+//!
+//! > **NOTE**: The lock is advisory (`flock` on Unix, and a share mode of the
+//! >           `lock` file on Windows), and is not supported on other
+//! >           platforms.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use std::{fs, io::Write as _};
+//! #
+//! # use arcane::es::{
+//! #     Event,
+//! #     store::{self, Lifecycle as _, Store as _, file::Fsync},
+//! #     stream::{self, ExpectedVersion},
+//! # };
+//! # use serde::{Deserialize, Serialize};
+//! #
+//! #[derive(Debug, Deserialize, Event, PartialEq, Serialize)]
+//! #[event(name = "chat.created", revision = 1)]
+//! struct ChatCreated;
+//!
+//! #[derive(Debug, Deserialize, Event, PartialEq, Serialize)]
+//! #[event(name = "message.posted", revision = 1)]
+//! struct MessagePosted {
+//!     text: String,
+//! }
+//!
+//! #[derive(Debug, Deserialize, Event, PartialEq, Serialize)]
+//! #[event(revision, stream = "chat")]
+//! enum ChatEvent {
+//!     Created(ChatCreated),
+//!     MessagePosted(MessagePosted),
+//! }
+//!
+//! let dir = std::env::temp_dir()
+//!     .join(format!("arcane-store-file-{}", std::process::id()));
+//! let open = || {
+//!     store::file::Options::new()
+//!         .fsync(Fsync::Never)
+//!         .segment_size(256)
+//!         .open::<ChatEvent>(&dir)
+//!         .unwrap()
+//! };
+//! let (chat1, chat2) = (
+//!     stream::Id::new("chat", "1").unwrap(),
+//!     stream::Id::new("chat", "2").unwrap(),
+//! );
+//! let post = |text: &str| {
+//!     ChatEvent::MessagePosted(MessagePosted { text: text.into() })
+//! };
+//!
+//! let mut store = open();
+//! for chat in [&chat1, &chat2] {
+//!     let events = vec![ChatEvent::Created(ChatCreated), post("hi")];
+//!     store.append(chat, ExpectedVersion::NoStream, events).unwrap();
+//! }
+//! store.append(&chat1, ExpectedVersion::Any, vec![post("bye")]).unwrap();
+//! store.sync().unwrap();
+//!
+//! let records = store.records(&chat1).unwrap();
+//! assert_eq!(records.len(), 3);
+//! assert_eq!(records[2].name, "message.posted");
+//! assert_eq!(records[2].version.get(), 1);
+//!
+//! // Torn write of a crashed append is truncated on opening.
+//! drop(store);
+//! let last = fs::read_dir(&dir)
+//!     .unwrap()
+//!     .map(|e| e.unwrap().path())
+//!     .filter(|p| p.extension().is_some_and(|ext| ext == "seg"))
+//!     .max()
+//!     .unwrap();
+//! let len = fs::metadata(&last).unwrap().len();
+//! let mut file = fs::OpenOptions::new().append(true).open(&last).unwrap();
+//! file.write_all(&[42, 0, 0, 0, 1, 2]).unwrap();
+//!
+//! let mut store = open();
+//! assert_eq!(fs::metadata(&last).unwrap().len(), len);
+//! assert_eq!(store.version(&chat1), stream::Position::new(3));
+//! assert_eq!(store.read_stream(&chat1).unwrap().len(), 3);
+//!
+//! // Records of deleted and truncated streams are dropped on compaction.
+//! assert!(store.delete(&chat1).unwrap());
+//! assert_eq!(store.read_stream(&chat1).unwrap().len(), 0);
+//! assert_eq!(store.truncate(&chat2, stream::Position::FIRST).unwrap(), 0);
+//! let before = stream::Position::new(10).unwrap();
+//! assert_eq!(store.truncate(&chat2, before).unwrap(), 1);
+//! assert!(store.compact().unwrap() > 0);
+//! drop(store);
+//!
+//! let mut store = open();
+//! let recorded = store.read_category("chat").unwrap();
+//! assert_eq!(
+//!     recorded
+//!         .iter()
+//!         .map(|r| (r.position.get(), &r.event))
+//!         .collect::<Vec<_>>(),
+//!     [(2, &post("hi"))],
+//! );
+//! assert!(recorded.iter().all(|r| r.stream == chat2));
+//!
+//! // Tombstoned streams stay readable, but reject appends.
+//! let pos = store.tombstone(&chat2, ExpectedVersion::Any).unwrap();
+//! let err = store.append(&chat2, ExpectedVersion::Any, vec![post("hey")]);
+//! assert!(matches!(err, Err(store::AppendError::Tombstoned(_))));
+//! drop(store);
+//!
+//! let store = open();
+//! let chat = store.read_stream(&chat2).unwrap();
+//! assert!(chat.is_truncated());
+//! assert_eq!(chat.len(), 1);
+//! assert_eq!(chat.tombstone(), Some(pos));
+//! let recorded = store.read_recorded(&chat2).unwrap();
+//! assert_eq!(recorded.len(), 1);
+//! assert_eq!(recorded[0].position.get(), 2);
+//! assert!(recorded[0].metadata.is_empty());
+//! # #[cfg(feature = "temporal")]
+//! # {
+//! # use arcane::es::temporal::History as _;
+//! let history = store.read_history(&chat2, None).unwrap();
+//! assert_eq!(history.truncated, stream::Position::new(1));
+//! assert_eq!(history.events.len(), 1);
+//! # }
+//! # #[cfg(feature = "subscription")]
+//! # {
+//! # use arcane::es::subscription::{Log as _, Offset, Shared, Subscription};
+//! # use futures::{StreamExt as _, executor::block_on};
+//! #
+//! // Global log is read by the stable offsets of the records.
+//! let log = store.read_log(Offset::START, 10).unwrap();
+//! assert_eq!(
+//!     log.iter().map(|(o, e)| (o.get(), e.stream())).collect::<Vec<_>>(),
+//!     [(3, &chat2), (5, &chat2)],
+//! );
+//! assert_eq!(store.end().unwrap(), Offset::new(6));
+//!
+//! let mut tail = Subscription::new(Shared::new(store), Offset::new(4));
+//! block_on(async {
+//!     let (offset, entry) = tail.next().await.unwrap().unwrap();
+//!     assert_eq!(offset, Offset::new(5));
+//!     assert!(matches!(entry, store::Entry::Tombstone { .. }));
+//! });
+//! # }
+//! # fs::remove_dir_all(&dir).unwrap();
+//! ```
+//!
+//! [CRC]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+
+mod frame;
+mod index;
+
+use std::{
+    collections::HashSet,
+    error::Error as StdError,
+    fmt, fs,
+    io::{self, BufReader, Read as _, Seek as _, SeekFrom, Write as _},
+    marker::PhantomData,
+    mem,
+    path::{Path, PathBuf},
+    slice,
+    time::{Duration, Instant, SystemTime},
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use self::{
+    frame::{Frame, Read},
+    index::{Index, Location},
+};
+#[cfg(feature = "subscription")]
+use super::Entry;
+use super::{
+    AppendError, Conflict, Lifecycle, Metadata, Recorded, Reserved, Store,
+    TOMBSTONE, Tombstoned,
+};
+#[cfg(doc)]
+use crate::es::codec::Format;
+#[cfg(feature = "subscription")]
+use crate::es::subscription;
+#[cfg(feature = "temporal")]
+use crate::es::temporal;
+#[cfg(feature = "tenant")]
+use crate::es::tenant;
+use crate::es::{
+    Event,
+    codec::{self, Codec as _, Codecs},
+    event,
+    stream::{self, ExpectedVersion, Stream},
+};
+
+/// Extension of segment files.
+const SEGMENT_EXT: &str = "seg";
+
+/// Name of the index file.
+const INDEX_FILE: &str = "index";
+
+/// Name of the lock file.
+const LOCK_FILE: &str = "lock";
+
+/// Policy of `fsync`ing the appended [`Record`]s to a disk.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Fsync {
+    /// Every append is `fsync`ed before returning, so no appended [`Event`] is
+    /// lost on a crash.
+    #[default]
+    Always,
+
+    /// Appends are `fsync`ed once the provided interval passes since the last
+    /// `fsync`, so only the [`Event`]s appended within it may be lost.
+    Interval(Duration),
+
+    /// Appends are never `fsync`ed explicitly, leaving it to the operating
+    /// system.
+    Never,
+}
+
+/// [`Event`] recorded into a segment file of a [`File`] [`Store`], along with
+/// its metadata.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Record {
+    /// [`stream::Id`] of the [`Stream`] the [`Event`] was appended to.
+    pub stream: stream::Id,
+
+    /// [`stream::Position`] of the [`Event`] in its [`Stream`].
+    pub position: stream::Position,
+
+    /// Offset of the [`Event`] in the global log of its [`File`] [`Store`],
+    /// starting from `0`.
+    ///
+    /// Stays the same once the preceding [`Record`]s are deleted, truncated
+    /// or compacted.
+    pub offset: u64,
+
+    /// [`event::Name`] of the [`Event`].
+    pub name: String,
+
+    /// [`event::Version`] of the [`Event`].
+    pub version: event::Version,
+
+    /// Time the [`Event`] was appended at.
+    pub timestamp: SystemTime,
+
+    /// [`Metadata`] the [`Event`] was appended with.
+    pub metadata: Metadata,
+
+    /// [`Event`] encoded with the [`Codecs`] of its [`File`] [`Store`].
+    pub payload: Vec<u8>,
+}
+
+/// [`Event`] encoded with [`Codecs`], to be appended via
+/// [`File::append_encoded()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Encoded {
+    /// [`event::Name`] of the [`Event`].
+    pub name: String,
+
+    /// [`event::Version`] of the [`Event`].
+    pub version: event::Version,
+
+    /// Encoded [`Event`] itself.
+    pub payload: Vec<u8>,
+
+    /// Time the [`Event`] was originally appended at, or [`None`] to record
+    /// the time of appending it.
+    pub timestamp: Option<SystemTime>,
+
+    /// [`Metadata`] to record along with the [`Event`].
+    pub metadata: Metadata,
+}
+
+/// Options of opening a [`File`] [`Store`].
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// [`Fsync`] policy of the appended [`Record`]s.
+    fsync: Fsync,
+
+    /// Size (in bytes) a segment file is rolled over after.
+    segment_size: u64,
+
+    /// [`Codecs`] encoding the appended [`Event`]s.
+    codecs: Codecs,
+
+    /// Indicator whether the [`File`] [`Store`] is opened read-only.
+    read_only: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Options {
+    /// Creates new [`Options`] with [`Fsync::Always`] policy, 64 MiB segment
+    /// files and [`Format::Json`] [`Codecs`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            fsync: Fsync::Always,
+            segment_size: 64 * 1024 * 1024,
+            codecs: Codecs::default(),
+            read_only: false,
+        }
+    }
+
+    /// Sets whether the [`File`] [`Store`] is opened read-only.
+    ///
+    /// A read-only [`File`] [`Store`] doesn't lock the directory, doesn't
+    /// create or repair any files in it, ignores a torn tail of the last
+    /// segment file, and rejects all writes with [`Error::ReadOnly`].
+    #[must_use]
+    pub const fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Sets the [`Fsync`] policy of the appended [`Record`]s.
+    #[must_use]
+    pub const fn fsync(mut self, fsync: Fsync) -> Self {
+        self.fsync = fsync;
+        self
+    }
+
+    /// Sets the size (in bytes) a segment file is rolled over after.
+    ///
+    /// The [`Record`]s of a single append are always written to the same
+    /// segment file, so it may exceed this size.
+    #[must_use]
+    pub const fn segment_size(mut self, bytes: u64) -> Self {
+        self.segment_size = bytes;
+        self
+    }
+
+    /// Sets the [`Codecs`] encoding the appended [`Event`]s.
+    ///
+    /// Changing [`Codecs`] of an existing [`File`] [`Store`] affects only the
+    /// newly appended [`Event`]s.
+    #[must_use]
+    pub fn codecs(mut self, codecs: Codecs) -> Self {
+        self.codecs = codecs;
+        self
+    }
+
+    /// Opens a [`File`] [`Store`] in the directory at the provided `path`
+    /// with these [`Options`], creating it if it doesn't exist.
+    ///
+    /// Locks the directory, truncates a torn tail of the last segment file,
+    /// and catches up (or rebuilds) the index with the segment files (only
+    /// the latter, if [read-only](Options::read_only())).
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Locked`] if the directory is opened by another [`File`]
+    ///   [`Store`].
+    /// - [`Error::Corrupted`] if any segment file except the last one is
+    ///   corrupted.
+    /// - [`Error::Unsupported`] if any segment file is of another format
+    ///   version.
+    /// - [`Error::Io`] if the directory cannot be read or written (or doesn't
+    ///   exist, if [read-only](Options::read_only())).
+    pub fn open<Ev>(self, path: impl AsRef<Path>) -> Result<File<Ev>, Error> {
+        let dir = path.as_ref().to_owned();
+        let lock = if self.read_only {
+            None
+        } else {
+            fs::create_dir_all(&dir)?;
+            let lock = lock_dir(&dir)?;
+            if list_segments(&dir)?.is_empty() {
+                drop(open_segment(&dir, 0)?);
+            }
+            Some(lock)
+        };
+
+        let index = Index::load(&dir.join(INDEX_FILE))?
+            .filter(|i| {
+                let Location { segment, offset } = i.watermark;
+                i.watermark == Location::default()
+                    || fs::metadata(segment_path(&dir, segment))
+                        .is_ok_and(|m| m.len() >= offset)
+            })
+            .unwrap_or_default();
+        let tail = if self.read_only { Tail::Ignore } else { Tail::Truncate };
+        let index = scan(&dir, index, tail)?;
+        let Location { segment, offset } = index.watermark;
+
+        Ok(File {
+            active: if self.read_only {
+                None
+            } else {
+                Some(open_segment(&dir, segment)?)
+            },
+            dir,
+            options: self,
+            segment,
+            len: offset,
+            index,
+            synced_at: Instant::now(),
+            poisoned: false,
+            sync_data: fs::File::sync_data,
+            _lock: lock,
+            _event: PhantomData,
+        })
+    }
+}
+
+/// Append-only [`Store`] keeping [`Event`]s in segment files on the local
+/// filesystem.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug)]
+pub struct File<Ev> {
+    /// Directory of the segment files and the index file.
+    dir: PathBuf,
+
+    /// [`Options`] this [`File`] [`Store`] was opened with.
+    options: Options,
+
+    /// Active segment file opened for appending, or [`None`] if this
+    /// [`File`] [`Store`] is read-only.
+    active: Option<fs::File>,
+
+    /// Number of the active segment file.
+    segment: u64,
+
+    /// Length (in bytes) of the active segment file.
+    len: u64,
+
+    /// [`Index`] of the segment files.
+    index: Index,
+
+    /// Time of the last `fsync`.
+    synced_at: Instant,
+
+    /// Indicator whether the active segment file failed to be `fsync`ed or
+    /// repaired, so its contents are unknown.
+    poisoned: bool,
+
+    /// Function `fsync`ing the data of the active segment file.
+    sync_data: fn(&fs::File) -> io::Result<()>,
+
+    /// Lock file of the directory, held while this [`File`] [`Store`] is
+    /// open, or [`None`] if it's read-only.
+    _lock: Option<fs::File>,
+
+    /// Type of the stored [`Event`]s.
+    _event: PhantomData<fn(Ev) -> Ev>,
+}
+
+impl<Ev> File<Ev> {
+    /// Opens a [`File`] [`Store`] in the directory at the provided `path` with
+    /// the default [`Options`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Options::open()`] for details.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Options::new().open(path)
+    }
+
+    /// Returns the directory of this [`File`] [`Store`].
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the current version of the [`Stream`] with the provided
+    /// [`stream::Id`], or [`None`] if it's empty.
+    #[must_use]
+    pub fn version(&self, stream: &stream::Id) -> Option<stream::Position> {
+        self.index.version(stream)
+    }
+
+    /// Returns all the non-empty [`Stream`]s of this [`File`] [`Store`] along
+    /// with their current versions, in no particular order.
+    pub fn streams(
+        &self,
+    ) -> impl Iterator<Item = (&stream::Id, stream::Position)> {
+        self.index.streams.iter().filter_map(|(id, s)| Some((id, s.version()?)))
+    }
+
+    /// Reads all the not truncated [`Record`]s of the [`Stream`] with the
+    /// provided [`stream::Id`] (including its tombstone) without decoding
+    /// them.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Corrupted`] if a [`Record`] cannot be read.
+    /// - [`Error::Io`] if a segment file cannot be read.
+    pub fn records(&self, stream: &stream::Id) -> Result<Vec<Record>, Error> {
+        let locations =
+            self.index.streams.get(stream).map_or(&[][..], |s| &s.locations);
+        self.read_at(locations.iter().copied()).collect()
+    }
+
+    /// Reads the not truncated [`Record`] at the provided
+    /// [`stream::Position`] of the [`Stream`] with the provided
+    /// [`stream::Id`] without decoding it.
+    ///
+    /// Returns [`None`] if there is no such [`Record`], or it's truncated.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Corrupted`] if the [`Record`] cannot be read.
+    /// - [`Error::Io`] if a segment file cannot be read.
+    pub fn record(
+        &self,
+        stream: &stream::Id,
+        position: stream::Position,
+    ) -> Result<Option<Record>, Error> {
+        let at = self
+            .index
+            .streams
+            .get(stream)
+            .filter(|s| position.get() > s.truncated)
+            .and_then(|s| {
+                let prev = stream::Position::new(position.get() - 1);
+                s.after(prev).first().copied()
+            });
+        self.read_at(at).next().transpose()
+    }
+
+    /// Lazily reads all the not truncated [`Record`]s of all the non-empty
+    /// [`Stream`]s (including tombstones) in the order they were appended,
+    /// without decoding them.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Corrupted`] if a [`Record`] cannot be read.
+    /// - [`Error::Io`] if a segment file cannot be read.
+    pub fn log(&self) -> impl Iterator<Item = Result<Record, Error>> {
+        self.read_at(self.locations())
+    }
+
+    /// Returns the [`Location`]s of all the not truncated [`Record`]s of all
+    /// the non-empty [`Stream`]s in the order they were appended.
+    fn locations(&self) -> Vec<Location> {
+        let mut locations = self
+            .index
+            .streams
+            .values()
+            .flat_map(|s| s.locations.iter().copied())
+            .collect::<Vec<_>>();
+        locations.sort_unstable();
+        locations
+    }
+
+    /// Checks whether the [`Stream`] with the provided [`stream::Id`] is not
+    /// tombstoned and matches the provided [`ExpectedVersion`].
+    ///
+    /// # Errors
+    ///
+    /// - [`AppendError::Tombstoned`] if the [`Stream`] is tombstoned.
+    /// - [`AppendError::Conflict`] if the [`Stream`] doesn't match the
+    ///   provided [`ExpectedVersion`].
+    fn check(
+        &self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+    ) -> Result<Option<stream::Position>, AppendError<Error>> {
+        let indexed = self.index.streams.get(stream);
+        let version = indexed.and_then(index::Indexed::version);
+        if let Some(position) =
+            version.filter(|_| indexed.is_some_and(|s| s.tombstoned))
+        {
+            return Err(Tombstoned { stream: stream.clone(), position }.into());
+        }
+        if !expected.matches(version) {
+            return Err(Conflict {
+                stream: stream.clone(),
+                expected,
+                actual: version,
+            }
+            .into());
+        }
+        Ok(version)
+    }
+
+    /// Appends the provided [`Encoded`] [`Event`]s to the end of the
+    /// [`Stream`] with the provided [`stream::Id`] as is, returning the new
+    /// version of this [`Stream`].
+    ///
+    /// Returns [`None`] if the [`Stream`] is still empty.
+    ///
+    /// # Errors
+    ///
+    /// - [`AppendError::Conflict`] if the current version of the [`Stream`]
+    ///   doesn't match the provided [`ExpectedVersion`]. Nothing is appended
+    ///   in this case.
+    /// - [`AppendError::Tombstoned`] if the [`Stream`] is tombstoned.
+    /// - [`AppendError::Reserved`] if any [`Encoded`] [`Event`] has the
+    ///   reserved [`TOMBSTONE`] name (use [`File::entomb()`] instead).
+    ///   Nothing is appended in this case.
+    /// - [`AppendError::Store`] if the [`Event`]s cannot be appended.
+    pub fn append_encoded(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+        events: Vec<Encoded>,
+    ) -> Result<Option<stream::Position>, AppendError<Error>> {
+        Reserved::check(stream, events.iter().map(|ev| &ev.name))?;
+        self.restore_encoded(stream, expected, events)
+    }
+
+    /// Appends the provided [`Encoded`] [`Event`]s to the end of the
+    /// [`Stream`] with the provided [`stream::Id`] as is, including the
+    /// tombstones with the reserved [`TOMBSTONE`] name (used to restore
+    /// [`Stream`]s from archives).
+    ///
+    /// # Errors
+    ///
+    /// - [`AppendError::Tombstoned`] if any [`Encoded`] [`Event`] follows a
+    ///   tombstone.
+    /// - Same as [`File::append_encoded()`] otherwise.
+    pub(crate) fn restore_encoded(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+        events: Vec<Encoded>,
+    ) -> Result<Option<stream::Position>, AppendError<Error>> {
+        let version = self.check(stream, expected)?;
+        self.append_frames(stream, version, events)
+    }
+
+    /// Appends the provided [`Encoded`] [`Event`]s as is to the empty
+    /// [`Stream`] with the provided [`stream::Id`], starting it from the
+    /// provided `first` [`stream::Position`] as if its preceding [`Event`]s
+    /// were truncated, and returns the new version of this [`Stream`].
+    ///
+    /// Used to restore truncated [`Stream`]s (from archives, for example).
+    ///
+    /// # Errors
+    ///
+    /// - [`AppendError::Conflict`] if the [`Stream`] is not empty. Nothing is
+    ///   appended in this case.
+    /// - Same as [`File::append_encoded()`] otherwise.
+    pub fn append_encoded_from(
+        &mut self,
+        stream: &stream::Id,
+        first: stream::Position,
+        events: Vec<Encoded>,
+    ) -> Result<Option<stream::Position>, AppendError<Error>> {
+        Reserved::check(stream, events.iter().map(|ev| &ev.name))?;
+        self.restore_encoded_from(stream, first, events)
+    }
+
+    /// Same as [`File::append_encoded_from()`], but including the tombstones
+    /// with the reserved [`TOMBSTONE`] name, as [`File::restore_encoded()`]
+    /// does.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`File::append_encoded_from()`] and
+    /// [`File::restore_encoded()`].
+    pub(crate) fn restore_encoded_from(
+        &mut self,
+        stream: &stream::Id,
+        first: stream::Position,
+        events: Vec<Encoded>,
+    ) -> Result<Option<stream::Position>, AppendError<Error>> {
+        let current = self.check(stream, ExpectedVersion::NoStream)?;
+        if events.is_empty() {
+            return Ok(current);
+        }
+        let truncated = stream::Position::new(first.get() - 1);
+        self.append_frames(stream, truncated, events)
+    }
+
+    /// Appends the provided [`Encoded`] [`Event`]s to the [`Stream`] with the
+    /// provided [`stream::Id`] following its provided checked `version`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`File::append_encoded()`].
+    fn append_frames(
+        &mut self,
+        stream: &stream::Id,
+        mut version: Option<stream::Position>,
+        events: Vec<Encoded>,
+    ) -> Result<Option<stream::Position>, AppendError<Error>> {
+        if events.is_empty() {
+            return Ok(version);
+        }
+
+        let now = SystemTime::now();
+        let count = events.len();
+        let mut frames = Vec::with_capacity(count);
+        for (n, ev) in events.into_iter().enumerate() {
+            if let Some(position) = version
+                .filter(|_| frames.last().is_some_and(Frame::is_tombstone))
+            {
+                return Err(
+                    Tombstoned { stream: stream.clone(), position }.into()
+                );
+            }
+            let position = stream::Position::following(version);
+            frames.push(Frame::Event {
+                record: Record {
+                    stream: stream.clone(),
+                    position,
+                    offset: self.index.next_offset.saturating_add(len_u64(n)),
+                    name: ev.name,
+                    version: ev.version,
+                    timestamp: ev.timestamp.unwrap_or(now),
+                    metadata: ev.metadata,
+                    payload: ev.payload,
+                },
+                last: n + 1 == count,
+            });
+            version = Some(position);
+        }
+
+        let locations = self.write(&frames).map_err(AppendError::Store)?;
+        for (frame, at) in frames.iter().zip(locations) {
+            _ = self.index.apply(frame, at);
+        }
+        Ok(version)
+    }
+
+    /// Tombstones the [`Stream`] with the provided [`stream::Id`] by appending
+    /// a tombstone with the reserved [`TOMBSTONE`] name to it, returning its
+    /// [`stream::Position`] (the same as [`Lifecycle::tombstone()`], but
+    /// without decoding [`Event`]s).
+    ///
+    /// # Errors
+    ///
+    /// - [`AppendError::Conflict`] if the current version of the [`Stream`]
+    ///   doesn't match the provided [`ExpectedVersion`].
+    /// - [`AppendError::Tombstoned`] if the [`Stream`] is tombstoned already.
+    /// - [`AppendError::Store`] if the tombstone cannot be appended.
+    pub fn entomb(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+    ) -> Result<stream::Position, AppendError<Error>> {
+        let tombstone = Encoded {
+            name: TOMBSTONE.to_owned(),
+            version: event::Version::MIN,
+            payload: Vec::new(),
+            timestamp: None,
+            metadata: Metadata::new(),
+        };
+        let version = self.check(stream, expected)?;
+        _ = self.append_frames(stream, version, vec![tombstone])?;
+        Ok(stream::Position::following(version))
+    }
+
+    /// Hard-deletes the [`Stream`] with the provided [`stream::Id`] by
+    /// appending a marker of it, so it becomes empty (the same as
+    /// [`Lifecycle::delete()`], but without decoding [`Event`]s).
+    ///
+    /// Returns `false` if the [`Stream`] is empty already.
+    ///
+    /// The [`Record`]s of the deleted [`Stream`] stay in the segment files
+    /// until [`File::compact()`]ed.
+    ///
+    /// # Errors
+    ///
+    /// If the marker cannot be appended.
+    pub fn erase(&mut self, stream: &stream::Id) -> Result<bool, Error> {
+        if self.index.version(stream).is_none() {
+            return Ok(false);
+        }
+        self.mark(&Frame::Deleted {
+            stream: stream.clone(),
+            timestamp: SystemTime::now(),
+        })?;
+        Ok(true)
+    }
+
+    /// Truncates the [`Stream`] with the provided [`stream::Id`] before the
+    /// provided [`stream::Position`] by appending a marker of it, returning
+    /// the number of the truncated [`Record`]s (the same as
+    /// [`Lifecycle::truncate()`], but without decoding [`Event`]s).
+    ///
+    /// The truncated [`Record`]s stay in the segment files until
+    /// [`File::compact()`]ed.
+    ///
+    /// # Errors
+    ///
+    /// If the marker cannot be appended.
+    pub fn truncate_before(
+        &mut self,
+        stream: &stream::Id,
+        before: stream::Position,
+    ) -> Result<usize, Error> {
+        let count = self.index.truncation(stream, before);
+        if count > 0 {
+            self.mark(&Frame::Truncated {
+                stream: stream.clone(),
+                before,
+                timestamp: SystemTime::now(),
+            })?;
+        }
+        Ok(count)
+    }
+
+    /// Appends the provided marker [`Frame`], and applies it to the index.
+    ///
+    /// # Errors
+    ///
+    /// If the marker [`Frame`] cannot be appended.
+    fn mark(&mut self, marker: &Frame) -> Result<(), Error> {
+        for at in self.write(slice::from_ref(marker))? {
+            _ = self.index.apply(marker, at);
+        }
+        Ok(())
+    }
+
+    /// `fsync`s the active segment file and saves the index, regardless of
+    /// the [`Fsync`] policy.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::ReadOnly`] if this [`File`] [`Store`] is read-only.
+    /// - [`Error::Poisoned`] if this [`File`] [`Store`] is poisoned.
+    /// - [`Error::Io`] if the active segment file cannot be `fsync`ed, or the
+    ///   index file cannot be written.
+    pub fn sync(&mut self) -> Result<(), Error> {
+        self.sync_active()?;
+        self.index.save(&self.dir.join(INDEX_FILE))?;
+        Ok(())
+    }
+
+    /// `fsync`s the active segment file, poisoning this [`File`] [`Store`] on
+    /// failure.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::ReadOnly`] if this [`File`] [`Store`] is read-only.
+    /// - [`Error::Poisoned`] if this [`File`] [`Store`] is poisoned already.
+    /// - [`Error::Io`] if the active segment file cannot be `fsync`ed.
+    fn sync_active(&mut self) -> Result<(), Error> {
+        if let Err(e) = (self.sync_data)(self.active()?) {
+            self.poisoned = true;
+            return Err(e.into());
+        }
+        self.synced_at = Instant::now();
+        Ok(())
+    }
+
+    /// Returns the active segment file to be written.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::ReadOnly`] if this [`File`] [`Store`] is read-only.
+    /// - [`Error::Poisoned`] if this [`File`] [`Store`] is poisoned.
+    fn active(&self) -> Result<&fs::File, Error> {
+        if self.poisoned {
+            return Err(Error::Poisoned);
+        }
+        self.active.as_ref().ok_or(Error::ReadOnly)
+    }
+
+    /// Verifies the [CRC]s of all the [`Record`]s in the segment files, and
+    /// that their [`stream::Position`]s are sequential.
+    ///
+    /// Returns the number of the [`Record`]s of the non-empty [`Stream`]s.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Corrupted`] if any [`Record`] is invalid.
+    /// - [`Error::Unsupported`] if any segment file is of another format
+    ///   version.
+    /// - [`Error::Io`] if the segment files cannot be read.
+    ///
+    /// [CRC]: https://en.wikipedia.org/wiki/Cyclic_redundancy_check
+    pub fn verify(&self) -> Result<usize, Error> {
+        let index = scan(&self.dir, Index::default(), Tail::Reject)?;
+        Ok(index.streams.values().map(|s| s.locations.len()).sum())
+    }
+
+    /// Rebuilds the index from scratch by scanning all the segment files,
+    /// and saves it.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::ReadOnly`] if this [`File`] [`Store`] is read-only.
+    /// - [`Error::Poisoned`] if this [`File`] [`Store`] is poisoned.
+    /// - [`Error::Corrupted`] if any segment file except the last one is
+    ///   corrupted.
+    /// - [`Error::Unsupported`] if any segment file is of another format
+    ///   version.
+    /// - [`Error::Io`] if the segment files cannot be read, or the index file
+    ///   cannot be written.
+    pub fn rebuild_index(&mut self) -> Result<(), Error> {
+        self.sync_active()?;
+        // Offsets of the compacted away `Record`s are not reused.
+        let index =
+            Index { next_offset: self.index.next_offset, ..Index::default() };
+        self.index = scan(&self.dir, index, Tail::Truncate)?;
+        self.len = self.index.watermark.offset;
+        self.sync()
+    }
+
+    /// Compacts the segment files by dropping the [`Lifecycle::delete()`]d
+    /// and [`Lifecycle::truncate()`]d [`Record`]s along with their markers,
+    /// and removing the emptied segment files.
+    ///
+    /// Returns the number of reclaimed bytes.
+    ///
+    /// > **NOTE**: Compaction rewrites the segment files, so if it crashes in
+    /// >           the middle, the index is rebuilt on the next opening.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::ReadOnly`] if this [`File`] [`Store`] is read-only.
+    /// - [`Error::Poisoned`] if this [`File`] [`Store`] is poisoned.
+    /// - [`Error::Corrupted`] if any segment file is corrupted.
+    /// - [`Error::Unsupported`] if any segment file is of another format
+    ///   version.
+    /// - [`Error::Io`] if the segment files cannot be read or written.
+    pub fn compact(&mut self) -> Result<u64, Error> {
+        self.sync_active()?;
+        // The index is reset to be rebuilt on the next opening if compaction
+        // crashes, but keeps the offset of the next `Record`, so the offsets
+        // of the compacted away `Record`s are not reused.
+        Index { next_offset: self.index.next_offset, ..Index::default() }
+            .save(&self.dir.join(INDEX_FILE))?;
+        sync_dir(&self.dir)?;
+
+        let live = self.locations().into_iter().collect::<HashSet<_>>();
+        let mut reclaimed = 0;
+        // Segment files are compacted in their order, so a crash never leaves
+        // the dropped records without their markers.
+        for segment in list_segments(&self.dir)? {
+            let path = segment_path(&self.dir, segment);
+            let mut reader = Reader::open(&path, segment)?;
+            let mut kept = frame::MAGIC.to_vec();
+            loop {
+                let offset = reader.offset;
+                match reader.next()? {
+                    Read::Frame(frame, _) => {
+                        if live.contains(&Location { segment, offset }) {
+                            frame.encode(&mut kept)?;
+                        }
+                    }
+                    Read::End => break,
+                    Read::Invalid => {
+                        return Err(Error::Corrupted { segment, offset });
+                    }
+                }
+            }
+
+            let kept_len = len_u64(kept.len());
+            if kept_len == reader.len {
+                continue;
+            }
+            reclaimed += reader.len - kept_len;
+            if kept_len == frame::MAGIC_LEN && segment != self.segment {
+                fs::remove_file(&path)?;
+            } else {
+                let tmp = path.with_extension("tmp");
+                fs::write(&tmp, &kept)?;
+                fs::File::open(&tmp)?.sync_all()?;
+                fs::rename(tmp, &path)?;
+            }
+        }
+        sync_dir(&self.dir)?;
+
+        self.active = Some(open_segment(&self.dir, self.segment)?);
+        self.rebuild_index()?;
+        Ok(reclaimed)
+    }
+
+    /// Appends the provided [`Frame`]s to the active segment file, rolling it
+    /// over beforehand if it's full.
+    ///
+    /// Returns the [`Location`]s of the written [`Frame`]s. The length of
+    /// the active segment file and the watermark of the index are advanced
+    /// only once the [`Frame`]s are written and `fsync`ed (if due).
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::ReadOnly`] if this [`File`] [`Store`] is read-only.
+    /// - [`Error::Poisoned`] if this [`File`] [`Store`] is poisoned, or
+    ///   becomes poisoned by failing to `fsync` or to truncate the written
+    ///   [`Frame`]s back.
+    /// - [`Error::Oversized`] or [`Error::Io`] if the [`Frame`]s cannot be
+    ///   encoded or written.
+    fn write(&mut self, frames: &[Frame]) -> Result<Vec<Location>, Error> {
+        _ = self.active()?;
+        if self.len >= self.options.segment_size {
+            self.roll_over()?;
+        }
+
+        let mut bytes = Vec::new();
+        let mut locations = Vec::with_capacity(frames.len());
+        for f in frames {
+            locations.push(Location {
+                segment: self.segment,
+                offset: self.len + len_u64(bytes.len()),
+            });
+            f.encode(&mut bytes)?;
+        }
+
+        let is_due = match self.options.fsync {
+            Fsync::Always => true,
+            Fsync::Interval(interval) => self.synced_at.elapsed() >= interval,
+            Fsync::Never => false,
+        };
+        let written = self.active()?.write_all(&bytes).map_err(Error::from);
+        if let Err(e) = written
+            .and_then(|()| if is_due { self.sync_active() } else { Ok(()) })
+        {
+            // Not to leave a torn tail behind, which would be followed by the
+            // next writes.
+            let truncated = self.active.as_ref().map(|f| f.set_len(self.len));
+            if !matches!(truncated, Some(Ok(()))) {
+                self.poisoned = true;
+            }
+            return Err(e);
+        }
+
+        self.len += len_u64(bytes.len());
+        self.index.watermark =
+            Location { segment: self.segment, offset: self.len };
+        Ok(locations)
+    }
+
+    /// Seals the active segment file, and starts a new one.
+    ///
+    /// # Errors
+    ///
+    /// If the active segment file cannot be `fsync`ed, the new one cannot be
+    /// created, or the index cannot be saved.
+    fn roll_over(&mut self) -> Result<(), Error> {
+        self.sync_active()?;
+        let segment = self.segment + 1;
+        self.active = Some(open_segment(&self.dir, segment)?);
+        sync_dir(&self.dir)?;
+        self.segment = segment;
+        self.len = frame::MAGIC_LEN;
+        self.index.watermark = Location { segment, offset: frame::MAGIC_LEN };
+        self.sync()
+    }
+
+    /// Lazily reads the [`Record`]s at the provided [`Location`]s.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Corrupted`] if there is no [`Record`] at any [`Location`].
+    /// - [`Error::Unsupported`] if a segment file is of another format
+    ///   version.
+    /// - [`Error::Io`] if a segment file cannot be read.
+    fn read_at(
+        &self,
+        locations: impl IntoIterator<Item = Location>,
+    ) -> impl Iterator<Item = Result<Record, Error>> {
+        let mut reader = None;
+        locations.into_iter().map(move |at| {
+            let mut r = match reader.take() {
+                Some((segment, r)) if segment == at.segment => r,
+                _ => Reader::open(
+                    &segment_path(&self.dir, at.segment),
+                    at.segment,
+                )?,
+            };
+            r.seek(at.offset)?;
+            let read = r.next()?;
+            reader = Some((at.segment, r));
+            let record = match read {
+                Read::Frame(frame, _) => match *frame {
+                    Frame::Event { record, .. } => Some(record),
+                    Frame::Deleted { .. } | Frame::Truncated { .. } => None,
+                },
+                Read::End | Read::Invalid => None,
+            };
+            record.ok_or(Error::Corrupted {
+                segment: at.segment,
+                offset: at.offset,
+            })
+        })
+    }
+}
+
+impl<Ev: DeserializeOwned> File<Ev> {
+    /// Decodes the [`Event`] of the provided [`Record`].
+    ///
+    /// # Errors
+    ///
+    /// If the [`Event`] cannot be decoded.
+    fn decode(&self, record: &Record) -> Result<Ev, Error> {
+        self.options
+            .codecs
+            .decode(&record.name, record.version, &record.payload)
+            .map_err(Error::Codec)
+    }
+
+    /// Reads and decodes the [`Event`]s (except tombstones) of all the
+    /// [`Stream`]s with the [`stream::Id`]s satisfying the provided
+    /// `predicate`, in the order they were appended.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Event`]s cannot be read or decoded.
+    fn read_where(
+        &self,
+        predicate: impl Fn(&stream::Id) -> bool,
+    ) -> Result<Vec<Recorded<Ev>>, Error> {
+        let mut locations = self
+            .index
+            .streams
+            .iter()
+            .filter(|(id, _)| predicate(id))
+            .flat_map(|(_, s)| s.locations.iter().copied())
+            .collect::<Vec<_>>();
+        locations.sort_unstable();
+        self.read_recorded_at(locations)
+    }
+
+    /// Reads and decodes the [`Event`]s (except tombstones) at the provided
+    /// [`Location`]s, in their order.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Event`]s cannot be read or decoded.
+    fn read_recorded_at(
+        &self,
+        locations: impl IntoIterator<Item = Location>,
+    ) -> Result<Vec<Recorded<Ev>>, Error> {
+        self.read_at(locations)
+            .filter(|rec| !rec.as_ref().is_ok_and(|r| r.name == TOMBSTONE))
+            .map(|rec| {
+                let rec = rec?;
+                Ok(Recorded {
+                    event: self.decode(&rec)?,
+                    stream: rec.stream,
+                    position: rec.position,
+                    metadata: rec.metadata,
+                })
+            })
+            .collect()
+    }
+}
+
+impl<Ev> Store<Ev> for File<Ev>
+where
+    Ev: event::Revisable<Revision = event::Version>
+        + Serialize
+        + DeserializeOwned,
+{
+    type Error = Error;
+
+    fn append_with(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+        events: Vec<Ev>,
+        metadata: &Metadata,
+    ) -> Result<Option<stream::Position>, AppendError<Self::Error>> {
+        // Checked before encoding, so encoding errors don't shadow conflicts.
+        _ = self.check(stream, expected)?;
+        Reserved::check(stream, events.iter().map(Event::name))?;
+        let events = events
+            .into_iter()
+            .map(|ev| {
+                let (name, revision) = (ev.name(), ev.revision());
+                Ok(Encoded {
+                    name: name.to_owned(),
+                    version: revision,
+                    payload: self.options.codecs.encode(name, revision, &ev)?,
+                    timestamp: None,
+                    metadata: metadata.clone(),
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map_err(|e| AppendError::Store(Error::Codec(e)))?;
+        self.append_encoded(stream, expected, events)
+    }
+
+    fn read_stream(
+        &self,
+        stream: &stream::Id,
+    ) -> Result<Stream<Ev>, Self::Error> {
+        let (mut events, mut tombstone) = (Vec::new(), None);
+        for rec in self.records(stream)? {
+            if rec.name == TOMBSTONE {
+                tombstone = Some(rec.position);
+            } else {
+                events.push((rec.position, self.decode(&rec)?));
+            }
+        }
+        Ok(Stream::from_raw(stream.clone(), events, tombstone))
+    }
+
+    fn read_recorded(
+        &self,
+        stream: &stream::Id,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error> {
+        let locations =
+            self.index.streams.get(stream).map_or(&[][..], |s| &s.locations);
+        self.read_recorded_at(locations.iter().copied())
+    }
+
+    fn read_category(
+        &self,
+        category: &str,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error> {
+        self.read_where(|id| id.is_in(category))
+    }
+}
+
+impl<Ev> Lifecycle<Ev> for File<Ev>
+where
+    Ev: event::Revisable<Revision = event::Version>
+        + Serialize
+        + DeserializeOwned,
+{
+    /// See [`File::entomb()`] for details.
+    fn tombstone(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+    ) -> Result<stream::Position, AppendError<Self::Error>> {
+        self.entomb(stream, expected)
+    }
+
+    /// See [`File::erase()`] for details.
+    fn delete(&mut self, stream: &stream::Id) -> Result<bool, Self::Error> {
+        self.erase(stream)
+    }
+
+    /// See [`File::truncate_before()`] for details.
+    fn truncate(
+        &mut self,
+        stream: &stream::Id,
+        before: stream::Position,
+    ) -> Result<usize, Self::Error> {
+        self.truncate_before(stream, before)
+    }
+}
+
+#[cfg(feature = "tenant")]
+impl<Ev> tenant::Streams<Ev> for File<Ev>
+where
+    Ev: event::Revisable<Revision = event::Version>
+        + Serialize
+        + DeserializeOwned,
+{
+    fn streams_of(
+        &self,
+        tenant: &tenant::Id,
+    ) -> Result<Vec<stream::Id>, Self::Error> {
+        Ok(tenant::of(tenant, self.streams().map(|(id, _)| id)))
+    }
+
+    fn read_category_of(
+        &self,
+        tenant: &tenant::Id,
+        category: &str,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error> {
+        self.read_where(|id| id.tenant() == Some(tenant) && id.is_in(category))
+    }
+}
+
+#[cfg(feature = "subscription")]
+impl<Ev> subscription::Log<Ev> for File<Ev>
+where
+    Ev: event::Revisable<Revision = event::Version>
+        + Serialize
+        + DeserializeOwned,
+{
+    /// Reads the not truncated [`Record`]s (including tombstones) by their
+    /// [`Record::offset`]s.
+    ///
+    /// > **NOTE**: Markers of the deleted [`Stream`]s are not recorded in the
+    /// >           global log of a [`File`], so are never read.
+    fn read_log(
+        &self,
+        from: subscription::Offset,
+        limit: usize,
+    ) -> Result<Vec<(subscription::Offset, Entry<Ev>)>, Self::Error> {
+        let locations = self.locations();
+
+        // Offsets grow along with the `Location`s, so the first `Record` at
+        // or after the `from` offset is binary searched.
+        let (mut lo, mut hi) = (0, locations.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let Some(rec) = self.read_at(locations.get(mid).copied()).next()
+            else {
+                break;
+            };
+            if rec?.offset < from.get() {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        let tail = locations.get(lo..).unwrap_or_default();
+        self.read_at(tail.iter().copied().take(limit))
+            .map(|rec| {
+                let rec = rec?;
+                let offset = subscription::Offset::new(rec.offset);
+                let entry = if rec.name == TOMBSTONE {
+                    Entry::Tombstone {
+                        stream: rec.stream,
+                        position: rec.position,
+                    }
+                } else {
+                    Entry::Event(Recorded {
+                        event: self.decode(&rec)?,
+                        stream: rec.stream,
+                        position: rec.position,
+                        metadata: rec.metadata,
+                    })
+                };
+                Ok((offset, entry))
+            })
+            .collect()
+    }
+
+    fn end(&self) -> Result<subscription::Offset, Self::Error> {
+        Ok(subscription::Offset::new(self.index.next_offset))
+    }
+}
+
+#[cfg(feature = "temporal")]
+impl<Ev> temporal::History<Ev> for File<Ev>
+where
+    Ev: event::Revisable<Revision = event::Version>
+        + Serialize
+        + DeserializeOwned,
+{
+    /// Reads the [`Record`]s of the [`Stream`] along with their
+    /// [`temporal::Meta`]data, where offsets are the [`Record::offset`]s.
+    fn read_history(
+        &self,
+        stream: &stream::Id,
+        after: Option<stream::Position>,
+    ) -> Result<temporal::Slice<Ev>, Self::Error> {
+        let indexed = self.index.streams.get(stream);
+        let locations = indexed.map_or(&[][..], |s| s.after(after));
+
+        let events = self
+            .read_at(locations.iter().copied())
+            .filter(|rec| !rec.as_ref().is_ok_and(|r| r.name == TOMBSTONE))
+            .map(|rec| {
+                let rec = rec?;
+                let meta = temporal::Meta {
+                    position: rec.position,
+                    offset: rec.offset,
+                    timestamp: rec.timestamp,
+                };
+                Ok((meta, self.decode(&rec)?))
+            })
+            .collect::<Result<_, Self::Error>>()?;
+        Ok(temporal::Slice {
+            truncated: indexed.and_then(|s| stream::Position::new(s.truncated)),
+            events,
+        })
+    }
+}
+
+/// Error of a [`File`] [`Store`].
+#[derive(Debug)]
+pub enum Error {
+    /// I/O operation failed.
+    Io(io::Error),
+
+    /// [`Event`] failed to be encoded or decoded.
+    Codec(codec::Error),
+
+    /// Segment file is corrupted at the provided location.
+    Corrupted {
+        /// Number of the segment file.
+        segment: u64,
+
+        /// Offset of the corrupted [`Record`] in the segment file.
+        offset: u64,
+    },
+
+    /// Segment file is of another format version.
+    Unsupported {
+        /// Number of the segment file.
+        segment: u64,
+    },
+
+    /// [`Record`] is too large to be framed.
+    Oversized,
+
+    /// Directory is locked by another [`File`] [`Store`].
+    Locked,
+
+    /// [`File`] [`Store`] failed to `fsync` or repair its active segment
+    /// file, and must be reopened.
+    Poisoned,
+
+    /// [`File`] [`Store`] is opened [read-only](Options::read_only()).
+    ReadOnly,
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O operation failed: {e}"),
+            Self::Codec(e) => write!(f, "codec failed: {e}"),
+            Self::Corrupted { segment, offset } => write!(
+                f,
+                "segment file `{segment}` is corrupted at offset `{offset}`",
+            ),
+            Self::Unsupported { segment } => write!(
+                f,
+                "segment file `{segment}` is of unsupported format version",
+            ),
+            Self::Oversized => write!(f, "record is too large to be framed"),
+            Self::Locked => {
+                write!(f, "directory is locked by another file store")
+            }
+            Self::Poisoned => {
+                write!(f, "file store is poisoned and must be reopened")
+            }
+            Self::ReadOnly => write!(f, "file store is opened read-only"),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Codec(e) => Some(e),
+            Self::Corrupted { .. }
+            | Self::Unsupported { .. }
+            | Self::Oversized
+            | Self::Locked
+            | Self::Poisoned
+            | Self::ReadOnly => None,
+        }
+    }
+}
+
+/// Sequential reader of the [`Frame`]s of a segment file.
+struct Reader {
+    /// Buffered segment file.
+    inner: BufReader<fs::File>,
+
+    /// Length (in bytes) of the segment file.
+    len: u64,
+
+    /// Offset of the next [`Frame`] to be read.
+    offset: u64,
+}
+
+impl Reader {
+    /// Opens the segment file with the provided number at the provided `path`
+    /// for reading, checking its header.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Corrupted`] if the segment file is shorter than its header.
+    /// - [`Error::Unsupported`] if the segment file is of another format
+    ///   version.
+    /// - [`Error::Io`] if the segment file cannot be opened.
+    fn open(path: &Path, segment: u64) -> Result<Self, Error> {
+        let file = fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < frame::MAGIC_LEN {
+            return Err(Error::Corrupted { segment, offset: 0 });
+        }
+        let mut inner = BufReader::new(file);
+        let mut magic = [0; 8];
+        inner.read_exact(&mut magic)?;
+        if &magic != frame::MAGIC {
+            return Err(Error::Unsupported { segment });
+        }
+        Ok(Self { inner, len, offset: frame::MAGIC_LEN })
+    }
+
+    /// Moves this [`Reader`] to the provided `offset`.
+    ///
+    /// # Errors
+    ///
+    /// If the segment file cannot be seeked.
+    fn seek(&mut self, offset: u64) -> io::Result<()> {
+        if offset != self.offset {
+            _ = self.inner.seek(SeekFrom::Start(offset))?;
+            self.offset = offset;
+        }
+        Ok(())
+    }
+
+    /// Reads the next [`Frame`].
+    ///
+    /// # Errors
+    ///
+    /// If the segment file cannot be read.
+    fn next(&mut self) -> io::Result<Read> {
+        let read =
+            frame::read(&mut self.inner, self.len.saturating_sub(self.offset))?;
+        if let Read::Frame(_, len) = &read {
+            self.offset += len;
+        }
+        Ok(read)
+    }
+}
+
+/// Handling of a torn tail of the last segment file by [`scan()`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Tail {
+    /// Torn tail is truncated.
+    Truncate,
+
+    /// Torn tail is left as is, and not indexed.
+    Ignore,
+
+    /// Torn tail is rejected as [`Error::Corrupted`].
+    Reject,
+}
+
+/// Catches the provided [`Index`] up with the segment files in the provided
+/// directory, starting from its watermark, handling a torn tail of the last
+/// segment file as the provided [`Tail`] defines.
+///
+/// # Errors
+///
+/// - [`Error::Corrupted`] if any segment file (except the last one, unless
+///   [`Tail::Reject`]) is corrupted.
+/// - [`Error::Unsupported`] if any segment file is of another format version.
+/// - [`Error::Io`] if the segment files cannot be read or truncated.
+fn scan(dir: &Path, mut index: Index, tail: Tail) -> Result<Index, Error> {
+    let segments = list_segments(dir)?;
+    let last = segments.last().copied().unwrap_or_default();
+    for segment in segments {
+        let Location { segment: from_segment, offset: from } = index.watermark;
+        if segment < from_segment {
+            continue;
+        }
+        let path = segment_path(dir, segment);
+        if fs::metadata(&path)?.len() < frame::MAGIC_LEN {
+            // Header is torn by a crash in the middle of creating the segment
+            // file, so it has no `Frame`s.
+            if tail == Tail::Reject || segment != last {
+                return Err(Error::Corrupted { segment, offset: 0 });
+            }
+            let offset = if tail == Tail::Truncate {
+                let mut file =
+                    fs::OpenOptions::new().write(true).open(&path)?;
+                file.set_len(0)?;
+                file.write_all(frame::MAGIC)?;
+                file.sync_all()?;
+                frame::MAGIC_LEN
+            } else {
+                0
+            };
+            index.watermark = Location { segment, offset };
+            continue;
+        }
+        let mut reader = Reader::open(&path, segment)?;
+        if segment == from_segment {
+            reader.seek(from.max(frame::MAGIC_LEN))?;
+        }
+
+        let (mut committed, mut pending) = (reader.offset, Vec::new());
+        let is_clean = loop {
+            let at = Location { segment, offset: reader.offset };
+            match reader.next()? {
+                Read::Frame(frame, _) => {
+                    let is_last = frame.is_last();
+                    pending.push((frame, at));
+                    if is_last {
+                        for (f, l) in mem::take(&mut pending) {
+                            if !index.apply(&f, l) {
+                                return Err(Error::Corrupted {
+                                    segment,
+                                    offset: l.offset,
+                                });
+                            }
+                        }
+                        committed = reader.offset;
+                    }
+                }
+                Read::End => break pending.is_empty(),
+                Read::Invalid => break false,
+            }
+        };
+        index.watermark = Location { segment, offset: committed };
+
+        if !is_clean {
+            if tail == Tail::Reject || segment != last {
+                return Err(Error::Corrupted { segment, offset: committed });
+            }
+            if tail == Tail::Truncate {
+                let file = fs::OpenOptions::new().write(true).open(&path)?;
+                file.set_len(committed)?;
+                file.sync_all()?;
+            }
+        }
+    }
+    Ok(index)
+}
+
+/// Lists the numbers of the segment files in the provided directory in their
+/// order.
+///
+/// # Errors
+///
+/// If the directory cannot be read.
+fn list_segments(dir: &Path) -> io::Result<Vec<u64>> {
+    let mut segments = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != SEGMENT_EXT) {
+            continue;
+        }
+        if let Some(n) = path.file_stem().and_then(|s| s.to_str()?.parse().ok())
+        {
+            segments.push(n);
+        }
+    }
+    segments.sort_unstable();
+    Ok(segments)
+}
+
+/// Returns the path of the segment file with the provided number in the
+/// provided directory.
+fn segment_path(dir: &Path, segment: u64) -> PathBuf {
+    dir.join(format!("{segment:020}.{SEGMENT_EXT}"))
+}
+
+/// Opens the segment file with the provided number in the provided directory
+/// for appending, creating it with its header if it doesn't exist.
+///
+/// # Errors
+///
+/// If the segment file cannot be opened, or its header cannot be written.
+fn open_segment(dir: &Path, segment: u64) -> io::Result<fs::File> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(segment_path(dir, segment))?;
+    if file.metadata()?.len() == 0 {
+        file.write_all(frame::MAGIC)?;
+    }
+    Ok(file)
+}
+
+/// Locks the provided directory exclusively by its lock file, returning the
+/// lock file, which holds the lock until dropped.
+///
+/// # Errors
+///
+/// - [`Error::Locked`] if the directory is locked already.
+/// - [`Error::Io`] if the lock file cannot be opened or locked.
+fn lock_dir(dir: &Path) -> Result<fs::File, Error> {
+    let mut options = fs::OpenOptions::new();
+    _ = options.create(true).truncate(false).write(true);
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt as _;
+
+        _ = options.share_mode(0);
+    }
+    let file = match options.open(dir.join(LOCK_FILE)) {
+        Ok(file) => file,
+        // `ERROR_SHARING_VIOLATION` of Windows.
+        Err(e) if cfg!(windows) && e.raw_os_error() == Some(32) => {
+            return Err(Error::Locked);
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    #[cfg(unix)]
+    {
+        use std::os::fd::AsRawFd as _;
+
+        // SAFETY: The file descriptor is owned by the `file`, so stays valid
+        //         during the call.
+        let res = unsafe {
+            libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB)
+        };
+        if res != 0 {
+            let e = io::Error::last_os_error();
+            return Err(if e.kind() == io::ErrorKind::WouldBlock {
+                Error::Locked
+            } else {
+                e.into()
+            });
+        }
+    }
+    Ok(file)
+}
+
+/// Syncs the provided directory, making the creations, renames and removals
+/// of its files durable.
+///
+/// # Errors
+///
+/// If the directory cannot be synced.
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    if cfg!(unix) {
+        fs::File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Converts the provided length into a [`u64`], saturating on overflow.
+fn len_u64(len: usize) -> u64 {
+    u64::try_from(len).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod spec {
+    use std::{
+        env, fs,
+        io::{self, Write as _},
+        path::{Path, PathBuf},
+        process,
+    };
+
+    use super::{
+        Encoded, Error, File, Fsync, INDEX_FILE, Options, segment_path,
+    };
+    use crate::es::{
+        event,
+        store::{AppendError, Metadata, TOMBSTONE},
+        stream::{self, ExpectedVersion},
+    };
+
+    /// Returns a new empty directory for the provided test.
+    fn dir(test: &str) -> PathBuf {
+        let dir = env::temp_dir()
+            .join(format!("arcane-store-file-{test}-{}", process::id()));
+        drop(fs::remove_dir_all(&dir));
+        dir
+    }
+
+    /// Opens a [`File`] store in the provided directory read-only.
+    fn open_read_only(dir: &Path) -> File<()> {
+        Options::new().read_only(true).open(dir).expect("opens read-only")
+    }
+
+    /// Opens a [`File`] store in the provided directory, `fsync`ing every
+    /// append.
+    fn open(dir: &Path) -> File<()> {
+        Options::new().fsync(Fsync::Always).open(dir).expect("opens")
+    }
+
+    /// Returns the [`stream::Id`] of the tested stream.
+    fn chat() -> stream::Id {
+        stream::Id::new("chat", "1").expect("valid ID")
+    }
+
+    /// Appends [`Encoded`] events with the provided payloads to the tested
+    /// stream.
+    fn append(
+        store: &mut File<()>,
+        payloads: &[&[u8]],
+    ) -> Result<Option<stream::Position>, AppendError<Error>> {
+        append_to(store, &chat(), payloads)
+    }
+
+    /// Appends [`Encoded`] events with the provided payloads to the stream
+    /// with the provided [`stream::Id`].
+    fn append_to(
+        store: &mut File<()>,
+        stream: &stream::Id,
+        payloads: &[&[u8]],
+    ) -> Result<Option<stream::Position>, AppendError<Error>> {
+        let events = payloads
+            .iter()
+            .map(|p| Encoded {
+                name: "chat.message".into(),
+                version: event::Version::try_new(1).expect("valid version"),
+                payload: p.to_vec(),
+                timestamp: None,
+                metadata: Metadata::new(),
+            })
+            .collect();
+        store.append_encoded(stream, ExpectedVersion::Any, events)
+    }
+
+    /// Returns the offsets of the tested stream.
+    fn offsets(store: &File<()>) -> Vec<u64> {
+        let records = store.records(&chat()).expect("reads records");
+        records.into_iter().map(|r| r.offset).collect()
+    }
+
+    /// Returns the payloads of the tested stream.
+    fn payloads(store: &File<()>) -> Vec<Vec<u8>> {
+        let records = store.records(&chat()).expect("reads records");
+        records.into_iter().map(|r| r.payload).collect()
+    }
+
+    #[test]
+    fn locks_directory() {
+        let dir = dir("lock");
+        let store = open(&dir);
+
+        let second = Options::new().open::<()>(&dir);
+        assert!(matches!(second, Err(Error::Locked)), "{second:?}");
+
+        drop(store);
+        drop(open(&dir));
+        fs::remove_dir_all(&dir).expect("removes directory");
+    }
+
+    #[test]
+    fn reopens_with_and_without_index() {
+        let dir = dir("reopen");
+        let mut store = open(&dir);
+        _ = append(&mut store, &[b"a", b"b"]).expect("appends");
+        store.sync().expect("syncs");
+        drop(store);
+
+        let mut reopened = open(&dir);
+        assert_eq!(reopened.version(&chat()), stream::Position::new(2));
+        _ = append(&mut reopened, &[b"c"]).expect("appends");
+        drop(reopened);
+
+        fs::remove_file(dir.join(INDEX_FILE)).expect("removes index");
+        let rebuilt = open(&dir);
+        assert_eq!(rebuilt.version(&chat()), stream::Position::new(3));
+        assert_eq!(payloads(&rebuilt), [b"a", b"b", b"c"]);
+
+        drop(rebuilt);
+        fs::remove_dir_all(&dir).expect("removes directory");
+    }
+
+    #[test]
+    fn repairs_torn_tail() {
+        let dir = dir("torn");
+        let mut store = open(&dir);
+        _ = append(&mut store, &[b"a"]).expect("appends");
+        drop(store);
+
+        let path = segment_path(&dir, 0);
+        let len = fs::metadata(&path).expect("reads metadata").len();
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .expect("opens segment");
+        file.write_all(&[42, 0, 0, 0, 1, 2]).expect("writes");
+        drop(file);
+
+        let mut repaired = open(&dir);
+        assert_eq!(fs::metadata(&path).expect("reads metadata").len(), len);
+        assert_eq!(repaired.verify().expect("verifies"), 1);
+        _ = append(&mut repaired, &[b"b"]).expect("appends");
+        assert_eq!(payloads(&repaired), [b"a", b"b"]);
+
+        drop(repaired);
+        fs::remove_dir_all(&dir).expect("removes directory");
+    }
+
+    #[test]
+    fn rejects_unsupported_format() {
+        let dir = dir("format");
+        let mut store = open(&dir);
+        _ = append(&mut store, &[b"a"]).expect("appends");
+        drop(store);
+
+        let path = segment_path(&dir, 0);
+        let segment = fs::read(&path).expect("reads segment");
+        let (magic, frames) = segment.split_at(8);
+        fs::write(&path, frames).expect("writes segment");
+        for opened in [
+            Options::new().open::<()>(&dir),
+            Options::new().read_only(true).open::<()>(&dir),
+        ] {
+            assert!(
+                matches!(opened, Err(Error::Unsupported { segment: 0 })),
+                "{opened:?}",
+            );
+        }
+        assert_eq!(fs::read(&path).expect("reads segment"), frames);
+
+        fs::write(&path, &magic[..3]).expect("writes segment");
+        let mut repaired = open(&dir);
+        assert_eq!(fs::read(&path).expect("reads segment"), magic);
+        _ = append(&mut repaired, &[b"b"]).expect("appends");
+        assert_eq!(payloads(&repaired), [b"b"]);
+
+        drop(repaired);
+        fs::remove_dir_all(&dir).expect("removes directory");
+    }
+
+    #[test]
+    fn keeps_offsets_stable() {
+        let dir = dir("offsets");
+        let other = stream::Id::new("chat", "2").expect("valid ID");
+        let mut store = open(&dir);
+        _ = append(&mut store, &[b"a"]).expect("appends");
+        _ = append_to(&mut store, &other, &[b"b", b"c"]).expect("appends");
+        _ = append(&mut store, &[b"d"]).expect("appends");
+        _ = append_to(&mut store, &other, &[b"e"]).expect("appends");
+        assert_eq!(offsets(&store), [0, 3]);
+
+        assert!(store.erase(&other).expect("deletes"));
+        assert_eq!(
+            store
+                .truncate_before(
+                    &chat(),
+                    stream::Position::new(2).expect("valid position")
+                )
+                .expect("truncates"),
+            1
+        );
+        assert!(store.compact().expect("compacts") > 0);
+        _ = append(&mut store, &[b"f"]).expect("appends");
+        assert_eq!(offsets(&store), [3, 5]);
+        drop(store);
+
+        let mut reopened = open(&dir);
+        _ = append(&mut reopened, &[b"g"]).expect("appends");
+        assert_eq!(offsets(&reopened), [3, 5, 6]);
+        drop(reopened);
+
+        fs::remove_file(dir.join(INDEX_FILE)).expect("removes index");
+        let rebuilt = open(&dir);
+        assert_eq!(offsets(&rebuilt), [3, 5, 6]);
+
+        drop(rebuilt);
+        fs::remove_dir_all(&dir).expect("removes directory");
+    }
+
+    #[test]
+    fn persists_metadata() {
+        let dir = dir("metadata");
+        let metadata = Metadata::new().caused_by("cmd-1");
+        let mut store = open(&dir);
+        _ = append(&mut store, &[b"a"]).expect("appends");
+        let events = vec![Encoded {
+            name: "chat.message".into(),
+            version: event::Version::try_new(1).expect("valid version"),
+            payload: b"b".to_vec(),
+            timestamp: None,
+            metadata: metadata.clone().correlated_with("flow-1"),
+        }];
+        _ = store
+            .append_encoded(&chat(), ExpectedVersion::Any, events)
+            .expect("appends");
+        _ = store.compact().expect("compacts");
+        drop(store);
+
+        let reopened = open(&dir);
+        let record = |pos| {
+            let pos = stream::Position::new(pos).expect("valid position");
+            let rec = reopened.record(&chat(), pos).expect("reads record");
+            rec.expect("has record").metadata
+        };
+        assert_eq!(record(1), Metadata::new());
+        assert_eq!(record(2), metadata.correlated_with("flow-1"));
+
+        drop(reopened);
+        fs::remove_dir_all(&dir).expect("removes directory");
+    }
+
+    #[test]
+    fn rejects_reserved_name() {
+        let dir = dir("reserved");
+        let mut store = open(&dir);
+        _ = append(&mut store, &[b"a"]).expect("appends");
+        let forged = || Encoded {
+            name: TOMBSTONE.into(),
+            version: event::Version::MIN,
+            payload: Vec::new(),
+            timestamp: None,
+            metadata: Metadata::new(),
+        };
+        let rejected =
+            store.append_encoded(&chat(), ExpectedVersion::Any, vec![forged()]);
+        assert!(
+            matches!(rejected, Err(AppendError::Reserved(_))),
+            "{rejected:?}",
+        );
+        let first = stream::Position::new(5).expect("valid position");
+        let other = stream::Id::new("chat", "2").expect("valid ID");
+        let restored = store.append_encoded_from(&other, first, vec![forged()]);
+        assert!(
+            matches!(restored, Err(AppendError::Reserved(_))),
+            "{restored:?}",
+        );
+        assert_eq!(store.version(&other), None);
+        assert_eq!(offsets(&store), [0]);
+
+        let entombed =
+            store.entomb(&chat(), ExpectedVersion::Any).expect("tombstones");
+        assert_eq!(entombed.get(), 2);
+        let tombstoned = append(&mut store, &[b"b"]);
+        assert!(
+            matches!(tombstoned, Err(AppendError::Tombstoned(_))),
+            "{tombstoned:?}",
+        );
+
+        drop(store);
+        fs::remove_dir_all(&dir).expect("removes directory");
+    }
+
+    #[test]
+    fn opens_read_only() {
+        let dir = dir("read-only");
+        let missing = Options::new().read_only(true).open::<()>(&dir);
+        assert!(matches!(missing, Err(Error::Io(_))), "{missing:?}");
+
+        fs::create_dir_all(&dir).expect("creates directory");
+        drop(open_read_only(&dir));
+        assert_eq!(fs::read_dir(&dir).expect("reads directory").count(), 0);
+
+        let mut store = open(&dir);
+        _ = append(&mut store, &[b"a"]).expect("appends");
+        let path = segment_path(&dir, 0);
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .expect("opens segment");
+        file.write_all(&[42, 0, 0, 0, 1, 2]).expect("writes");
+        drop(file);
+        let len = fs::metadata(&path).expect("reads metadata").len();
+
+        let mut reader = open_read_only(&dir);
+        assert_eq!(payloads(&reader), [b"a"]);
+        assert_eq!(fs::metadata(&path).expect("reads metadata").len(), len);
+        let rejected = append(&mut reader, &[b"b"]);
+        assert!(
+            matches!(rejected, Err(AppendError::Store(Error::ReadOnly))),
+            "{rejected:?}",
+        );
+        assert!(matches!(reader.sync(), Err(Error::ReadOnly)));
+        assert!(matches!(reader.compact(), Err(Error::ReadOnly)));
+
+        drop((store, reader));
+        fs::remove_dir_all(&dir).expect("removes directory");
+    }
+
+    #[test]
+    fn poisons_on_fsync_failure() {
+        let dir = dir("poison");
+        let mut store = open(&dir);
+        _ = append(&mut store, &[b"a"]).expect("appends");
+        let len =
+            fs::metadata(segment_path(&dir, 0)).expect("reads metadata").len();
+
+        store.sync_data = |_| Err(io::Error::other("fsync failed"));
+        let failed = append(&mut store, &[b"b"]);
+        assert!(
+            matches!(failed, Err(AppendError::Store(Error::Io(_)))),
+            "{failed:?}",
+        );
+        assert_eq!(store.version(&chat()), stream::Position::new(1));
+        assert_eq!(
+            fs::metadata(segment_path(&dir, 0)).expect("reads metadata").len(),
+            len,
+        );
+
+        store.sync_data = fs::File::sync_data;
+        let poisoned = append(&mut store, &[b"c"]);
+        assert!(
+            matches!(poisoned, Err(AppendError::Store(Error::Poisoned))),
+            "{poisoned:?}",
+        );
+        assert!(matches!(store.sync(), Err(Error::Poisoned)));
+        assert_eq!(payloads(&store), [b"a"]);
+        drop(store);
+
+        let mut reopened = open(&dir);
+        assert_eq!(payloads(&reopened), [b"a"]);
+        _ = append(&mut reopened, &[b"d"]).expect("appends");
+        assert_eq!(payloads(&reopened), [b"a", b"d"]);
+
+        drop(reopened);
+        fs::remove_dir_all(&dir).expect("removes directory");
+    }
+}
@@ -0,0 +1,782 @@
+//! [`Store`] of [`Event`]s.
+//!
+//! # Deletion and truncation
+//!
+//! [`Stream`]s supporting the [`Lifecycle`] of a [`Store`] may be removed in
+//! the following ways, all of them keeping the [`Offset`]s of the remaining
+//! [`Entry`]s in the global log intact:
+//! - [`Lifecycle::tombstone()`] soft-deletes a [`Stream`] by appending a
+//!   tombstone with the reserved [`TOMBSTONE`] name to it. Its [`Event`]s stay
+//!   readable, but nothing can be appended to it anymore. [`Subscription`]s
+//!   deliver the tombstone as an [`Entry::Tombstone`], so projections may
+//!   mark the [`Stream`] as closed.
+//! - [`Lifecycle::delete()`] hard-deletes a [`Stream`] by erasing all its
+//!   [`Event`]s, so it becomes empty and may be started anew.
+//!   [`Subscription`]s don't deliver the erased [`Event`]s anymore, but
+//!   deliver an [`Entry::Deleted`] instead, so projections may drop
+//!   everything derived from the [`Stream`].
+//! - [`Lifecycle::truncate()`] erases the [`Event`]s of a [`Stream`] before
+//!   the provided [`stream::Position`], keeping its version (usually, once a
+//!   snapshot is taken). [`Subscription`]s silently skip the erased
+//!   [`Event`]s, so projections rebuilt from scratch should start from the
+//!   snapshot too.
+//!
+//! [`Offset`]: super::subscription::Offset
+//! [`Subscription`]: super::subscription::Subscription
+
+#[cfg(feature = "file")]
+pub mod file;
+pub mod retry;
+
+#[cfg(feature = "outbox")]
+use std::collections::VecDeque;
+#[cfg(feature = "temporal")]
+use std::time::SystemTime;
+use std::{
+    collections::HashMap, convert::Infallible, error::Error as StdError, fmt,
+    mem,
+};
+
+use derive_more::{Display, Error};
+
+#[cfg(feature = "file")]
+#[doc(inline)]
+pub use self::file::File;
+#[doc(inline)]
+pub use self::retry::Retry;
+#[cfg(feature = "outbox")]
+use super::outbox;
+use super::{
+    Event, event,
+    stream::{self, ExpectedVersion, Stream},
+};
+
+/// Reserved [`event::Name`] of tombstones of the [`Lifecycle::tombstone()`]d
+/// [`Stream`]s.
+///
+/// [`Event`]s must not use this [`event::Name`].
+pub const TOMBSTONE: event::Name = "$tombstone";
+
+/// [`Event`] recorded into a [`Store`], along with its [`stream::Id`],
+/// [`stream::Position`] and [`Metadata`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Recorded<Ev> {
+    /// [`stream::Id`] of the [`Stream`] the [`Event`] was recorded into.
+    pub stream: stream::Id,
+
+    /// [`stream::Position`] of the [`Event`] in its [`Stream`].
+    pub position: stream::Position,
+
+    /// [`Metadata`] the [`Event`] was appended with.
+    pub metadata: Metadata,
+
+    /// Recorded [`Event`] itself.
+    pub event: Ev,
+}
+
+/// Metadata of [`Event`]s not carried by the [`Event`]s themselves, recorded
+/// along with them via [`Store::append_with()`].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Metadata {
+    /// Identifier of the message (usually, a [`Command::id()`]) that caused
+    /// the [`Event`]s, if any.
+    ///
+    /// [`Command::id()`]: crate::cqrs::Command::id()
+    pub causation: Option<String>,
+
+    /// Identifier of the whole conversation (workflow) the [`Event`]s belong
+    /// to, if any.
+    pub correlation: Option<String>,
+}
+
+impl Metadata {
+    /// Creates new empty [`Metadata`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { causation: None, correlation: None }
+    }
+
+    /// Sets the identifier of the message that caused the [`Event`]s.
+    #[must_use]
+    pub fn caused_by(mut self, id: impl Into<String>) -> Self {
+        self.causation = Some(id.into());
+        self
+    }
+
+    /// Sets the identifier of the conversation the [`Event`]s belong to.
+    #[must_use]
+    pub fn correlated_with(mut self, id: impl Into<String>) -> Self {
+        self.correlation = Some(id.into());
+        self
+    }
+
+    /// Indicates whether these [`Metadata`] are empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.causation.is_none() && self.correlation.is_none()
+    }
+}
+
+/// Entry of the global log of a [`Store`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Entry<Ev> {
+    /// [`Recorded`] [`Event`].
+    Event(Recorded<Ev>),
+
+    /// Tombstone of a [`Lifecycle::tombstone()`]d [`Stream`].
+    Tombstone {
+        /// [`stream::Id`] of the [`Stream`].
+        stream: stream::Id,
+
+        /// [`stream::Position`] of the tombstone in its [`Stream`].
+        position: stream::Position,
+    },
+
+    /// Marker of a [`Lifecycle::delete()`]d [`Stream`], which preceding
+    /// [`Entry`]s are erased.
+    Deleted {
+        /// [`stream::Id`] of the [`Stream`].
+        stream: stream::Id,
+    },
+}
+
+impl<Ev> Entry<Ev> {
+    /// Returns the [`stream::Id`] of the [`Stream`] this [`Entry`] belongs to.
+    #[must_use]
+    pub const fn stream(&self) -> &stream::Id {
+        match self {
+            Self::Event(rec) => &rec.stream,
+            Self::Tombstone { stream, .. } | Self::Deleted { stream } => stream,
+        }
+    }
+
+    /// Returns the [`Recorded`] [`Event`] of this [`Entry`], if it's the one.
+    #[must_use]
+    pub const fn as_event(&self) -> Option<&Recorded<Ev>> {
+        match self {
+            Self::Event(rec) => Some(rec),
+            Self::Tombstone { .. } | Self::Deleted { .. } => None,
+        }
+    }
+
+    /// Converts this [`Entry`] into its [`Recorded`] [`Event`], if it's the
+    /// one.
+    #[must_use]
+    pub fn into_event(self) -> Option<Recorded<Ev>> {
+        match self {
+            Self::Event(rec) => Some(rec),
+            Self::Tombstone { .. } | Self::Deleted { .. } => None,
+        }
+    }
+}
+
+/// Append-only storage of [`Event`]s split into [`Stream`]s.
+pub trait Store<Ev> {
+    /// Error of this [`Store`].
+    type Error;
+
+    /// Appends the provided [`Event`]s to the end of the [`Stream`] with the
+    /// provided [`stream::Id`], returning the new version of this [`Stream`].
+    ///
+    /// Returns [`None`] if the [`Stream`] is still empty.
+    ///
+    /// # Errors
+    ///
+    /// - [`AppendError::Conflict`] if the current version of the [`Stream`]
+    ///   doesn't match the provided [`ExpectedVersion`]. Nothing is appended
+    ///   in this case.
+    /// - [`AppendError::Tombstoned`] if the [`Stream`] is
+    ///   [`Lifecycle::tombstone()`]d.
+    /// - [`AppendError::Reserved`] if any [`Event`] has the reserved
+    ///   [`TOMBSTONE`] name. Nothing is appended in this case.
+    /// - [`AppendError::Store`] if the [`Event`]s cannot be appended.
+    fn append(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+        events: Vec<Ev>,
+    ) -> Result<Option<stream::Position>, AppendError<Self::Error>> {
+        self.append_with(stream, expected, events, &Metadata::new())
+    }
+
+    /// Appends the provided [`Event`]s to the end of the [`Stream`] with the
+    /// provided [`stream::Id`] along with the provided [`Metadata`], returning
+    /// the new version of this [`Stream`].
+    ///
+    /// Returns [`None`] if the [`Stream`] is still empty.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Store::append()`].
+    fn append_with(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+        events: Vec<Ev>,
+        metadata: &Metadata,
+    ) -> Result<Option<stream::Position>, AppendError<Self::Error>>;
+
+    /// Reads the whole [`Stream`] with the provided [`stream::Id`].
+    ///
+    /// Returns an empty [`Stream`] if nothing was appended to it yet. The
+    /// returned [`Stream`] may be [truncated](Stream::is_truncated) and end
+    /// with a [tombstone](Stream::tombstone).
+    ///
+    /// # Errors
+    ///
+    /// If the [`Stream`] cannot be read.
+    fn read_stream(
+        &self,
+        stream: &stream::Id,
+    ) -> Result<Stream<Ev>, Self::Error>;
+
+    /// Reads all the [`Recorded`] [`Event`]s of the [`Stream`] with the
+    /// provided [`stream::Id`] along with their [`Metadata`], in their order.
+    ///
+    /// Tombstones and truncated [`Event`]s are not included.
+    ///
+    /// The default implementation filters [`Store::read_category()`], so
+    /// should be overridden by the [`Store`]s able to read a single
+    /// [`Stream`].
+    ///
+    /// # Errors
+    ///
+    /// If the [`Event`]s cannot be read.
+    fn read_recorded(
+        &self,
+        stream: &stream::Id,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error> {
+        let mut events = self.read_category(stream.category())?;
+        events.retain(|r| r.stream == *stream);
+        Ok(events)
+    }
+
+    /// Reads all the [`Event`]s of all the [`Stream`]s of the provided
+    /// [`stream::Category`] in the order they were appended to this [`Store`].
+    ///
+    /// Use [`stream::Categorized::CATEGORY`] to read all the [`Stream`]s of a
+    /// [`Categorized`] [`Event`], without listing its [`event::Name`]s.
+    ///
+    /// Tombstones are not included.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Event`]s cannot be read.
+    ///
+    /// [`Categorized`]: stream::Categorized
+    fn read_category(
+        &self,
+        category: &str,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error>;
+}
+
+/// [`Store`] supporting soft and hard deletion and truncation of its
+/// [`Stream`]s.
+///
+/// See the [module-level documentation](self#deletion-and-truncation) for
+/// details.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::{
+/// #     Event,
+/// #     store::{self, AppendError, Lifecycle as _, Store as _},
+/// #     stream::{self, ExpectedVersion},
+/// # };
+/// #
+/// #[derive(Clone, Debug, Event)]
+/// #[event(name = "message.posted")]
+/// struct MessagePosted;
+///
+/// #[derive(Clone, Debug, Event)]
+/// #[event(name = "$tombstone")]
+/// struct Forged;
+///
+/// let mut store = store::Memory::<MessagePosted>::new();
+/// let chat = stream::Id::new("chat", "1").unwrap();
+/// let posted = |n| vec![MessagePosted; n];
+/// store.append(&chat, ExpectedVersion::Any, posted(3)).unwrap();
+///
+/// // Truncation keeps the version of a stream.
+/// let before = stream::Position::new(3).unwrap();
+/// assert_eq!(store.truncate(&chat, before).unwrap(), 2);
+/// let stream = store.read_stream(&chat).unwrap();
+/// assert!(stream.is_truncated());
+/// assert_eq!(stream.first(), stream::Position::new(3));
+/// assert_eq!(stream.version(), stream::Position::new(3));
+///
+/// // Tombstoned stream stays readable, but rejects appends.
+/// let pos = store.tombstone(&chat, ExpectedVersion::Any).unwrap();
+/// assert_eq!(pos.get(), 4);
+/// assert_eq!(store.read_stream(&chat).unwrap().tombstone(), Some(pos));
+/// let err = store.append(&chat, ExpectedVersion::Any, posted(1));
+/// assert!(matches!(err, Err(AppendError::Tombstoned(_))));
+///
+/// // Events cannot pretend to be tombstones.
+/// let mut forged = store::Memory::<Forged>::new();
+/// let err = forged.append(&chat, ExpectedVersion::Any, vec![Forged]);
+/// assert!(matches!(err, Err(AppendError::Reserved(_))));
+/// assert!(forged.read_stream(&chat).unwrap().is_empty());
+///
+/// // Deleted stream may be started anew.
+/// assert!(store.delete(&chat).unwrap());
+/// assert!(store.read_stream(&chat).unwrap().is_empty());
+/// let ver = store.append(&chat, ExpectedVersion::NoStream, posted(1));
+/// assert_eq!(ver.unwrap(), Some(stream::Position::FIRST));
+/// ```
+pub trait Lifecycle<Ev>: Store<Ev> {
+    /// Soft-deletes the [`Stream`] with the provided [`stream::Id`] by
+    /// appending a tombstone with the reserved [`TOMBSTONE`] name to it,
+    /// returning its [`stream::Position`].
+    ///
+    /// The [`Event`]s of the [`Stream`] stay readable, but nothing can be
+    /// appended to it anymore. An empty [`Stream`] may be tombstoned too.
+    ///
+    /// # Errors
+    ///
+    /// - [`AppendError::Conflict`] if the current version of the [`Stream`]
+    ///   doesn't match the provided [`ExpectedVersion`].
+    /// - [`AppendError::Tombstoned`] if the [`Stream`] is tombstoned already.
+    /// - [`AppendError::Store`] if the tombstone cannot be appended.
+    fn tombstone(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+    ) -> Result<stream::Position, AppendError<Self::Error>>;
+
+    /// Hard-deletes the [`Stream`] with the provided [`stream::Id`] by
+    /// erasing all its [`Event`]s (along with its tombstone, if any), so it
+    /// becomes empty and may be started anew.
+    ///
+    /// Returns `false` if the [`Stream`] is empty already.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Stream`] cannot be deleted.
+    fn delete(&mut self, stream: &stream::Id) -> Result<bool, Self::Error>;
+
+    /// Truncates the [`Stream`] with the provided [`stream::Id`] by erasing
+    /// its [`Event`]s before the provided [`stream::Position`], returning
+    /// their number.
+    ///
+    /// The last [`Event`] (or tombstone) is never erased, so the [`Stream`]
+    /// keeps its version.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Stream`] cannot be truncated.
+    fn truncate(
+        &mut self,
+        stream: &stream::Id,
+        before: stream::Position,
+    ) -> Result<usize, Self::Error>;
+}
+
+/// Conflict of the actual version of a [`Stream`] with the
+/// [`ExpectedVersion`] on appending [`Event`]s to it.
+#[derive(Clone, Debug, Display, Eq, Error, PartialEq)]
+#[display(
+    "version conflict in `{stream}` stream: expected `{expected:?}`, but \
+     actual is `{actual:?}`"
+)]
+pub struct Conflict {
+    /// [`stream::Id`] of the conflicting [`Stream`].
+    pub stream: stream::Id,
+
+    /// [`ExpectedVersion`] of the [`Stream`].
+    pub expected: ExpectedVersion,
+
+    /// Actual version of the [`Stream`], or [`None`] if it's empty.
+    pub actual: Option<stream::Position>,
+}
+
+/// Rejection of appending to a [`Lifecycle::tombstone()`]d [`Stream`].
+#[derive(Clone, Debug, Display, Eq, Error, PartialEq)]
+#[display("`{stream}` stream is tombstoned at `{position}` position")]
+pub struct Tombstoned {
+    /// [`stream::Id`] of the tombstoned [`Stream`].
+    pub stream: stream::Id,
+
+    /// [`stream::Position`] of the tombstone.
+    pub position: stream::Position,
+}
+
+/// Rejection of appending an [`Event`] with the reserved [`TOMBSTONE`]
+/// [`event::Name`] to a [`Stream`].
+#[derive(Clone, Debug, Display, Eq, Error, PartialEq)]
+#[display(
+    "`{name}` event name is reserved, so cannot be appended to \
+           `{stream}` stream"
+)]
+pub struct Reserved {
+    /// [`stream::Id`] of the [`Stream`] the [`Event`] was appended to.
+    pub stream: stream::Id,
+
+    /// Reserved [`event::Name`] of the [`Event`].
+    #[error(not(source))]
+    pub name: String,
+}
+
+impl Reserved {
+    /// Checks whether none of the provided [`event::Name`]s of the [`Event`]s
+    /// appended to the [`Stream`] with the provided [`stream::Id`] is the
+    /// reserved [`TOMBSTONE`] one.
+    ///
+    /// # Errors
+    ///
+    /// If any of the provided [`event::Name`]s is the reserved [`TOMBSTONE`]
+    /// one.
+    pub(crate) fn check<N: AsRef<str>>(
+        stream: &stream::Id,
+        names: impl IntoIterator<Item = N>,
+    ) -> Result<(), Self> {
+        names.into_iter().find(|n| n.as_ref() == TOMBSTONE).map_or(
+            Ok(()),
+            |name| {
+                Err(Self {
+                    stream: stream.clone(),
+                    name: name.as_ref().to_owned(),
+                })
+            },
+        )
+    }
+}
+
+/// Error of [`Store::append()`]ing [`Event`]s.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AppendError<E> {
+    /// Actual version of a [`Stream`] conflicts with the [`ExpectedVersion`].
+    Conflict(Conflict),
+
+    /// [`Stream`] is [`Lifecycle::tombstone()`]d.
+    Tombstoned(Tombstoned),
+
+    /// [`Event`] has the reserved [`TOMBSTONE`] [`event::Name`].
+    Reserved(Reserved),
+
+    /// [`Store`] failed to append [`Event`]s.
+    Store(E),
+}
+
+impl<E> AppendError<E> {
+    /// Returns the [`Conflict`] of this [`AppendError`], if any.
+    #[must_use]
+    pub const fn as_conflict(&self) -> Option<&Conflict> {
+        match self {
+            Self::Conflict(c) => Some(c),
+            Self::Tombstoned(_) | Self::Reserved(_) | Self::Store(_) => None,
+        }
+    }
+}
+
+impl<E> From<Tombstoned> for AppendError<E> {
+    fn from(t: Tombstoned) -> Self {
+        Self::Tombstoned(t)
+    }
+}
+
+impl<E> From<Reserved> for AppendError<E> {
+    fn from(r: Reserved) -> Self {
+        Self::Reserved(r)
+    }
+}
+
+impl<E> From<Conflict> for AppendError<E> {
+    fn from(c: Conflict) -> Self {
+        Self::Conflict(c)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for AppendError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Conflict(c) => write!(f, "{c}"),
+            Self::Tombstoned(t) => write!(f, "{t}"),
+            Self::Reserved(r) => write!(f, "{r}"),
+            Self::Store(e) => write!(f, "failed to append events: {e}"),
+        }
+    }
+}
+
+impl<E: StdError + 'static> StdError for AppendError<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Conflict(c) => Some(c),
+            Self::Tombstoned(t) => Some(t),
+            Self::Reserved(r) => Some(r),
+            Self::Store(e) => Some(e),
+        }
+    }
+}
+
+/// In-memory [`Store`], mainly intended for tests and prototyping.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::{
+/// #     Event,
+/// #     store::{self, Store as _},
+/// #     stream::{self, Categorized as _, ExpectedVersion},
+/// # };
+/// #
+/// #[derive(Clone, Debug, Event)]
+/// #[event(name = "chat.created")]
+/// struct ChatCreated;
+///
+/// #[derive(Clone, Debug, Event)]
+/// #[event(stream = "chat")]
+/// enum ChatEvent {
+///     Created(ChatCreated),
+/// }
+///
+/// let mut store = store::Memory::<ChatEvent>::new();
+/// let (chat1, chat2) = (
+///     stream::Id::of::<ChatEvent>("1").unwrap(),
+///     stream::Id::of::<ChatEvent>("2").unwrap(),
+/// );
+/// let other = stream::Id::new("user", "1").unwrap();
+///
+/// let ev = || vec![ChatEvent::Created(ChatCreated)];
+/// let ver = store.append(&chat1, ExpectedVersion::NoStream, ev()).unwrap();
+/// assert_eq!(ver, stream::Position::new(1));
+/// store.append(&other, ExpectedVersion::Any, ev()).unwrap();
+/// store.append(&chat2, ExpectedVersion::Any, ev()).unwrap();
+///
+/// let err = store.append(&chat1, ExpectedVersion::NoStream, ev());
+/// let conflict = err.unwrap_err().as_conflict().cloned().unwrap();
+/// assert_eq!(conflict.actual, stream::Position::new(1));
+///
+/// let recorded = store.read_category(ChatEvent::CATEGORY).unwrap();
+/// assert_eq!(
+///     recorded.iter().map(|r| r.stream.to_string()).collect::<Vec<_>>(),
+///     ["chat-1", "chat-2"],
+/// );
+/// assert_eq!(store.read_stream(&chat2).unwrap().len(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Memory<Ev> {
+    /// All the [`Entry`]s in the order they were appended, where the erased
+    /// ones are left as [`None`], so the positions of the rest never change.
+    pub(crate) log: Vec<Option<Entry<Ev>>>,
+
+    /// Current versions of all the non-empty [`Stream`]s.
+    versions: HashMap<stream::Id, stream::Position>,
+
+    /// Positions of the tombstones of all the tombstoned [`Stream`]s.
+    tombstones: HashMap<stream::Id, stream::Position>,
+
+    /// [`outbox::Envelope`]s pending to be published.
+    #[cfg(feature = "outbox")]
+    pub(crate) outbox: VecDeque<outbox::Envelope>,
+
+    /// Times the [`Entry`]s were appended at, in the same order as the
+    /// [`Memory::log`].
+    #[cfg(feature = "temporal")]
+    pub(crate) timestamps: Vec<SystemTime>,
+}
+
+impl<Ev> Memory<Ev> {
+    /// Creates a new empty [`Memory`] [`Store`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            log: Vec::new(),
+            versions: HashMap::new(),
+            tombstones: HashMap::new(),
+            #[cfg(feature = "outbox")]
+            outbox: VecDeque::new(),
+            #[cfg(feature = "temporal")]
+            timestamps: Vec::new(),
+        }
+    }
+
+    /// Returns the current version of the [`Stream`] with the provided
+    /// [`stream::Id`], or [`None`] if it's empty.
+    #[must_use]
+    pub fn version(&self, stream: &stream::Id) -> Option<stream::Position> {
+        self.versions.get(stream).copied()
+    }
+
+    /// Returns all the non-empty [`Stream`]s of this [`Memory`] [`Store`]
+    /// along with their current versions, in no particular order.
+    pub fn streams(
+        &self,
+    ) -> impl Iterator<Item = (&stream::Id, stream::Position)> {
+        self.versions.iter().map(|(id, ver)| (id, *ver))
+    }
+
+    /// Returns all the non-erased [`Recorded`] [`Event`]s of this [`Memory`]
+    /// [`Store`] in the order they were appended.
+    pub fn log(&self) -> impl Iterator<Item = &Recorded<Ev>> {
+        self.log.iter().flatten().filter_map(Entry::as_event)
+    }
+
+    /// Checks whether the [`Stream`] with the provided [`stream::Id`] is not
+    /// tombstoned and matches the provided [`ExpectedVersion`].
+    ///
+    /// # Errors
+    ///
+    /// - [`AppendError::Tombstoned`] if the [`Stream`] is tombstoned.
+    /// - [`AppendError::Conflict`] if the [`Stream`] doesn't match the
+    ///   provided [`ExpectedVersion`].
+    fn check(
+        &self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+    ) -> Result<Option<stream::Position>, AppendError<Infallible>> {
+        if let Some(&position) = self.tombstones.get(stream) {
+            return Err(Tombstoned { stream: stream.clone(), position }.into());
+        }
+        let version = self.version(stream);
+        if !expected.matches(version) {
+            return Err(Conflict {
+                stream: stream.clone(),
+                expected,
+                actual: version,
+            }
+            .into());
+        }
+        Ok(version)
+    }
+
+    /// Pushes the provided [`Entry`]s to the end of the log at once.
+    fn push(&mut self, entries: impl IntoIterator<Item = Entry<Ev>>) {
+        #[cfg(feature = "temporal")]
+        let now = SystemTime::now();
+        for entry in entries {
+            self.log.push(Some(entry));
+            #[cfg(feature = "temporal")]
+            self.timestamps.push(now);
+        }
+    }
+
+    /// Erases the [`Entry`]s of the [`Stream`] with the provided
+    /// [`stream::Id`] matching the provided predicate, returning their
+    /// number.
+    fn erase(
+        &mut self,
+        stream: &stream::Id,
+        predicate: impl Fn(&Entry<Ev>) -> bool,
+    ) -> usize {
+        let mut erased = 0;
+        for slot in &mut self.log {
+            if slot
+                .as_ref()
+                .is_some_and(|e| e.stream() == stream && predicate(e))
+            {
+                drop(mem::take(slot));
+                erased += 1;
+            }
+        }
+        erased
+    }
+}
+
+impl<Ev> Default for Memory<Ev> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Ev: Clone + Event> Store<Ev> for Memory<Ev> {
+    type Error = Infallible;
+
+    fn append_with(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+        events: Vec<Ev>,
+        metadata: &Metadata,
+    ) -> Result<Option<stream::Position>, AppendError<Self::Error>> {
+        let mut version = self.check(stream, expected)?;
+        Reserved::check(stream, events.iter().map(Event::name))?;
+        let mut entries = Vec::with_capacity(events.len());
+        for event in events {
+            let position = stream::Position::following(version);
+            let rec = Recorded {
+                stream: stream.clone(),
+                position,
+                metadata: metadata.clone(),
+                event,
+            };
+            entries.push(Entry::Event(rec));
+            version = Some(position);
+        }
+        self.push(entries);
+        if let Some(v) = version {
+            _ = self.versions.insert(stream.clone(), v);
+        }
+        Ok(version)
+    }
+
+    fn read_stream(
+        &self,
+        stream: &stream::Id,
+    ) -> Result<Stream<Ev>, Self::Error> {
+        let events = self
+            .log()
+            .filter(|r| r.stream == *stream)
+            .map(|r| (r.position, r.event.clone()))
+            .collect();
+        Ok(Stream::from_raw(
+            stream.clone(),
+            events,
+            self.tombstones.get(stream).copied(),
+        ))
+    }
+
+    fn read_recorded(
+        &self,
+        stream: &stream::Id,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error> {
+        Ok(self.log().filter(|r| r.stream == *stream).cloned().collect())
+    }
+
+    fn read_category(
+        &self,
+        category: &str,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error> {
+        Ok(self.log().filter(|r| r.stream.is_in(category)).cloned().collect())
+    }
+}
+
+impl<Ev: Clone + Event> Lifecycle<Ev> for Memory<Ev> {
+    fn tombstone(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+    ) -> Result<stream::Position, AppendError<Self::Error>> {
+        let position =
+            stream::Position::following(self.check(stream, expected)?);
+        self.push([Entry::Tombstone { stream: stream.clone(), position }]);
+        _ = self.versions.insert(stream.clone(), position);
+        _ = self.tombstones.insert(stream.clone(), position);
+        Ok(position)
+    }
+
+    fn delete(&mut self, stream: &stream::Id) -> Result<bool, Self::Error> {
+        if self.versions.remove(stream).is_none() {
+            return Ok(false);
+        }
+        _ = self.tombstones.remove(stream);
+        _ = self.erase(stream, |_| true);
+        self.push([Entry::Deleted { stream: stream.clone() }]);
+        Ok(true)
+    }
+
+    fn truncate(
+        &mut self,
+        stream: &stream::Id,
+        before: stream::Position,
+    ) -> Result<usize, Self::Error> {
+        let Some(version) = self.version(stream) else {
+            return Ok(0);
+        };
+        let before = before.min(version);
+        Ok(self.erase(stream, |e| {
+            e.as_event().is_some_and(|r| r.position < before)
+        }))
+    }
+}
@@ -0,0 +1,414 @@
+//! [`Retry`]ing of commands on [`Conflict`]s.
+
+use std::{error::Error as StdError, fmt, thread, time::Duration};
+
+#[cfg(doc)]
+use super::TOMBSTONE;
+use super::{AppendError, Conflict, Metadata, Reserved, Store, Tombstoned};
+#[cfg(doc)]
+use crate::es::{Event, stream::ExpectedVersion};
+use crate::es::{
+    event,
+    stream::{self, Stream},
+};
+
+/// Delay between [`Retry`] attempts.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Backoff {
+    /// No delay.
+    #[default]
+    None,
+
+    /// The same delay before every retry.
+    Fixed(Duration),
+
+    /// Delay doubling before every retry, starting from the `initial` one and
+    /// capped by the `max` one.
+    Exponential {
+        /// Delay before the first retry.
+        initial: Duration,
+
+        /// Maximum delay before a retry.
+        max: Duration,
+    },
+}
+
+impl Backoff {
+    /// Returns the delay before the provided `retry` (starting from `1`).
+    #[must_use]
+    pub fn delay(self, retry: u32) -> Duration {
+        match self {
+            Self::None => Duration::ZERO,
+            Self::Fixed(delay) => delay,
+            Self::Exponential { initial, max } => initial
+                .saturating_mul(2_u32.saturating_pow(retry.saturating_sub(1)))
+                .min(max),
+        }
+    }
+}
+
+/// Hook deciding whether the concurrently appended [`Event`]s (the first
+/// argument) commute with ours (the second argument), so ours may be appended
+/// on top of them without re-running the command.
+pub type Resolver<Ev> = fn(&[Ev], &[Ev]) -> bool;
+
+/// Hook blocking the current thread for the provided delay between [`Retry`]
+/// attempts.
+pub type Sleep = fn(Duration);
+
+/// Executor of a command against a [`Stream`], retrying it on [`Conflict`]s.
+///
+/// The command is run against a state [`event::Sourced`] from the whole
+/// [`Stream`], and its [`Event`]s are appended expecting the [`Stream`] to be
+/// of the same version it was loaded with. On a [`Conflict`], the [`Stream`]
+/// is reloaded and the command is re-run, up to
+/// [`Retry::max_retries()`] times.
+///
+/// [`Retry`] is blocking: [`Backoff`] delays block the current thread via
+/// [`thread::sleep()`] by default (or via the [`Sleep`] hook set by
+/// [`Retry::sleep_with()`]). In asynchronous contexts, either run it on a
+/// thread allowed to block, or use [`Backoff::None`].
+///
+/// # Example
+///
+/// ```rust
+/// # use std::time::Duration;
+/// #
+/// # use arcane::es::{
+/// #     Event,
+/// #     event::Sourced,
+/// #     store::{self, AppendError, Retry, Store, retry::Backoff},
+/// #     stream::{self, ExpectedVersion, Position, Stream},
+/// # };
+/// #
+/// #[derive(Clone, Debug, Event)]
+/// #[event(name = "counter.incremented", revision = 1)]
+/// struct Incremented;
+///
+/// #[derive(Clone, Debug, Event)]
+/// enum CounterEvent {
+///     Incremented(Incremented),
+/// }
+///
+/// #[derive(Default)]
+/// struct Counter(u32);
+///
+/// impl Sourced<Incremented> for Counter {
+///     fn apply(&mut self, _: &Incremented) {
+///         self.0 += 1;
+///     }
+/// }
+///
+/// impl Sourced<CounterEvent> for Counter {
+///     fn apply(&mut self, CounterEvent::Incremented(ev): &CounterEvent) {
+///         self.apply(ev);
+///     }
+/// }
+///
+/// /// `store::Memory` appending a concurrent `Event` on the first append.
+/// #[derive(Default)]
+/// struct Racy(store::Memory<CounterEvent>, bool);
+///
+/// impl Store<CounterEvent> for Racy {
+///     type Error = std::convert::Infallible;
+///
+///     fn append_with(
+///         &mut self,
+///         id: &stream::Id,
+///         expected: ExpectedVersion,
+///         events: Vec<CounterEvent>,
+///         metadata: &store::Metadata,
+///     ) -> Result<Option<Position>, AppendError<Self::Error>> {
+///         if !self.1 {
+///             self.1 = true;
+///             let ev = vec![CounterEvent::Incremented(Incremented)];
+///             self.0.append(id, ExpectedVersion::Any, ev)?;
+///         }
+///         self.0.append_with(id, expected, events, metadata)
+///     }
+///
+///     fn read_stream(
+///         &self,
+///         id: &stream::Id,
+///     ) -> Result<Stream<CounterEvent>, Self::Error> {
+///         self.0.read_stream(id)
+///     }
+///
+///     fn read_category(
+///         &self,
+///         category: &str,
+///     ) -> Result<Vec<store::Recorded<CounterEvent>>, Self::Error> {
+///         self.0.read_category(category)
+///     }
+/// }
+///
+/// let id = stream::Id::new("counter", "1").unwrap();
+/// let increment_up_to_2 = |c: &Counter| {
+///     if c.0 >= 2 {
+///         return Err("limit reached");
+///     }
+///     Ok(vec![CounterEvent::Incremented(Incremented)])
+/// };
+///
+/// // The command is re-run after the conflict.
+/// let mut store = Racy::default();
+/// let ver = Retry::new().execute(&mut store, &id, increment_up_to_2);
+/// assert_eq!(ver.unwrap(), Position::new(2));
+///
+/// // The command is not retried at all.
+/// let mut store = Racy::default();
+/// let res =
+///     Retry::new().max_retries(0).execute(&mut store, &id, increment_up_to_2);
+/// assert!(matches!(res, Err(store::retry::Error::Conflict(_))));
+///
+/// // Delays between retries are made via the provided hook.
+/// let mut store = Racy::default();
+/// let ver = Retry::new()
+///     .backoff(Backoff::Fixed(Duration::from_secs(3600)))
+///     .sleep_with(|delay| assert_eq!(delay, Duration::from_secs(3600)))
+///     .execute(&mut store, &id, increment_up_to_2);
+/// assert_eq!(ver.unwrap(), Position::new(2));
+///
+/// // Increments always commute, so the command is not re-run.
+/// let mut store = Racy::default();
+/// let ver = Retry::new().resolve_with(|_, _| true).execute(
+///     &mut store,
+///     &id,
+///     increment_up_to_2,
+/// );
+/// assert_eq!(ver.unwrap(), Position::new(2));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Retry<Ev> {
+    /// Maximum number of retries after the first attempt.
+    max_retries: u32,
+
+    /// [`Backoff`] between retries.
+    backoff: Backoff,
+
+    /// [`Resolver`] of [`Conflict`]s, if any.
+    resolver: Option<Resolver<Ev>>,
+
+    /// [`Sleep`] hook delaying retries.
+    sleep: Sleep,
+}
+
+impl<Ev> Default for Retry<Ev> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Ev> Retry<Ev> {
+    /// Creates a new [`Retry`] with `3` retries, no [`Backoff`], no
+    /// [`Resolver`] and sleeping via [`thread::sleep()`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_retries: 3,
+            backoff: Backoff::None,
+            resolver: None,
+            sleep: thread::sleep,
+        }
+    }
+
+    /// Sets the maximum number of retries after the first attempt.
+    #[must_use]
+    pub const fn max_retries(mut self, n: u32) -> Self {
+        self.max_retries = n;
+        self
+    }
+
+    /// Sets the [`Backoff`] between retries.
+    #[must_use]
+    pub const fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets the [`Sleep`] hook delaying retries according to the [`Backoff`].
+    #[must_use]
+    pub const fn sleep_with(mut self, sleep: Sleep) -> Self {
+        self.sleep = sleep;
+        self
+    }
+
+    /// Sets the [`Resolver`] deciding whether the concurrently appended
+    /// [`Event`]s commute with ours, so the command needs no re-running.
+    #[must_use]
+    pub const fn resolve_with(mut self, resolver: Resolver<Ev>) -> Self {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    /// Executes the provided `command` against the state sourced from the
+    /// [`Stream`] with the provided [`stream::Id`], and appends the produced
+    /// [`Event`]s to it, retrying on [`Conflict`]s.
+    ///
+    /// Returns the new version of the [`Stream`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Command`] if the `command` fails.
+    /// - [`Error::Store`] if the [`Store`] fails.
+    /// - [`Error::Conflict`] if all the retries are exhausted.
+    /// - [`Error::Tombstoned`] if the [`Stream`] is tombstoned, in which case
+    ///   the `command` is not run.
+    /// - [`Error::Reserved`] if the `command` produces an [`Event`] with the
+    ///   reserved [`TOMBSTONE`] name.
+    pub fn execute<St, S, C, E>(
+        &self,
+        store: &mut St,
+        stream: &stream::Id,
+        command: C,
+    ) -> Result<Option<stream::Position>, Error<E, St::Error>>
+    where
+        Ev: Clone,
+        St: Store<Ev> + ?Sized,
+        S: Default + event::Sourced<Ev>,
+        C: FnMut(&S) -> Result<Vec<Ev>, E>,
+    {
+        self.execute_with(store, stream, &Metadata::new(), command)
+    }
+
+    /// Same as [`Retry::execute()`], but appends the produced [`Event`]s along
+    /// with the provided [`Metadata`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Retry::execute()`].
+    pub fn execute_with<St, S, C, E>(
+        &self,
+        store: &mut St,
+        stream: &stream::Id,
+        metadata: &Metadata,
+        mut command: C,
+    ) -> Result<Option<stream::Position>, Error<E, St::Error>>
+    where
+        Ev: Clone,
+        St: Store<Ev> + ?Sized,
+        S: Default + event::Sourced<Ev>,
+        C: FnMut(&S) -> Result<Vec<Ev>, E>,
+    {
+        let loaded = store.read_stream(stream).map_err(Error::Store)?;
+        tombstoned(&loaded)?;
+        let mut version = loaded.version();
+        let mut events = command(&source(&loaded)).map_err(Error::Command)?;
+
+        let mut retry = 0;
+        loop {
+            let appended = store.append_with(
+                stream,
+                version.into(),
+                events.clone(),
+                metadata,
+            );
+            let conflict = match appended {
+                Ok(ver) => return Ok(ver),
+                Err(AppendError::Store(e)) => return Err(Error::Store(e)),
+                Err(AppendError::Tombstoned(t)) => {
+                    return Err(Error::Tombstoned(t));
+                }
+                Err(AppendError::Reserved(r)) => {
+                    return Err(Error::Reserved(r));
+                }
+                Err(AppendError::Conflict(c)) => c,
+            };
+            if retry >= self.max_retries {
+                return Err(Error::Conflict(conflict));
+            }
+            retry += 1;
+
+            let delay = self.backoff.delay(retry);
+            if !delay.is_zero() {
+                (self.sleep)(delay);
+            }
+
+            let reloaded = store.read_stream(stream).map_err(Error::Store)?;
+            tombstoned(&reloaded)?;
+            if let Some(resolve) = self.resolver {
+                let concurrent = reloaded
+                    .iter()
+                    .filter(|(pos, _)| version.is_none_or(|v| *pos > v))
+                    .map(|(_, ev)| ev.clone())
+                    .collect::<Vec<_>>();
+                if resolve(&concurrent, &events) {
+                    version = reloaded.version();
+                    continue;
+                }
+            }
+            version = reloaded.version();
+            events = command(&source(&reloaded)).map_err(Error::Command)?;
+        }
+    }
+}
+
+/// Sources a new state from all the [`Event`]s of the provided [`Stream`].
+fn source<S, Ev>(stream: &Stream<Ev>) -> S
+where
+    S: Default + event::Sourced<Ev>,
+{
+    let mut state = S::default();
+    stream.replay(&mut state);
+    state
+}
+
+/// Checks whether the provided [`Stream`] is not tombstoned.
+///
+/// # Errors
+///
+/// [`Error::Tombstoned`] if the provided [`Stream`] is tombstoned.
+fn tombstoned<Ev, C, S>(stream: &Stream<Ev>) -> Result<(), Error<C, S>> {
+    stream.tombstone().map_or(Ok(()), |position| {
+        let stream = stream.id().clone();
+        Err(Error::Tombstoned(Tombstoned { stream, position }))
+    })
+}
+
+/// Error of [`Retry::execute()`]ing a command.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error<C, S> {
+    /// Command failed.
+    Command(C),
+
+    /// [`Store`] failed.
+    Store(S),
+
+    /// All the retries are exhausted, while the [`Stream`] still conflicts
+    /// with the [`ExpectedVersion`].
+    Conflict(Conflict),
+
+    /// [`Stream`] is tombstoned.
+    Tombstoned(Tombstoned),
+
+    /// [`Event`] has the reserved [`TOMBSTONE`] name.
+    Reserved(Reserved),
+}
+
+impl<C: fmt::Display, S: fmt::Display> fmt::Display for Error<C, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Command(e) => write!(f, "command failed: {e}"),
+            Self::Store(e) => write!(f, "store failed: {e}"),
+            Self::Conflict(c) => write!(f, "retries exhausted: {c}"),
+            Self::Tombstoned(t) => write!(f, "{t}"),
+            Self::Reserved(r) => write!(f, "{r}"),
+        }
+    }
+}
+
+impl<C, S> StdError for Error<C, S>
+where
+    C: StdError + 'static,
+    S: StdError + 'static,
+{
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Command(e) => Some(e),
+            Self::Store(e) => Some(e),
+            Self::Conflict(c) => Some(c),
+            Self::Tombstoned(t) => Some(t),
+            Self::Reserved(r) => Some(r),
+        }
+    }
+}
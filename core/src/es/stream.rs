@@ -0,0 +1,639 @@
+//! [`Stream`] machinery.
+
+use std::{
+    fmt,
+    num::{NonZero, ParseIntError},
+    slice,
+    str::FromStr,
+};
+
+use derive_more::{Display, Error, Into};
+
+#[cfg(feature = "tenant")]
+use super::tenant;
+use super::{Event, event};
+
+/// Category of [`Stream`]s, grouping all the [`Stream`]s of the same
+/// aggregate type (like `chat`).
+pub type Category = &'static str;
+
+/// Separator between a [`Category`] and an identifier in an [`Id`].
+pub const SEPARATOR: char = '-';
+
+/// [`Event`] belonging to [`Stream`]s of a single [`Category`].
+pub trait Categorized: Event {
+    /// [`Category`] of [`Stream`]s this [`Event`] belongs to.
+    const CATEGORY: Category;
+}
+
+/// Identifier of a [`Stream`], consisting of its [`Category`] and an
+/// identifier within it.
+///
+/// Displayed and parsed in the `<category>-<id>` form (like `chat-42`), where
+/// the `<category>` cannot contain the [`SEPARATOR`].
+///
+/// With the `tenant` feature, an [`Id`] may also belong to a tenant, being
+/// displayed and parsed in the `<tenant>/<category>-<id>` form (like
+/// `acme/chat-42`) then, and the `<category>` cannot contain the
+/// [`tenant::SEPARATOR`] either.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::stream;
+/// #
+/// let id: stream::Id = "chat-42".parse().unwrap();
+///
+/// assert_eq!(id.category(), "chat");
+/// assert_eq!(id.id(), "42");
+/// assert_eq!(id, stream::Id::new("chat", "42").unwrap());
+/// assert_eq!(id.to_string(), "chat-42");
+///
+/// assert!("chat".parse::<stream::Id>().is_err());
+/// assert!("-42".parse::<stream::Id>().is_err());
+/// ```
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(try_from = "String", into = "String")
+)]
+#[cfg_attr(
+    feature = "tenant",
+    expect(clippy::struct_field_names, reason = "`id` is the domain term")
+)]
+pub struct Id {
+    /// Tenant this [`Id`] belongs to, if any.
+    #[cfg(feature = "tenant")]
+    tenant: Option<tenant::Id>,
+
+    /// [`Category`] of this [`Id`].
+    category: String,
+
+    /// Identifier of a [`Stream`] within its [`Category`].
+    id: String,
+}
+
+impl Id {
+    /// Creates a new [`Id`] out of the provided `category` and `id`.
+    ///
+    /// # Errors
+    ///
+    /// - If the provided `category` is empty or contains the [`SEPARATOR`]
+    ///   (or the [`tenant::SEPARATOR`]) or whitespaces.
+    /// - If the provided `id` is empty or contains whitespaces.
+    pub fn new(
+        category: impl Into<String>,
+        id: impl Into<String>,
+    ) -> Result<Self, ParseIdError> {
+        let (category, id) = (category.into(), id.into());
+        #[cfg(feature = "tenant")]
+        if category.contains(tenant::SEPARATOR) {
+            return Err(ParseIdError::Category);
+        }
+        if category.is_empty()
+            || category.contains(|c: char| c == SEPARATOR || c.is_whitespace())
+        {
+            return Err(ParseIdError::Category);
+        }
+        if id.is_empty() || id.contains(char::is_whitespace) {
+            return Err(ParseIdError::Id);
+        }
+        Ok(Self {
+            #[cfg(feature = "tenant")]
+            tenant: None,
+            category,
+            id,
+        })
+    }
+
+    /// Creates a new [`Id`] of a [`Stream`] in the [`Category`] of the
+    /// specified [`Categorized`] [`Event`].
+    ///
+    /// # Errors
+    ///
+    /// If the provided `id` is empty or contains whitespaces.
+    pub fn of<Ev: Categorized + ?Sized>(
+        id: impl Into<String>,
+    ) -> Result<Self, ParseIdError> {
+        Self::new(Ev::CATEGORY, id)
+    }
+
+    /// Returns the [`Category`] of this [`Id`].
+    #[must_use]
+    pub fn category(&self) -> &str {
+        &self.category
+    }
+
+    /// Returns the identifier of a [`Stream`] within its [`Category`].
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Checks whether this [`Id`] belongs to the provided [`Category`].
+    #[must_use]
+    pub fn is_in(&self, category: &str) -> bool {
+        self.category == category
+    }
+
+    /// Returns the tenant this [`Id`] belongs to, if any.
+    #[cfg(feature = "tenant")]
+    #[must_use]
+    pub const fn tenant(&self) -> Option<&tenant::Id> {
+        self.tenant.as_ref()
+    }
+
+    /// Places this [`Id`] into the provided tenant, replacing the one it
+    /// belongs to, if any.
+    #[cfg(feature = "tenant")]
+    #[must_use]
+    pub fn in_tenant(mut self, tenant: tenant::Id) -> Self {
+        self.tenant = Some(tenant);
+        self
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(feature = "tenant")]
+        if let Some(tenant) = &self.tenant {
+            write!(f, "{tenant}{}", tenant::SEPARATOR)?;
+        }
+        write!(f, "{}{SEPARATOR}{}", self.category, self.id)
+    }
+}
+
+impl FromStr for Id {
+    type Err = ParseIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Tenants and categories cannot contain the `SEPARATOR`, so the tenant
+        // one met before it always separates a tenant.
+        #[cfg(feature = "tenant")]
+        if let Some((prefix, rest)) = s
+            .split_once(tenant::SEPARATOR)
+            .filter(|(prefix, _)| !prefix.contains(SEPARATOR))
+        {
+            let owner = tenant::Id::new(prefix)?;
+            let (category, id) =
+                rest.split_once(SEPARATOR).ok_or(ParseIdError::Separator)?;
+            return Self::new(category, id)
+                .map(|stream| stream.in_tenant(owner));
+        }
+        let (category, id) =
+            s.split_once(SEPARATOR).ok_or(ParseIdError::Separator)?;
+        Self::new(category, id)
+    }
+}
+
+impl TryFrom<String> for Id {
+    type Error = ParseIdError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Id> for String {
+    fn from(id: Id) -> Self {
+        id.to_string()
+    }
+}
+
+/// Error of parsing an [`Id`].
+#[derive(Clone, Copy, Debug, Display, Eq, Error, Hash, PartialEq)]
+pub enum ParseIdError {
+    /// [`Id`] doesn't contain the [`SEPARATOR`].
+    #[display("stream ID must be in the `<category>{SEPARATOR}<id>` form")]
+    Separator,
+
+    /// [`Category`] is empty or contains the [`SEPARATOR`] or whitespaces.
+    #[display(
+        "stream category cannot be empty or contain `{SEPARATOR}` or \
+         whitespaces"
+    )]
+    Category,
+
+    /// Identifier within a [`Category`] is empty or contains whitespaces.
+    #[display("stream ID cannot be empty or contain whitespaces")]
+    Id,
+
+    /// Tenant is not a valid [`tenant::Id`].
+    #[cfg(feature = "tenant")]
+    #[display("{}", tenant::ParseIdError)]
+    Tenant,
+}
+
+#[cfg(feature = "tenant")]
+impl From<tenant::ParseIdError> for ParseIdError {
+    fn from(_: tenant::ParseIdError) -> Self {
+        Self::Tenant
+    }
+}
+
+/// [`NonZero`] [`u64`] position of an [`Event`] in its [`Stream`], starting
+/// from `1`.
+///
+/// The [`Position`] of the last [`Event`] in a [`Stream`] represents the
+/// current version of this [`Stream`].
+#[derive(
+    Clone, Copy, Debug, Display, Eq, Hash, Into, Ord, PartialEq, PartialOrd,
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(transparent)
+)]
+pub struct Position(NonZero<u64>);
+
+impl Position {
+    /// [`Position`] of the first [`Event`] in a [`Stream`].
+    pub const FIRST: Self = Self(NonZero::<u64>::MIN);
+
+    /// Creates a new [`Position`] out of the provided `value`.
+    ///
+    /// The provided `value` should not be `0` (zero).
+    #[must_use]
+    pub const fn new(value: u64) -> Option<Self> {
+        match NonZero::new(value) {
+            Some(v) => Some(Self(v)),
+            None => None,
+        }
+    }
+
+    /// Returns the value of this [`Position`] as a primitive type.
+    #[inline]
+    #[must_use]
+    pub const fn get(self) -> u64 {
+        self.0.get()
+    }
+
+    /// Returns the [`Position`] following this one, or [`None`] on overflow.
+    #[inline]
+    #[must_use]
+    pub const fn next(self) -> Option<Self> {
+        match self.0.checked_add(1) {
+            Some(v) => Some(Self(v)),
+            None => None,
+        }
+    }
+
+    /// Returns the [`Position`] of an [`Event`] to be appended to a [`Stream`]
+    /// of the provided version.
+    ///
+    /// # Panics
+    ///
+    /// If the provided version is the last possible [`Position`].
+    pub(crate) const fn following(version: Option<Self>) -> Self {
+        let Some(version) = version else {
+            return Self::FIRST;
+        };
+        let Some(pos) = version.next() else {
+            panic!("`Stream` `Position` overflow")
+        };
+        pos
+    }
+}
+
+impl FromStr for Position {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+/// Expected version of a [`Stream`] to append [`Event`]s to, used for
+/// optimistic concurrency control.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::stream::{ExpectedVersion, Position};
+/// #
+/// let v2 = Position::new(2);
+///
+/// assert!(ExpectedVersion::Any.matches(None));
+/// assert!(ExpectedVersion::NoStream.matches(None));
+/// assert!(!ExpectedVersion::NoStream.matches(v2));
+/// assert!(ExpectedVersion::StreamExists.matches(v2));
+/// assert!(ExpectedVersion::from(v2).matches(v2));
+/// assert!(!ExpectedVersion::from(v2).matches(Position::new(3)));
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum ExpectedVersion {
+    /// Any version of a [`Stream`], including an empty one.
+    #[default]
+    Any,
+
+    /// [`Stream`] must be empty.
+    NoStream,
+
+    /// [`Stream`] must be non-empty.
+    StreamExists,
+
+    /// [`Stream`] must be of exactly this version.
+    Exact(Position),
+}
+
+impl ExpectedVersion {
+    /// Checks whether the provided `actual` version of a [`Stream`] (or
+    /// [`None`] if it's empty) satisfies this [`ExpectedVersion`].
+    #[must_use]
+    pub const fn matches(self, actual: Option<Position>) -> bool {
+        match (self, actual) {
+            (Self::Any, _)
+            | (Self::NoStream, None)
+            | (Self::StreamExists, Some(_)) => true,
+            (Self::Exact(expected), Some(actual)) => {
+                expected.get() == actual.get()
+            }
+            (Self::NoStream, Some(_))
+            | (Self::StreamExists | Self::Exact(_), None) => false,
+        }
+    }
+}
+
+impl From<Option<Position>> for ExpectedVersion {
+    /// Creates an [`ExpectedVersion`] exactly matching the provided version of
+    /// a [`Stream`] (or [`None`] if it's empty).
+    fn from(version: Option<Position>) -> Self {
+        version.map_or(Self::NoStream, Self::Exact)
+    }
+}
+
+impl From<Position> for ExpectedVersion {
+    fn from(version: Position) -> Self {
+        Self::Exact(version)
+    }
+}
+
+/// Stream of [`Event`]s of a single aggregate, paired with their
+/// [`Position`]s.
+///
+/// A [`Stream`] may start after the [`Position::FIRST`] once its history is
+/// truncated, and may end with a tombstone once it's deleted.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::{
+/// #     Event,
+/// #     event::{Initialized, Sourced},
+/// #     stream::{self, Stream},
+/// # };
+/// #
+/// #[derive(Event)]
+/// #[event(name = "chat.created", revision = 1)]
+/// struct ChatCreated;
+///
+/// #[derive(Event)]
+/// #[event(name = "chat.message.posted", revision = 1)]
+/// struct MessagePosted;
+///
+/// #[derive(Event)]
+/// #[event(stream = "chat")]
+/// enum ChatEvent {
+///     #[event(init)]
+///     Created(ChatCreated),
+///     MessagePosted(MessagePosted),
+/// }
+///
+/// #[derive(Debug, Eq, PartialEq)]
+/// struct Chat {
+///     message_count: usize,
+/// }
+///
+/// impl Initialized<ChatCreated> for Chat {
+///     fn init(_: &ChatCreated) -> Self {
+///         Self { message_count: 0 }
+///     }
+/// }
+///
+/// impl Sourced<MessagePosted> for Chat {
+///     fn apply(&mut self, _: &MessagePosted) {
+///         self.message_count += 1;
+///     }
+/// }
+///
+/// let mut stream = Stream::new(stream::Id::of::<ChatEvent>("42").unwrap());
+/// stream.push(ChatEvent::Created(ChatCreated));
+/// stream.push(ChatEvent::MessagePosted(MessagePosted));
+///
+/// assert_eq!(stream.id().to_string(), "chat-42");
+/// assert_eq!(stream.version().map(stream::Position::get), Some(2));
+///
+/// let mut chat = Option::<Chat>::None;
+/// stream.replay(&mut chat);
+/// assert_eq!(chat, Some(Chat { message_count: 1 }));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Stream<Ev> {
+    /// [`Id`] of this [`Stream`].
+    id: Id,
+
+    /// [`Event`]s of this [`Stream`] paired with their [`Position`]s.
+    events: Vec<(Position, Ev)>,
+
+    /// [`Position`] of the tombstone ending this [`Stream`], if it's deleted.
+    tombstone: Option<Position>,
+}
+
+impl<Ev> Stream<Ev> {
+    /// Creates a new empty [`Stream`] with the provided [`Id`].
+    #[must_use]
+    pub const fn new(id: Id) -> Self {
+        Self { id, events: Vec::new(), tombstone: None }
+    }
+
+    /// Creates a new [`Stream`] with the provided [`Id`] out of the provided
+    /// [`Event`]s paired with their [`Position`]s.
+    ///
+    /// Returns [`None`] if the provided [`Position`]s are not strictly
+    /// sequential starting from the [`Position::FIRST`].
+    #[must_use]
+    pub fn from_events(
+        id: Id,
+        events: impl IntoIterator<Item = (Position, Ev)>,
+    ) -> Option<Self> {
+        let events = events.into_iter().collect::<Vec<_>>();
+        if events.first().is_some_and(|(pos, _)| *pos != Position::FIRST) {
+            return None;
+        }
+        Self::from_truncated(id, events)
+    }
+
+    /// Creates a new [`Stream`] with the provided [`Id`] out of the provided
+    /// [`Event`]s paired with their [`Position`]s, which history before them
+    /// is truncated.
+    ///
+    /// Returns [`None`] if the provided [`Position`]s are not strictly
+    /// sequential.
+    #[must_use]
+    pub fn from_truncated(
+        id: Id,
+        events: impl IntoIterator<Item = (Position, Ev)>,
+    ) -> Option<Self> {
+        let events = events.into_iter().collect::<Vec<_>>();
+        let sequential = events.windows(2).all(|pair| match pair {
+            [(prev, _), (next, _)] => prev.next() == Some(*next),
+            _ => true,
+        });
+        sequential.then_some(Self { id, events, tombstone: None })
+    }
+
+    /// Creates a new [`Stream`] out of the provided parts, without checking
+    /// their [`Position`]s.
+    pub(crate) const fn from_raw(
+        id: Id,
+        events: Vec<(Position, Ev)>,
+        tombstone: Option<Position>,
+    ) -> Self {
+        Self { id, events, tombstone }
+    }
+
+    /// Returns the [`Id`] of this [`Stream`].
+    #[must_use]
+    pub const fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Returns the current version of this [`Stream`] (the [`Position`] of
+    /// its last [`Event`] or tombstone), or [`None`] if this [`Stream`] is
+    /// empty.
+    #[must_use]
+    pub fn version(&self) -> Option<Position> {
+        self.tombstone.or_else(|| self.events.last().map(|(pos, _)| *pos))
+    }
+
+    /// Returns the [`Position`] of the first [`Event`] in this [`Stream`], or
+    /// [`None`] if it has no [`Event`]s.
+    ///
+    /// It's after the [`Position::FIRST`] if this [`Stream`] is
+    /// [truncated](Stream::is_truncated).
+    #[must_use]
+    pub fn first(&self) -> Option<Position> {
+        self.events.first().map(|(pos, _)| *pos)
+    }
+
+    /// Indicates whether the history of this [`Stream`] before its
+    /// [`Stream::first()`] [`Event`] is truncated.
+    ///
+    /// States shouldn't be sourced from a truncated [`Stream`] alone, but
+    /// rather from a snapshot taken before its [`Stream::first()`]
+    /// [`Event`].
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.first().is_some_and(|pos| pos != Position::FIRST)
+    }
+
+    /// Returns the [`Position`] of the tombstone ending this [`Stream`], if
+    /// it's deleted.
+    #[must_use]
+    pub const fn tombstone(&self) -> Option<Position> {
+        self.tombstone
+    }
+
+    /// Returns the number of [`Event`]s in this [`Stream`].
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Indicates whether this [`Stream`] has no [`Event`]s.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Appends the provided [`Event`] to the end of this [`Stream`], returning
+    /// its [`Position`].
+    ///
+    /// # Panics
+    ///
+    /// If this [`Stream`] already contains [`u64::MAX`] [`Event`]s.
+    pub fn push(&mut self, event: Ev) -> Position {
+        let pos = Position::following(self.version());
+        self.events.push((pos, event));
+        pos
+    }
+
+    /// Appends a tombstone to the end of this [`Stream`], returning its
+    /// [`Position`].
+    ///
+    /// Returns the [`Position`] of the existing tombstone if this [`Stream`]
+    /// is deleted already.
+    ///
+    /// # Panics
+    ///
+    /// If this [`Stream`] already contains [`u64::MAX`] [`Event`]s.
+    pub fn push_tombstone(&mut self) -> Position {
+        if let Some(pos) = self.tombstone {
+            return pos;
+        }
+        let pos = Position::following(self.version());
+        self.tombstone = Some(pos);
+        pos
+    }
+
+    /// Returns an [`Iterator`] over the [`Event`]s of this [`Stream`] paired
+    /// with their [`Position`]s.
+    #[must_use]
+    pub fn iter(&self) -> Iter<'_, Ev> {
+        Iter(self.events.iter())
+    }
+
+    /// Applies all the [`Event`]s of this [`Stream`] to the provided `state`
+    /// in their order.
+    pub fn replay<S>(&self, state: &mut S)
+    where
+        S: event::Sourced<Ev>,
+    {
+        for (_, ev) in &self.events {
+            state.apply(ev);
+        }
+    }
+
+    /// Returns the [`Event`]s of this [`Stream`] paired with their
+    /// [`Position`]s.
+    #[must_use]
+    pub fn into_events(self) -> Vec<(Position, Ev)> {
+        self.events
+    }
+}
+
+impl<'s, Ev> IntoIterator for &'s Stream<Ev> {
+    type Item = (Position, &'s Ev);
+    type IntoIter = Iter<'s, Ev>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// [`Iterator`] over the [`Event`]s of a [`Stream`] paired with their
+/// [`Position`]s.
+#[derive(Clone, Debug)]
+pub struct Iter<'s, Ev>(slice::Iter<'s, (Position, Ev)>);
+
+impl<'s, Ev> Iterator for Iter<'s, Ev> {
+    type Item = (Position, &'s Ev);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(pos, ev)| (*pos, ev))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<Ev> ExactSizeIterator for Iter<'_, Ev> {}
+
+impl<Ev> DoubleEndedIterator for Iter<'_, Ev> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(pos, ev)| (*pos, ev))
+    }
+}
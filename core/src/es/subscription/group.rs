@@ -0,0 +1,241 @@
+//! Consumer [`Group`]s of [`Subscription`]s with persisted [`Checkpoints`].
+
+use std::{collections::HashMap, convert::Infallible};
+
+#[cfg(doc)]
+use super::super::Event;
+use super::{super::stream, Offset, Subscription};
+
+/// Storage of [`Offset`]s processed by consumers.
+pub trait Checkpoints {
+    /// Error of these [`Checkpoints`].
+    type Error;
+
+    /// Loads the [`Offset`] of the next [`Event`] to be processed by the
+    /// provided `consumer`, if any.
+    ///
+    /// # Errors
+    ///
+    /// If the checkpoint cannot be loaded.
+    fn load(&self, consumer: &str) -> Result<Option<Offset>, Self::Error>;
+
+    /// Saves the [`Offset`] of the next [`Event`] to be processed by the
+    /// provided `consumer`.
+    ///
+    /// # Errors
+    ///
+    /// If the checkpoint cannot be saved.
+    fn save(&mut self, consumer: &str, next: Offset)
+    -> Result<(), Self::Error>;
+}
+
+/// In-memory [`Checkpoints`], mainly intended for tests and prototyping.
+#[derive(Clone, Debug, Default)]
+pub struct Memory {
+    /// [`Offset`]s of the next [`Event`]s to be processed by consumers.
+    offsets: HashMap<String, Offset>,
+}
+
+impl Memory {
+    /// Creates new empty [`Memory`] [`Checkpoints`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Checkpoints for Memory {
+    type Error = Infallible;
+
+    fn load(&self, consumer: &str) -> Result<Option<Offset>, Self::Error> {
+        Ok(self.offsets.get(consumer).copied())
+    }
+
+    fn save(
+        &mut self,
+        consumer: &str,
+        next: Offset,
+    ) -> Result<(), Self::Error> {
+        _ = self.offsets.insert(consumer.to_owned(), next);
+        Ok(())
+    }
+}
+
+/// Named group of consumers sharing a single durable [`Subscription`].
+///
+/// Each member of a [`Group`] receives the [`Event`]s of its own partition of
+/// [`Stream`]s, so the [`Event`]s of a single [`Stream`] are always processed
+/// by the same member in order.
+///
+/// Delivers [`Event`]s with at-least-once semantics: a member resumes from
+/// the [`Offset`] following the last [`Group::ack()`]ed one, so the
+/// [`Event`]s processed but not acknowledged yet are delivered again.
+///
+/// > **NOTE**: Changing the number of [`Group`] members makes them start from
+/// >           new checkpoints.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::{
+/// #     Event,
+/// #     store::{self, Store as _},
+/// #     stream::{self, ExpectedVersion},
+/// #     subscription::{Group, Shared, group},
+/// # };
+/// # use futures::{FutureExt as _, StreamExt as _};
+/// #
+/// #[derive(Clone, Debug, Event)]
+/// #[event(name = "chat.created")]
+/// struct ChatCreated;
+///
+/// let mut store = Shared::new(store::Memory::new());
+/// for id in ["1", "2", "3", "4"] {
+///     let chat = stream::Id::new("chat", id).unwrap();
+///     store.append(&chat, ExpectedVersion::Any, vec![ChatCreated]).unwrap();
+/// }
+///
+/// let mut checkpoints = group::Memory::new();
+/// let members = [0, 1].map(|i| Group::new("search").member(i, 2).unwrap());
+///
+/// let mut delivered = 0;
+/// for member in &members {
+///     let mut sub = member.subscribe(store.clone(), &checkpoints).unwrap();
+///     while let Some(Some(Ok((offset, _)))) = sub.next().now_or_never() {
+///         member.ack(&mut checkpoints, offset).unwrap();
+///         delivered += 1;
+///     }
+/// }
+/// assert_eq!(delivered, 4);
+///
+/// // Resumes after the acknowledged `Event`s.
+/// let mut sub = members[0].subscribe(store.clone(), &checkpoints).unwrap();
+/// assert!(sub.next().now_or_never().is_none());
+///
+/// // Rebalanced members start from their new checkpoints, still delivering
+/// // every `Stream` to exactly one of them.
+/// let members = [0, 1, 2].map(|i| Group::new("search").member(i, 3).unwrap());
+/// let mut streams = vec![];
+/// for member in &members {
+///     let mut sub = member.subscribe(store.clone(), &checkpoints).unwrap();
+///     while let Some(Some(Ok((offset, entry)))) = sub.next().now_or_never() {
+///         streams.push(entry.stream().to_string());
+///         member.ack(&mut checkpoints, offset).unwrap();
+///     }
+/// }
+/// streams.sort();
+/// assert_eq!(streams, ["chat-1", "chat-2", "chat-3", "chat-4"]);
+/// ```
+///
+/// [`Stream`]: stream::Stream
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Group {
+    /// Name of this [`Group`].
+    name: String,
+
+    /// Index of the member of this [`Group`].
+    member: u32,
+
+    /// Total number of members in this [`Group`].
+    members: u32,
+}
+
+impl Group {
+    /// Creates a new [`Group`] with the provided `name` and a single member.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), member: 0, members: 1 }
+    }
+
+    /// Makes this [`Group`] a member with the provided `index` out of the
+    /// provided total number of `members`.
+    ///
+    /// Returns [`None`] if the `index` is out of `members`.
+    #[must_use]
+    pub fn member(mut self, index: u32, members: u32) -> Option<Self> {
+        (index < members).then(|| {
+            self.member = index;
+            self.members = members;
+            self
+        })
+    }
+
+    /// Returns the name of this [`Group`].
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the name of the consumer, which [`Checkpoints`] are stored
+    /// under.
+    ///
+    /// Equals to the [`Group::name()`] for a single-member [`Group`].
+    #[must_use]
+    pub fn consumer(&self) -> String {
+        if self.members == 1 {
+            self.name.clone()
+        } else {
+            format!("{}[{}/{}]", self.name, self.member, self.members)
+        }
+    }
+
+    /// Creates a [`Subscription`] of this [`Group`] member to the provided
+    /// [`Log`], resuming from its [`Checkpoints`].
+    ///
+    /// # Errors
+    ///
+    /// If the [`Checkpoints`] cannot be loaded.
+    ///
+    /// [`Log`]: super::Log
+    pub fn subscribe<L, Ev, C>(
+        &self,
+        log: L,
+        checkpoints: &C,
+    ) -> Result<Subscription<L, Ev>, C::Error>
+    where
+        C: Checkpoints + ?Sized,
+    {
+        let from = checkpoints.load(&self.consumer())?.unwrap_or(Offset::START);
+        let mut sub = Subscription::new(log, from);
+        if self.members > 1 {
+            sub.partition = Some((self.member, self.members));
+        }
+        Ok(sub)
+    }
+
+    /// Acknowledges the [`Event`] at the provided [`Offset`] as processed by
+    /// this [`Group`] member.
+    ///
+    /// Should be called in the order the [`Event`]s are delivered.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Checkpoints`] cannot be saved.
+    pub fn ack<C>(
+        &self,
+        checkpoints: &mut C,
+        offset: Offset,
+    ) -> Result<(), C::Error>
+    where
+        C: Checkpoints + ?Sized,
+    {
+        checkpoints.save(&self.consumer(), offset.next())
+    }
+}
+
+/// Returns the partition of the provided [`stream::Id`] out of the provided
+/// number of `members`.
+///
+/// Uses FNV-1a hashing, so the partitions are stable across builds.
+pub(super) fn partition_of(stream: &stream::Id, members: u32) -> u32 {
+    const BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let bytes = stream
+        .category()
+        .bytes()
+        .chain([u8::try_from(stream::SEPARATOR).unwrap_or_default()])
+        .chain(stream.id().bytes());
+    let hash = bytes.fold(BASIS, |h, b| (h ^ u64::from(b)).wrapping_mul(PRIME));
+    u32::try_from(hash % u64::from(members.max(1))).unwrap_or_default()
+}
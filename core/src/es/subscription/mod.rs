@@ -0,0 +1,607 @@
+//! [`Subscription`]s to the global [`Log`] of a [`Store`].
+//!
+//! A [`Subscription`] starts from an [`Offset`] in the global [`Log`], catches
+//! up through its history in batches, and then switches to waiting for the
+//! newly appended [`Event`]s of a [`Live`] [`Log`]. As the [`Log`] is always
+//! read by [`Offset`]s, no [`Event`] is missed or delivered twice on this
+//! switch.
+//!
+//! Besides [`Event`]s, a [`Subscription`] delivers tombstones and markers of
+//! the deleted [`Stream`]s as [`Entry`]s, but never the [`Event`]s erased
+//! before being read (see the [`store`] module for details).
+//!
+//! Durable [`Subscription`]s are organized into consumer [`Group`]s, which
+//! persist their progress in [`Checkpoints`].
+//!
+//! # Example
+//!
+//! ```rust
+//! # use arcane::es::{
+//! #     Event,
+//! #     store::{self, Entry, Lifecycle as _, Store as _},
+//! #     stream::{self, ExpectedVersion},
+//! #     subscription::{Offset, Shared, Subscription},
+//! # };
+//! # use futures::{StreamExt as _, executor::block_on};
+//! #
+//! #[derive(Clone, Debug, Event)]
+//! #[event(name = "chat.created")]
+//! struct ChatCreated;
+//!
+//! #[derive(Clone, Debug, Event)]
+//! #[event(name = "message.posted")]
+//! struct MessagePosted;
+//!
+//! #[derive(Clone, Debug, Event)]
+//! enum ChatEvent {
+//!     Created(ChatCreated),
+//!     Posted(MessagePosted),
+//! }
+//!
+//! #[derive(Clone, Debug, Event)]
+//! enum MessageEvent {
+//!     Posted(MessagePosted),
+//! }
+//!
+//! let mut store = Shared::new(store::Memory::<ChatEvent>::new());
+//! let chat = stream::Id::new("chat", "1").unwrap();
+//! let created = || ChatEvent::Created(ChatCreated);
+//! let posted = || ChatEvent::Posted(MessagePosted);
+//!
+//! store
+//!     .append(&chat, ExpectedVersion::Any, vec![created(), posted()])
+//!     .unwrap();
+//!
+//! let mut messages =
+//!     Subscription::new(store.clone(), Offset::START).of::<MessageEvent>();
+//! block_on(async {
+//!     let (offset, entry) = messages.next().await.unwrap().unwrap();
+//!     assert_eq!(offset, Offset::new(1));
+//!     assert_eq!(entry.as_event().unwrap().position.get(), 2);
+//!     assert!(messages.is_live());
+//! });
+//!
+//! store
+//!     .append(&chat, ExpectedVersion::Any, vec![created(), posted()])
+//!     .unwrap();
+//! store.tombstone(&chat, ExpectedVersion::Any).unwrap();
+//! block_on(async {
+//!     let (offset, _) = messages.next().await.unwrap().unwrap();
+//!     assert_eq!(offset, Offset::new(3));
+//!
+//!     let (offset, entry) = messages.next().await.unwrap().unwrap();
+//!     assert_eq!(offset, Offset::new(4));
+//!     assert!(matches!(entry, Entry::Tombstone { .. }));
+//! });
+//! ```
+
+pub mod group;
+
+use std::{
+    collections::VecDeque,
+    fmt, mem,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Waker},
+};
+
+use derive_more::{Display, Into};
+use futures_core::Stream as AsyncStream;
+
+#[doc(inline)]
+pub use self::group::{Checkpoints, Group};
+#[cfg(feature = "reflect")]
+use super::event::reflect;
+#[cfg(feature = "tenant")]
+use super::tenant;
+use super::{
+    Event,
+    store::{self, AppendError, Entry, Lifecycle, Recorded, Store},
+    stream::{self, ExpectedVersion, Stream},
+};
+
+/// Default number of [`Event`]s read from a [`Log`] at once.
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// Position of an [`Event`] in the global [`Log`] of a [`Store`], starting
+/// from `0`.
+#[derive(
+    Clone, Copy, Debug, Display, Eq, Hash, Into, Ord, PartialEq, PartialOrd,
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(transparent)
+)]
+pub struct Offset(u64);
+
+impl Offset {
+    /// [`Offset`] of the first [`Event`] in a [`Log`].
+    pub const START: Self = Self(0);
+
+    /// Creates a new [`Offset`] out of the provided `value`.
+    #[must_use]
+    pub const fn new(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// Returns the value of this [`Offset`] as a primitive type.
+    #[inline]
+    #[must_use]
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the [`Offset`] following this one.
+    #[inline]
+    #[must_use]
+    pub const fn next(self) -> Self {
+        Self(self.0.saturating_add(1))
+    }
+}
+
+/// [`Store`] exposing all its [`Entry`]s as a single global log, in the order
+/// they were appended.
+pub trait Log<Ev>: Store<Ev> {
+    /// Reads up to `limit` [`Entry`]s starting from the provided [`Offset`],
+    /// along with their [`Offset`]s.
+    ///
+    /// The erased [`Entry`]s are skipped, so the returned [`Offset`]s may be
+    /// not sequential. Returns an empty [`Vec`] if there are no [`Entry`]s at
+    /// or after the provided [`Offset`] yet.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Event`]s cannot be read.
+    fn read_log(
+        &self,
+        from: Offset,
+        limit: usize,
+    ) -> Result<Vec<(Offset, Entry<Ev>)>, Self::Error>;
+
+    /// Returns the [`Offset`] following the last [`Entry`] of this [`Log`],
+    /// which the next appended [`Entry`] gets.
+    ///
+    /// Once a consumer has processed everything before this [`Offset`], it
+    /// has seen all the [`Event`]s appended so far.
+    ///
+    /// # Errors
+    ///
+    /// If this [`Log`] cannot be read.
+    fn end(&self) -> Result<Offset, Self::Error>;
+}
+
+/// [`Log`] notifying about the newly appended [`Event`]s.
+pub trait Live<Ev>: Log<Ev> {
+    /// Checks whether there is an [`Event`] at the provided [`Offset`] in this
+    /// [`Log`], otherwise scheduling the current task to be woken once any
+    /// [`Event`] is appended.
+    ///
+    /// # Errors
+    ///
+    /// If this [`Log`] cannot be read.
+    fn poll_appended(
+        &self,
+        from: Offset,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>>;
+}
+
+impl<Ev: Clone + Event> Log<Ev> for store::Memory<Ev> {
+    fn read_log(
+        &self,
+        from: Offset,
+        limit: usize,
+    ) -> Result<Vec<(Offset, Entry<Ev>)>, Self::Error> {
+        let start = usize::try_from(from.get()).unwrap_or(usize::MAX);
+        let tail = self.log.get(start..).unwrap_or_default();
+        Ok((from.get()..)
+            .map(Offset)
+            .zip(tail)
+            .filter_map(|(offset, entry)| Some((offset, entry.clone()?)))
+            .take(limit)
+            .collect())
+    }
+
+    fn end(&self) -> Result<Offset, Self::Error> {
+        Ok(Offset(u64::try_from(self.log.len()).unwrap_or(u64::MAX)))
+    }
+}
+
+/// [`Store`] shared between its writers and [`Subscription`]s, making any
+/// [`Log`] [`Live`].
+///
+/// Cloning a [`Shared`] [`Store`] gives another handle to the same [`Store`].
+pub struct Shared<S> {
+    /// Shared [`Store`] itself.
+    store: Arc<Mutex<S>>,
+
+    /// [`Waker`]s of the [`Subscription`]s waiting for new [`Event`]s.
+    wakers: Arc<Mutex<Vec<Waker>>>,
+}
+
+impl<S> Shared<S> {
+    /// Wraps the provided [`Store`] into a [`Shared`] one.
+    #[must_use]
+    pub fn new(store: S) -> Self {
+        Self {
+            store: Arc::new(Mutex::new(store)),
+            wakers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Locks the underlying [`Store`] for an exclusive access.
+    ///
+    /// > **NOTE**: [`Event`]s appended directly to the locked [`Store`] don't
+    /// >           wake the waiting [`Subscription`]s.
+    pub fn lock(&self) -> MutexGuard<'_, S> {
+        self.store.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Wakes all the [`Subscription`]s waiting for new [`Event`]s.
+    fn wake_all(&self) {
+        let wakers = mem::take(
+            &mut *self.wakers.lock().unwrap_or_else(PoisonError::into_inner),
+        );
+        for w in wakers {
+            w.wake();
+        }
+    }
+}
+
+impl<S> Clone for Shared<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            wakers: Arc::clone(&self.wakers),
+        }
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for Shared<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Shared")
+            .field("store", &*self.lock())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: Store<Ev>, Ev> Store<Ev> for Shared<S> {
+    type Error = S::Error;
+
+    fn append_with(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+        events: Vec<Ev>,
+        metadata: &store::Metadata,
+    ) -> Result<Option<stream::Position>, AppendError<Self::Error>> {
+        let res = self.lock().append_with(stream, expected, events, metadata);
+        if res.is_ok() {
+            self.wake_all();
+        }
+        res
+    }
+
+    fn read_stream(
+        &self,
+        stream: &stream::Id,
+    ) -> Result<Stream<Ev>, Self::Error> {
+        self.lock().read_stream(stream)
+    }
+
+    fn read_recorded(
+        &self,
+        stream: &stream::Id,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error> {
+        self.lock().read_recorded(stream)
+    }
+
+    fn read_category(
+        &self,
+        category: &str,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error> {
+        self.lock().read_category(category)
+    }
+}
+
+impl<S: Lifecycle<Ev>, Ev> Lifecycle<Ev> for Shared<S> {
+    fn tombstone(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+    ) -> Result<stream::Position, AppendError<Self::Error>> {
+        let res = self.lock().tombstone(stream, expected);
+        if res.is_ok() {
+            self.wake_all();
+        }
+        res
+    }
+
+    fn delete(&mut self, stream: &stream::Id) -> Result<bool, Self::Error> {
+        let res = self.lock().delete(stream);
+        if res.as_ref().is_ok_and(|deleted| *deleted) {
+            self.wake_all();
+        }
+        res
+    }
+
+    fn truncate(
+        &mut self,
+        stream: &stream::Id,
+        before: stream::Position,
+    ) -> Result<usize, Self::Error> {
+        self.lock().truncate(stream, before)
+    }
+}
+
+impl<S: Log<Ev>, Ev> Log<Ev> for Shared<S> {
+    fn read_log(
+        &self,
+        from: Offset,
+        limit: usize,
+    ) -> Result<Vec<(Offset, Entry<Ev>)>, Self::Error> {
+        self.lock().read_log(from, limit)
+    }
+
+    fn end(&self) -> Result<Offset, Self::Error> {
+        self.lock().end()
+    }
+}
+
+impl<S: Log<Ev>, Ev> Live<Ev> for Shared<S> {
+    fn poll_appended(
+        &self,
+        from: Offset,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        // Registered before reading, so an `Event` appended in-between still
+        // wakes the task.
+        {
+            let mut wakers =
+                self.wakers.lock().unwrap_or_else(PoisonError::into_inner);
+            if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+        }
+        match self.read_log(from, 1) {
+            Ok(events) if events.is_empty() => Poll::Pending,
+            Ok(_) => Poll::Ready(Ok(())),
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+}
+
+/// Filter of [`Event`]s delivered by a [`Subscription`].
+type Filter<Ev> = fn(&Ev) -> bool;
+
+/// Asynchronous [`Stream`] of the [`Entry`]s of a [`Log`], along with their
+/// [`Offset`]s.
+///
+/// Reads the next batch of [`Event`]s only once the previous one is consumed,
+/// so a slow consumer never makes a [`Subscription`] buffer more than a
+/// single batch.
+///
+/// If the [`Log`] fails to be read, the error is yielded and the reading is
+/// retried on the next poll from the same [`Offset`].
+///
+/// # Example
+///
+/// ```rust
+/// # use std::thread;
+/// #
+/// # use arcane::es::{
+/// #     Event,
+/// #     store::{self, Store as _},
+/// #     stream::{self, ExpectedVersion},
+/// #     subscription::{Offset, Shared, Subscription},
+/// # };
+/// # use futures::{FutureExt as _, StreamExt as _, executor::block_on};
+/// #
+/// #[derive(Clone, Debug, Event)]
+/// #[event(name = "message.posted")]
+/// struct MessagePosted;
+///
+/// let mut store = Shared::new(store::Memory::new());
+/// let chat = stream::Id::new("chat", "1").unwrap();
+/// store.append(&chat, ExpectedVersion::Any, vec![MessagePosted; 4]).unwrap();
+///
+/// // Catches up in batches, and becomes live once the history is exhausted.
+/// let mut sub = Subscription::new(store.clone(), Offset::START).batch_size(2);
+/// let mut offsets = vec![];
+/// for _ in 0..4 {
+///     let (offset, _) = block_on(sub.next()).unwrap().unwrap();
+///     offsets.push(offset.get());
+///     assert!(!sub.is_live());
+/// }
+/// assert!(sub.next().now_or_never().is_none());
+/// assert!(sub.is_live());
+///
+/// // Events appended concurrently are delivered without gaps or duplicates.
+/// let writer = thread::spawn({
+///     let mut store = store.clone();
+///     move || {
+///         for _ in 0..10 {
+///             store
+///                 .append(&chat, ExpectedVersion::Any, vec![MessagePosted])
+///                 .unwrap();
+///         }
+///     }
+/// });
+/// for _ in 0..10 {
+///     let (offset, _) = block_on(sub.next()).unwrap().unwrap();
+///     offsets.push(offset.get());
+/// }
+/// writer.join().unwrap();
+/// assert_eq!(offsets, (0..14).collect::<Vec<_>>());
+/// assert!(sub.next().now_or_never().is_none());
+/// ```
+///
+/// [`Stream`]: AsyncStream
+pub struct Subscription<L, Ev> {
+    /// [`Log`] to read [`Event`]s from.
+    log: L,
+
+    /// [`Offset`] of the next [`Event`] to be read from the [`Log`].
+    next: Offset,
+
+    /// Number of [`Event`]s read from the [`Log`] at once.
+    batch_size: usize,
+
+    /// [`Filter`] of the delivered [`Event`]s, if any.
+    filter: Option<Filter<Ev>>,
+
+    /// Partition of the [`Log`] delivered to a [`Group`] member, as its
+    /// index and the total number of [`Group`] members.
+    partition: Option<(u32, u32)>,
+
+    /// Tenant which [`Stream`]s are only delivered, if any.
+    #[cfg(feature = "tenant")]
+    tenant: Option<tenant::Id>,
+
+    /// Read [`Event`]s not delivered yet.
+    buffer: VecDeque<(Offset, Entry<Ev>)>,
+
+    /// Indicator whether this [`Subscription`] has caught up with the [`Log`].
+    live: bool,
+}
+
+impl<L, Ev> Subscription<L, Ev> {
+    /// Creates a new [`Subscription`] to the provided [`Log`], starting from
+    /// the provided [`Offset`].
+    #[must_use]
+    pub const fn new(log: L, from: Offset) -> Self {
+        Self {
+            log,
+            next: from,
+            batch_size: DEFAULT_BATCH_SIZE,
+            filter: None,
+            partition: None,
+            #[cfg(feature = "tenant")]
+            tenant: None,
+            buffer: VecDeque::new(),
+            live: false,
+        }
+    }
+
+    /// Sets the number of [`Event`]s read from the [`Log`] at once.
+    ///
+    /// Zero is treated as `1`.
+    #[must_use]
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Delivers only the [`Event`]s which [`Event::name()`]s belong to the
+    /// [`reflect::Static::NAMES`] (or [`reflect::Static::ALIASES`]) of the
+    /// specified [`Event`].
+    ///
+    /// Tombstones and markers of the deleted [`Stream`]s are delivered
+    /// regardless.
+    #[cfg(feature = "reflect")]
+    #[must_use]
+    pub fn of<E: reflect::Static + ?Sized>(mut self) -> Self
+    where
+        Ev: Event,
+    {
+        self.filter = Some(|ev| E::canonical_name(ev.name()).is_some());
+        self
+    }
+
+    /// Delivers only the [`Entry`]s of the [`Stream`]s belonging to the
+    /// provided tenant.
+    #[cfg(feature = "tenant")]
+    #[must_use]
+    pub fn tenant(mut self, tenant: tenant::Id) -> Self {
+        self.tenant = Some(tenant);
+        self
+    }
+
+    /// Returns the [`Offset`] of the next [`Event`] to be read from the
+    /// [`Log`].
+    ///
+    /// [`Event`]s read but not delivered yet are not counted.
+    #[must_use]
+    pub const fn offset(&self) -> Offset {
+        self.next
+    }
+
+    /// Indicates whether this [`Subscription`] has caught up with the history
+    /// of its [`Log`] and now waits for new [`Event`]s.
+    #[must_use]
+    pub const fn is_live(&self) -> bool {
+        self.live
+    }
+
+    /// Checks whether the provided [`Entry`] should be delivered by this
+    /// [`Subscription`].
+    fn accepts(&self, entry: &Entry<Ev>) -> bool {
+        #[cfg(feature = "tenant")]
+        if self
+            .tenant
+            .as_ref()
+            .is_some_and(|t| entry.stream().tenant() != Some(t))
+        {
+            return false;
+        }
+        self.filter.is_none_or(|f| entry.as_event().is_none_or(|r| f(&r.event)))
+            && self.partition.is_none_or(|(member, members)| {
+                group::partition_of(entry.stream(), members) == member
+            })
+    }
+}
+
+impl<L: fmt::Debug, Ev> fmt::Debug for Subscription<L, Ev> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("log", &self.log)
+            .field("next", &self.next)
+            .field("batch_size", &self.batch_size)
+            .field("partition", &self.partition)
+            .field("buffered", &self.buffer.len())
+            .field("live", &self.live)
+            .finish_non_exhaustive()
+    }
+}
+
+// Nothing is structurally pinned.
+impl<L, Ev> Unpin for Subscription<L, Ev> {}
+
+impl<L: Live<Ev>, Ev> AsyncStream for Subscription<L, Ev> {
+    type Item = Result<(Offset, Entry<Ev>), L::Error>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(ev) = this.buffer.pop_front() {
+                return Poll::Ready(Some(Ok(ev)));
+            }
+
+            let batch = match this.log.read_log(this.next, this.batch_size) {
+                Ok(b) => b,
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            };
+            if let Some((last, _)) = batch.last() {
+                this.next = last.next();
+                this.live = batch.len() < this.batch_size;
+                let accepted: Vec<_> = batch
+                    .into_iter()
+                    .filter(|(_, r)| this.accepts(r))
+                    .collect();
+                this.buffer.extend(accepted);
+                continue;
+            }
+
+            this.live = true;
+            match this.log.poll_appended(this.next, cx) {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e))),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
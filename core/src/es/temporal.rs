@@ -0,0 +1,672 @@
+//! Temporal queries sourcing states as of points in the history of their
+//! [`Stream`]s.
+//!
+//! A [`Loader`] sources a state via [`Sourced`] from the [`Event`]s of a
+//! [`Stream`] read from a [`History`], only up to a [`Cutoff`]: a
+//! [`stream::Position`], an offset in the global log of a [`Store`], or a time
+//! the [`Event`]s were appended at. Each [`Event`] is matched against a
+//! [`Cutoff`] by its [`Meta`]data. The latest [`Snapshot`] taken not after the
+//! [`Cutoff`] is reused, if [`Snapshots`] are provided.
+//!
+//! [`Loader::diff()`] compares states at two points of the history, along with
+//! the [`Event`]s in between.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use arcane::es::{
+//! #     Event,
+//! #     event::{Initialized, Sourced},
+//! #     store::{self, Store as _},
+//! #     stream::{self, ExpectedVersion},
+//! #     temporal::{self, Cutoff, Loader, Snapshot, Snapshots as _},
+//! # };
+//! #
+//! #[derive(Clone, Debug, Event)]
+//! #[event(name = "chat.created", revision = 1)]
+//! struct ChatCreated;
+//!
+//! #[derive(Clone, Debug, Event)]
+//! #[event(name = "message.posted", revision = 1)]
+//! struct MessagePosted;
+//!
+//! #[derive(Clone, Debug, Event)]
+//! enum ChatEvent {
+//!     #[event(init)]
+//!     Created(ChatCreated),
+//!     MessagePosted(MessagePosted),
+//! }
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct Chat {
+//!     messages: usize,
+//! }
+//!
+//! impl Initialized<ChatCreated> for Chat {
+//!     fn init(_: &ChatCreated) -> Self {
+//!         Self { messages: 0 }
+//!     }
+//! }
+//!
+//! impl Sourced<MessagePosted> for Chat {
+//!     fn apply(&mut self, _: &MessagePosted) {
+//!         self.messages += 1;
+//!     }
+//! }
+//!
+//! let mut store = store::Memory::new();
+//! let chat = stream::Id::new("chat", "1").unwrap();
+//! let posted = || ChatEvent::MessagePosted(MessagePosted);
+//! let events = vec![ChatEvent::Created(ChatCreated), posted(), posted()];
+//! store.append(&chat, ExpectedVersion::NoStream, events).unwrap();
+//! store.append(&chat, ExpectedVersion::Any, vec![posted()]).unwrap();
+//!
+//! let loader = Loader::new(&store);
+//! let pos = |n| Cutoff::Position(stream::Position::new(n).unwrap());
+//!
+//! let at = loader.load::<Option<Chat>, _>(&chat, pos(2)).unwrap();
+//! assert_eq!(at.state, Some(Chat { messages: 1 }));
+//! let meta = at.meta.unwrap();
+//! assert_eq!((meta.position.get(), meta.offset), (2, 1));
+//!
+//! // Events appended together share their time.
+//! let at =
+//!     loader.load::<Option<Chat>, _>(&chat, Cutoff::Time(meta.timestamp));
+//! assert_eq!(at.unwrap().state, Some(Chat { messages: 2 }));
+//!
+//! let before = loader.load::<Option<Chat>, _>(&chat, Cutoff::Offset(0));
+//! assert_eq!(before.unwrap().state, Some(Chat { messages: 0 }));
+//!
+//! let diff = loader.diff::<Option<Chat>, _>(&chat, pos(1), pos(3)).unwrap();
+//! assert!(diff.is_changed());
+//! assert_eq!(diff.events.len(), 2);
+//! assert_eq!(diff.after.state, Some(Chat { messages: 2 }));
+//!
+//! // Snapshots are trusted, so the events preceding them are not replayed.
+//! let mut snapshots = temporal::Memory::new();
+//! let mut snapshot = loader.load::<Option<Chat>, _>(&chat, pos(2)).unwrap();
+//! snapshot.state = Some(Chat { messages: 10 });
+//! snapshots.save(&chat, Snapshot::try_from(snapshot).unwrap()).unwrap();
+//!
+//! let loader = loader.snapshots(&snapshots);
+//! let at = loader.load::<Option<Chat>, _>(&chat, pos(3)).unwrap();
+//! assert_eq!(at.state, Some(Chat { messages: 11 }));
+//! let at = loader.load::<Option<Chat>, _>(&chat, pos(1)).unwrap();
+//! assert_eq!(at.state, Some(Chat { messages: 0 }));
+//! ```
+//!
+//! [`Stream`]: stream::Stream
+
+use std::{
+    collections::HashMap, convert::Infallible, error::Error as StdError, fmt,
+    time::SystemTime,
+};
+
+use super::{
+    Event,
+    event::Sourced,
+    store::{self, Store},
+    stream,
+};
+
+/// Metadata of a recorded [`Event`], locating it in the history.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Meta {
+    /// [`stream::Position`] of the [`Event`] in its [`Stream`].
+    ///
+    /// [`Stream`]: stream::Stream
+    pub position: stream::Position,
+
+    /// Offset of the [`Event`] in the global log of its [`Store`], starting
+    /// from `0`.
+    ///
+    /// Stays the same once the preceding [`Event`]s are deleted or truncated,
+    /// so it may be persisted along with a [`Snapshot`].
+    pub offset: u64,
+
+    /// Time the [`Event`] was appended at.
+    pub timestamp: SystemTime,
+}
+
+/// Point in the history a state is sourced up to (inclusively).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Cutoff {
+    /// Last [`stream::Position`] to be sourced.
+    Position(stream::Position),
+
+    /// Last offset in the global log of a [`Store`] to be sourced.
+    Offset(u64),
+
+    /// Last time to source the [`Event`]s appended at.
+    ///
+    /// > **NOTE**: The times of the [`Event`]s of a [`Stream`] are assumed to
+    /// >           be non-decreasing (as the ones recorded by a [`Store`]
+    /// >           are), so sourcing stops at the first [`Event`] appended
+    /// >           after this time, to never skip an [`Event`] in the middle.
+    /// >           If a clock goes backwards, the [`Event`]s following such
+    /// >           first one are not sourced, even if appended before this
+    /// >           time.
+    ///
+    /// [`Stream`]: stream::Stream
+    Time(SystemTime),
+}
+
+impl Cutoff {
+    /// Checks whether the [`Event`] with the provided [`Meta`] is within this
+    /// [`Cutoff`].
+    #[must_use]
+    pub fn includes(self, meta: &Meta) -> bool {
+        match self {
+            Self::Position(p) => meta.position <= p,
+            Self::Offset(o) => meta.offset <= o,
+            Self::Time(t) => meta.timestamp <= t,
+        }
+    }
+}
+
+/// [`Event`]s of a [`Stream`] read from a [`History`].
+///
+/// [`Stream`]: stream::Stream
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Slice<Ev> {
+    /// Last truncated [`stream::Position`] of the [`Stream`], if it's
+    /// truncated, so its preceding [`Event`]s cannot be read anymore.
+    ///
+    /// [`Stream`]: stream::Stream
+    pub truncated: Option<stream::Position>,
+
+    /// Read [`Event`]s along with their [`Meta`]data, in their order.
+    pub events: Vec<(Meta, Ev)>,
+}
+
+/// [`Store`] reading [`Event`]s along with their [`Meta`]data.
+pub trait History<Ev>: Store<Ev> {
+    /// Reads the [`Event`]s of the [`Stream`] with the provided [`stream::Id`]
+    /// following the provided [`stream::Position`] (or all of them, if
+    /// [`None`]), along with their [`Meta`]data and the truncation point of
+    /// the [`Stream`].
+    ///
+    /// # Errors
+    ///
+    /// If the [`Event`]s cannot be read.
+    ///
+    /// [`Stream`]: stream::Stream
+    fn read_history(
+        &self,
+        stream: &stream::Id,
+        after: Option<stream::Position>,
+    ) -> Result<Slice<Ev>, Self::Error>;
+}
+
+impl<Ev: Clone + Event> History<Ev> for store::Memory<Ev> {
+    fn read_history(
+        &self,
+        stream: &stream::Id,
+        after: Option<stream::Position>,
+    ) -> Result<Slice<Ev>, Self::Error> {
+        // Truncation erases `Entry`s from the start of a `Stream`, so its
+        // first remaining one follows the truncated ones.
+        let first = self.log.iter().flatten().find_map(|entry| match entry {
+            store::Entry::Event(rec) => {
+                (rec.stream == *stream).then_some(rec.position)
+            }
+            store::Entry::Tombstone { stream: id, position } => {
+                (id == stream).then_some(*position)
+            }
+            store::Entry::Deleted { .. } => None,
+        });
+        let events = self
+            .log
+            .iter()
+            .zip(&self.timestamps)
+            .zip(0..)
+            .filter_map(|((entry, timestamp), offset)| {
+                let rec = entry.as_ref()?.as_event()?;
+                (rec.stream == *stream
+                    && after.is_none_or(|p| rec.position > p))
+                .then(|| {
+                    let meta = Meta {
+                        position: rec.position,
+                        offset,
+                        timestamp: *timestamp,
+                    };
+                    (meta, rec.event.clone())
+                })
+            })
+            .collect();
+        Ok(Slice {
+            truncated: first.and_then(|p| stream::Position::new(p.get() - 1)),
+            events,
+        })
+    }
+}
+
+/// State sourced as of a point in the history of its [`Stream`].
+///
+/// [`Stream`]: stream::Stream
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct At<S> {
+    /// Sourced state itself.
+    pub state: S,
+
+    /// [`Meta`]data of the last [`Event`] the state is sourced from, or
+    /// [`None`] if there are no such [`Event`]s.
+    pub meta: Option<Meta>,
+}
+
+/// State saved as of the [`Event`] with the provided [`Meta`]data, so its
+/// preceding [`Event`]s are not replayed again.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Snapshot<S> {
+    /// [`Meta`]data of the last [`Event`] the state is sourced from.
+    pub meta: Meta,
+
+    /// Snapshotted state itself.
+    pub state: S,
+}
+
+impl<S> TryFrom<At<S>> for Snapshot<S> {
+    type Error = At<S>;
+
+    /// Converts the provided [`At`] state into a [`Snapshot`].
+    ///
+    /// # Errors
+    ///
+    /// If the state is not sourced from any [`Event`].
+    fn try_from(at: At<S>) -> Result<Self, Self::Error> {
+        match at.meta {
+            Some(meta) => Ok(Self { meta, state: at.state }),
+            None => Err(at),
+        }
+    }
+}
+
+/// Storage of [`Snapshot`]s of states.
+pub trait Snapshots<S> {
+    /// Error of these [`Snapshots`].
+    type Error;
+
+    /// Returns the latest [`Snapshot`] of the [`Stream`] with the provided
+    /// [`stream::Id`] within the provided [`Cutoff`], if any.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Snapshot`] cannot be loaded.
+    ///
+    /// [`Stream`]: stream::Stream
+    fn latest(
+        &self,
+        stream: &stream::Id,
+        cutoff: Cutoff,
+    ) -> Result<Option<Snapshot<S>>, Self::Error>;
+
+    /// Saves the provided [`Snapshot`] of the [`Stream`] with the provided
+    /// [`stream::Id`], replacing the one taken at the same
+    /// [`stream::Position`], if any.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Snapshot`] cannot be saved.
+    ///
+    /// [`Stream`]: stream::Stream
+    fn save(
+        &mut self,
+        stream: &stream::Id,
+        snapshot: Snapshot<S>,
+    ) -> Result<(), Self::Error>;
+}
+
+/// [`Snapshots`] not storing anything.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct NoSnapshots;
+
+impl<S> Snapshots<S> for NoSnapshots {
+    type Error = Infallible;
+
+    fn latest(
+        &self,
+        _: &stream::Id,
+        _: Cutoff,
+    ) -> Result<Option<Snapshot<S>>, Self::Error> {
+        Ok(None)
+    }
+
+    fn save(
+        &mut self,
+        _: &stream::Id,
+        _: Snapshot<S>,
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// In-memory [`Snapshots`], mainly intended for tests and prototyping.
+#[derive(Clone, Debug)]
+pub struct Memory<S> {
+    /// [`Snapshot`]s of every [`Stream`] ordered by their
+    /// [`stream::Position`]s.
+    ///
+    /// [`Stream`]: stream::Stream
+    snapshots: HashMap<stream::Id, Vec<Snapshot<S>>>,
+}
+
+impl<S> Memory<S> {
+    /// Creates new empty [`Memory`] [`Snapshots`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self { snapshots: HashMap::new() }
+    }
+}
+
+impl<S> Default for Memory<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Clone> Snapshots<S> for Memory<S> {
+    type Error = Infallible;
+
+    fn latest(
+        &self,
+        stream: &stream::Id,
+        cutoff: Cutoff,
+    ) -> Result<Option<Snapshot<S>>, Self::Error> {
+        Ok(self.snapshots.get(stream).and_then(|s| {
+            s.iter().rev().find(|s| cutoff.includes(&s.meta)).cloned()
+        }))
+    }
+
+    fn save(
+        &mut self,
+        stream: &stream::Id,
+        snapshot: Snapshot<S>,
+    ) -> Result<(), Self::Error> {
+        let snapshots = self.snapshots.entry(stream.clone()).or_default();
+        match snapshots
+            .binary_search_by_key(&snapshot.meta.position, |s| s.meta.position)
+        {
+            Ok(i) => snapshots[i] = snapshot,
+            Err(i) => snapshots.insert(i, snapshot),
+        }
+        Ok(())
+    }
+}
+
+/// Loader of states as of points in the history of their [`Stream`]s.
+///
+/// See the [module-level documentation](self) for details.
+///
+/// [`Stream`]: stream::Stream
+#[derive(Debug)]
+pub struct Loader<'l, St: ?Sized, Sn: ?Sized = NoSnapshots> {
+    /// [`History`] to read [`Event`]s from.
+    store: &'l St,
+
+    /// [`Snapshots`] to reuse.
+    snapshots: &'l Sn,
+}
+
+impl<St: ?Sized, Sn: ?Sized> Clone for Loader<'_, St, Sn> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<St: ?Sized, Sn: ?Sized> Copy for Loader<'_, St, Sn> {}
+
+impl<'l, St: ?Sized> Loader<'l, St> {
+    /// Creates a new [`Loader`] reading [`Event`]s from the provided
+    /// [`History`] without reusing any [`Snapshots`].
+    #[must_use]
+    pub const fn new(store: &'l St) -> Self {
+        Self { store, snapshots: &NoSnapshots }
+    }
+}
+
+impl<'l, St: ?Sized, Sn: ?Sized> Loader<'l, St, Sn> {
+    /// Makes this [`Loader`] reuse the provided [`Snapshots`].
+    #[must_use]
+    pub const fn snapshots<T: ?Sized>(
+        self,
+        snapshots: &'l T,
+    ) -> Loader<'l, St, T> {
+        Loader { store: self.store, snapshots }
+    }
+
+    /// Sources a state of the [`Stream`] with the provided [`stream::Id`] up
+    /// to the provided [`Cutoff`].
+    ///
+    /// Starts from the latest [`Snapshot`] within the [`Cutoff`], if any, or
+    /// from the [`Default`] state otherwise.
+    ///
+    /// A truncated [`Stream`] is sourced only if such [`Snapshot`] covers its
+    /// truncated [`Event`]s, as otherwise the sourced state would silently
+    /// miss them (or be empty for the [`Cutoff`]s before its truncation
+    /// point).
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Truncated`] if the [`Stream`] is truncated after the
+    ///   [`Snapshot`] to start from (or it's absent).
+    /// - [`Error::Store`] if the [`History`] fails.
+    /// - [`Error::Snapshots`] if the [`Snapshots`] fail.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use arcane::es::{
+    /// #     Event,
+    /// #     event::Sourced,
+    /// #     store::{self, Lifecycle as _, Store as _},
+    /// #     stream::{self, ExpectedVersion},
+    /// #     temporal::{self, Cutoff, Loader, Snapshot, Snapshots as _},
+    /// # };
+    /// #
+    /// #[derive(Clone, Debug, Event)]
+    /// #[event(name = "message.posted", revision = 1)]
+    /// struct MessagePosted;
+    ///
+    /// #[derive(Clone, Debug, Default, PartialEq)]
+    /// struct Chat {
+    ///     messages: usize,
+    /// }
+    ///
+    /// impl Sourced<MessagePosted> for Chat {
+    ///     fn apply(&mut self, _: &MessagePosted) {
+    ///         self.messages += 1;
+    ///     }
+    /// }
+    ///
+    /// let mut store = store::Memory::new();
+    /// let chat = stream::Id::new("chat", "1").unwrap();
+    /// let events = vec![MessagePosted; 4];
+    /// store.append(&chat, ExpectedVersion::NoStream, events).unwrap();
+    /// let pos = |n| stream::Position::new(n).unwrap();
+    ///
+    /// let mut snapshots = temporal::Memory::new();
+    /// let at = Loader::new(&store).load(&chat, Cutoff::Position(pos(2)));
+    /// let snapshot = Snapshot::<Chat>::try_from(at.unwrap()).unwrap();
+    /// snapshots.save(&chat, snapshot).unwrap();
+    ///
+    /// assert_eq!(store.truncate(&chat, pos(3)).unwrap(), 2);
+    ///
+    /// // Truncated events are not silently skipped, whatever the cutoff is.
+    /// let loader = Loader::new(&store);
+    /// for cutoff in [Cutoff::Position(pos(1)), Cutoff::Position(pos(4))] {
+    ///     assert!(matches!(
+    ///         loader.load::<Chat, _>(&chat, cutoff),
+    ///         Err(temporal::Error::Truncated(p)) if p == pos(2),
+    ///     ));
+    /// }
+    ///
+    /// // Unless a snapshot covers them.
+    /// let loader = loader.snapshots(&snapshots);
+    /// let at = loader.load::<Chat, _>(&chat, Cutoff::Position(pos(4)));
+    /// assert_eq!(at.unwrap().state, Chat { messages: 4 });
+    /// let at = loader.load::<Chat, _>(&chat, Cutoff::Position(pos(1)));
+    /// assert!(matches!(at, Err(temporal::Error::Truncated(_))));
+    /// ```
+    ///
+    /// [`Stream`]: stream::Stream
+    pub fn load<S, Ev>(
+        &self,
+        stream: &stream::Id,
+        cutoff: Cutoff,
+    ) -> Result<At<S>, Error<St::Error, Sn::Error>>
+    where
+        St: History<Ev>,
+        Sn: Snapshots<S>,
+        S: Default + Sourced<Ev>,
+    {
+        let mut at = self
+            .snapshots
+            .latest(stream, cutoff)
+            .map_err(Error::Snapshots)?
+            .map_or_else(
+                || At { state: S::default(), meta: None },
+                |s| At { state: s.state, meta: Some(s.meta) },
+            );
+        let history =
+            self.history(stream, at.meta, cutoff).map_err(Error::Store)?;
+        if let Some(truncated) = history
+            .truncated
+            .filter(|t| at.meta.is_none_or(|m| m.position < *t))
+        {
+            return Err(Error::Truncated(truncated));
+        }
+        for (meta, ev) in history.events {
+            at.state.apply(&ev);
+            at.meta = Some(meta);
+        }
+        Ok(at)
+    }
+
+    /// Compares the states of the [`Stream`] with the provided [`stream::Id`]
+    /// as of the provided `from` and `to` [`Cutoff`]s.
+    ///
+    /// If `to` precedes `from`, then both states are the same.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Loader::load()`] for the `from` [`Cutoff`].
+    ///
+    /// [`Stream`]: stream::Stream
+    pub fn diff<S, Ev>(
+        &self,
+        stream: &stream::Id,
+        from: Cutoff,
+        to: Cutoff,
+    ) -> Result<Diff<S, Ev>, Error<St::Error, Sn::Error>>
+    where
+        St: History<Ev>,
+        Sn: Snapshots<S>,
+        S: Clone + Default + Sourced<Ev>,
+    {
+        let before = self.load(stream, from)?;
+        let mut after = before.clone();
+        let events =
+            self.history(stream, before.meta, to).map_err(Error::Store)?.events;
+        for (meta, ev) in &events {
+            after.state.apply(ev);
+            after.meta = Some(*meta);
+        }
+        Ok(Diff { before, after, events })
+    }
+
+    /// Reads the [`Event`]s of the [`Stream`] with the provided [`stream::Id`]
+    /// following the provided [`Meta`] up to the provided [`Cutoff`], along
+    /// with its truncation point.
+    ///
+    /// # Errors
+    ///
+    /// If the [`History`] fails.
+    ///
+    /// [`Stream`]: stream::Stream
+    fn history<Ev>(
+        &self,
+        stream: &stream::Id,
+        after: Option<Meta>,
+        cutoff: Cutoff,
+    ) -> Result<Slice<Ev>, St::Error>
+    where
+        St: History<Ev>,
+    {
+        let mut slice =
+            self.store.read_history(stream, after.map(|m| m.position))?;
+        let within = slice
+            .events
+            .iter()
+            .position(|(meta, _)| !cutoff.includes(meta))
+            .unwrap_or(slice.events.len());
+        slice.events.truncate(within);
+        Ok(slice)
+    }
+}
+
+/// Difference between states of a [`Stream`] at two points of its history.
+///
+/// [`Stream`]: stream::Stream
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diff<S, Ev> {
+    /// State at the earlier point.
+    pub before: At<S>,
+
+    /// State at the later point.
+    pub after: At<S>,
+
+    /// [`Event`]s between the points, along with their [`Meta`]data.
+    pub events: Vec<(Meta, Ev)>,
+}
+
+impl<S: PartialEq, Ev> Diff<S, Ev> {
+    /// Indicates whether the state has changed between the points.
+    #[must_use]
+    pub fn is_changed(&self) -> bool {
+        self.before.state != self.after.state
+    }
+}
+
+/// Error of a [`Loader`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error<St, Sn> {
+    /// [`Stream`] is truncated up to the provided [`stream::Position`]
+    /// (inclusively), and no [`Snapshot`] covers its truncated [`Event`]s.
+    ///
+    /// [`Stream`]: stream::Stream
+    Truncated(stream::Position),
+
+    /// [`History`] failed.
+    Store(St),
+
+    /// [`Snapshots`] failed.
+    Snapshots(Sn),
+}
+
+impl<St: fmt::Display, Sn: fmt::Display> fmt::Display for Error<St, Sn> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated(p) => write!(
+                f,
+                "history is truncated up to position `{p}` not covered by \
+                 snapshot",
+            ),
+            Self::Store(e) => write!(f, "failed to read history: {e}"),
+            Self::Snapshots(e) => write!(f, "failed to load snapshot: {e}"),
+        }
+    }
+}
+
+impl<St, Sn> StdError for Error<St, Sn>
+where
+    St: StdError + 'static,
+    Sn: StdError + 'static,
+{
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Truncated(_) => None,
+            Self::Store(e) => Some(e),
+            Self::Snapshots(e) => Some(e),
+        }
+    }
+}
@@ -0,0 +1,498 @@
+//! Multi-tenant [`Store`]s isolating the [`Stream`]s of their tenants.
+//!
+//! A tenant is a first-class part of a [`stream::Id`] (displayed as
+//! `<tenant>/<category>-<id>`, like `acme/chat-42`), so every [`Recorded`]
+//! [`Event`] and [`Entry`] of a global log carries the tenant it belongs to.
+//!
+//! A [`Tenanted`] [`Store`] doesn't expose the [`Store`] API by itself, but
+//! only hands out [`Scoped`] handles, each one to a single tenant:
+//! - all the [`stream::Id`]s passed to a [`Scoped`] handle are placed into its
+//!   tenant;
+//! - [`Store::read_category()`] and [`Subscription`]s of a [`Scoped`] handle
+//!   deliver the [`Stream`]s of its tenant only.
+//!
+//! As a [`Scoped`] handle neither changes its tenant nor gives access to the
+//! underlying [`Store`], a handler accepting it cannot read or write the
+//! [`Event`]s of another tenant, which is enforced at compile time.
+//!
+//! The whole tenant is hard-deleted via [`Tenanted::delete()`], which may be
+//! resumed after a failure.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use arcane::es::{
+//! #     Event,
+//! #     store::{self, Store},
+//! #     stream::{self, ExpectedVersion},
+//! #     subscription::{Offset, Shared},
+//! #     tenant::{self, Tenanted},
+//! # };
+//! # use futures::{StreamExt as _, executor::block_on};
+//! #
+//! #[derive(Clone, Debug, Event)]
+//! #[event(name = "message.posted")]
+//! struct MessagePosted;
+//!
+//! // Handler cannot reach other tenants via its `store`.
+//! fn post(store: &mut impl Store<MessagePosted>, chat: &str) {
+//!     let chat = stream::Id::new("chat", chat).unwrap();
+//!     let ev = vec![MessagePosted];
+//!     assert!(store.append(&chat, ExpectedVersion::Any, ev).is_ok());
+//! }
+//!
+//! let mut tenants = Tenanted::new(Shared::new(store::Memory::new()));
+//! let (acme, umbrella) = (
+//!     "acme".parse::<tenant::Id>().unwrap(),
+//!     "umbrella".parse::<tenant::Id>().unwrap(),
+//! );
+//! let mut acme_store = tenants.scope(acme.clone());
+//! let mut umbrella_store = tenants.scope(umbrella.clone());
+//!
+//! post(&mut acme_store, "1");
+//! post(&mut umbrella_store, "1");
+//! post(&mut umbrella_store, "2");
+//!
+//! let chat = stream::Id::new("chat", "1").unwrap();
+//! let stream = acme_store.read_stream(&chat).unwrap();
+//! assert_eq!(stream.id().to_string(), "acme/chat-1");
+//! assert_eq!(stream.len(), 1);
+//!
+//! let recorded = umbrella_store.read_category("chat").unwrap();
+//! assert_eq!(
+//!     recorded.iter().map(|r| r.stream.to_string()).collect::<Vec<_>>(),
+//!     ["umbrella/chat-1", "umbrella/chat-2"],
+//! );
+//!
+//! let mut acme_events = acme_store.subscribe(Offset::START);
+//! block_on(async {
+//!     let (offset, entry) = acme_events.next().await.unwrap().unwrap();
+//!     assert_eq!(offset, Offset::START);
+//!     assert_eq!(entry.stream().tenant(), Some(&acme));
+//! });
+//!
+//! assert_eq!(tenants.delete::<MessagePosted>(&umbrella).unwrap(), 2);
+//! assert!(umbrella_store.read_category("chat").unwrap().is_empty());
+//! assert_eq!(acme_store.read_category("chat").unwrap().len(), 1);
+//! ```
+//!
+//! [`Subscription`]: subscription::Subscription
+
+use std::{fmt, str::FromStr};
+
+use derive_more::{Display, Error};
+
+#[cfg(doc)]
+use super::store::Entry;
+use super::{
+    Event,
+    store::{self, AppendError, Lifecycle, Recorded, Store},
+    stream::{self, ExpectedVersion, Stream},
+    subscription::{self, Offset, Subscription},
+};
+
+/// Separator between a tenant and the rest of a [`stream::Id`].
+pub const SEPARATOR: char = '/';
+
+/// Identifier of a tenant.
+///
+/// Cannot be empty or contain the [`SEPARATOR`], the [`stream::SEPARATOR`] or
+/// whitespaces.
+///
+/// # Example
+///
+/// ```rust
+/// # use arcane::es::{stream, tenant};
+/// #
+/// let acme: tenant::Id = "acme".parse().unwrap();
+/// assert_eq!(acme.as_str(), "acme");
+///
+/// let chat: stream::Id = "acme/chat-42".parse().unwrap();
+/// assert_eq!(chat.tenant(), Some(&acme));
+/// assert_eq!((chat.category(), chat.id()), ("chat", "42"));
+/// assert_eq!(chat, stream::Id::new("chat", "42").unwrap().in_tenant(acme),);
+///
+/// assert!("acme-eu".parse::<tenant::Id>().is_err());
+/// assert!("/chat-42".parse::<stream::Id>().is_err());
+/// assert!("acme/eu/chat-42".parse::<stream::Id>().is_err());
+/// assert!(stream::Id::new("acme/chat", "42").is_err());
+/// ```
+#[derive(Clone, Debug, Display, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct Id(String);
+
+impl Id {
+    /// Creates a new [`Id`] out of the provided `value`.
+    ///
+    /// # Errors
+    ///
+    /// If the provided `value` is empty or contains the [`SEPARATOR`], the
+    /// [`stream::SEPARATOR`] or whitespaces.
+    pub fn new(value: impl Into<String>) -> Result<Self, ParseIdError> {
+        let value = value.into();
+        if value.is_empty()
+            || value.contains(|c: char| {
+                c == SEPARATOR || c == stream::SEPARATOR || c.is_whitespace()
+            })
+        {
+            return Err(ParseIdError);
+        }
+        Ok(Self(value))
+    }
+
+    /// Returns this [`Id`] as a string.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Id {
+    type Err = ParseIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for Id {
+    type Error = ParseIdError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::new(s)
+    }
+}
+
+impl From<Id> for String {
+    fn from(id: Id) -> Self {
+        id.0
+    }
+}
+
+/// Error of parsing an [`Id`].
+#[derive(Clone, Copy, Debug, Display, Eq, Error, Hash, PartialEq)]
+#[display(
+    "tenant ID cannot be empty or contain `{SEPARATOR}`, `{}` or whitespaces",
+    stream::SEPARATOR
+)]
+pub struct ParseIdError;
+
+/// [`Store`] listing the [`Stream`]s of its tenants.
+pub trait Streams<Ev>: Store<Ev> {
+    /// Returns the [`stream::Id`]s of all the non-empty [`Stream`]s of the
+    /// provided tenant (including the tombstoned ones), in their order.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Stream`]s cannot be listed.
+    fn streams_of(&self, tenant: &Id) -> Result<Vec<stream::Id>, Self::Error>;
+
+    /// Reads all the [`Event`]s of the [`Categorized`] [`Stream`]s of the
+    /// provided tenant (the same as [`Store::read_category()`], but without
+    /// reading the [`Stream`]s of other tenants).
+    ///
+    /// # Errors
+    ///
+    /// If the [`Event`]s cannot be read.
+    ///
+    /// [`Categorized`]: stream::Categorized
+    fn read_category_of(
+        &self,
+        tenant: &Id,
+        category: &str,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error>;
+}
+
+impl<Ev: Clone + Event> Streams<Ev> for store::Memory<Ev> {
+    fn streams_of(&self, tenant: &Id) -> Result<Vec<stream::Id>, Self::Error> {
+        Ok(of(tenant, self.streams().map(|(id, _)| id)))
+    }
+
+    fn read_category_of(
+        &self,
+        tenant: &Id,
+        category: &str,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error> {
+        Ok(self
+            .log()
+            .filter(|r| {
+                r.stream.tenant() == Some(tenant) && r.stream.is_in(category)
+            })
+            .cloned()
+            .collect())
+    }
+}
+
+impl<S: Streams<Ev>, Ev> Streams<Ev> for subscription::Shared<S> {
+    fn streams_of(&self, tenant: &Id) -> Result<Vec<stream::Id>, Self::Error> {
+        self.lock().streams_of(tenant)
+    }
+
+    fn read_category_of(
+        &self,
+        tenant: &Id,
+        category: &str,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error> {
+        self.lock().read_category_of(tenant, category)
+    }
+}
+
+/// Collects the provided [`stream::Id`]s belonging to the provided tenant in
+/// their order.
+pub(crate) fn of<'s>(
+    tenant: &'s Id,
+    streams: impl IntoIterator<Item = &'s stream::Id>,
+) -> Vec<stream::Id> {
+    let mut ids = streams
+        .into_iter()
+        .filter(|id| id.tenant() == Some(tenant))
+        .cloned()
+        .collect::<Vec<_>>();
+    ids.sort_unstable();
+    ids
+}
+
+/// Multi-tenant [`Store`] handing out [`Scoped`] handles to its tenants.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Clone, Debug, Default)]
+pub struct Tenanted<S> {
+    /// Underlying [`Store`] of all the tenants.
+    store: S,
+}
+
+impl<S> Tenanted<S> {
+    /// Wraps the provided [`Store`] into a [`Tenanted`] one.
+    #[must_use]
+    pub const fn new(store: S) -> Self {
+        Self { store }
+    }
+
+    /// Returns a [`Scoped`] handle to the provided tenant of this [`Tenanted`]
+    /// [`Store`].
+    ///
+    /// The underlying [`Store`] should be a shared one (like
+    /// [`subscription::Shared`]), so all the handles refer to it.
+    #[must_use]
+    pub fn scope(&self, tenant: Id) -> Scoped<S>
+    where
+        S: Clone,
+    {
+        Scoped { store: self.store.clone(), tenant }
+    }
+
+    /// Hard-deletes all the [`Stream`]s of the provided tenant via
+    /// [`Lifecycle::delete()`] in their order, returning their number.
+    ///
+    /// Deletion is not atomic, but resumable: the already deleted [`Stream`]s
+    /// are not listed by [`Streams::streams_of()`] anymore, so calling it
+    /// again after a failure deletes the remaining ones only.
+    ///
+    /// # Errors
+    ///
+    /// [`DeleteError`] if the [`Stream`]s cannot be listed or deleted, along
+    /// with the number of the [`Stream`]s deleted before the failure (which
+    /// stay deleted).
+    pub fn delete<Ev>(
+        &mut self,
+        tenant: &Id,
+    ) -> Result<usize, DeleteError<<S as Store<Ev>>::Error>>
+    where
+        S: Streams<Ev> + Lifecycle<Ev>,
+    {
+        let streams = self
+            .store
+            .streams_of(tenant)
+            .map_err(|source| DeleteError { deleted: 0, source })?;
+        let mut deleted = 0;
+        for stream in streams {
+            if self
+                .store
+                .delete(&stream)
+                .map_err(|source| DeleteError { deleted, source })?
+            {
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Unwraps this [`Tenanted`] [`Store`], returning the underlying one of
+    /// all the tenants.
+    #[must_use]
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+}
+
+/// Error of [`Tenanted::delete()`]ing a tenant, interrupted in the middle.
+#[derive(Clone, Copy, Debug, Display, Eq, Error, PartialEq)]
+#[display(
+    "tenant deletion failed after deleting `{deleted}` streams: {source}"
+)]
+pub struct DeleteError<E> {
+    /// Number of the [`Stream`]s deleted before the failure.
+    pub deleted: usize,
+
+    /// Error of the underlying [`Store`].
+    pub source: E,
+}
+
+/// Handle to a single tenant of a [`Tenanted`] [`Store`].
+///
+/// All the [`stream::Id`]s passed to it are placed into its tenant, and only
+/// the [`Stream`]s of its tenant are read from it.
+///
+/// # Example
+///
+/// A [`stream::Id`] of another tenant is re-scoped into the handle's one, so
+/// it never reaches the foreign [`Stream`].
+///
+/// ```rust
+/// # use arcane::es::{
+/// #     Event,
+/// #     store::{self, Lifecycle as _, Store},
+/// #     stream::{self, ExpectedVersion},
+/// #     subscription::Shared,
+/// #     tenant::Tenanted,
+/// # };
+/// #
+/// #[derive(Clone, Debug, Event)]
+/// #[event(name = "message.posted")]
+/// struct MessagePosted;
+///
+/// let tenants = Tenanted::new(Shared::new(store::Memory::new()));
+/// let mut acme = tenants.scope("acme".parse().unwrap());
+/// let mut umbrella = tenants.scope("umbrella".parse().unwrap());
+///
+/// let chat = stream::Id::new("chat", "1").unwrap();
+/// let ev = vec![MessagePosted, MessagePosted];
+/// umbrella.append(&chat, ExpectedVersion::Any, ev).unwrap();
+///
+/// let foreign: stream::Id = "umbrella/chat-1".parse().unwrap();
+/// let stream = acme.read_stream(&foreign).unwrap();
+/// assert_eq!(stream.id().to_string(), "acme/chat-1");
+/// assert!(stream.is_empty());
+/// assert!(acme.read_recorded(&foreign).unwrap().is_empty());
+///
+/// // Expectations are checked against the own tenant's `Stream` too.
+/// let expected = ExpectedVersion::Exact(stream::Position::new(2).unwrap());
+/// assert!(acme.append(&foreign, expected, vec![MessagePosted]).is_err());
+/// acme.append(&foreign, ExpectedVersion::Any, vec![MessagePosted]).unwrap();
+/// assert!(!acme.delete(&"umbrella/chat-2".parse().unwrap()).unwrap());
+/// acme.tombstone(&foreign, ExpectedVersion::Any).unwrap();
+///
+/// let stream = umbrella.read_stream(&foreign).unwrap();
+/// assert_eq!(stream.id().to_string(), "umbrella/chat-1");
+/// assert_eq!(stream.len(), 2);
+/// assert_eq!(stream.tombstone(), None);
+/// let stream = acme.read_stream(&chat).unwrap();
+/// assert_eq!(stream.len(), 1);
+/// assert_eq!(stream.tombstone(), stream::Position::new(2));
+/// assert_eq!(umbrella.read_category("chat").unwrap().len(), 2);
+/// ```
+#[derive(Clone)]
+pub struct Scoped<S> {
+    /// Underlying [`Store`] of all the tenants.
+    store: S,
+
+    /// Tenant of this [`Scoped`] handle.
+    tenant: Id,
+}
+
+impl<S> Scoped<S> {
+    /// Returns the tenant of this [`Scoped`] handle.
+    #[must_use]
+    pub const fn tenant(&self) -> &Id {
+        &self.tenant
+    }
+
+    /// Creates a new [`Subscription`] to the [`Entry`]s of this tenant only,
+    /// starting from the provided [`Offset`] in the global log.
+    #[must_use]
+    pub fn subscribe<Ev>(&self, from: Offset) -> Subscription<S, Ev>
+    where
+        S: Clone,
+    {
+        Subscription::new(self.store.clone(), from).tenant(self.tenant.clone())
+    }
+
+    /// Places the provided [`stream::Id`] into the tenant of this [`Scoped`]
+    /// handle.
+    fn scope(&self, stream: &stream::Id) -> stream::Id {
+        stream.clone().in_tenant(self.tenant.clone())
+    }
+}
+
+impl<S> fmt::Debug for Scoped<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scoped")
+            .field("tenant", &self.tenant)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: Streams<Ev>, Ev> Store<Ev> for Scoped<S> {
+    type Error = S::Error;
+
+    fn append_with(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+        events: Vec<Ev>,
+        metadata: &store::Metadata,
+    ) -> Result<Option<stream::Position>, AppendError<Self::Error>> {
+        let stream = self.scope(stream);
+        self.store.append_with(&stream, expected, events, metadata)
+    }
+
+    fn read_stream(
+        &self,
+        stream: &stream::Id,
+    ) -> Result<Stream<Ev>, Self::Error> {
+        self.store.read_stream(&self.scope(stream))
+    }
+
+    fn read_recorded(
+        &self,
+        stream: &stream::Id,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error> {
+        self.store.read_recorded(&self.scope(stream))
+    }
+
+    fn read_category(
+        &self,
+        category: &str,
+    ) -> Result<Vec<Recorded<Ev>>, Self::Error> {
+        self.store.read_category_of(&self.tenant, category)
+    }
+}
+
+impl<S: Lifecycle<Ev> + Streams<Ev>, Ev> Lifecycle<Ev> for Scoped<S> {
+    fn tombstone(
+        &mut self,
+        stream: &stream::Id,
+        expected: ExpectedVersion,
+    ) -> Result<stream::Position, AppendError<Self::Error>> {
+        let stream = self.scope(stream);
+        self.store.tombstone(&stream, expected)
+    }
+
+    fn delete(&mut self, stream: &stream::Id) -> Result<bool, Self::Error> {
+        let stream = self.scope(stream);
+        self.store.delete(&stream)
+    }
+
+    fn truncate(
+        &mut self,
+        stream: &stream::Id,
+        before: stream::Position,
+    ) -> Result<usize, Self::Error> {
+        let stream = self.scope(stream);
+        self.store.truncate(&stream, before)
+    }
+}
@@ -145,12 +145,20 @@
     variant_size_differences
 )]
 
+#[cfg(feature = "cqrs")]
+pub mod cqrs;
 #[cfg(feature = "es")]
 pub mod es;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(any(feature = "archive", feature = "file", feature = "pii"))]
+#[doc(hidden)]
+pub mod util;
 
 #[cfg(test)]
 mod only_for_doc_tests {
     use arcane as _;
+    use futures as _;
 }
 
 pub use ref_cast::RefCast;
@@ -0,0 +1,282 @@
+//! Given/When/Then harness for testing [`Sourced`] states and command
+//! handlers.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use arcane::es::event::{Event, Initialized, Sourced};
+//! # use arcane::testing::given;
+//! #
+//! #[derive(Debug, Event, PartialEq)]
+//! #[event(name = "chat.created", revision = 1)]
+//! struct ChatCreated;
+//!
+//! #[derive(Debug, Event, PartialEq)]
+//! #[event(name = "message.posted", revision = 1)]
+//! struct MessagePosted;
+//!
+//! #[derive(Debug, Event, PartialEq)]
+//! enum ChatEvent {
+//!     #[event(init)]
+//!     Created(ChatCreated),
+//!     MessagePosted(MessagePosted),
+//! }
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Chat {
+//!     message_count: usize,
+//! }
+//!
+//! impl Initialized<ChatCreated> for Chat {
+//!     fn init(_: &ChatCreated) -> Self {
+//!         Self { message_count: 0 }
+//!     }
+//! }
+//!
+//! impl Sourced<MessagePosted> for Chat {
+//!     fn apply(&mut self, _: &MessagePosted) {
+//!         self.message_count += 1;
+//!     }
+//! }
+//!
+//! fn post_message(
+//!     chat: &Option<Chat>,
+//! ) -> Result<Vec<ChatEvent>, &'static str> {
+//!     chat.as_ref()
+//!         .map(|_| vec![ChatEvent::MessagePosted(MessagePosted)])
+//!         .ok_or("no chat")
+//! }
+//!
+//! given([ChatEvent::Created(ChatCreated)])
+//!     .when(post_message)
+//!     .then([ChatEvent::MessagePosted(MessagePosted)]);
+//!
+//! given([]).when(post_message).then_error("no chat");
+//!
+//! given([
+//!     ChatEvent::Created(ChatCreated),
+//!     ChatEvent::MessagePosted(MessagePosted),
+//! ])
+//! .then_state(Some(Chat { message_count: 1 }));
+//! ```
+//!
+//! On a mismatch, the harness panics listing both the expected and the actual
+//! [`Event`]s by their [`Event::name()`]s, and by their
+//! [`Revisable::revision()`]s too, if [`Given::with_revisions()`] is used:
+//!
+//! ```rust,should_panic
+//! # use arcane::es::event::{Event, Sourced};
+//! # use arcane::testing::given;
+//! #
+//! # #[derive(Debug, Event, PartialEq)]
+//! # #[event(name = "chat.created", revision = 1)]
+//! # struct ChatCreated;
+//! #
+//! # #[derive(Debug, Event, PartialEq)]
+//! # #[event(name = "chat.renamed", revision = 2)]
+//! # struct ChatRenamed;
+//! #
+//! # #[derive(Debug, Event, PartialEq)]
+//! # #[event(rev)]
+//! # enum ChatEvent {
+//! #     Created(ChatCreated),
+//! #     Renamed(ChatRenamed),
+//! # }
+//! #
+//! # #[derive(Default)]
+//! # struct Chat;
+//! #
+//! # impl Sourced<ChatEvent> for Chat {
+//! #     fn apply(&mut self, _: &ChatEvent) {}
+//! # }
+//! #
+//! given([])
+//!     .with_revisions()
+//!     .when(|_: &Chat| Ok::<_, ()>(vec![ChatEvent::Created(ChatCreated)]))
+//!     .then([ChatEvent::Renamed(ChatRenamed)]);
+//! // panicked at: events mismatch
+//! //   expected:
+//! //     #1 `chat.renamed` (revision 2): Renamed(ChatRenamed)
+//! //   actual:
+//! //     #1 `chat.created` (revision 1): Created(ChatCreated)
+//! ```
+
+use std::fmt::{self, Write as _};
+
+use crate::es::event::{Event, Revisable, RevisionOf, Sourced};
+
+/// Describer of an [`Event`] in the mismatch reports.
+type Describe<Ev> = fn(&Ev) -> String;
+
+/// Starts a scenario with the provided historical [`Event`]s.
+#[must_use]
+pub fn given<Ev>(events: impl IntoIterator<Item = Ev>) -> Given<Ev>
+where
+    Ev: Event + fmt::Debug,
+{
+    Given { events: events.into_iter().collect(), describe: named::<Ev> }
+}
+
+/// Scenario with the historical [`Event`]s, created by [`given()`].
+#[derive(Clone, Debug)]
+pub struct Given<Ev> {
+    /// Historical [`Event`]s.
+    events: Vec<Ev>,
+
+    /// [`Describe`]r of [`Event`]s in mismatch reports.
+    describe: Describe<Ev>,
+}
+
+impl<Ev> Given<Ev> {
+    /// Makes mismatch reports list [`Revisable::revision()`]s of [`Event`]s
+    /// along with their [`Event::name()`]s.
+    #[must_use]
+    pub fn with_revisions(mut self) -> Self
+    where
+        Ev: Revisable + fmt::Debug,
+        RevisionOf<Ev>: fmt::Display,
+    {
+        self.describe = revised::<Ev>;
+        self
+    }
+
+    /// Runs the provided `command` against the state [`Sourced`] from the
+    /// historical [`Event`]s.
+    #[must_use]
+    pub fn when<S, C, E>(self, command: C) -> When<Ev, E>
+    where
+        S: Default + Sourced<Ev>,
+        C: FnOnce(&S) -> Result<Vec<Ev>, E>,
+    {
+        let state = self.source::<S>();
+        When { result: command(&state), describe: self.describe }
+    }
+
+    /// Asserts the state [`Sourced`] from the historical [`Event`]s to be
+    /// equal to the `expected` one.
+    ///
+    /// # Panics
+    ///
+    /// If the [`Sourced`] state differs from the `expected` one.
+    #[expect(
+        clippy::needless_pass_by_value,
+        reason = "mirrors `assert_eq!()` ergonomics"
+    )]
+    #[track_caller]
+    pub fn then_state<S>(self, expected: S)
+    where
+        S: Default + Sourced<Ev> + PartialEq + fmt::Debug,
+    {
+        let actual = self.source::<S>();
+        assert!(
+            actual == expected,
+            "state mismatch\n  given:\n{}  expected:\n    {expected:?}\n  \
+             actual:\n    {actual:?}",
+            list(&self.events, self.describe),
+        );
+    }
+
+    /// Sources a new state from the historical [`Event`]s.
+    fn source<S: Default + Sourced<Ev>>(&self) -> S {
+        let mut state = S::default();
+        for ev in &self.events {
+            state.apply(ev);
+        }
+        state
+    }
+}
+
+/// Outcome of a command run via [`Given::when()`].
+#[derive(Clone, Debug)]
+pub struct When<Ev, E> {
+    /// Result of the command.
+    result: Result<Vec<Ev>, E>,
+
+    /// [`Describe`]r of [`Event`]s in mismatch reports.
+    describe: Describe<Ev>,
+}
+
+impl<Ev, E> When<Ev, E> {
+    /// Asserts the command to succeed producing exactly the `expected`
+    /// [`Event`]s.
+    ///
+    /// # Panics
+    ///
+    /// If the command fails or produces different [`Event`]s.
+    #[track_caller]
+    pub fn then(self, expected: impl IntoIterator<Item = Ev>)
+    where
+        Ev: PartialEq,
+        E: fmt::Debug,
+    {
+        let expected = expected.into_iter().collect::<Vec<_>>();
+        match self.result {
+            Ok(actual) => assert!(
+                actual == expected,
+                "events mismatch\n  expected:\n{}  actual:\n{}",
+                list(&expected, self.describe),
+                list(&actual, self.describe),
+            ),
+            Err(e) => panic!(
+                "expected events, but command failed\n  expected:\n{}  \
+                 error:\n    {e:?}",
+                list(&expected, self.describe),
+            ),
+        }
+    }
+
+    /// Asserts the command to fail with the `expected` error.
+    ///
+    /// # Panics
+    ///
+    /// If the command succeeds or fails with a different error.
+    #[expect(
+        clippy::needless_pass_by_value,
+        reason = "mirrors `assert_eq!()` ergonomics"
+    )]
+    #[track_caller]
+    pub fn then_error(self, expected: E)
+    where
+        E: PartialEq + fmt::Debug,
+    {
+        match self.result {
+            Ok(actual) => panic!(
+                "expected error, but command succeeded\n  expected:\n    \
+                 {expected:?}\n  actual:\n{}",
+                list(&actual, self.describe),
+            ),
+            Err(actual) => assert!(
+                actual == expected,
+                "error mismatch\n  expected:\n    {expected:?}\n  actual:\n    \
+                 {actual:?}",
+            ),
+        }
+    }
+}
+
+/// Describes the provided [`Event`] by its [`Event::name()`].
+fn named<Ev: Event + fmt::Debug>(ev: &Ev) -> String {
+    format!("`{}`: {ev:?}", ev.name())
+}
+
+/// Describes the provided [`Event`] by its [`Event::name()`] and
+/// [`Revisable::revision()`].
+fn revised<Ev>(ev: &Ev) -> String
+where
+    Ev: Revisable + fmt::Debug,
+    RevisionOf<Ev>: fmt::Display,
+{
+    format!("`{}` (revision {}): {ev:?}", ev.name(), ev.revision())
+}
+
+/// Lists the provided [`Event`]s one per line.
+fn list<Ev>(events: &[Ev], describe: Describe<Ev>) -> String {
+    if events.is_empty() {
+        return "    <none>\n".to_owned();
+    }
+    let mut out = String::new();
+    for (i, ev) in events.iter().enumerate() {
+        _ = writeln!(out, "    #{} {}", i + 1, describe(ev));
+    }
+    out
+}
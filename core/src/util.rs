@@ -0,0 +1,48 @@
+//! Helpers shared between the modules of this crate and its CLI.
+//!
+//! Not a part of the public API, so may change at any time.
+
+#[cfg(any(feature = "archive", feature = "file"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Encodes the provided `bytes` as a lowercase hexadecimal string.
+#[cfg(any(feature = "archive", feature = "pii"))]
+#[must_use]
+pub fn hex(bytes: &[u8]) -> String {
+    /// Hexadecimal digits by their values.
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    let mut out = String::with_capacity(bytes.len().saturating_mul(2));
+    for b in bytes {
+        out.extend(
+            [b >> 4, b & 0xf].map(|n| char::from(DIGITS[usize::from(n)])),
+        );
+    }
+    out
+}
+
+/// Decodes the provided hexadecimal string, if it's a valid one.
+#[cfg(any(feature = "archive", feature = "pii"))]
+#[must_use]
+pub fn unhex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    hex.as_bytes()
+        .chunks_exact(2)
+        .map(|pair| {
+            let digit = |c: u8| char::from(c).to_digit(16);
+            let &[hi, lo] = pair else { return None };
+            u8::try_from((digit(hi)? << 4) | digit(lo)?).ok()
+        })
+        .collect()
+}
+
+/// Returns the number of microseconds passed since [`UNIX_EPOCH`] till the
+/// provided `time`, saturating on overflow.
+#[cfg(any(feature = "archive", feature = "file"))]
+#[must_use]
+pub fn micros(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_micros()).unwrap_or(u64::MAX))
+}
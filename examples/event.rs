@@ -1,5 +1,6 @@
 use arcane::es::event::{
-    Event, Initialized, Name, Revisable, Sourced, Sourcing, Version, reflect,
+    Event, Initialized, Name, Revisable, Sourced, Sourcing, Version, name,
+    reflect,
 };
 
 #[derive(Event)]
@@ -10,6 +11,10 @@ struct ChatCreated;
 #[event(name = "message.posted", rev = 1)]
 struct MessagePosted;
 
+#[derive(Event)]
+#[event(name = "message.edited", alias = "message.changed")]
+struct MessageEdited;
+
 #[derive(Event)]
 enum ChatEvent {
     #[event(init)]
@@ -24,6 +29,13 @@ enum MessageEvent {
     MessagePosted(MessagePosted),
 }
 
+#[derive(Event)]
+#[event(prefix = "archived.", case = "snake_case", max_len = 64)]
+enum ArchivedEvent {
+    MessagePosted(MessagePosted),
+    MessageEdited(MessageEdited),
+}
+
 #[derive(Event)]
 enum AnyEvent {
     Chat(ChatEvent),
@@ -91,12 +103,35 @@ fn main() {
         "message.posted",
     ]);
 
+    assert_names::<ArchivedEvent>([
+        "archived.message.posted",
+        "archived.message.edited",
+    ]);
+    assert_eq!(
+        <ArchivedEvent as reflect::Static>::canonical_name(
+            "archived.message.changed",
+        ),
+        Some("archived.message.edited"),
+    );
+    assert_eq!(
+        <ArchivedEvent as reflect::Static>::canonical_name("message.changed"),
+        None,
+    );
+
     assert_revisions::<MessagePosted>([Version::try_new(1).unwrap()]);
+    assert!(<MessageEvent as reflect::Compatible>::supports(
+        "message.posted",
+        Version::MIN,
+    ));
+    assert!(!<MessageEvent as reflect::Compatible>::supports(
+        "message.posted",
+        Version::MIN.next().unwrap(),
+    ));
 
     let mut chat = Option::<Chat>::None;
     let mut message = Option::<Message>::None;
 
-    let ev = ChatEvent::Created(ChatCreated.into());
+    let ev = ChatEvent::Created(ChatCreated);
     chat.apply(&ev);
     assert_eq!(ev.name(), "chat.created");
     assert_eq!(chat, Some(Chat { message_count: 0 }));
@@ -110,16 +145,24 @@ fn main() {
     chat.apply(ev);
     assert_eq!(chat, Some(Chat { message_count: 2 }));
 
-    let ev = MessageEvent::MessagePosted(MessagePosted.into());
+    let ev = MessageEvent::MessagePosted(MessagePosted);
     message.apply(&ev);
     assert_eq!(ev.name(), "message.posted");
     assert_eq!(message, Some(Message));
     assert_eq!(ev.revision(), Version::try_new(1).unwrap());
 
-    let ev = AnyEvent::Chat(ChatEvent::Created(ChatCreated.into()));
+    let ev = AnyEvent::Chat(ChatEvent::Created(ChatCreated));
     assert_eq!(ev.name(), "chat.created");
 
-    let ev =
-        AnyEvent::Message(MessageEvent::MessagePosted(MessagePosted.into()));
+    let ev = AnyEvent::Message(MessageEvent::MessagePosted(MessagePosted));
     assert_eq!(ev.name(), "message.posted");
+
+    let ev = ArchivedEvent::MessageEdited(MessageEdited);
+    assert_eq!(ev.name(), "archived.message.edited");
+
+    let ev = ArchivedEvent::MessagePosted(MessagePosted);
+    assert_eq!(ev.name(), "archived.message.posted");
+    let parsed = name::Parsed::parse(ev.name()).unwrap();
+    assert!(parsed.is_in("archived"));
+    assert_eq!(parsed.segment(), "posted");
 }
@@ -0,0 +1,301 @@
+//! `arcane` CLI for inspecting and operating event stores.
+//!
+//! Events are printed as JSON lines with their stream IDs, positions, offsets
+//! in the global log, names, revisions, timestamps and causation and
+//! correlation IDs (if any). Payloads are printed as JSON if they can be
+//! decoded into it, or as hexadecimal strings of their encoded bytes
+//! otherwise.
+//!
+//! Events are exported and imported as portable archives (see the
+//! `arcane::es::archive` module).
+//!
+//! Inspecting commands open the store read-only, so they never modify it and
+//! may run alongside another process writing it, seeing the events committed
+//! by the time of opening. Modifying commands lock the store, so they fail if
+//! it's opened by another process.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    error::Error as StdError,
+    fs,
+    io::{self, BufReader, Write as _},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use arcane::{
+    es::{
+        archive::{self, Catalog},
+        codec::{Codec as _, Codecs},
+        store::file::{self, Fsync, Record},
+        stream::{self, ExpectedVersion},
+    },
+    util,
+};
+use clap::{Parser, Subcommand};
+use serde_json::{Value as Json, json};
+
+/// Boxed error of a [`Command`].
+type Error = Box<dyn StdError>;
+
+/// Inspects and operates event stores.
+#[derive(Debug, Parser)]
+#[command(name = "arcane", version)]
+struct Cli {
+    /// Directory of the file store.
+    #[arg(short, long, value_name = "DIR")]
+    store: PathBuf,
+
+    /// Command to execute.
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Command of the [`Cli`].
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Lists all the non-empty streams along with their versions.
+    Streams {
+        /// Category to list the streams of.
+        #[arg(short, long)]
+        category: Option<String>,
+    },
+
+    /// Prints the last events of the global log.
+    Tail {
+        /// Number of events to print.
+        #[arg(short = 'n', long, default_value_t = 10)]
+        lines: usize,
+    },
+
+    /// Prints all the events of a stream.
+    Dump {
+        /// ID of the stream (`<category>-<id>`).
+        stream: stream::Id,
+    },
+
+    /// Prints the numbers of events per name and revision.
+    Stats,
+
+    /// Verifies CRCs and positions of all the events.
+    Verify,
+
+    /// Exports all the events into an archive in the order they were
+    /// appended.
+    Export {
+        /// File to export into, instead of the standard output.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Imports the events from an archive, skipping the already present ones.
+    ///
+    /// An archive file is verified before importing anything, while the
+    /// standard input is imported up to its first invalid event.
+    Import {
+        /// File to import from, instead of the standard input.
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+
+    /// Soft-deletes a stream by appending a tombstone to it, so nothing can
+    /// be appended to it anymore.
+    Tombstone {
+        /// ID of the stream (`<category>-<id>`).
+        stream: stream::Id,
+    },
+
+    /// Hard-deletes a stream, so it becomes empty.
+    Delete {
+        /// ID of the stream (`<category>-<id>`).
+        stream: stream::Id,
+    },
+
+    /// Truncates the events of a stream before a position, always keeping
+    /// its last event.
+    Truncate {
+        /// ID of the stream (`<category>-<id>`).
+        stream: stream::Id,
+
+        /// Position to truncate the events before.
+        #[arg(short, long)]
+        before: stream::Position,
+    },
+
+    /// Drops the events of the deleted and truncated streams from the segment
+    /// files.
+    Compact,
+}
+
+impl Command {
+    /// Indicates whether this [`Command`] only inspects the store, so opens
+    /// it read-only.
+    const fn is_read_only(&self) -> bool {
+        match self {
+            Self::Streams { .. }
+            | Self::Tail { .. }
+            | Self::Dump { .. }
+            | Self::Stats
+            | Self::Verify
+            | Self::Export { .. } => true,
+            Self::Import { .. }
+            | Self::Tombstone { .. }
+            | Self::Delete { .. }
+            | Self::Truncate { .. }
+            | Self::Compact => false,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli.store, cli.command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the provided [`Command`] against the store in the provided directory.
+fn run(dir: &Path, command: Command) -> Result<(), Error> {
+    if !dir.is_dir() {
+        return Err(format!("no store at `{}`", dir.display()).into());
+    }
+    // Events are operated without decoding them into a concrete type.
+    let mut store = file::Options::new()
+        .fsync(Fsync::Never)
+        .read_only(command.is_read_only())
+        .open::<Json>(dir)?;
+    let codecs = Codecs::default();
+    let mut out = io::stdout().lock();
+
+    match command {
+        Command::Streams { category } => {
+            let mut streams = store
+                .streams()
+                .filter(|(id, _)| category.as_ref().is_none_or(|c| id.is_in(c)))
+                .collect::<Vec<_>>();
+            streams.sort_unstable();
+            for (id, version) in streams {
+                writeln!(out, "{id}\t{version}")?;
+            }
+        }
+        Command::Tail { lines } => {
+            let mut last = VecDeque::with_capacity(lines);
+            for rec in store.log() {
+                if lines == 0 {
+                    break;
+                }
+                if last.len() == lines {
+                    _ = last.pop_front();
+                }
+                last.push_back(rec?);
+            }
+            for rec in last {
+                writeln!(out, "{}", to_json(&codecs, &rec))?;
+            }
+        }
+        Command::Dump { stream } => {
+            for rec in store.records(&stream)? {
+                writeln!(out, "{}", to_json(&codecs, &rec))?;
+            }
+        }
+        Command::Stats => {
+            let mut counts = BTreeMap::<_, usize>::new();
+            for rec in store.log() {
+                let rec = rec?;
+                *counts.entry((rec.name, rec.version)).or_default() += 1;
+            }
+            for ((name, revision), count) in counts {
+                writeln!(out, "{name}\t{revision}\t{count}")?;
+            }
+        }
+        Command::Verify => {
+            let count = store.verify()?;
+            writeln!(out, "{count} events verified")?;
+        }
+        Command::Export { output } => {
+            let mut catalog = Catalog::new();
+            for rec in store.log() {
+                let rec = rec?;
+                _ = catalog.insert(rec.name, rec.version);
+            }
+            let output: Box<dyn io::Write> = match output {
+                Some(path) => {
+                    Box::new(io::BufWriter::new(fs::File::create(path)?))
+                }
+                None => Box::new(out),
+            };
+            let count = archive::export(&store, catalog, output)?;
+            eprintln!("{count} events exported");
+        }
+        Command::Import { input } => {
+            let input: Box<dyn io::BufRead> = match input {
+                Some(path) => {
+                    _ = archive::verify(BufReader::new(fs::File::open(
+                        &path,
+                    )?))?;
+                    Box::new(BufReader::new(fs::File::open(path)?))
+                }
+                None => Box::new(io::stdin().lock()),
+            };
+            let imported = archive::import(input, &mut store)?;
+            store.sync()?;
+            writeln!(
+                out,
+                "{} events imported, {} skipped",
+                imported.appended, imported.skipped,
+            )?;
+        }
+        Command::Tombstone { stream } => {
+            let pos = store.entomb(&stream, ExpectedVersion::Any)?;
+            store.sync()?;
+            writeln!(out, "{stream} tombstoned at {pos}")?;
+        }
+        Command::Delete { stream } => {
+            if !store.erase(&stream)? {
+                return Err(format!("stream `{stream}` is empty").into());
+            }
+            store.sync()?;
+        }
+        Command::Truncate { stream, before } => {
+            let truncated = store.truncate_before(&stream, before)?;
+            store.sync()?;
+            writeln!(out, "{truncated} events truncated")?;
+        }
+        Command::Compact => {
+            let reclaimed = store.compact()?;
+            writeln!(out, "{reclaimed} bytes reclaimed")?;
+        }
+    }
+    Ok(())
+}
+
+/// Converts the provided [`Record`] into a JSON line.
+///
+/// Payloads which cannot be decoded into JSON are included as hexadecimal
+/// strings of their encoded bytes.
+fn to_json(codecs: &Codecs, rec: &Record) -> Json {
+    let timestamp = humantime::format_rfc3339_micros(rec.timestamp);
+    let mut line = json!({
+        "stream": rec.stream.to_string(),
+        "position": rec.position.get(),
+        "offset": rec.offset,
+        "name": rec.name,
+        "revision": rec.version.get(),
+        "timestamp": timestamp.to_string(),
+    });
+    match codecs.decode::<Json>(&rec.name, rec.version, &rec.payload) {
+        Ok(payload) => line["payload"] = payload,
+        Err(_) => line["encoded"] = util::hex(&rec.payload).into(),
+    }
+    if let Some(id) = &rec.metadata.causation {
+        line["causation"] = id.as_str().into();
+    }
+    if let Some(id) = &rec.metadata.correlation {
+        line["correlation"] = id.as_str().into();
+    }
+    line
+}
@@ -3,22 +3,33 @@
 #[cfg(feature = "derive")]
 #[doc(hidden)]
 pub mod codegen {
-    #[doc(inline)]
-    pub use arcane_codegen::const_concat_slices;
     #[doc(inline)]
     pub use arcane_codegen::es::event::{
-        Reflect, concat_slices, has_different_types_with_same_name_and_revision,
+        Reflect, concat_slices,
+        has_different_types_with_same_name_and_revision, is_alias,
+    };
+    #[doc(inline)]
+    pub use arcane_codegen::{
+        const_concat_aliases, const_concat_slices, const_prefix_aliases,
+        const_prefix_meta, const_prefix_names,
     };
 }
 
 #[cfg(feature = "derive")]
 #[doc(inline)]
 pub use arcane_codegen::es::event::Event;
+#[cfg(feature = "proptest")]
+#[doc(inline)]
+pub use arcane_core::es::event::arbitrary;
 #[cfg(feature = "reflect")]
 #[doc(inline)]
 pub use arcane_core::es::event::reflect;
+#[cfg(feature = "registry")]
+#[doc(inline)]
+pub use arcane_core::es::event::registry;
 #[doc(inline)]
 pub use arcane_core::es::event::{
-    Concrete, Event, Initial, Initialized, Name, Revisable, Revision,
-    RevisionOf, Sourced, Sourcing, Static, Version,
+    Concrete, Event, Initial, Initialized, Name, ParseVersionRangeError,
+    Revisable, Revision, RevisionOf, Sourced, Sourcing, Static, Version,
+    VersionRange, name,
 };
@@ -4,10 +4,41 @@
 
 pub mod event;
 
+#[cfg(feature = "archive")]
+#[doc(inline)]
+pub use arcane_core::es::archive;
+#[cfg(feature = "cloudevents")]
+#[doc(inline)]
+pub use arcane_core::es::cloudevents;
+#[cfg(feature = "codec")]
+#[doc(inline)]
+pub use arcane_core::es::codec;
+#[cfg(feature = "outbox")]
+#[doc(inline)]
+pub use arcane_core::es::outbox;
+#[cfg(feature = "pii")]
+#[doc(inline)]
+pub use arcane_core::es::pii;
+#[cfg(feature = "protobuf")]
+#[doc(inline)]
+pub use arcane_core::es::protobuf;
+#[cfg(feature = "temporal")]
+#[doc(inline)]
+pub use arcane_core::es::temporal;
+#[cfg(feature = "tenant")]
+#[doc(inline)]
+pub use arcane_core::es::tenant;
+#[doc(inline)]
+pub use arcane_core::es::{EventStore, Stream, StreamId, store, stream};
+#[cfg(feature = "subscription")]
+#[doc(inline)]
+pub use arcane_core::es::{Subscription, subscription};
+
 #[doc(inline)]
 pub use self::event::{
     Concrete as ConcreteEvent, Event, Initialized as EventInitialized,
     Name as EventName, Revisable as RevisableEvent, Revision as EventRevision,
     RevisionOf as EventRevisionOf, Sourced as EventSourced,
     Sourcing as EventSourcing, Static as StaticEvent, Version as EventVersion,
+    VersionRange as EventVersionRange,
 };
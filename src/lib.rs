@@ -145,9 +145,17 @@
     variant_size_differences
 )]
 
+#[cfg(feature = "cqrs")]
+#[doc(inline)]
+pub use arcane_core::cqrs;
 #[cfg(feature = "es")]
 pub mod es;
-
 #[cfg(feature = "derive")]
 use arcane_codegen as _;
 pub use arcane_core::RefCast;
+#[cfg(feature = "testing")]
+#[doc(inline)]
+pub use arcane_core::testing;
+#[cfg(any(feature = "archive", feature = "file", feature = "pii"))]
+#[doc(hidden)]
+pub use arcane_core::util;
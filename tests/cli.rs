@@ -0,0 +1,194 @@
+//! Tests of the `arcane` CLI.
+
+use std::{
+    env, fs,
+    io::Write as _,
+    path::{Path, PathBuf},
+    process::{self, Command, Output},
+};
+
+use arcane::es::{
+    event,
+    store::{
+        self,
+        file::{self, Encoded},
+    },
+    stream::{self, ExpectedVersion},
+};
+
+/// Returns a new empty directory for the provided test.
+fn dir(test: &str) -> PathBuf {
+    let dir =
+        env::temp_dir().join(format!("arcane-cli-{test}-{}", process::id()));
+    drop(fs::remove_dir_all(&dir));
+    dir
+}
+
+/// Creates a file store in the provided directory with the `chat-1` stream
+/// of the provided JSON messages.
+fn seed(dir: &Path, messages: &[&str]) {
+    let mut store = file::File::<()>::open(dir).expect("opens store");
+    let events = messages
+        .iter()
+        .map(|m| Encoded {
+            name: "message.posted".into(),
+            version: event::Version::try_new(1).expect("valid version"),
+            payload: m.as_bytes().to_vec(),
+            timestamp: None,
+            metadata: store::Metadata::new(),
+        })
+        .collect();
+    _ = store
+        .append_encoded(&chat(), ExpectedVersion::NoStream, events)
+        .expect("appends");
+    store.sync().expect("syncs");
+}
+
+/// Returns the ID of the seeded stream.
+fn chat() -> stream::Id {
+    stream::Id::new("chat", "1").expect("valid ID")
+}
+
+/// Runs the `arcane` CLI against the store in the provided directory.
+fn arcane(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_arcane"))
+        .arg("--store")
+        .arg(dir)
+        .args(args)
+        .output()
+        .expect("runs `arcane`")
+}
+
+/// Returns the standard output of the provided successful [`Output`].
+fn stdout(output: Output) -> String {
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).expect("UTF-8 output")
+}
+
+/// Returns the lengths of all the files in the provided directory.
+fn files(dir: &Path) -> Vec<(PathBuf, u64)> {
+    let mut files = fs::read_dir(dir)
+        .expect("reads directory")
+        .map(|e| {
+            let path = e.expect("reads entry").path();
+            let len = fs::metadata(&path).expect("reads metadata").len();
+            (path, len)
+        })
+        .collect::<Vec<_>>();
+    files.sort_unstable();
+    files
+}
+
+#[test]
+fn inspects_without_modifying() {
+    let dir = dir("inspect");
+    seed(&dir, &[r#""a""#, r#""b""#, r#""c""#]);
+    assert_eq!(stdout(arcane(&dir, &["verify"])), "3 events verified\n");
+
+    let last = files(&dir)
+        .into_iter()
+        .map(|(p, _)| p)
+        .filter(|p| p.extension().is_some_and(|ext| ext == "seg"))
+        .max()
+        .expect("has segment files");
+    let mut torn =
+        fs::OpenOptions::new().append(true).open(&last).expect("opens segment");
+    torn.write_all(&[42, 0, 0, 0, 1, 2]).expect("writes");
+    drop(torn);
+    let before = files(&dir);
+
+    assert_eq!(stdout(arcane(&dir, &["streams"])), "chat-1\t3\n");
+    let tail = stdout(arcane(&dir, &["tail", "-n", "2"]));
+    let payloads = tail
+        .lines()
+        .map(|l| {
+            let line = serde_json::from_str::<serde_json::Value>(l)
+                .expect("JSON line");
+            line["payload"].clone()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(payloads, ["b", "c"]);
+    assert_eq!(stdout(arcane(&dir, &["dump", "chat-1"])).lines().count(), 3);
+    assert_eq!(stdout(arcane(&dir, &["stats"])), "message.posted\t1\t3\n");
+
+    assert_eq!(files(&dir), before);
+    assert!(!arcane(&dir.join("missing"), &["streams"]).status.success());
+    assert!(!dir.join("missing").exists());
+
+    fs::remove_dir_all(&dir).expect("removes directory");
+}
+
+#[test]
+fn inspects_locked_store() {
+    let dir = dir("locked");
+    seed(&dir, &[r#""a""#]);
+
+    let store = file::File::<()>::open(&dir).expect("opens store");
+    assert_eq!(stdout(arcane(&dir, &["streams"])), "chat-1\t1\n");
+    let delete = arcane(&dir, &["delete", "chat-1"]);
+    assert!(!delete.status.success(), "{delete:?}");
+    assert!(String::from_utf8_lossy(&delete.stderr).contains("locked"));
+    drop(store);
+
+    fs::remove_dir_all(&dir).expect("removes directory");
+}
+
+#[test]
+fn operates_streams() {
+    let dir = dir("operate");
+    seed(&dir, &[r#""a""#, r#""b""#, r#""c""#]);
+
+    assert_eq!(
+        stdout(arcane(&dir, &["truncate", "chat-1", "--before", "3"])),
+        "2 events truncated\n",
+    );
+    assert_eq!(stdout(arcane(&dir, &["dump", "chat-1"])).lines().count(), 1);
+    assert_eq!(
+        stdout(arcane(&dir, &["tombstone", "chat-1"])),
+        "chat-1 tombstoned at 4\n",
+    );
+    assert_eq!(stdout(arcane(&dir, &["delete", "chat-1"])), "");
+    assert_eq!(stdout(arcane(&dir, &["streams"])), "");
+    assert_ne!(stdout(arcane(&dir, &["compact"])), "0 bytes reclaimed\n");
+
+    fs::remove_dir_all(&dir).expect("removes directory");
+}
+
+#[test]
+fn exports_and_imports() {
+    let (from, into) = (dir("export"), dir("import"));
+    seed(&from, &[r#""a""#, r#""b""#]);
+    let archive = from.with_extension("arc");
+
+    let archive_path = archive.to_str().expect("UTF-8 path");
+    drop(stdout(arcane(&from, &["export", "--output", archive_path])));
+
+    fs::create_dir_all(&into).expect("creates directory");
+    assert_eq!(
+        stdout(arcane(&into, &["import", "--input", archive_path])),
+        "2 events imported, 0 skipped\n",
+    );
+    assert_eq!(
+        stdout(arcane(&into, &["import", "--input", archive_path])),
+        "0 events imported, 2 skipped\n",
+    );
+    assert_eq!(
+        stdout(arcane(&into, &["dump", "chat-1"])),
+        stdout(arcane(&from, &["dump", "chat-1"])),
+    );
+
+    // Truncated archive files are rejected before importing anything.
+    let text = fs::read_to_string(&archive).expect("reads archive");
+    let (truncated, _) = text.trim_end().rsplit_once('\n').expect("lines");
+    fs::write(&archive, truncated).expect("writes archive");
+    let empty = dir("import-truncated");
+    fs::create_dir_all(&empty).expect("creates directory");
+    let import = arcane(&empty, &["import", "--input", archive_path]);
+    assert!(!import.status.success(), "{import:?}");
+    assert_eq!(stdout(arcane(&empty, &["streams"])), "");
+    fs::remove_dir_all(&empty).expect("removes directory");
+
+    fs::remove_file(&archive).expect("removes archive");
+    fs::remove_dir_all(&from).expect("removes directory");
+    fs::remove_dir_all(&into).expect("removes directory");
+}